@@ -5,12 +5,14 @@
 
 pub mod context;
 pub mod error;
+pub mod recall;
 pub mod service;
 
 // Re-exports
 pub use context::AgentContext;
 pub use error::{AgentError, Result};
+pub use recall::RecalledMemory;
 pub use service::{
-    AgentResponse, AgentService, AgentStreamResponse, ApprovalCallback, MessageQueueCallback,
-    ProgressCallback, ProgressEvent, SudoCallback, ToolApprovalInfo,
+    AgentResponse, AgentService, AgentStreamResponse, ApprovalCallback, ApprovalDecision,
+    MessageQueueCallback, ProgressCallback, ProgressEvent, SudoCallback, ToolApprovalInfo,
 };
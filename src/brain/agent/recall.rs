@@ -0,0 +1,268 @@
+//! Recall — bring relevant long-term memory into the system brain each turn.
+//!
+//! When `[memory.recall]` is enabled, the incoming user message is searched
+//! against `memory_write` entries, daily memory logs and recent session history.
+//! The best matches are packed under a token budget and appended to the system
+//! brain, so the model doesn't have to remember to call `memory_search` itself.
+
+use crate::brain::tokenizer::count_tokens;
+use crate::config::RecallConfig;
+use crate::db::Pool;
+use crate::memory::{COLLECTION_ENTRIES, COLLECTION_SESSIONS, MemoryResult};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Recent sessions brought up to date before each search; only their new
+/// messages are read.
+const RECENT_SESSIONS: usize = 20;
+/// Messages shorter than this ("ok", "go on") aren't worth a search.
+const MIN_WORDS: usize = 3;
+
+const BLOCK_HEADER: &str = "## Recalled Memory\n\n\
+    Notes from long-term memory that matched the user's latest message. \
+    They may be outdated or unrelated — what the user says now takes precedence. \
+    Use memory_search for more.\n\n";
+
+/// A memory snippet injected into the system brain for one turn.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecalledMemory {
+    /// Where it came from, e.g. `memory entry a1b2c3d4` or `session "Fix login"`
+    pub source: String,
+    pub snippet: String,
+    /// Tokens the snippet's line adds to the system brain
+    pub tokens: usize,
+}
+
+/// Find memories relevant to `message`, best first, within the configured
+/// result count and token budget. Returns nothing when recall is disabled,
+/// the message is too short, or the memory store is unavailable.
+pub async fn recall(
+    pool: &Pool,
+    config: &RecallConfig,
+    session_id: Uuid,
+    message: &str,
+) -> Vec<RecalledMemory> {
+    if !config.enabled
+        || config.max_results == 0
+        || config.token_budget == 0
+        || message.split_whitespace().count() < MIN_WORDS
+    {
+        return Vec::new();
+    }
+
+    let store = match crate::memory::get_store() {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::debug!("Memory recall skipped: {e}");
+            return Vec::new();
+        }
+    };
+
+    if config.sessions {
+        use crate::db::repository::{SessionListOptions, SessionRepository};
+
+        let recent = SessionRepository::new(pool.clone())
+            .list(SessionListOptions {
+                include_archived: false,
+                limit: Some(RECENT_SESSIONS + 1),
+                offset: 0,
+            })
+            .await
+            .unwrap_or_default();
+        let others: Vec<_> = recent.into_iter().filter(|s| s.id != session_id).collect();
+        crate::brain::tools::session_search::index_sessions(pool, store, &others).await;
+    }
+
+    // Over-fetch: the current session and (optionally) all sessions are dropped below
+    let results = match crate::memory::search_related(store, message, config.max_results * 2).await
+    {
+        Ok(results) => results,
+        Err(e) => {
+            tracing::warn!("Memory recall search failed: {e}");
+            return Vec::new();
+        }
+    };
+
+    let current_doc = format!("{session_id}.md");
+    let candidates = results
+        .into_iter()
+        .filter(|r| {
+            r.collection != COLLECTION_SESSIONS
+                || (config.sessions && !r.path.ends_with(&current_doc))
+        })
+        .filter_map(|r| {
+            let snippet = r.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+            if snippet.is_empty() {
+                return None;
+            }
+            let source = source_label(&r);
+            let tokens = count_tokens(&format_line(&source, &snippet));
+            Some(RecalledMemory {
+                source,
+                snippet,
+                tokens,
+            })
+        })
+        .collect();
+
+    pack(candidates, config.max_results, config.token_budget)
+}
+
+/// Keep candidates in rank order while they fit the budget. A snippet that
+/// doesn't fit is skipped rather than ending the pass, so a shorter,
+/// lower-ranked one can still use the remaining room.
+fn pack(
+    candidates: Vec<RecalledMemory>,
+    max_results: usize,
+    token_budget: usize,
+) -> Vec<RecalledMemory> {
+    let mut used = 0;
+    let mut packed = Vec::new();
+    for memory in candidates {
+        if packed.len() == max_results {
+            break;
+        }
+        if used + memory.tokens > token_budget {
+            continue;
+        }
+        used += memory.tokens;
+        packed.push(memory);
+    }
+    packed
+}
+
+fn source_label(result: &MemoryResult) -> String {
+    match result.collection.as_str() {
+        COLLECTION_ENTRIES => {
+            let id = result.path.rsplit_once('#').map_or("", |(_, id)| id);
+            format!("memory entry {id}")
+        }
+        COLLECTION_SESSIONS => format!("session \"{}\"", result.title),
+        _ => {
+            let name = std::path::Path::new(&result.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| result.path.clone());
+            format!("memory log {name}")
+        }
+    }
+}
+
+fn format_line(source: &str, snippet: &str) -> String {
+    format!("- [{source}] {snippet}\n")
+}
+
+/// Most tokens a recall block can add to the system brain, so history can be
+/// trimmed to leave room for it before the block is known.
+pub fn reserved_tokens(config: &RecallConfig) -> usize {
+    if !config.enabled || config.max_results == 0 || config.token_budget == 0 {
+        return 0;
+    }
+    count_tokens(BLOCK_HEADER) + config.token_budget
+}
+
+/// System brain section listing the recalled memories.
+pub fn format_block(memories: &[RecalledMemory]) -> String {
+    let mut block = String::from(BLOCK_HEADER);
+    for memory in memories {
+        block.push_str(&format_line(&memory.source, &memory.snippet));
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(source: &str, tokens: usize) -> RecalledMemory {
+        RecalledMemory {
+            source: source.to_string(),
+            snippet: "snippet".to_string(),
+            tokens,
+        }
+    }
+
+    fn result(collection: &str, path: &str, title: &str) -> MemoryResult {
+        MemoryResult {
+            path: path.to_string(),
+            collection: collection.to_string(),
+            title: title.to_string(),
+            snippet: String::new(),
+            rank: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_pack_respects_budget_and_count() {
+        let candidates = vec![memory("a", 300), memory("b", 600), memory("c", 200)];
+        let packed = pack(candidates.clone(), 5, 600);
+        // b doesn't fit after a, but c still does
+        assert_eq!(
+            packed.iter().map(|m| m.source.as_str()).collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+
+        let packed = pack(candidates, 1, 10_000);
+        assert_eq!(packed.len(), 1);
+        assert_eq!(packed[0].source, "a");
+    }
+
+    #[test]
+    fn test_source_label() {
+        assert_eq!(
+            source_label(&result(
+                "entries",
+                "/home/u/.opencrabs/memory/entries.json#a1b2c3d4",
+                "fact: x"
+            )),
+            "memory entry a1b2c3d4"
+        );
+        assert_eq!(
+            source_label(&result("sessions", "/x/abc.md", "Fix login")),
+            "session \"Fix login\""
+        );
+        assert_eq!(
+            source_label(&result(
+                "memory",
+                "/home/u/.opencrabs/memory/2026-02-15.md",
+                ""
+            )),
+            "memory log 2026-02-15.md"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled_recall_returns_nothing() {
+        let db = crate::db::Database::connect_in_memory().await.unwrap();
+        let config = RecallConfig::default();
+        let recalled = recall(
+            db.pool(),
+            &config,
+            Uuid::new_v4(),
+            "what did we decide about the auth token refresh",
+        )
+        .await;
+        assert!(recalled.is_empty());
+    }
+
+    #[test]
+    fn test_format_block() {
+        let block = format_block(&[RecalledMemory {
+            source: "memory entry a1b2c3d4".to_string(),
+            snippet: "preference: tabs".to_string(),
+            tokens: 10,
+        }]);
+        assert!(block.starts_with("## Recalled Memory"));
+        assert!(block.ends_with("- [memory entry a1b2c3d4] preference: tabs\n"));
+    }
+
+    #[test]
+    fn test_reserved_tokens() {
+        let mut config = RecallConfig::default();
+        assert_eq!(reserved_tokens(&config), 0);
+        config.enabled = true;
+        config.token_budget = 500;
+        config.max_results = 3;
+        assert!(reserved_tokens(&config) > 500);
+    }
+}
@@ -5,12 +5,17 @@
 
 use super::context::AgentContext;
 use super::error::{AgentError, Result};
+use super::recall::{self, RecalledMemory};
 use crate::brain::provider::{
     ContentBlock, ImageSource, LLMRequest, LLMResponse, Message, Provider, ProviderStream, Role,
     StopReason,
 };
-use crate::brain::tools::{ToolExecutionContext, ToolRegistry};
+use crate::brain::tools::sandbox::SandboxViolationKind;
+use crate::brain::tools::{PolicyAction, ToolExecutionContext, ToolRegistry};
+use crate::services::checkpoint::TurnCheckpoint;
 use crate::services::{MessageService, ServiceContext, SessionService};
+use futures::StreamExt;
+use serde::Serialize;
 use serde_json::Value;
 use std::future::Future;
 use std::pin::Pin;
@@ -31,14 +36,28 @@ pub struct ToolApprovalInfo {
     pub capabilities: Vec<String>,
 }
 
+/// The user's answer to an approval request
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApprovalDecision {
+    /// Run the tool as requested
+    Approved,
+    /// Run the tool with input the user edited in the approval prompt
+    Edited(Value),
+    /// Don't run the tool
+    Denied,
+}
+
 /// Type alias for approval callback function
-/// Returns true if approved, false if denied
+/// Returns the user's decision for the tool call
 pub type ApprovalCallback = Arc<
-    dyn Fn(ToolApprovalInfo) -> Pin<Box<dyn Future<Output = Result<bool>> + Send>> + Send + Sync,
+    dyn Fn(ToolApprovalInfo) -> Pin<Box<dyn Future<Output = Result<ApprovalDecision>> + Send>>
+        + Send
+        + Sync,
 >;
 
 /// Progress event emitted during tool execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ProgressEvent {
     Thinking,
     ToolStarted { tool_name: String, tool_input: Value },
@@ -52,10 +71,29 @@ pub enum ProgressEvent {
     CompactionSummary { summary: String },
     /// Build completed — TUI should offer restart
     RestartReady { status: String },
+    /// The provider chain failed over (or returned) to a different backend
+    ProviderSwitched { from: String, to: String, reason: String },
+    /// Progress from a delegated sub-agent, tagged with its label
+    Subagent { agent: String, event: Box<ProgressEvent> },
+    /// Memories matching the user's message were added to the system brain
+    MemoryRecalled { memories: Vec<RecalledMemory> },
+    /// The OS sandbox blocked a tool call; `detail` is the error the model sees
+    SandboxViolation { tool_name: String, kind: SandboxViolationKind, detail: String },
 //    /// A queued user message was injected into the agent context between tool iterations
 //    QueuedMessageInjected { content: String },
 }
 
+/// Result of one tool call, in the shape the tool loop persists
+struct ToolCallOutcome {
+    /// `ToolResult` block sent back to the model
+    result: ContentBlock,
+    /// Short description for DB persistence
+    description: String,
+    success: bool,
+    /// Output truncated for DB persistence / progress display
+    output: String,
+}
+
 /// Callback for reporting progress during agent execution
 pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
 
@@ -82,7 +120,8 @@ pub struct AgentService {
     /// Tool registry for executing tools
     tool_registry: Arc<ToolRegistry>,
 
-    /// Maximum tool execution iterations (0 = unlimited, relies on loop detection)
+    /// Maximum tool execution iterations; the turn fails once they're used up
+    /// (0 = unlimited, relies on loop detection)
     max_tool_iterations: usize,
 
     /// System brain template
@@ -97,6 +136,9 @@ pub struct AgentService {
     /// Max output tokens for API calls from config
     max_tokens: u32,
 
+    /// Max read-only tool calls run concurrently within one iteration
+    max_concurrent_tools: usize,
+
     /// Callback for requesting tool approval from user
     approval_callback: Option<ApprovalCallback>,
 
@@ -114,6 +156,13 @@ pub struct AgentService {
 
     /// Brain path (~/.opencrabs/) for loading brain files
     brain_path: Option<std::path::PathBuf>,
+
+    /// Automatic memory recall settings from config
+    memory_recall: crate::config::RecallConfig,
+
+    /// Checkpoint that file writes are saved into instead of a fresh one per
+    /// turn, so a delegating agent's `/undo` also reverts its sub-agents
+    checkpoint: Option<TurnCheckpoint>,
 }
 
 impl AgentService {
@@ -130,12 +179,15 @@ impl AgentService {
             auto_approve_tools: false,
             context_limit: config.agent.context_limit,
             max_tokens: config.agent.max_tokens,
+            max_concurrent_tools: config.agent.max_concurrent as usize,
             approval_callback: None,
             progress_callback: None,
             message_queue_callback: None,
             sudo_callback: None,
             working_directory: Arc::new(std::sync::RwLock::new(std::env::current_dir().unwrap_or_default())),
             brain_path: None,
+            memory_recall: config.memory.recall,
+            checkpoint: None,
         }
     }
 
@@ -171,6 +223,18 @@ impl AgentService {
         self
     }
 
+    /// Save file writes into an existing checkpoint (e.g. the delegating turn's)
+    pub fn with_checkpoint(mut self, checkpoint: Option<TurnCheckpoint>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Set how many read-only tool calls may run at once
+    pub fn with_max_concurrent_tools(mut self, max: usize) -> Self {
+        self.max_concurrent_tools = max;
+        self
+    }
+
     /// Set the tool registry
     pub fn with_tool_registry(mut self, registry: Arc<ToolRegistry>) -> Self {
         self.tool_registry = registry;
@@ -228,6 +292,20 @@ impl AgentService {
         Arc::clone(&self.working_directory)
     }
 
+    /// Set automatic memory recall (overrides `[memory.recall]` from config)
+    pub fn with_memory_recall(mut self, recall: crate::config::RecallConfig) -> Self {
+        self.memory_recall = recall;
+        self
+    }
+
+    /// Turn recall from past sessions on or off, keeping the memory recall
+    /// settings. Channel agents answer people other than the owner and must
+    /// not surface the owner's other conversations.
+    pub fn with_session_recall(mut self, enabled: bool) -> Self {
+        self.memory_recall.sessions = enabled;
+        self
+    }
+
     /// Set the brain path (~/.opencrabs/)
     pub fn with_brain_path(mut self, brain_path: std::path::PathBuf) -> Self {
         self.brain_path = Some(brain_path);
@@ -416,6 +494,7 @@ impl AgentService {
             context_window as usize,
             self.tool_registry.count(),
             self.default_system_brain.as_deref(),
+            recall::reserved_tokens(&self.memory_recall),
         );
 
         let mut context =
@@ -425,11 +504,21 @@ impl AgentService {
         if let Some(brain) = &self.default_system_brain {
             context.system_brain = Some(brain.clone());
         }
+        self.inject_recalled_memory(&mut context, &user_message).await;
 
         // Build user message — detect and attach images from paths/URLs
         let user_msg = Self::build_user_message(&user_message).await;
         context.add_message(user_msg);
 
+        // Label for the turn's file checkpoint (shown by /checkpoints)
+        let checkpoint_label: String = user_message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(60)
+            .collect();
+
         // Save user message to database (text only — images are ephemeral)
         let _user_db_msg = message_service
             .create_message(session_id, "user".to_string(), user_message)
@@ -501,26 +590,32 @@ impl AgentService {
             .with_read_only_mode(read_only_mode);
         tool_context.sudo_callback = self.sudo_callback.clone();
         tool_context.shared_working_directory = Some(Arc::clone(&self.working_directory));
+        tool_context.checkpoint = Some(self.checkpoint.clone().unwrap_or_else(|| {
+            TurnCheckpoint::new(self.context.clone(), session_id, checkpoint_label)
+        }));
 
         // Tool execution loop
         let mut iteration = 0;
         let mut total_input_tokens = 0u32;
         let mut total_output_tokens = 0u32;
+        let mut total_reasoning_tokens = 0u32;
         let mut last_input_tokens = 0u32;
         let mut final_response: Option<LLMResponse> = None;
         let mut accumulated_text = String::new(); // Collect text from all iterations (not just final)
         let mut recent_tool_calls: Vec<String> = Vec::new(); // Track tool calls to detect loops
         let mut loop_break_reason: Option<String> = None; // Why the loop broke (if not normal exit)
+        let mut iterations_exhausted = false;
 
         loop {
-            // Safety: warn every 50 iterations but never hard-stop
-            // Loop detection (below) is the real safety net
+            // Configured cap (--max-iterations, delegate's max_iterations).
+            // Without one, loop detection (below) is the safety net.
             if self.max_tool_iterations > 0 && iteration >= self.max_tool_iterations {
                 tracing::warn!(
-                    "Tool iteration {} exceeded configured max of {} — continuing (loop detection is active)",
-                    iteration,
-                    self.max_tool_iterations
+                    "Stopping after {} tool iterations (configured max)",
+                    iteration
                 );
+                iterations_exhausted = true;
+                break;
             }
             // Check for cancellation
             if let Some(ref token) = cancel_token
@@ -651,6 +746,7 @@ impl AgentService {
             last_input_tokens = response.usage.input_tokens;
             total_input_tokens += response.usage.input_tokens;
             total_output_tokens += response.usage.output_tokens;
+            total_reasoning_tokens += response.usage.reasoning_tokens;
 
             // Calibrate context token count with the API's real input_tokens.
             // Even with tiktoken, there's some drift since Anthropic's tokenizer differs slightly.
@@ -897,270 +993,48 @@ impl AgentService {
             let mut tool_descriptions: Vec<String> = Vec::new(); // For DB persistence
             let mut tool_outputs: Vec<(bool, String)> = Vec::new(); // (success, output) parallel to descriptions
 
-            for (tool_id, tool_name, tool_input) in tool_uses {
-                // Check for cancellation before each tool
+            // Consecutive read-only calls run concurrently (bounded by
+            // `agent.max_concurrent`); anything that writes, executes or needs
+            // approval runs alone. `buffered` yields in call order, so results
+            // line up with the model's tool_use blocks.
+            let max_concurrent = self.max_concurrent_tools.max(1);
+            let mut pending_calls = tool_uses.into_iter().peekable();
+            while let Some(first_call) = pending_calls.next() {
+                // Check for cancellation before each batch
                 if let Some(ref token) = cancel_token
                     && token.is_cancelled() {
                         break;
                     }
 
-                tracing::info!(
-                    "Executing tool '{}' (iteration {})",
-                    tool_name,
-                    iteration,
-                );
-
-                // Save tool input for progress reporting (before it's moved to execute)
-                let tool_input_for_progress = tool_input.clone();
-
-                // Build short description for DB persistence
-                tool_descriptions.push(Self::format_tool_summary(&tool_name, &tool_input));
-
-                // Emit tool started progress
-                if let Some(ref cb) = self.progress_callback {
-                    cb(ProgressEvent::ToolStarted {
-                        tool_name: tool_name.clone(),
-                        tool_input: tool_input_for_progress.clone(),
-                    });
-                }
-
-                // Check if approval is needed
-                let needs_approval = if let Some(tool) = self.tool_registry.get(&tool_name) {
-                    tool.requires_approval()
-                        && !self.auto_approve_tools
-                        && !tool_context.auto_approve
-                } else {
-                    false
-                };
-
-                // Request approval if needed
-                if needs_approval {
-                    if let Some(ref approval_callback) = self.approval_callback {
-                        // Get tool details for approval request
-                        let tool_info = if let Some(tool) = self.tool_registry.get(&tool_name) {
-                            ToolApprovalInfo {
-                                tool_name: tool_name.clone(),
-                                tool_description: tool.description().to_string(),
-                                tool_input: tool_input.clone(),
-                                capabilities: tool
-                                    .capabilities()
-                                    .iter()
-                                    .map(|c| format!("{:?}", c))
-                                    .collect(),
-                            }
-                        } else {
-                            // Tool not found, skip approval
-                            let err = format!("Tool not found: {}", tool_name);
-                            tool_outputs.push((false, err.clone()));
-                            tool_results.push(ContentBlock::ToolResult {
-                                tool_use_id: tool_id,
-                                content: err,
-                                is_error: Some(true),
-                            });
-                            continue;
-                        };
-
-                        // Call approval callback
-                        tracing::info!("Requesting user approval for tool '{}'", tool_name);
-                        match approval_callback(tool_info).await {
-                            Ok(approved) => {
-                                if !approved {
-                                    tracing::warn!("User denied approval for tool '{}'", tool_name);
-                                    tool_outputs.push((false, "User denied permission".to_string()));
-                                    tool_results.push(ContentBlock::ToolResult {
-                                        tool_use_id: tool_id,
-                                        content: "User denied permission to execute this tool"
-                                            .to_string(),
-                                        is_error: Some(true),
-                                    });
-                                    continue;
-                                }
-                                tracing::info!("User approved tool '{}'", tool_name);
-                                // Create approved context for this tool execution
-                                let approved_tool_context = ToolExecutionContext {
-                                    session_id: tool_context.session_id,
-                                    working_directory: tool_context.working_directory.clone(),
-                                    env_vars: tool_context.env_vars.clone(),
-                                    auto_approve: true, // User approved this execution
-                                    timeout_secs: tool_context.timeout_secs,
-                                    read_only_mode: tool_context.read_only_mode,
-                                    sudo_callback: tool_context.sudo_callback.clone(),
-                                    shared_working_directory: tool_context.shared_working_directory.clone(),
-                                };
-
-                                // Execute the tool with approved context
-                                match self
-                                    .tool_registry
-                                    .execute(&tool_name, tool_input, &approved_tool_context)
-                                    .await
-                                {
-                                    Ok(result) => {
-                                        let success = result.success;
-                                        let content = if result.success {
-                                            result.output
-                                        } else {
-                                            result.error.unwrap_or_else(|| {
-                                                "Tool execution failed".to_string()
-                                            })
-                                        };
-                                        
-                                        // GRANULAR LOG: Tool execution result
-                                        if success {
-                                            tracing::info!(
-                                                "[TOOL_EXEC] ✅ Tool '{}' executed successfully, output_len={}",
-                                                tool_name,
-                                                content.len()
-                                            );
-                                        } else {
-                                            tracing::error!(
-                                                "[TOOL_EXEC] ❌ Tool '{}' failed: {}",
-                                                tool_name,
-                                                content.chars().take(200).collect::<String>()
-                                            );
-                                        }
-                                        
-                                        let output_summary: String = content.chars().take(2000).collect();
-                                        tool_outputs.push((success, output_summary.clone()));
-                                        if let Some(ref cb) = self.progress_callback {
-                                            cb(ProgressEvent::ToolCompleted {
-                                                tool_name: tool_name.clone(),
-                                                tool_input: tool_input_for_progress.clone(),
-                                                success,
-                                                summary: output_summary,
-                                            });
-                                        }
-                                        tool_results.push(ContentBlock::ToolResult {
-                                            tool_use_id: tool_id,
-                                            content,
-                                            is_error: Some(!success),
-                                        });
-                                    }
-                                    Err(e) => {
-                                        let err_msg = format!("Tool execution error: {}", e);
-                                        // GRANULAR LOG: Tool execution error
-                                        tracing::error!(
-                                            "[TOOL_EXEC] 💥 Tool '{}' error: {}",
-                                            tool_name,
-                                            err_msg
-                                        );
-                                        let output_summary: String = err_msg.chars().take(2000).collect();
-                                        tool_outputs.push((false, output_summary.clone()));
-                                        if let Some(ref cb) = self.progress_callback {
-                                            cb(ProgressEvent::ToolCompleted {
-                                                tool_name: tool_name.clone(),
-                                                tool_input: tool_input_for_progress.clone(),
-                                                success: false,
-                                                summary: output_summary,
-                                            });
-                                        }
-                                        tool_results.push(ContentBlock::ToolResult {
-                                            tool_use_id: tool_id,
-                                            content: err_msg,
-                                            is_error: Some(true),
-                                        });
-                                    }
-                                }
-                                continue; // Skip the normal execution path below
-                            }
-                            Err(e) => {
-                                tracing::error!("Approval callback error: {}", e);
-                                tool_outputs.push((false, format!("Approval failed: {}", e)));
-                                tool_results.push(ContentBlock::ToolResult {
-                                    tool_use_id: tool_id,
-                                    content: format!("Approval request failed: {}", e),
-                                    is_error: Some(true),
-                                });
-                                continue;
-                            }
-                        }
-                    } else {
-                        // No approval callback configured, deny execution
-                        tracing::warn!(
-                            "Tool '{}' requires approval but no approval callback configured",
-                            tool_name
-                        );
-                        tool_outputs.push((false, "No approval mechanism configured".to_string()));
-                        tool_results.push(ContentBlock::ToolResult {
-                            tool_use_id: tool_id,
-                            content: "Tool requires approval but no approval mechanism configured"
-                                .to_string(),
-                            is_error: Some(true),
-                        });
-                        continue;
+                let mut batch = vec![first_call];
+                if self.can_run_concurrently(&batch[0].1, &batch[0].2, &tool_context) {
+                    while let Some(call) = pending_calls.next_if(|(_, name, input)| {
+                        self.can_run_concurrently(name, input, &tool_context)
+                    })
+                    {
+                        batch.push(call);
                     }
                 }
+                if batch.len() > 1 {
+                    tracing::info!(
+                        "Running {} read-only tools concurrently (max {})",
+                        batch.len(),
+                        max_concurrent
+                    );
+                }
 
-                // Execute the tool (no approval needed)
-                match self
-                    .tool_registry
-                    .execute(&tool_name, tool_input, &tool_context)
-                    .await
-                {
-                    Ok(result) => {
-                        let success = result.success;
-                        let content = if result.success {
-                            result.output
-                        } else {
-                            result
-                                .error
-                                .unwrap_or_else(|| "Tool execution failed".to_string())
-                        };
-                        
-                        // GRANULAR LOG: Direct tool execution result
-                        if success {
-                            tracing::info!(
-                                "[TOOL_EXEC] ✅ Tool '{}' executed successfully, output_len={}",
-                                tool_name,
-                                content.len()
-                            );
-                        } else {
-                            tracing::error!(
-                                "[TOOL_EXEC] ❌ Tool '{}' failed: {}",
-                                tool_name,
-                                content.chars().take(200).collect::<String>()
-                            );
-                        }
-                        
-                        let output_summary: String = content.chars().take(2000).collect();
-                        tool_outputs.push((success, output_summary.clone()));
-                        if let Some(ref cb) = self.progress_callback {
-                            cb(ProgressEvent::ToolCompleted {
-                                tool_name: tool_name.clone(),
-                                tool_input: tool_input_for_progress.clone(),
-                                success,
-                                summary: output_summary,
-                            });
-                        }
-                        tool_results.push(ContentBlock::ToolResult {
-                            tool_use_id: tool_id,
-                            content,
-                            is_error: Some(!success),
-                        });
-                    }
-                    Err(e) => {
-                        let err_msg = format!("Tool execution error: {}", e);
-                        // GRANULAR LOG: Direct tool execution error
-                        tracing::error!(
-                            "[TOOL_EXEC] 💥 Tool '{}' error: {}",
-                            tool_name,
-                            err_msg
-                        );
-                        let output_summary: String = err_msg.chars().take(2000).collect();
-                        tool_outputs.push((false, output_summary.clone()));
-                        if let Some(ref cb) = self.progress_callback {
-                            cb(ProgressEvent::ToolCompleted {
-                                tool_name: tool_name.clone(),
-                                tool_input: tool_input_for_progress.clone(),
-                                success: false,
-                                summary: output_summary,
-                            });
-                        }
-                        tool_results.push(ContentBlock::ToolResult {
-                            tool_use_id: tool_id,
-                            content: err_msg,
-                            is_error: Some(true),
-                        });
-                    }
+                let outcomes: Vec<ToolCallOutcome> = futures::stream::iter(batch)
+                    .map(|(tool_id, tool_name, tool_input)| {
+                        self.execute_tool_call(tool_id, tool_name, tool_input, &tool_context, iteration)
+                    })
+                    .buffered(max_concurrent)
+                    .collect()
+                    .await;
+
+                for outcome in outcomes {
+                    tool_descriptions.push(outcome.description);
+                    tool_outputs.push((outcome.success, outcome.output));
+                    tool_results.push(outcome.result);
                 }
             }
 
@@ -1264,6 +1138,9 @@ impl AgentService {
         }
 
         let response = final_response.ok_or_else(|| {
+            if iterations_exhausted {
+                return AgentError::MaxIterationsExceeded(self.max_tool_iterations);
+            }
             let reason = loop_break_reason.unwrap_or_else(|| "Tool loop ended without final response".to_string());
             AgentError::Internal(reason)
         })?;
@@ -1303,6 +1180,7 @@ impl AgentService {
             usage: crate::brain::provider::TokenUsage {
                 input_tokens: total_input_tokens,
                 output_tokens: total_output_tokens,
+                reasoning_tokens: total_reasoning_tokens,
             },
             context_tokens: last_input_tokens,
             cost,
@@ -1343,6 +1221,7 @@ impl AgentService {
             context_window as usize,
             self.tool_registry.count(),
             self.default_system_brain.as_deref(),
+            recall::reserved_tokens(&self.memory_recall),
         );
 
         let mut context =
@@ -1352,6 +1231,7 @@ impl AgentService {
         if let Some(brain) = &self.default_system_brain {
             context.system_brain = Some(brain.clone());
         }
+        self.inject_recalled_memory(&mut context, &user_message).await;
 
         // Add user message
         let user_msg = Message::user(user_message.clone());
@@ -1376,6 +1256,46 @@ impl AgentService {
         Ok((model_name, request, message_service, session_service))
     }
 
+    /// Append memories relevant to `user_message` to the context's system brain
+    /// when `[memory.recall]` is enabled, and report them to the UI.
+    async fn inject_recalled_memory(&self, context: &mut AgentContext, user_message: &str) {
+        let memories = recall::recall(
+            &self.context.pool,
+            &self.memory_recall,
+            context.session_id,
+            user_message,
+        )
+        .await;
+        if memories.is_empty() {
+            return;
+        }
+
+        let block = recall::format_block(&memories);
+        context.token_count += crate::brain::tokenizer::count_tokens(&block);
+        context.system_brain = Some(match context.system_brain.take() {
+            Some(brain) => format!("{brain}\n\n{block}"),
+            None => block,
+        });
+        tracing::debug!("Recalled {} memories into the system brain", memories.len());
+
+        if let Some(ref cb) = self.progress_callback {
+            cb(ProgressEvent::MemoryRecalled { memories });
+        }
+    }
+
+    /// Forward provider failover switches to the progress callback
+    fn report_provider_switches(&self, switches: Vec<crate::brain::provider::ProviderSwitch>) {
+        for switch in switches {
+            if let Some(ref cb) = self.progress_callback {
+                cb(ProgressEvent::ProviderSwitched {
+                    from: switch.from,
+                    to: switch.to,
+                    reason: switch.reason,
+                });
+            }
+        }
+    }
+
     /// Stream a request and accumulate into an LLMResponse.
     ///
     /// Sends text deltas to the progress callback as `StreamingChunk` events
@@ -1383,9 +1303,11 @@ impl AgentService {
     /// once the stream completes, ready for tool extraction.
     async fn stream_complete(&self, request: LLMRequest, cancel_token: Option<&CancellationToken>) -> std::result::Result<LLMResponse, crate::brain::provider::ProviderError> {
         use crate::brain::provider::{ContentDelta, StreamEvent, TokenUsage};
-        use futures::StreamExt;
 
-        let mut stream = self.provider.stream(request).await?;
+        let (stream_result, switches) =
+            crate::brain::provider::collect_switches(self.provider.stream(request)).await;
+        self.report_provider_switches(switches);
+        let mut stream = stream_result?;
 
         // Accumulate state from stream events
         let mut id = String::new();
@@ -1393,6 +1315,7 @@ impl AgentService {
         let mut stop_reason: Option<StopReason> = None;
         let mut input_tokens = 0u32;
         let mut output_tokens = 0u32;
+        let mut reasoning_tokens = 0u32;
 
         // Track partial content blocks by index
         // Text blocks: accumulate text deltas
@@ -1469,6 +1392,11 @@ impl AgentService {
                 StreamEvent::MessageDelta { delta, usage } => {
                     stop_reason = delta.stop_reason;
                     output_tokens = usage.output_tokens;
+                    reasoning_tokens = usage.reasoning_tokens;
+                    // Some providers (Bedrock) only report input usage at the end
+                    if usage.input_tokens > 0 {
+                        input_tokens = usage.input_tokens;
+                    }
                 }
                 StreamEvent::MessageStop => break,
                 StreamEvent::Ping => {}
@@ -1491,20 +1419,25 @@ impl AgentService {
             model,
             content: content_blocks,
             stop_reason,
-            usage: TokenUsage { input_tokens, output_tokens },
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens,
+                reasoning_tokens,
+            },
         })
     }
 
     /// Trim DB messages to fit within the context budget.
     ///
     /// Keeps only the most recent messages that fit within ~60% of the context window
-    /// after reserving space for tool definitions, brain, and response.
+    /// after reserving space for tool definitions, brain, recalled memory, and response.
     /// Uses tiktoken cl100k_base for accurate token counting — no more chars/N guessing.
     fn trim_messages_to_budget(
         all_messages: Vec<crate::db::models::Message>,
         context_window: usize,
         tool_count: usize,
         brain: Option<&str>,
+        recall_budget: usize,
     ) -> Vec<crate::db::models::Message> {
         use crate::brain::tokenizer;
 
@@ -1513,6 +1446,7 @@ impl AgentService {
         let history_budget = context_window
             .saturating_sub(tool_budget)
             .saturating_sub(brain_budget)
+            .saturating_sub(recall_budget)
             .saturating_sub(16384) // reserve for response
             * 60 / 100; // Target 60% to leave headroom for tool results and overhead
 
@@ -1754,6 +1688,226 @@ impl AgentService {
         }
     }
 
+    /// Whether a tool call may run alongside others: concurrency-safe and not
+    /// waiting on an approval prompt
+    fn can_run_concurrently(
+        &self,
+        tool_name: &str,
+        tool_input: &Value,
+        tool_context: &ToolExecutionContext,
+    ) -> bool {
+        self.tool_registry
+            .get(tool_name)
+            .is_some_and(|tool| tool.is_concurrency_safe_for(tool_input))
+            && !self.needs_approval(tool_name, tool_input, tool_context)
+    }
+
+    /// Whether the permission policy asks for this call and nothing has
+    /// pre-approved it. Denied calls skip the prompt — `execute` rejects them.
+    fn needs_approval(
+        &self,
+        tool_name: &str,
+        tool_input: &Value,
+        tool_context: &ToolExecutionContext,
+    ) -> bool {
+        self.tool_registry
+            .check_policy(tool_name, tool_input, tool_context)
+            .is_some_and(|decision| decision.action == PolicyAction::Ask)
+            && !self.auto_approve_tools
+            && !tool_context.auto_approve
+    }
+
+    /// Run a single tool call: progress events, approval, execution.
+    ///
+    /// Never fails — errors, denials and missing tools become an error
+    /// `ToolResult` so the model can react to them.
+    async fn execute_tool_call(
+        &self,
+        tool_id: String,
+        tool_name: String,
+        mut tool_input: Value,
+        tool_context: &ToolExecutionContext,
+        iteration: usize,
+    ) -> ToolCallOutcome {
+        tracing::info!(
+            "Executing tool '{}' (iteration {})",
+            tool_name,
+            iteration,
+        );
+
+        // Build short description for DB persistence
+        let description = Self::format_tool_summary(&tool_name, &tool_input);
+
+        // Emit tool started progress
+        if let Some(ref cb) = self.progress_callback {
+            cb(ProgressEvent::ToolStarted {
+                tool_name: tool_name.clone(),
+                tool_input: tool_input.clone(),
+            });
+        }
+
+        // Outcome for calls that never reach execution (no ToolCompleted event)
+        let rejected = |output: String, content: String| ToolCallOutcome {
+            result: ContentBlock::ToolResult {
+                tool_use_id: tool_id.clone(),
+                content,
+                is_error: Some(true),
+            },
+            description: description.clone(),
+            success: false,
+            output,
+        };
+
+        let mut approved_tool_context = None;
+        let mut input_edited = false;
+        if self.needs_approval(&tool_name, &tool_input, tool_context) {
+            let Some(ref approval_callback) = self.approval_callback else {
+                // No approval callback configured, deny execution
+                tracing::warn!(
+                    "Tool '{}' requires approval but no approval callback configured",
+                    tool_name
+                );
+                return rejected(
+                    "No approval mechanism configured".to_string(),
+                    "Tool requires approval but no approval mechanism configured".to_string(),
+                );
+            };
+
+            // Get tool details for approval request
+            let Some(tool) = self.tool_registry.get(&tool_name) else {
+                let err = format!("Tool not found: {}", tool_name);
+                return rejected(err.clone(), err);
+            };
+            let tool_info = ToolApprovalInfo {
+                tool_name: tool_name.clone(),
+                tool_description: tool.description().to_string(),
+                tool_input: tool_input.clone(),
+                capabilities: tool
+                    .capabilities()
+                    .iter()
+                    .map(|c| format!("{:?}", c))
+                    .collect(),
+            };
+
+            // Call approval callback
+            tracing::info!("Requesting user approval for tool '{}'", tool_name);
+            match approval_callback(tool_info).await {
+                Ok(decision @ (ApprovalDecision::Approved | ApprovalDecision::Edited(_))) => {
+                    tracing::info!("User approved tool '{}'", tool_name);
+                    if let ApprovalDecision::Edited(edited) = decision {
+                        tool_input = edited;
+                        input_edited = true;
+                    }
+                    // Create approved context for this tool execution
+                    approved_tool_context = Some(ToolExecutionContext {
+                        auto_approve: true, // User approved this execution
+                        ..tool_context.clone()
+                    });
+                }
+                Ok(ApprovalDecision::Denied) => {
+                    tracing::warn!("User denied approval for tool '{}'", tool_name);
+                    return rejected(
+                        "User denied permission".to_string(),
+                        "User denied permission to execute this tool".to_string(),
+                    );
+                }
+                Err(e) => {
+                    tracing::error!("Approval callback error: {}", e);
+                    return rejected(
+                        format!("Approval failed: {}", e),
+                        format!("Approval request failed: {}", e),
+                    );
+                }
+            }
+        }
+
+        let execution_context = approved_tool_context.as_ref().unwrap_or(tool_context);
+        let (success, content) = match self
+            .tool_registry
+            .execute(&tool_name, tool_input.clone(), execution_context)
+            .await
+        {
+            Ok(result) => {
+                let success = result.success;
+                let content = if result.success {
+                    result.output
+                } else if let Some(kind) = result.sandbox_violation {
+                    let detail = result.error.unwrap_or_default();
+                    if let Some(ref cb) = self.progress_callback {
+                        cb(ProgressEvent::SandboxViolation {
+                            tool_name: tool_name.clone(),
+                            kind,
+                            detail: detail.clone(),
+                        });
+                    }
+                    // Keep the output so the model sees what ran before the block
+                    format!("{}\n\n{}", detail, result.output)
+                } else {
+                    result
+                        .error
+                        .unwrap_or_else(|| "Tool execution failed".to_string())
+                };
+
+                // GRANULAR LOG: Tool execution result
+                if success {
+                    tracing::info!(
+                        "[TOOL_EXEC] ✅ Tool '{}' executed successfully, output_len={}",
+                        tool_name,
+                        content.len()
+                    );
+                } else {
+                    tracing::error!(
+                        "[TOOL_EXEC] ❌ Tool '{}' failed: {}",
+                        tool_name,
+                        content.chars().take(200).collect::<String>()
+                    );
+                }
+                (success, content)
+            }
+            Err(e) => {
+                let err_msg = format!("Tool execution error: {}", e);
+                // GRANULAR LOG: Tool execution error
+                tracing::error!(
+                    "[TOOL_EXEC] 💥 Tool '{}' error: {}",
+                    tool_name,
+                    err_msg
+                );
+                (false, err_msg)
+            }
+        };
+
+        // The model still sees its original call, so say what actually ran
+        let content = if input_edited {
+            format!(
+                "(input edited by the user before running: {})\n\n{}",
+                tool_input, content
+            )
+        } else {
+            content
+        };
+
+        let output_summary: String = content.chars().take(2000).collect();
+        if let Some(ref cb) = self.progress_callback {
+            cb(ProgressEvent::ToolCompleted {
+                tool_name: tool_name.clone(),
+                tool_input,
+                success,
+                summary: output_summary.clone(),
+            });
+        }
+
+        ToolCallOutcome {
+            result: ContentBlock::ToolResult {
+                tool_use_id: tool_id,
+                content,
+                is_error: Some(!success),
+            },
+            description,
+            success,
+            output: output_summary,
+        }
+    }
+
     /// Compact tool description for DB persistence (mirrors TUI's format_tool_description)
     fn format_tool_summary(tool_name: &str, tool_input: &Value) -> String {
         match tool_name {
@@ -1778,6 +1932,10 @@ impl AgentService {
                 let path = tool_input.get("path").and_then(|v| v.as_str()).unwrap_or(".");
                 format!("ls {}", path)
             }
+            "git" => {
+                let op = tool_input.get("operation").and_then(|v| v.as_str()).unwrap_or("?");
+                format!("git {}", op)
+            }
             "glob" => {
                 let p = tool_input.get("pattern").and_then(|v| v.as_str()).unwrap_or("?");
                 format!("Glob {}", p)
@@ -1802,6 +1960,14 @@ impl AgentService {
                 let q = tool_input.get("query").and_then(|v| v.as_str()).unwrap_or("?");
                 format!("Memory: {}", q)
             }
+            "memory_write" => {
+                let op = tool_input.get("operation").and_then(|v| v.as_str()).unwrap_or("?");
+                format!("Memory {}", op)
+            }
+            "code_search" => {
+                let q = tool_input.get("query").and_then(|v| v.as_str()).unwrap_or("?");
+                format!("Code: {}", q)
+            }
             other => other.to_string(),
         }
     }
@@ -1827,7 +1993,7 @@ impl AgentService {
 }
 
 /// Response from the agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AgentResponse {
     /// Message ID in database
     pub message_id: Uuid,
@@ -1867,7 +2033,7 @@ pub struct AgentStreamResponse {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::db::Database;
     use crate::brain::provider::{LLMRequest, LLMResponse, TokenUsage};
@@ -1892,6 +2058,7 @@ mod tests {
                 usage: TokenUsage {
                     input_tokens: 10,
                     output_tokens: 20,
+                    reasoning_tokens: 0,
                 },
             })
         }
@@ -2013,6 +2180,72 @@ mod tests {
         assert!(!response.content.is_empty());
     }
 
+    /// Convert a complete response into the stream events a provider would emit
+    pub(crate) fn stream_from_response(response: LLMResponse) -> ProviderStream {
+        use crate::brain::provider::{ContentDelta, MessageDelta, StreamEvent, StreamMessage};
+
+        let mut events = vec![
+            Ok(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: response.id.clone(),
+                    model: response.model.clone(),
+                    role: Role::Assistant,
+                    usage: response.usage,
+                },
+            }),
+        ];
+
+        for (i, block) in response.content.iter().enumerate() {
+            // ContentBlockStart sends empty shells; actual content comes via deltas
+            match block {
+                ContentBlock::Text { text } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::Text { text: String::new() },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::TextDelta { text: text.clone() },
+                    }));
+                }
+                ContentBlock::ToolUse { id, name, input } => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: ContentBlock::ToolUse {
+                            id: id.clone(),
+                            name: name.clone(),
+                            input: serde_json::Value::Object(Default::default()),
+                        },
+                    }));
+                    events.push(Ok(StreamEvent::ContentBlockDelta {
+                        index: i,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: serde_json::to_string(input).unwrap_or_default(),
+                        },
+                    }));
+                }
+                _ => {
+                    events.push(Ok(StreamEvent::ContentBlockStart {
+                        index: i,
+                        content_block: block.clone(),
+                    }));
+                }
+            }
+            events.push(Ok(StreamEvent::ContentBlockStop { index: i }));
+        }
+
+        events.push(Ok(StreamEvent::MessageDelta {
+            delta: MessageDelta {
+                stop_reason: response.stop_reason,
+                stop_sequence: None,
+            },
+            usage: response.usage,
+        }));
+        events.push(Ok(StreamEvent::MessageStop));
+
+        Box::pin(futures::stream::iter(events))
+    }
+
     /// Mock provider that simulates tool use
     struct MockProviderWithTools {
         call_count: std::sync::Mutex<usize>,
@@ -2055,6 +2288,7 @@ mod tests {
                     usage: TokenUsage {
                         input_tokens: 10,
                         output_tokens: 20,
+                        reasoning_tokens: 0,
                     },
                 })
             } else {
@@ -2069,6 +2303,7 @@ mod tests {
                     usage: TokenUsage {
                         input_tokens: 15,
                         output_tokens: 25,
+                        reasoning_tokens: 0,
                     },
                 })
             }
@@ -2078,70 +2313,9 @@ mod tests {
             &self,
             request: LLMRequest,
         ) -> crate::brain::provider::Result<ProviderStream> {
-            use crate::brain::provider::{ContentDelta, MessageDelta, StreamEvent, StreamMessage};
-
             // Get the response that complete() would return, then convert to stream events
             let response = self.complete(request).await?;
-            let mut events = vec![
-                Ok(StreamEvent::MessageStart {
-                    message: StreamMessage {
-                        id: response.id.clone(),
-                        model: response.model.clone(),
-                        role: Role::Assistant,
-                        usage: response.usage,
-                    },
-                }),
-            ];
-
-            for (i, block) in response.content.iter().enumerate() {
-                // ContentBlockStart sends empty shells; actual content comes via deltas
-                match block {
-                    ContentBlock::Text { text } => {
-                        events.push(Ok(StreamEvent::ContentBlockStart {
-                            index: i,
-                            content_block: ContentBlock::Text { text: String::new() },
-                        }));
-                        events.push(Ok(StreamEvent::ContentBlockDelta {
-                            index: i,
-                            delta: ContentDelta::TextDelta { text: text.clone() },
-                        }));
-                    }
-                    ContentBlock::ToolUse { id, name, input } => {
-                        events.push(Ok(StreamEvent::ContentBlockStart {
-                            index: i,
-                            content_block: ContentBlock::ToolUse {
-                                id: id.clone(),
-                                name: name.clone(),
-                                input: serde_json::Value::Object(Default::default()),
-                            },
-                        }));
-                        events.push(Ok(StreamEvent::ContentBlockDelta {
-                            index: i,
-                            delta: ContentDelta::InputJsonDelta {
-                                partial_json: serde_json::to_string(input).unwrap_or_default(),
-                            },
-                        }));
-                    }
-                    _ => {
-                        events.push(Ok(StreamEvent::ContentBlockStart {
-                            index: i,
-                            content_block: block.clone(),
-                        }));
-                    }
-                }
-                events.push(Ok(StreamEvent::ContentBlockStop { index: i }));
-            }
-
-            events.push(Ok(StreamEvent::MessageDelta {
-                delta: MessageDelta {
-                    stop_reason: response.stop_reason,
-                    stop_sequence: None,
-                },
-                usage: response.usage,
-            }));
-            events.push(Ok(StreamEvent::MessageStop));
-
-            Ok(Box::pin(futures::stream::iter(events)))
+            Ok(stream_from_response(response))
         }
 
         fn name(&self) -> &str {
@@ -2244,6 +2418,289 @@ mod tests {
         assert!(response.usage.output_tokens >= 45); // 20 + 25
     }
 
+    /// Provider that never stops asking for tools, with a new argument each
+    /// time so loop detection doesn't end the turn first
+    struct EndlessToolProvider {
+        call_count: Arc<std::sync::Mutex<usize>>,
+    }
+
+    #[async_trait]
+    impl Provider for EndlessToolProvider {
+        async fn complete(
+            &self,
+            _request: LLMRequest,
+        ) -> crate::brain::provider::Result<LLMResponse> {
+            let call_num = {
+                let mut count = self.call_count.lock().unwrap();
+                *count += 1;
+                *count
+            };
+            Ok(LLMResponse {
+                id: format!("endless-{}", call_num),
+                model: "mock-model".to_string(),
+                content: vec![ContentBlock::ToolUse {
+                    id: format!("tool-{}", call_num),
+                    name: "test_tool".to_string(),
+                    input: serde_json::json!({ "message": format!("step {}", call_num) }),
+                }],
+                stop_reason: Some(StopReason::ToolUse),
+                usage: TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                    reasoning_tokens: 0,
+                },
+            })
+        }
+
+        async fn stream(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<ProviderStream> {
+            let response = self.complete(request).await?;
+            Ok(stream_from_response(response))
+        }
+
+        fn name(&self) -> &str {
+            "endless"
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["mock-model".to_string()]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            Some(200_000)
+        }
+
+        fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+            0.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_tool_iterations_stops_the_loop() {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+        let call_count = Arc::new(std::sync::Mutex::new(0));
+        let provider = Arc::new(EndlessToolProvider {
+            call_count: call_count.clone(),
+        });
+
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(MockTool));
+        let agent_service = AgentService::new(provider, context.clone())
+            .with_tool_registry(Arc::new(registry))
+            .with_auto_approve_tools(true)
+            .with_max_tool_iterations(3);
+
+        let session = SessionService::new(context)
+            .create_session(Some("Endless".to_string()))
+            .await
+            .unwrap();
+        let result = agent_service
+            .send_message_with_tools(session.id, "Keep going".to_string(), None)
+            .await;
+
+        assert!(matches!(result, Err(AgentError::MaxIterationsExceeded(3))));
+        assert_eq!(*call_count.lock().unwrap(), 3);
+    }
+
+    /// Provider that asks for three `slow_read` calls at once, then records
+    /// the order of the tool results it gets back
+    struct BatchToolProvider {
+        call_count: std::sync::Mutex<usize>,
+        result_order: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Provider for BatchToolProvider {
+        async fn complete(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<LLMResponse> {
+            let call_num = {
+                let mut count = self.call_count.lock().unwrap();
+                *count += 1;
+                *count
+            };
+
+            let content = if call_num == 1 {
+                (1..=3)
+                    .map(|i| ContentBlock::ToolUse {
+                        id: format!("read-{}", i),
+                        name: "slow_read".to_string(),
+                        // Later calls finish first, so completion order != call order
+                        input: serde_json::json!({ "delay_ms": 120 - i * 40 }),
+                    })
+                    .collect()
+            } else {
+                if let Some(last) = request.messages.last() {
+                    *self.result_order.lock().unwrap() = last
+                        .content
+                        .iter()
+                        .filter_map(|b| match b {
+                            ContentBlock::ToolResult { tool_use_id, .. } => Some(tool_use_id.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                }
+                vec![ContentBlock::Text { text: "done".to_string() }]
+            };
+
+            Ok(LLMResponse {
+                id: format!("batch-{}", call_num),
+                model: "mock-model".to_string(),
+                content,
+                stop_reason: Some(if call_num == 1 { StopReason::ToolUse } else { StopReason::EndTurn }),
+                usage: TokenUsage {
+                    input_tokens: 10,
+                    output_tokens: 10,
+                    reasoning_tokens: 0,
+                },
+            })
+        }
+
+        async fn stream(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<ProviderStream> {
+            let response = self.complete(request).await?;
+            Ok(stream_from_response(response))
+        }
+
+        fn name(&self) -> &str {
+            "mock-batch"
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["mock-model".to_string()]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            Some(4096)
+        }
+
+        fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+            0.001
+        }
+    }
+
+    /// Read-only tool that sleeps and tracks how many calls overlap
+    struct SlowReadTool {
+        active: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl crate::brain::tools::Tool for SlowReadTool {
+        fn name(&self) -> &str {
+            "slow_read"
+        }
+
+        fn description(&self) -> &str {
+            "Slow read-only tool"
+        }
+
+        fn input_schema(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "object" })
+        }
+
+        fn capabilities(&self) -> Vec<crate::brain::tools::ToolCapability> {
+            vec![crate::brain::tools::ToolCapability::ReadFiles]
+        }
+
+        async fn execute(
+            &self,
+            input: serde_json::Value,
+            _context: &crate::brain::tools::ToolExecutionContext,
+        ) -> crate::brain::tools::Result<crate::brain::tools::ToolResult> {
+            use std::sync::atomic::Ordering;
+
+            let now_active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now_active, Ordering::SeqCst);
+            let delay = input["delay_ms"].as_u64().unwrap_or(10);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(crate::brain::tools::ToolResult::success("read".to_string()))
+        }
+    }
+
+    async fn run_batch(max_concurrent: usize) -> (usize, Vec<String>, Vec<String>) {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+
+        let result_order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let provider = Arc::new(BatchToolProvider {
+            call_count: std::sync::Mutex::new(0),
+            result_order: result_order.clone(),
+        });
+
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(SlowReadTool {
+            active: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            peak: peak.clone(),
+        }));
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        let progress: ProgressCallback = Arc::new(move |event| {
+            let label = match event {
+                ProgressEvent::ToolStarted { .. } => "started",
+                ProgressEvent::ToolCompleted { .. } => "completed",
+                _ => return,
+            };
+            events_clone.lock().unwrap().push(label.to_string());
+        });
+
+        let agent_service = AgentService::new(provider, context.clone())
+            .with_tool_registry(Arc::new(registry))
+            .with_max_concurrent_tools(max_concurrent)
+            .with_progress_callback(Some(progress));
+
+        let session = SessionService::new(context)
+            .create_session(Some("Batch".to_string()))
+            .await
+            .unwrap();
+        agent_service
+            .send_message_with_tools(session.id, "read three files".to_string(), None)
+            .await
+            .unwrap();
+
+        let order = result_order.lock().unwrap().clone();
+        let events = events.lock().unwrap().clone();
+        (peak.load(std::sync::atomic::Ordering::SeqCst), order, events)
+    }
+
+    #[tokio::test]
+    async fn test_read_only_tools_run_concurrently_in_order() {
+        let (peak, order, events) = run_batch(4).await;
+
+        assert_eq!(peak, 3);
+        assert_eq!(order, vec!["read-1", "read-2", "read-3"]);
+        assert_eq!(events.iter().filter(|e| *e == "started").count(), 3);
+        assert_eq!(events.iter().filter(|e| *e == "completed").count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_one_runs_tools_sequentially() {
+        let (peak, order, _) = run_batch(1).await;
+
+        assert_eq!(peak, 1);
+        assert_eq!(order, vec!["read-1", "read-2", "read-3"]);
+    }
+
     #[tokio::test]
     async fn test_message_queue_injection_between_tool_calls() {
         let db = Database::connect_in_memory().await.unwrap();
@@ -140,61 +140,67 @@ impl AnthropicProvider {
 
     /// Handle API error response
     async fn handle_error(&self, response: reqwest::Response) -> ProviderError {
-        let status = response.status().as_u16();
-
-        // Extract Retry-After header for rate limits
-        let retry_after = response.headers().get("retry-after").and_then(|v| {
-            v.to_str().ok().and_then(|s| {
-                // Retry-After can be either seconds or HTTP date
-                // Try parsing as seconds first
-                s.parse::<u64>().ok()
-            })
-        });
-
-        // Try to parse error body
-        if let Ok(error_body) = response.json::<AnthropicError>().await {
-            let message = if status == 429 {
-                // Enhance rate limit error message
-                if let Some(secs) = retry_after {
-                    format!(
-                        "{} (retry after {} seconds)",
-                        error_body.error.message, secs
-                    )
-                } else {
-                    format!(
-                        "{} (rate limited, please retry later)",
-                        error_body.error.message
-                    )
-                }
-            } else {
-                error_body.error.message
-            };
+        anthropic_error(response).await
+    }
+}
 
-            return if status == 429 {
-                ProviderError::RateLimitExceeded(message)
+/// Map an Anthropic Messages API error response to a `ProviderError`.
+/// Shared with the Vertex provider (Claude on Vertex returns the same shape).
+pub(crate) async fn anthropic_error(response: reqwest::Response) -> ProviderError {
+    let status = response.status().as_u16();
+
+    // Extract Retry-After header for rate limits
+    let retry_after = response.headers().get("retry-after").and_then(|v| {
+        v.to_str().ok().and_then(|s| {
+            // Retry-After can be either seconds or HTTP date
+            // Try parsing as seconds first
+            s.parse::<u64>().ok()
+        })
+    });
+
+    // Try to parse error body
+    if let Ok(error_body) = response.json::<AnthropicError>().await {
+        let message = if status == 429 {
+            // Enhance rate limit error message
+            if let Some(secs) = retry_after {
+                format!(
+                    "{} (retry after {} seconds)",
+                    error_body.error.message, secs
+                )
             } else {
-                ProviderError::ApiError {
-                    status,
-                    message,
-                    error_type: Some(error_body.error.error_type),
-                }
-            };
-        }
+                format!(
+                    "{} (rate limited, please retry later)",
+                    error_body.error.message
+                )
+            }
+        } else {
+            error_body.error.message
+        };
 
-        // Fallback error
-        if status == 429 {
-            let message = if let Some(secs) = retry_after {
-                format!("Rate limit exceeded (retry after {} seconds)", secs)
-            } else {
-                "Rate limit exceeded, please retry later".to_string()
-            };
+        return if status == 429 {
             ProviderError::RateLimitExceeded(message)
         } else {
             ProviderError::ApiError {
                 status,
-                message: "Unknown error".to_string(),
-                error_type: None,
+                message,
+                error_type: Some(error_body.error.error_type),
             }
+        };
+    }
+
+    // Fallback error
+    if status == 429 {
+        let message = if let Some(secs) = retry_after {
+            format!("Rate limit exceeded (retry after {} seconds)", secs)
+        } else {
+            "Rate limit exceeded, please retry later".to_string()
+        };
+        ProviderError::RateLimitExceeded(message)
+    } else {
+        ProviderError::ApiError {
+            status,
+            message: "Unknown error".to_string(),
+            error_type: None,
         }
     }
 }
@@ -294,58 +300,7 @@ impl Provider for AnthropicProvider {
         )
         .await?;
 
-        // Parse Server-Sent Events stream with cross-chunk buffering.
-        // TCP chunks can split SSE events, so we buffer partial lines.
-        let byte_stream = response.bytes_stream();
-        let buffer = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
-
-        let event_stream = byte_stream
-            .map(
-                move |chunk_result| -> Vec<std::result::Result<StreamEvent, ProviderError>> {
-                    match chunk_result {
-                        Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
-                        Ok(chunk) => {
-                            let text = String::from_utf8_lossy(&chunk);
-                            let mut buf = buffer.lock().expect("SSE buffer lock poisoned");
-                            buf.push_str(&text);
-
-                            let mut events = Vec::new();
-
-                            // Process complete lines (terminated by \n)
-                            while let Some(newline_pos) = buf.find('\n') {
-                                let line = buf[..newline_pos].trim().to_string();
-                                buf.drain(..=newline_pos);
-
-                                if let Some(json_str) = line.strip_prefix("data: ") {
-                                    if json_str == "[DONE]" {
-                                        continue;
-                                    }
-                                    match serde_json::from_str::<StreamEvent>(json_str) {
-                                        Ok(event) => events.push(Ok(event)),
-                                        Err(e) => {
-                                            tracing::warn!(
-                                                "Failed to parse SSE event JSON: {}. Data: {}",
-                                                e,
-                                                json_str.chars().take(200).collect::<String>()
-                                            );
-                                            // Don't propagate parse errors for individual events
-                                        }
-                                    }
-                                }
-                            }
-
-                            if events.is_empty() {
-                                vec![Ok(StreamEvent::Ping)]
-                            } else {
-                                events
-                            }
-                        }
-                    }
-                },
-            )
-            .flat_map(futures::stream::iter);
-
-        Ok(Box::pin(event_stream))
+        Ok(anthropic_sse_stream(response))
     }
 
     fn supports_streaming(&self) -> bool {
@@ -463,6 +418,63 @@ impl Provider for AnthropicProvider {
     }
 }
 
+/// Parse an Anthropic Messages SSE body into our stream events.
+/// Shared with the Vertex provider (`streamRawPredict` emits the same events).
+pub(crate) fn anthropic_sse_stream(response: reqwest::Response) -> ProviderStream {
+    // Parse Server-Sent Events stream with cross-chunk buffering.
+    // TCP chunks can split SSE events, so we buffer partial lines.
+    let byte_stream = response.bytes_stream();
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+
+    let event_stream = byte_stream
+        .map(
+            move |chunk_result| -> Vec<std::result::Result<StreamEvent, ProviderError>> {
+                match chunk_result {
+                    Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+                    Ok(chunk) => {
+                        let text = String::from_utf8_lossy(&chunk);
+                        let mut buf = buffer.lock().expect("SSE buffer lock poisoned");
+                        buf.push_str(&text);
+
+                        let mut events = Vec::new();
+
+                        // Process complete lines (terminated by \n)
+                        while let Some(newline_pos) = buf.find('\n') {
+                            let line = buf[..newline_pos].trim().to_string();
+                            buf.drain(..=newline_pos);
+
+                            if let Some(json_str) = line.strip_prefix("data: ") {
+                                if json_str == "[DONE]" {
+                                    continue;
+                                }
+                                match serde_json::from_str::<StreamEvent>(json_str) {
+                                    Ok(event) => events.push(Ok(event)),
+                                    Err(e) => {
+                                        tracing::warn!(
+                                            "Failed to parse SSE event JSON: {}. Data: {}",
+                                            e,
+                                            json_str.chars().take(200).collect::<String>()
+                                        );
+                                        // Don't propagate parse errors for individual events
+                                    }
+                                }
+                            }
+                        }
+
+                        if events.is_empty() {
+                            vec![Ok(StreamEvent::Ping)]
+                        } else {
+                            events
+                        }
+                    }
+                }
+            },
+        )
+        .flat_map(futures::stream::iter);
+
+    Box::pin(event_stream)
+}
+
 // Anthropic-specific request format
 #[derive(Debug, Serialize)]
 struct AnthropicRequest {
@@ -0,0 +1,504 @@
+//! AWS credential and region resolution
+//!
+//! Follows the same lookup order as the AWS CLI for the cases we support:
+//! 1. `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`
+//! 2. `~/.aws/credentials` (or `AWS_SHARED_CREDENTIALS_FILE`), profile from `AWS_PROFILE`
+//! 3. `credential_process` for the profile in `~/.aws/config` (SSO, assumed roles)
+//! 4. ECS container credentials, then EC2 instance metadata (IMDSv2)
+//!
+//! [`CredentialsProvider`] re-resolves them before they expire, so temporary
+//! sessions keep working through a long run.
+//!
+//! Region comes from `AWS_REGION`, `AWS_DEFAULT_REGION`, then `~/.aws/config`
+//! (or `AWS_CONFIG_FILE`), defaulting to `us-east-1`.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEFAULT_REGION: &str = "us-east-1";
+/// Refresh temporary credentials this long before they expire
+const EXPIRY_MARGIN_SECS: i64 = 5 * 60;
+/// Credentials without an expiry are re-read this often, so files rewritten
+/// by `aws sso login` or a refresh script are picked up
+const RELOAD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const METADATA_TIMEOUT: Duration = Duration::from_secs(2);
+const ECS_ENDPOINT: &str = "http://169.254.170.2";
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+
+/// AWS credentials used for SigV4 signing
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    /// Set for temporary credentials (process, container, instance metadata)
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"[REDACTED]")
+            .field("session_token", &self.session_token.as_ref().map(|_| "[REDACTED]"))
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl AwsCredentials {
+    /// Resolve credentials from env vars, the shared credentials file, then
+    /// the profile's `credential_process`. Blocking: may run a command.
+    pub fn load() -> Option<Self> {
+        if let Some(creds) = Self::from_env() {
+            tracing::debug!("AWS credentials loaded from environment");
+            return Some(creds);
+        }
+
+        if let Some(path) = std::env::var("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .ok()
+            .or_else(|| dirs::home_dir().map(|h| h.join(".aws").join("credentials")))
+            && let Some(creds) = Self::from_profile_file(&path, &profile_name())
+        {
+            tracing::debug!("AWS credentials loaded from {:?} [{}]", path, profile_name());
+            return Some(creds);
+        }
+
+        let command = config_file_path()
+            .and_then(|p| profile_setting(&p, &profile_name(), "credential_process"))?;
+        match Self::from_process(&command) {
+            Ok(creds) => {
+                tracing::debug!(
+                    "AWS credentials loaded from credential_process [{}]",
+                    profile_name()
+                );
+                Some(creds)
+            }
+            Err(e) => {
+                tracing::warn!("AWS credential_process failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Read credentials from the standard env vars
+    pub fn from_env() -> Option<Self> {
+        let access_key_id = std::env::var("AWS_ACCESS_KEY_ID").ok().filter(|v| !v.is_empty())?;
+        let secret_access_key = std::env::var("AWS_SECRET_ACCESS_KEY")
+            .ok()
+            .filter(|v| !v.is_empty())?;
+        Some(Self {
+            access_key_id,
+            secret_access_key,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok().filter(|v| !v.is_empty()),
+            expires_at: None,
+        })
+    }
+
+    /// Read credentials for `profile` from an INI-style credentials file
+    pub fn from_profile_file(path: &Path, profile: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let sections = parse_ini(&content);
+        let section = sections.get(profile)?;
+        Some(Self {
+            access_key_id: section.get("aws_access_key_id")?.clone(),
+            secret_access_key: section.get("aws_secret_access_key")?.clone(),
+            session_token: section.get("aws_session_token").cloned(),
+            expires_at: None,
+        })
+    }
+
+    /// Run a `credential_process` command and parse its JSON output
+    pub fn from_process(command: &str) -> Result<Self, String> {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let output = std::process::Command::new(shell)
+            .args([flag, command])
+            .output()
+            .map_err(|e| format!("failed to run '{}': {}", command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "'{}' exited with {}: {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Self::from_json(&output.stdout)
+    }
+
+    /// Parse the JSON shape shared by `credential_process`, the ECS container
+    /// endpoint and instance metadata
+    fn from_json(bytes: &[u8]) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Temporary {
+            access_key_id: String,
+            secret_access_key: String,
+            #[serde(default, alias = "Token")]
+            session_token: Option<String>,
+            #[serde(default)]
+            expiration: Option<DateTime<Utc>>,
+        }
+
+        let t: Temporary = serde_json::from_slice(bytes)
+            .map_err(|e| format!("invalid credentials JSON: {}", e))?;
+        Ok(Self {
+            access_key_id: t.access_key_id,
+            secret_access_key: t.secret_access_key,
+            session_token: t.session_token.filter(|t| !t.is_empty()),
+            expires_at: t.expiration,
+        })
+    }
+
+    /// Fetch credentials from the ECS container endpoint when its env vars are
+    /// set, otherwise from EC2 instance metadata
+    async fn from_metadata(client: &reqwest::Client) -> Option<Self> {
+        let result = if let Ok(uri) = std::env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+            fetch_json(client.get(format!("{}{}", ECS_ENDPOINT, uri))).await
+        } else if let Ok(url) = std::env::var("AWS_CONTAINER_CREDENTIALS_FULL_URI") {
+            let mut request = client.get(url);
+            if let Ok(token) = std::env::var("AWS_CONTAINER_AUTHORIZATION_TOKEN") {
+                request = request.header(reqwest::header::AUTHORIZATION, token);
+            }
+            fetch_json(request).await
+        } else {
+            from_imds(client).await
+        };
+
+        match result {
+            Ok(creds) => {
+                tracing::debug!("AWS credentials loaded from instance metadata");
+                Some(creds)
+            }
+            Err(e) => {
+                tracing::debug!("No AWS credentials from instance metadata: {}", e);
+                None
+            }
+        }
+    }
+}
+
+async fn fetch_json(request: reqwest::RequestBuilder) -> Result<AwsCredentials, String> {
+    let response = request
+        .timeout(METADATA_TIMEOUT)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    AwsCredentials::from_json(&bytes)
+}
+
+/// IMDSv2: session token, then the instance role's credentials
+async fn from_imds(client: &reqwest::Client) -> Result<AwsCredentials, String> {
+    let text = |request: reqwest::RequestBuilder| async move {
+        request
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?
+            .text()
+            .await
+            .map_err(|e| e.to_string())
+    };
+
+    let token = text(
+        client
+            .put(format!("{}/latest/api/token", IMDS_ENDPOINT))
+            .header("x-aws-ec2-metadata-token-ttl-seconds", "21600"),
+    )
+    .await?;
+    let roles_url = format!(
+        "{}/latest/meta-data/iam/security-credentials/",
+        IMDS_ENDPOINT
+    );
+    let roles = text(
+        client
+            .get(&roles_url)
+            .header("x-aws-ec2-metadata-token", &token),
+    )
+    .await?;
+    let role = roles
+        .lines()
+        .next()
+        .filter(|r| !r.is_empty())
+        .ok_or("no instance role attached")?;
+    fetch_json(
+        client
+            .get(format!("{}{}", roles_url, role))
+            .header("x-aws-ec2-metadata-token", &token),
+    )
+    .await
+}
+
+/// Hands out current credentials, re-resolving them from the lookup chain
+/// shortly before they expire (or periodically when they carry no expiry).
+pub struct CredentialsProvider {
+    /// Fixed credentials are never refreshed
+    fixed: Option<AwsCredentials>,
+    cached: tokio::sync::Mutex<Option<(AwsCredentials, Instant)>>,
+    client: reqwest::Client,
+}
+
+impl CredentialsProvider {
+    /// Use `credentials` as-is for every request
+    pub fn fixed(credentials: AwsCredentials) -> Self {
+        Self {
+            fixed: Some(credentials),
+            cached: tokio::sync::Mutex::new(None),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Resolve from the lookup chain, seeded with `initial` if already loaded
+    pub fn chain(initial: Option<AwsCredentials>) -> Self {
+        Self {
+            fixed: None,
+            cached: tokio::sync::Mutex::new(initial.map(|c| (c, Instant::now()))),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Credentials to sign the next request with
+    pub async fn credentials(&self) -> Result<AwsCredentials, String> {
+        if let Some(creds) = &self.fixed {
+            return Ok(creds.clone());
+        }
+
+        let mut cached = self.cached.lock().await;
+        if let Some((creds, loaded_at)) = cached.as_ref()
+            && !needs_refresh(creds, loaded_at.elapsed(), Utc::now())
+        {
+            return Ok(creds.clone());
+        }
+
+        let loaded = match tokio::task::spawn_blocking(AwsCredentials::load).await {
+            Ok(Some(creds)) => Some(creds),
+            _ => AwsCredentials::from_metadata(&self.client).await,
+        };
+        match (loaded, cached.take()) {
+            (Some(creds), _) => {
+                *cached = Some((creds.clone(), Instant::now()));
+                Ok(creds)
+            }
+            // Keep signing with the old ones until they actually expire
+            (None, Some((creds, loaded_at)))
+                if creds.expires_at.is_none_or(|at| at > Utc::now()) =>
+            {
+                tracing::warn!("AWS credential refresh failed, reusing current credentials");
+                *cached = Some((creds.clone(), loaded_at));
+                Ok(creds)
+            }
+            (None, _) => Err(
+                "No AWS credentials found (env, ~/.aws/credentials, credential_process or instance metadata)"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+fn needs_refresh(creds: &AwsCredentials, age: Duration, now: DateTime<Utc>) -> bool {
+    match creds.expires_at {
+        Some(at) => (at - now).num_seconds() < EXPIRY_MARGIN_SECS,
+        None => age >= RELOAD_INTERVAL,
+    }
+}
+
+/// Resolve the AWS region to call
+pub fn resolve_region() -> String {
+    if let Some(region) = std::env::var("AWS_REGION")
+        .or_else(|_| std::env::var("AWS_DEFAULT_REGION"))
+        .ok()
+        .filter(|r| !r.is_empty())
+    {
+        return region;
+    }
+
+    config_file_path()
+        .and_then(|p| region_from_config_file(&p, &profile_name()))
+        .unwrap_or_else(|| DEFAULT_REGION.to_string())
+}
+
+fn config_file_path() -> Option<PathBuf> {
+    std::env::var("AWS_CONFIG_FILE")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".aws").join("config")))
+}
+
+/// Read `region` for `profile` from `~/.aws/config`
+pub fn region_from_config_file(path: &Path, profile: &str) -> Option<String> {
+    profile_setting(path, profile, "region")
+}
+
+/// Read `key` for `profile` from `~/.aws/config`, where non-default profiles
+/// are written as `[profile name]`
+fn profile_setting(path: &Path, profile: &str, key: &str) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let sections = parse_ini(&content);
+    let section = if profile == "default" {
+        "default".to_string()
+    } else {
+        format!("profile {}", profile)
+    };
+    sections
+        .get(&section)
+        .or_else(|| sections.get(profile))
+        .and_then(|s| s.get(key))
+        .cloned()
+}
+
+fn profile_name() -> String {
+    std::env::var("AWS_PROFILE")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Minimal INI parser: `[section]` headers and `key = value` lines
+fn parse_ini(content: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim().to_string();
+            sections.entry(name.clone()).or_default();
+            current = Some(name);
+        } else if let Some((key, value)) = line.split_once('=')
+            && let Some(section) = current.as_ref()
+        {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_profile_file_parsing() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[default]\naws_access_key_id = AKIDDEFAULT\naws_secret_access_key = secret1\n\n\
+             # work account\n[work]\naws_access_key_id=AKIDWORK\naws_secret_access_key=secret2\naws_session_token=tok"
+        )
+        .unwrap();
+
+        let default = AwsCredentials::from_profile_file(file.path(), "default").unwrap();
+        assert_eq!(default.access_key_id, "AKIDDEFAULT");
+        assert!(default.session_token.is_none());
+
+        let work = AwsCredentials::from_profile_file(file.path(), "work").unwrap();
+        assert_eq!(work.secret_access_key, "secret2");
+        assert_eq!(work.session_token.as_deref(), Some("tok"));
+
+        assert!(AwsCredentials::from_profile_file(file.path(), "missing").is_none());
+    }
+
+    #[test]
+    fn test_region_from_config_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "[default]\nregion = us-west-2\n[profile work]\nregion = eu-central-1"
+        )
+        .unwrap();
+
+        assert_eq!(
+            region_from_config_file(file.path(), "default").as_deref(),
+            Some("us-west-2")
+        );
+        assert_eq!(
+            region_from_config_file(file.path(), "work").as_deref(),
+            Some("eu-central-1")
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let creds = AwsCredentials {
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "super-secret".to_string(),
+            session_token: Some("token".to_string()),
+            expires_at: None,
+        };
+        let debug = format!("{:?}", creds);
+        assert!(!debug.contains("super-secret"));
+        assert!(!debug.contains("\"token\""));
+    }
+
+    #[test]
+    fn test_temporary_credentials_json() {
+        // credential_process output
+        let creds = AwsCredentials::from_json(
+            br#"{"Version":1,"AccessKeyId":"ASIA1","SecretAccessKey":"s","SessionToken":"t","Expiration":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(creds.session_token.as_deref(), Some("t"));
+        assert_eq!(
+            creds.expires_at.map(|t| t.to_rfc3339()).as_deref(),
+            Some("2026-01-01T00:00:00+00:00")
+        );
+
+        // Instance metadata names the session token "Token"
+        let creds = AwsCredentials::from_json(
+            br#"{"Code":"Success","AccessKeyId":"ASIA2","SecretAccessKey":"s","Token":"t2","Expiration":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert_eq!(creds.session_token.as_deref(), Some("t2"));
+
+        assert!(AwsCredentials::from_json(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Utc::now();
+        let mut creds = AwsCredentials {
+            access_key_id: "AKID".to_string(),
+            secret_access_key: "s".to_string(),
+            session_token: None,
+            expires_at: None,
+        };
+        assert!(!needs_refresh(&creds, Duration::from_secs(60), now));
+        assert!(needs_refresh(&creds, RELOAD_INTERVAL, now));
+
+        creds.expires_at = Some(now + chrono::Duration::hours(1));
+        assert!(!needs_refresh(&creds, RELOAD_INTERVAL * 2, now));
+        creds.expires_at = Some(now + chrono::Duration::minutes(2));
+        assert!(needs_refresh(&creds, Duration::ZERO, now));
+    }
+
+    #[tokio::test]
+    async fn test_fixed_provider_never_reloads() {
+        let provider = CredentialsProvider::fixed(AwsCredentials {
+            access_key_id: "AKIDFIXED".to_string(),
+            secret_access_key: "s".to_string(),
+            session_token: None,
+            expires_at: Some(Utc::now() - chrono::Duration::hours(1)),
+        });
+        let creds = provider.credentials().await.unwrap();
+        assert_eq!(creds.access_key_id, "AKIDFIXED");
+    }
+}
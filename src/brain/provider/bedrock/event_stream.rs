@@ -0,0 +1,242 @@
+//! AWS event-stream binary framing (`application/vnd.amazon.eventstream`)
+//!
+//! Each message is:
+//! `total_len:u32 | headers_len:u32 | prelude_crc:u32 | headers | payload | message_crc:u32`
+//! with big-endian integers and CRC32 (IEEE) checksums. Bedrock streams one
+//! JSON payload per message, identified by the `:event-type` header.
+
+use std::collections::HashMap;
+
+const PRELUDE_LEN: usize = 12;
+const CRC_LEN: usize = 4;
+/// Refuse absurd frames rather than buffering forever on a corrupt stream
+const MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// One decoded event-stream message
+#[derive(Debug, Clone)]
+pub struct EventStreamMessage {
+    /// String-valued headers (`:event-type`, `:message-type`, ...)
+    pub headers: HashMap<String, String>,
+    pub payload: Vec<u8>,
+}
+
+impl EventStreamMessage {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// Incremental decoder — feed raw bytes, pull complete messages
+#[derive(Default)]
+pub struct EventStreamDecoder {
+    buf: Vec<u8>,
+    /// Set after a corrupt frame; later bytes can't be re-framed
+    failed: bool,
+}
+
+impl EventStreamDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        if !self.failed {
+            self.buf.extend_from_slice(bytes);
+        }
+    }
+
+    /// Decode the next complete message, `Ok(None)` if more bytes are needed.
+    ///
+    /// A corrupt frame leaves no way to find the next boundary, so after the
+    /// first error the buffer is dropped and the decoder yields nothing more.
+    pub fn next_message(&mut self) -> Result<Option<EventStreamMessage>, String> {
+        let result = self.decode();
+        if result.is_err() {
+            self.buf.clear();
+            self.failed = true;
+        }
+        result
+    }
+
+    fn decode(&mut self) -> Result<Option<EventStreamMessage>, String> {
+        if self.buf.len() < PRELUDE_LEN {
+            return Ok(None);
+        }
+
+        let total_len = read_u32(&self.buf[0..4]) as usize;
+        let headers_len = read_u32(&self.buf[4..8]) as usize;
+        let prelude_crc = read_u32(&self.buf[8..12]);
+
+        if crc32(&self.buf[0..8]) != prelude_crc {
+            return Err("event-stream prelude checksum mismatch".to_string());
+        }
+        if total_len > MAX_MESSAGE_LEN || total_len < PRELUDE_LEN + headers_len + CRC_LEN {
+            return Err(format!("invalid event-stream message length {}", total_len));
+        }
+        if self.buf.len() < total_len {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = self.buf.drain(..total_len).collect();
+        let message_crc = read_u32(&frame[total_len - CRC_LEN..]);
+        if crc32(&frame[..total_len - CRC_LEN]) != message_crc {
+            return Err("event-stream message checksum mismatch".to_string());
+        }
+
+        let headers = parse_headers(&frame[PRELUDE_LEN..PRELUDE_LEN + headers_len])?;
+        let payload = frame[PRELUDE_LEN + headers_len..total_len - CRC_LEN].to_vec();
+
+        Ok(Some(EventStreamMessage { headers, payload }))
+    }
+}
+
+fn parse_headers(mut bytes: &[u8]) -> Result<HashMap<String, String>, String> {
+    let mut headers = HashMap::new();
+
+    while !bytes.is_empty() {
+        let name_len = bytes[0] as usize;
+        let name = bytes
+            .get(1..1 + name_len)
+            .ok_or("truncated header name")?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        bytes = &bytes[1 + name_len..];
+
+        let value_type = *bytes.first().ok_or("truncated header type")?;
+        bytes = &bytes[1..];
+
+        // Fixed-size types are skipped; only strings are surfaced
+        let skip = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = bytes.get(0..2).map(read_u16).ok_or("truncated header length")? as usize;
+                let value = bytes.get(2..2 + len).ok_or("truncated header value")?;
+                if value_type == 7 {
+                    headers.insert(name, String::from_utf8_lossy(value).into_owned());
+                }
+                bytes = &bytes[2 + len..];
+                continue;
+            }
+            other => return Err(format!("unknown event-stream header type {}", other)),
+        };
+        bytes = bytes.get(skip..).ok_or("truncated header value")?;
+    }
+
+    Ok(headers)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn read_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+/// CRC32 (IEEE 802.3, reflected polynomial 0xEDB88320)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Encode a message with string headers (test fixtures only)
+#[cfg(test)]
+pub(crate) fn encode_message(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+    let mut header_bytes = Vec::new();
+    for (name, value) in headers {
+        header_bytes.push(name.len() as u8);
+        header_bytes.extend_from_slice(name.as_bytes());
+        header_bytes.push(7);
+        header_bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        header_bytes.extend_from_slice(value.as_bytes());
+    }
+
+    let total_len = (PRELUDE_LEN + header_bytes.len() + payload.len() + CRC_LEN) as u32;
+    let mut out = Vec::with_capacity(total_len as usize);
+    out.extend_from_slice(&total_len.to_be_bytes());
+    out.extend_from_slice(&(header_bytes.len() as u32).to_be_bytes());
+    let prelude_crc = crc32(&out);
+    out.extend_from_slice(&prelude_crc.to_be_bytes());
+    out.extend_from_slice(&header_bytes);
+    out.extend_from_slice(payload);
+    let message_crc = crc32(&out);
+    out.extend_from_slice(&message_crc.to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let frame = encode_message(
+            &[(":event-type", "contentBlockDelta"), (":message-type", "event")],
+            br#"{"contentBlockIndex":0}"#,
+        );
+
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+        let msg = decoder.next_message().unwrap().unwrap();
+        assert_eq!(msg.header(":event-type"), Some("contentBlockDelta"));
+        assert_eq!(msg.payload, br#"{"contentBlockIndex":0}"#);
+        assert!(decoder.next_message().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_split_across_chunks() {
+        let mut bytes = encode_message(&[(":event-type", "a")], b"one");
+        bytes.extend(encode_message(&[(":event-type", "b")], b"two"));
+
+        let mut decoder = EventStreamDecoder::new();
+        let mut seen = Vec::new();
+        for chunk in bytes.chunks(5) {
+            decoder.push(chunk);
+            while let Some(msg) = decoder.next_message().unwrap() {
+                seen.push(msg.header(":event-type").unwrap().to_string());
+            }
+        }
+        assert_eq!(seen, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_corrupt_checksum_is_rejected() {
+        let mut frame = encode_message(&[(":event-type", "a")], b"payload");
+        let last = frame.len() - 1;
+        frame[last] ^= 0xFF;
+
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+        assert!(decoder.next_message().is_err());
+    }
+
+    #[test]
+    fn test_corrupt_prelude_reports_one_error() {
+        let mut frame = encode_message(&[(":event-type", "a")], b"payload");
+        frame[8] ^= 0xFF;
+
+        let mut decoder = EventStreamDecoder::new();
+        decoder.push(&frame);
+        assert!(decoder.next_message().is_err());
+
+        // The rest of the stream is dropped instead of failing frame after frame
+        decoder.push(&encode_message(&[(":event-type", "b")], b"next"));
+        assert!(decoder.next_message().unwrap().is_none());
+    }
+}
@@ -0,0 +1,1085 @@
+//! AWS Bedrock Provider Implementation
+//!
+//! Calls Claude models through the Bedrock Runtime Converse API
+//! (`/converse` and the event-stream `/converse-stream`), signing each request
+//! with SigV4 using credentials from the AWS env vars, profile files,
+//! `credential_process` or instance metadata, refreshed before they expire.
+//!
+//! Only Anthropic Claude model IDs are accepted — other Bedrock model families
+//! are rejected before any request is sent.
+//!
+//! ## Supported Models
+//! - anthropic.claude-opus-4-1-20250805-v1:0
+//! - anthropic.claude-sonnet-4-5-20250929-v1:0
+//! - anthropic.claude-sonnet-4-20250514-v1:0
+//! - anthropic.claude-haiku-4-5-20251001-v1:0
+//! - anthropic.claude-3-7-sonnet-20250219-v1:0
+//! - anthropic.claude-3-5-sonnet-20241022-v2:0
+//! - anthropic.claude-3-5-haiku-20241022-v1:0
+//! - anthropic.claude-3-haiku-20240307-v1:0
+//!
+//! Cross-region inference profile IDs (`us.`, `eu.`, `apac.`, `global.`
+//! prefixes) are accepted and priced like the base model. Newer models can only
+//! be invoked on-demand through a profile, so the default model is one.
+
+mod credentials;
+mod event_stream;
+mod sigv4;
+
+pub use credentials::{AwsCredentials, CredentialsProvider, resolve_region};
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use async_trait::async_trait;
+use event_stream::EventStreamDecoder;
+use futures::stream::StreamExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+const BEDROCK_SERVICE: &str = "bedrock";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+/// Claude Sonnet 4.5 has no on-demand throughput under its base model ID
+const DEFAULT_MODEL: &str = "global.anthropic.claude-sonnet-4-5-20250929-v1:0";
+
+/// AWS Bedrock provider (Claude only)
+#[derive(Clone)]
+pub struct BedrockProvider {
+    credentials: Arc<CredentialsProvider>,
+    region: String,
+    endpoint: String,
+    client: Client,
+    custom_default_model: Option<String>,
+}
+
+impl BedrockProvider {
+    /// Create a provider for the regional Bedrock Runtime endpoint
+    pub fn new(credentials: CredentialsProvider, region: String) -> Self {
+        let endpoint = format!("https://bedrock-runtime.{}.amazonaws.com", region);
+        Self::with_endpoint(credentials, region, endpoint)
+    }
+
+    /// Create with a custom endpoint (VPC endpoints, fixture servers)
+    pub fn with_endpoint(
+        credentials: CredentialsProvider,
+        region: String,
+        endpoint: String,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .pool_idle_timeout(DEFAULT_POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(2)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            credentials: Arc::new(credentials),
+            region,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            client,
+            custom_default_model: None,
+        }
+    }
+
+    /// Set custom default model
+    pub fn with_default_model(mut self, model: String) -> Self {
+        self.custom_default_model = Some(model);
+        self
+    }
+
+    /// Reject anything that isn't a Claude model (security policy)
+    fn ensure_claude(model: &str) -> Result<()> {
+        if model.contains("anthropic.claude") {
+            Ok(())
+        } else {
+            Err(ProviderError::InvalidRequest(format!(
+                "Bedrock is restricted to Anthropic Claude models, got '{}'",
+                model
+            )))
+        }
+    }
+
+    fn url(&self, model: &str, action: &str) -> Result<reqwest::Url> {
+        let url = format!(
+            "{}/model/{}/{}",
+            self.endpoint,
+            urlencoding::encode(model),
+            action
+        );
+        reqwest::Url::parse(&url)
+            .map_err(|e| ProviderError::Internal(format!("Invalid Bedrock URL {}: {}", url, e)))
+    }
+
+    /// Build a signed POST request with the current credentials
+    async fn signed_post(
+        &self,
+        url: reqwest::Url,
+        body: Vec<u8>,
+        accept: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let credentials = self
+            .credentials
+            .credentials()
+            .await
+            .map_err(ProviderError::Internal)?;
+        let signed = sigv4::sign_request(
+            &credentials,
+            &self.region,
+            BEDROCK_SERVICE,
+            "POST",
+            &url,
+            &[("content-type", "application/json"), ("accept", accept)],
+            &body,
+            chrono::Utc::now(),
+        );
+
+        let mut builder = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::ACCEPT, accept);
+        for (name, value) in signed {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.body(body))
+    }
+
+    /// Convert our generic request to a Converse request body
+    fn to_converse_request(&self, request: &LLMRequest) -> ConverseRequest {
+        let mut system: Vec<SystemBlock> = request
+            .system
+            .iter()
+            .map(|text| SystemBlock { text: text.clone() })
+            .collect();
+        let mut messages: Vec<ConverseMessage> = Vec::new();
+
+        for msg in &request.messages {
+            let role = match msg.role {
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::System => {
+                    for block in &msg.content {
+                        if let ContentBlock::Text { text } = block {
+                            system.push(SystemBlock { text: text.clone() });
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let mut content = Vec::new();
+            for block in &msg.content {
+                match block {
+                    ContentBlock::Text { text } => {
+                        // Converse rejects empty text blocks
+                        if !text.is_empty() {
+                            content.push(serde_json::json!({ "text": text }));
+                        }
+                    }
+                    ContentBlock::Image { source } => match source {
+                        ImageSource::Base64 { media_type, data } => {
+                            let format = media_type.trim_start_matches("image/");
+                            let format = if format == "jpg" { "jpeg" } else { format };
+                            content.push(serde_json::json!({
+                                "image": { "format": format, "source": { "bytes": data } }
+                            }));
+                        }
+                        ImageSource::Url { url } => {
+                            tracing::warn!("Bedrock Converse does not accept image URLs, skipping {}", url);
+                        }
+                    },
+                    ContentBlock::ToolUse { id, name, input } => {
+                        content.push(serde_json::json!({
+                            "toolUse": { "toolUseId": id, "name": name, "input": input }
+                        }));
+                    }
+                    ContentBlock::ToolResult {
+                        tool_use_id,
+                        content: result,
+                        is_error,
+                    } => {
+                        let status = if is_error.unwrap_or(false) { "error" } else { "success" };
+                        content.push(serde_json::json!({
+                            "toolResult": {
+                                "toolUseId": tool_use_id,
+                                "content": [{ "text": result }],
+                                "status": status
+                            }
+                        }));
+                    }
+                }
+            }
+
+            if content.is_empty() {
+                continue;
+            }
+
+            // Converse requires alternating roles — fold consecutive same-role turns
+            match messages.last_mut() {
+                Some(last) if last.role == role => last.content.extend(content),
+                _ => messages.push(ConverseMessage {
+                    role: role.to_string(),
+                    content,
+                }),
+            }
+        }
+
+        let tool_config = request.tools.as_ref().filter(|t| !t.is_empty()).map(|tools| {
+            ToolConfig {
+                tools: tools
+                    .iter()
+                    .map(|tool| {
+                        serde_json::json!({
+                            "toolSpec": {
+                                "name": tool.name,
+                                "description": tool.description,
+                                "inputSchema": { "json": tool.input_schema }
+                            }
+                        })
+                    })
+                    .collect(),
+            }
+        });
+
+        ConverseRequest {
+            messages,
+            system,
+            inference_config: InferenceConfig {
+                max_tokens: Some(request.max_tokens.unwrap_or(16384)),
+                temperature: request.temperature,
+            },
+            tool_config,
+        }
+    }
+
+    /// Convert a Converse response to our generic format
+    #[allow(clippy::wrong_self_convention)]
+    fn from_converse_response(&self, response: ConverseResponse, model: &str) -> LLMResponse {
+        let content = response
+            .output
+            .message
+            .map(|m| m.content)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|block| {
+                if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
+                    (!text.is_empty()).then(|| ContentBlock::Text {
+                        text: text.to_string(),
+                    })
+                } else {
+                    block.get("toolUse").map(|tool_use| ContentBlock::ToolUse {
+                        id: tool_use["toolUseId"].as_str().unwrap_or_default().to_string(),
+                        name: tool_use["name"].as_str().unwrap_or_default().to_string(),
+                        input: tool_use
+                            .get("input")
+                            .cloned()
+                            .unwrap_or_else(|| serde_json::json!({})),
+                    })
+                }
+            })
+            .collect();
+
+        LLMResponse {
+            id: String::new(),
+            model: model.to_string(),
+            content,
+            stop_reason: map_stop_reason(&response.stop_reason),
+            usage: response.usage.into(),
+        }
+    }
+}
+
+fn map_stop_reason(reason: &str) -> Option<StopReason> {
+    match reason {
+        "end_turn" => Some(StopReason::EndTurn),
+        "tool_use" => Some(StopReason::ToolUse),
+        "max_tokens" => Some(StopReason::MaxTokens),
+        "stop_sequence" => Some(StopReason::StopSequence),
+        _ => None,
+    }
+}
+
+/// Strip cross-region profile prefixes so lookups use the base model ID
+fn base_model_id(model: &str) -> &str {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    for prefix in ["us.", "eu.", "apac.", "global."] {
+        if let Some(rest) = model.strip_prefix(prefix) {
+            return rest;
+        }
+    }
+    model
+}
+
+/// Map a Bedrock HTTP or stream exception to a `ProviderError`
+fn bedrock_error(status: u16, error_type: Option<&str>, message: String) -> ProviderError {
+    let error_type = error_type.map(|t| t.split(':').next().unwrap_or(t).to_string());
+    match (status, error_type.as_deref()) {
+        (429, _) | (_, Some("ThrottlingException")) => ProviderError::RateLimitExceeded(message),
+        (403, _) | (_, Some("AccessDeniedException")) => ProviderError::InvalidApiKey,
+        (_, Some("ModelTimeoutException")) => ProviderError::ApiError {
+            status: 504,
+            message,
+            error_type,
+        },
+        _ => ProviderError::ApiError {
+            status,
+            message,
+            error_type,
+        },
+    }
+}
+
+async fn handle_error(response: reqwest::Response) -> ProviderError {
+    let status = response.status().as_u16();
+    let error_type = response
+        .headers()
+        .get("x-amzn-errortype")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let message = response
+        .json::<BedrockErrorBody>()
+        .await
+        .map(|b| b.message)
+        .unwrap_or_else(|_| "Unknown error".to_string());
+
+    bedrock_error(status, error_type.as_deref(), message)
+}
+
+/// Translates Converse stream events into our `StreamEvent`s.
+///
+/// Converse only sends `contentBlockStart` for tool-use blocks; text blocks
+/// start implicitly with their first delta. Usage arrives in a trailing
+/// `metadata` event *after* `messageStop`, so the final `MessageDelta` and
+/// `MessageStop` are held back until then.
+#[derive(Default)]
+struct ConverseStreamState {
+    model: String,
+    started_blocks: std::collections::HashSet<usize>,
+    stop_reason: Option<StopReason>,
+    finished: bool,
+}
+
+impl ConverseStreamState {
+    fn new(model: String) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+
+    fn handle(&mut self, event_type: &str, payload: serde_json::Value) -> Vec<StreamEvent> {
+        let index = payload["contentBlockIndex"].as_u64().unwrap_or(0) as usize;
+
+        match event_type {
+            "messageStart" => vec![StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: String::new(),
+                    model: self.model.clone(),
+                    role: Role::Assistant,
+                    usage: TokenUsage {
+                        input_tokens: 0,
+                        output_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                },
+            }],
+            "contentBlockStart" => {
+                let Some(tool_use) = payload["start"].get("toolUse") else {
+                    return Vec::new();
+                };
+                self.started_blocks.insert(index);
+                vec![StreamEvent::ContentBlockStart {
+                    index,
+                    content_block: ContentBlock::ToolUse {
+                        id: tool_use["toolUseId"].as_str().unwrap_or_default().to_string(),
+                        name: tool_use["name"].as_str().unwrap_or_default().to_string(),
+                        input: serde_json::json!({}),
+                    },
+                }]
+            }
+            "contentBlockDelta" => {
+                let delta = &payload["delta"];
+                let mut events = Vec::new();
+                if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
+                    if self.started_blocks.insert(index) {
+                        events.push(StreamEvent::ContentBlockStart {
+                            index,
+                            content_block: ContentBlock::Text {
+                                text: String::new(),
+                            },
+                        });
+                    }
+                    events.push(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::TextDelta {
+                            text: text.to_string(),
+                        },
+                    });
+                } else if let Some(json) = delta["toolUse"].get("input").and_then(|i| i.as_str()) {
+                    events.push(StreamEvent::ContentBlockDelta {
+                        index,
+                        delta: ContentDelta::InputJsonDelta {
+                            partial_json: json.to_string(),
+                        },
+                    });
+                }
+                // reasoningContent deltas are not surfaced
+                events
+            }
+            "contentBlockStop" => vec![StreamEvent::ContentBlockStop { index }],
+            "messageStop" => {
+                self.stop_reason = payload["stopReason"].as_str().and_then(map_stop_reason);
+                Vec::new()
+            }
+            "metadata" => {
+                let usage = TokenUsage {
+                    input_tokens: payload["usage"]["inputTokens"].as_u64().unwrap_or(0) as u32,
+                    output_tokens: payload["usage"]["outputTokens"].as_u64().unwrap_or(0) as u32,
+                    reasoning_tokens: 0,
+                };
+                self.finish(usage)
+            }
+            other => {
+                tracing::debug!("Ignoring Bedrock stream event: {}", other);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Emit the closing events once (on `metadata`, or when the body ends)
+    fn finish(&mut self, usage: TokenUsage) -> Vec<StreamEvent> {
+        if self.finished {
+            return Vec::new();
+        }
+        self.finished = true;
+        vec![
+            StreamEvent::MessageDelta {
+                delta: MessageDelta {
+                    stop_reason: self.stop_reason.clone(),
+                    stop_sequence: None,
+                },
+                usage,
+            },
+            StreamEvent::MessageStop,
+        ]
+    }
+}
+
+#[async_trait]
+impl Provider for BedrockProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        Self::ensure_claude(&model)?;
+        tracing::info!(
+            "Bedrock API request: model={}, region={}, messages={}",
+            model,
+            self.region,
+            request.messages.len()
+        );
+
+        let body = serde_json::to_vec(&self.to_converse_request(&request))?;
+        let url = self.url(&model, "converse")?;
+        let retry_config = RetryConfig::default();
+
+        let result = retry_with_backoff(
+            || async {
+                // Re-sign on every attempt: signatures embed the timestamp
+                let response = self
+                    .signed_post(url.clone(), body.clone(), "application/json")
+                    .await?
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(handle_error(response).await);
+                }
+
+                let converse: ConverseResponse = response.json().await?;
+                let llm_response = self.from_converse_response(converse, &model);
+
+                tracing::info!(
+                    "Bedrock API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+                    llm_response.usage.input_tokens,
+                    llm_response.usage.output_tokens,
+                    llm_response.stop_reason
+                );
+
+                Ok(llm_response)
+            },
+            &retry_config,
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("Bedrock API request failed: {}", e);
+        }
+
+        result
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        Self::ensure_claude(&model)?;
+        tracing::info!(
+            "Bedrock streaming request: model={}, region={}, messages={}",
+            model,
+            self.region,
+            request.messages.len()
+        );
+
+        let body = serde_json::to_vec(&self.to_converse_request(&request))?;
+        let url = self.url(&model, "converse-stream")?;
+        let retry_config = RetryConfig::default();
+
+        let response = retry_with_backoff(
+            || async {
+                let response = self
+                    .signed_post(url.clone(), body.clone(), "application/vnd.amazon.eventstream")
+                    .await?
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(handle_error(response).await);
+                }
+
+                Ok(response)
+            },
+            &retry_config,
+        )
+        .await?;
+
+        let decoder = std::sync::Arc::new(std::sync::Mutex::new(EventStreamDecoder::new()));
+        let state = std::sync::Arc::new(std::sync::Mutex::new(ConverseStreamState::new(model)));
+        let tail_state = state.clone();
+
+        let event_stream = response
+            .bytes_stream()
+            .map(
+                move |chunk_result| -> Vec<std::result::Result<StreamEvent, ProviderError>> {
+                    let chunk = match chunk_result {
+                        Ok(chunk) => chunk,
+                        Err(e) => return vec![Err(ProviderError::StreamError(e.to_string()))],
+                    };
+
+                    let mut decoder = decoder.lock().expect("event-stream decoder lock poisoned");
+                    let mut state = state.lock().expect("Bedrock stream state lock poisoned");
+                    decoder.push(&chunk);
+
+                    let mut events = Vec::new();
+                    loop {
+                        let message = match decoder.next_message() {
+                            Ok(Some(message)) => message,
+                            Ok(None) => break,
+                            Err(e) => {
+                                events.push(Err(ProviderError::StreamError(e)));
+                                break;
+                            }
+                        };
+
+                        let payload: serde_json::Value =
+                            serde_json::from_slice(&message.payload).unwrap_or_default();
+
+                        if message.header(":message-type") == Some("exception") {
+                            let message_text = payload["message"]
+                                .as_str()
+                                .unwrap_or("Bedrock stream exception")
+                                .to_string();
+                            events.push(Err(bedrock_error(
+                                500,
+                                message.header(":exception-type"),
+                                message_text,
+                            )));
+                            continue;
+                        }
+
+                        let event_type = message.header(":event-type").unwrap_or_default();
+                        events.extend(state.handle(event_type, payload).into_iter().map(Ok));
+                    }
+
+                    if events.is_empty() {
+                        vec![Ok(StreamEvent::Ping)]
+                    } else {
+                        events
+                    }
+                },
+            )
+            .flat_map(futures::stream::iter)
+            // Close the message even if the trailing metadata event never arrived
+            .chain(
+                futures::stream::once(async move {
+                    tail_state
+                        .lock()
+                        .expect("Bedrock stream state lock poisoned")
+                        .finish(TokenUsage {
+                            input_tokens: 0,
+                            output_tokens: 0,
+                            reasoning_tokens: 0,
+                        })
+                        .into_iter()
+                        .map(Ok)
+                        .collect::<Vec<_>>()
+                })
+                .flat_map(futures::stream::iter),
+            );
+
+        Ok(Box::pin(event_stream))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "bedrock"
+    }
+
+    fn default_model(&self) -> &str {
+        self.custom_default_model
+            .as_deref()
+            .unwrap_or(DEFAULT_MODEL)
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec![
+            "anthropic.claude-opus-4-1-20250805-v1:0".to_string(),
+            "anthropic.claude-sonnet-4-5-20250929-v1:0".to_string(),
+            "anthropic.claude-sonnet-4-20250514-v1:0".to_string(),
+            "anthropic.claude-haiku-4-5-20251001-v1:0".to_string(),
+            "anthropic.claude-3-7-sonnet-20250219-v1:0".to_string(),
+            "anthropic.claude-3-5-sonnet-20241022-v2:0".to_string(),
+            "anthropic.claude-3-5-haiku-20241022-v1:0".to_string(),
+            "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+        ]
+    }
+
+    fn validate_model(&self, model: &str) -> bool {
+        let base = base_model_id(model);
+        self.supported_models().iter().any(|m| m == base)
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        match base_model_id(model) {
+            "anthropic.claude-opus-4-1-20250805-v1:0"
+            | "anthropic.claude-sonnet-4-5-20250929-v1:0"
+            | "anthropic.claude-sonnet-4-20250514-v1:0"
+            | "anthropic.claude-haiku-4-5-20251001-v1:0"
+            | "anthropic.claude-3-7-sonnet-20250219-v1:0"
+            | "anthropic.claude-3-5-sonnet-20241022-v2:0"
+            | "anthropic.claude-3-5-haiku-20241022-v1:0"
+            | "anthropic.claude-3-haiku-20240307-v1:0" => Some(200_000),
+            _ => None,
+        }
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        // Costs per million tokens (on-demand)
+        let (input_cost, output_cost) = match base_model_id(model) {
+            "anthropic.claude-opus-4-1-20250805-v1:0" => (15.0, 75.0),
+            "anthropic.claude-sonnet-4-5-20250929-v1:0" => (3.0, 15.0),
+            "anthropic.claude-sonnet-4-20250514-v1:0" => (3.0, 15.0),
+            "anthropic.claude-haiku-4-5-20251001-v1:0" => (1.0, 5.0),
+            "anthropic.claude-3-7-sonnet-20250219-v1:0" => (3.0, 15.0),
+            "anthropic.claude-3-5-sonnet-20241022-v2:0" => (3.0, 15.0),
+            "anthropic.claude-3-5-haiku-20241022-v1:0" => (0.80, 4.0),
+            "anthropic.claude-3-haiku-20240307-v1:0" => (0.25, 1.25),
+            _ => return 0.0,
+        };
+
+        let input_cost_total = (input_tokens as f64 / 1_000_000.0) * input_cost;
+        let output_cost_total = (output_tokens as f64 / 1_000_000.0) * output_cost;
+
+        input_cost_total + output_cost_total
+    }
+}
+
+// ============================================================================
+// Converse API Types
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseRequest {
+    messages: Vec<ConverseMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    system: Vec<SystemBlock>,
+    inference_config: InferenceConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_config: Option<ToolConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConverseMessage {
+    role: String,
+    content: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InferenceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ToolConfig {
+    tools: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseResponse {
+    output: ConverseOutput,
+    #[serde(default)]
+    stop_reason: String,
+    usage: ConverseUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseOutput {
+    message: Option<ConverseMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+impl From<ConverseUsage> for TokenUsage {
+    fn from(usage: ConverseUsage) -> Self {
+        TokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            reasoning_tokens: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BedrockErrorBody {
+    #[serde(alias = "Message")]
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event_stream::encode_message;
+    use serde_json::json;
+
+    const MODEL: &str = "anthropic.claude-3-haiku-20240307-v1:0";
+
+    fn test_credentials() -> CredentialsProvider {
+        CredentialsProvider::fixed(AwsCredentials {
+            access_key_id: "AKIDTEST".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+            expires_at: None,
+        })
+    }
+
+    fn event(event_type: &str, payload: serde_json::Value) -> Vec<u8> {
+        encode_message(
+            &[
+                (":event-type", event_type),
+                (":content-type", "application/json"),
+                (":message-type", "event"),
+            ],
+            payload.to_string().as_bytes(),
+        )
+    }
+
+    /// Recorded `converse-stream` body: text, then a tool call, then metadata
+    fn tool_call_fixture() -> Vec<u8> {
+        [
+            event("messageStart", json!({ "role": "assistant" })),
+            event("contentBlockDelta", json!({ "contentBlockIndex": 0, "delta": { "text": "Checking" } })),
+            event("contentBlockDelta", json!({ "contentBlockIndex": 0, "delta": { "text": " files." } })),
+            event("contentBlockStop", json!({ "contentBlockIndex": 0 })),
+            event("contentBlockStart", json!({
+                "contentBlockIndex": 1,
+                "start": { "toolUse": { "toolUseId": "tooluse_abc", "name": "ls" } }
+            })),
+            event("contentBlockDelta", json!({ "contentBlockIndex": 1, "delta": { "toolUse": { "input": "{\"path\":" } } })),
+            event("contentBlockDelta", json!({ "contentBlockIndex": 1, "delta": { "toolUse": { "input": "\"src\"}" } } })),
+            event("contentBlockStop", json!({ "contentBlockIndex": 1 })),
+            event("messageStop", json!({ "stopReason": "tool_use" })),
+            event("metadata", json!({ "usage": { "inputTokens": 21, "outputTokens": 9 }, "metrics": { "latencyMs": 300 } })),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_rejects_non_claude_models() {
+        assert!(BedrockProvider::ensure_claude(MODEL).is_ok());
+        assert!(BedrockProvider::ensure_claude("us.anthropic.claude-sonnet-4-20250514-v1:0").is_ok());
+        assert!(BedrockProvider::ensure_claude("meta.llama3-70b-instruct-v1:0").is_err());
+        assert!(BedrockProvider::ensure_claude("amazon.titan-text-express-v1").is_err());
+    }
+
+    #[test]
+    fn test_default_model_is_an_inference_profile() {
+        let provider = BedrockProvider::new(test_credentials(), "us-east-1".to_string());
+        let model = provider.default_model();
+        assert!(model.starts_with("global."));
+        assert!(provider.validate_model(model));
+        assert_eq!(provider.context_window(model), Some(200_000));
+    }
+
+    #[test]
+    fn test_url_encodes_model_id() {
+        let provider = BedrockProvider::new(test_credentials(), "us-west-2".to_string());
+        let url = provider.url(MODEL, "converse").unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://bedrock-runtime.us-west-2.amazonaws.com/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"
+        );
+    }
+
+    #[test]
+    fn test_converse_request_translation() {
+        let provider = BedrockProvider::new(test_credentials(), "us-east-1".to_string());
+        let request = LLMRequest::new(
+            MODEL,
+            vec![
+                Message::user("hi"),
+                Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "t1".to_string(),
+                        name: "ls".to_string(),
+                        input: json!({}),
+                    }],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![
+                        ContentBlock::ToolResult {
+                            tool_use_id: "t1".to_string(),
+                            content: "boom".to_string(),
+                            is_error: Some(true),
+                        },
+                        ContentBlock::Image {
+                            source: ImageSource::Base64 {
+                                media_type: "image/png".to_string(),
+                                data: "aGk=".to_string(),
+                            },
+                        },
+                    ],
+                },
+            ],
+        )
+        .with_system("be brief")
+        .with_tools(vec![Tool {
+            name: "ls".to_string(),
+            description: "list".to_string(),
+            input_schema: json!({ "type": "object" }),
+        }]);
+
+        let body = serde_json::to_value(provider.to_converse_request(&request)).unwrap();
+        assert_eq!(body["system"][0]["text"], "be brief");
+        assert_eq!(body["messages"][1]["content"][0]["toolUse"]["toolUseId"], "t1");
+        assert_eq!(body["messages"][2]["content"][0]["toolResult"]["status"], "error");
+        assert_eq!(body["messages"][2]["content"][1]["image"]["format"], "png");
+        assert_eq!(body["toolConfig"]["tools"][0]["toolSpec"]["inputSchema"]["json"]["type"], "object");
+        assert_eq!(body["inferenceConfig"]["maxTokens"], 16384);
+    }
+
+    #[test]
+    fn test_cost_and_context_use_base_model() {
+        let provider = BedrockProvider::new(test_credentials(), "us-east-1".to_string());
+        let profile = "us.anthropic.claude-sonnet-4-5-20250929-v1:0";
+        assert_eq!(provider.context_window(profile), Some(200_000));
+        assert_eq!(provider.calculate_cost(profile, 1_000_000, 1_000_000), 18.0);
+        assert!(provider.validate_model(profile));
+        assert_eq!(provider.calculate_cost("unknown", 1000, 1000), 0.0);
+    }
+
+    #[test]
+    fn test_error_mapping() {
+        assert!(matches!(
+            bedrock_error(400, Some("ThrottlingException:http://internal.amazon.com/"), "slow down".into()),
+            ProviderError::RateLimitExceeded(_)
+        ));
+        assert!(matches!(bedrock_error(403, None, "no".into()), ProviderError::InvalidApiKey));
+        assert!(matches!(
+            bedrock_error(400, Some("ValidationException"), "bad".into()),
+            ProviderError::ApiError { status: 400, .. }
+        ));
+    }
+
+    // --- Fixture server tests ---
+
+    #[tokio::test]
+    async fn test_complete_against_fixture_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse")
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(
+                    r"^AWS4-HMAC-SHA256 Credential=AKIDTEST/\d{8}/us-east-1/bedrock/aws4_request, SignedHeaders=accept;content-type;host;x-amz-date, Signature=[0-9a-f]{64}$".to_string(),
+                ),
+            )
+            .match_header("x-amz-date", mockito::Matcher::Regex(r"^\d{8}T\d{6}Z$".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "output": { "message": { "role": "assistant", "content": [{ "text": "Hello!" }] } },
+                    "stopReason": "end_turn",
+                    "usage": { "inputTokens": 10, "outputTokens": 3, "totalTokens": 13 }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let provider = BedrockProvider::with_endpoint(test_credentials(), "us-east-1".to_string(), server.url());
+        let response = provider
+            .complete(LLMRequest::new(MODEL, vec![Message::user("hi")]))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(response.usage.input_tokens, 10);
+        assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "Hello!"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_against_fixture_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream")
+            .match_header("accept", "application/vnd.amazon.eventstream")
+            .with_status(200)
+            .with_header("content-type", "application/vnd.amazon.eventstream")
+            .with_body(tool_call_fixture())
+            .create_async()
+            .await;
+
+        let provider = BedrockProvider::with_endpoint(test_credentials(), "us-east-1".to_string(), server.url());
+        let events: Vec<StreamEvent> = provider
+            .stream(LLMRequest::new(MODEL, vec![Message::user("list src")]))
+            .await
+            .unwrap()
+            .filter_map(|e| async move { e.ok() })
+            .filter(|e| futures::future::ready(!matches!(e, StreamEvent::Ping)))
+            .collect()
+            .await;
+
+        mock.assert_async().await;
+
+        assert!(matches!(events[0], StreamEvent::MessageStart { .. }));
+        assert!(matches!(
+            events[1],
+            StreamEvent::ContentBlockStart { index: 0, content_block: ContentBlock::Text { .. } }
+        ));
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentBlockDelta { delta: ContentDelta::TextDelta { text }, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Checking files.");
+
+        let tool_json: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentBlockDelta { index: 1, delta: ContentDelta::InputJsonDelta { partial_json } } => {
+                    Some(partial_json.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(tool_json, r#"{"path":"src"}"#);
+
+        let delta = events
+            .iter()
+            .find_map(|e| match e {
+                StreamEvent::MessageDelta { delta, usage } => Some((delta.stop_reason.clone(), *usage)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(delta.0, Some(StopReason::ToolUse));
+        assert_eq!(delta.1.input_tokens, 21);
+        assert_eq!(delta.1.output_tokens, 9);
+
+        // Exactly one MessageStop even though the tail closer also runs
+        assert_eq!(events.iter().filter(|e| matches!(e, StreamEvent::MessageStop)).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_exception_is_surfaced() {
+        let body = [
+            event("messageStart", json!({ "role": "assistant" })),
+            encode_message(
+                &[(":message-type", "exception"), (":exception-type", "throttlingException")],
+                json!({ "message": "Too many requests" }).to_string().as_bytes(),
+            ),
+        ]
+        .concat();
+
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse-stream")
+            .with_status(200)
+            .with_body(body)
+            .create_async()
+            .await;
+
+        let provider = BedrockProvider::with_endpoint(test_credentials(), "us-east-1".to_string(), server.url());
+        let results: Vec<_> = provider
+            .stream(LLMRequest::new(MODEL, vec![Message::user("hi")]))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert!(results.iter().any(|r| r.is_err()));
+    }
+
+    #[tokio::test]
+    async fn test_non_claude_model_never_hits_network() {
+        let provider = BedrockProvider::with_endpoint(
+            test_credentials(),
+            "us-east-1".to_string(),
+            "http://127.0.0.1:9".to_string(),
+        );
+        let err = provider
+            .complete(LLMRequest::new("meta.llama3-70b-instruct-v1:0", vec![Message::user("hi")]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::InvalidRequest(_)));
+    }
+}
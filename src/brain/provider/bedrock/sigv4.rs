@@ -0,0 +1,227 @@
+//! AWS Signature Version 4 request signing
+//!
+//! Just enough SigV4 for JSON POSTs to Bedrock Runtime: header-based auth,
+//! double-encoded canonical URIs (every service except S3), and optional
+//! session tokens for temporary credentials.
+
+use super::credentials::AwsCredentials;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Compute the headers needed to authenticate a request.
+///
+/// `extra_headers` are additional headers that will be sent *and* signed
+/// (e.g. `content-type`). The returned list contains `x-amz-date`, the session
+/// token when present, and `authorization`; callers add them to the request.
+#[allow(clippy::too_many_arguments)]
+pub fn sign_request(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    method: &str,
+    url: &reqwest::Url,
+    extra_headers: &[(&str, &str)],
+    payload: &[u8],
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+
+    let mut headers: Vec<(String, String)> = extra_headers
+        .iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .collect();
+    headers.push(("host".to_string(), host));
+    headers.push(("x-amz-date".to_string(), amz_date.clone()));
+    if let Some(token) = &credentials.session_token {
+        headers.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method,
+        canonical_uri(url.path()),
+        canonical_query(url),
+        canonical_headers,
+        signed_headers,
+        hex_sha256(payload)
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        hex_sha256(canonical_request.as_bytes())
+    );
+
+    let key = signing_key(&credentials.secret_access_key, &date, region, service);
+    let signature = to_hex(&hmac(&key, string_to_sign.as_bytes()));
+
+    let mut out = vec![("x-amz-date".to_string(), amz_date)];
+    if let Some(token) = &credentials.session_token {
+        out.push(("x-amz-security-token".to_string(), token.clone()));
+    }
+    out.push((
+        "authorization".to_string(),
+        format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, credentials.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    out
+}
+
+/// Non-S3 services expect each path segment to be URI-encoded twice; the
+/// request path is already encoded once, so encode it once more here.
+fn canonical_uri(path: &str) -> String {
+    if path.is_empty() {
+        return "/".to_string();
+    }
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| {
+            (
+                urlencoding::encode(&k).into_owned(),
+                urlencoding::encode(&v).into_owned(),
+            )
+        })
+        .collect();
+    pairs.sort();
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn signing_key(secret: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac(format!("AWS4{}", secret).as_bytes(), date.as_bytes());
+    let k_region = hmac(&k_date, region.as_bytes());
+    let k_service = hmac(&k_region, service.as_bytes());
+    hmac(&k_service, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn example_credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_signing_key_matches_aws_example() {
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20150830",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            to_hex(&key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+
+    #[test]
+    fn test_signature_matches_aws_example() {
+        // Example request from the AWS SigV4 documentation (IAM ListUsers)
+        let url = reqwest::Url::parse("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08")
+            .unwrap();
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+
+        let headers = sign_request(
+            &example_credentials(),
+            "us-east-1",
+            "iam",
+            "GET",
+            &url,
+            &[("Content-Type", "application/x-www-form-urlencoded; charset=utf-8")],
+            b"",
+            now,
+        );
+
+        let auth = &headers.iter().find(|(k, _)| k == "authorization").unwrap().1;
+        assert_eq!(
+            auth,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date, \
+             Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+        assert!(headers.iter().any(|(k, v)| k == "x-amz-date" && v == "20150830T123600Z"));
+    }
+
+    #[test]
+    fn test_session_token_is_signed() {
+        let mut creds = example_credentials();
+        creds.session_token = Some("session".to_string());
+        let url = reqwest::Url::parse("https://bedrock-runtime.us-east-1.amazonaws.com/model/x/converse")
+            .unwrap();
+
+        let headers = sign_request(&creds, "us-east-1", "bedrock", "POST", &url, &[], b"{}", Utc::now());
+
+        assert!(headers.iter().any(|(k, v)| k == "x-amz-security-token" && v == "session"));
+        let auth = &headers.iter().find(|(k, _)| k == "authorization").unwrap().1;
+        assert!(auth.contains("SignedHeaders=host;x-amz-date;x-amz-security-token"));
+    }
+
+    #[test]
+    fn test_canonical_uri_double_encodes_model_ids() {
+        // Model IDs contain ':' which is sent as %3A and signed as %253A
+        assert_eq!(
+            canonical_uri("/model/anthropic.claude-3-haiku-20240307-v1%3A0/converse"),
+            "/model/anthropic.claude-3-haiku-20240307-v1%253A0/converse"
+        );
+        assert_eq!(canonical_uri("/"), "/");
+    }
+}
@@ -4,7 +4,11 @@
 
 use super::{
     anthropic::AnthropicProvider,
+    bedrock::{AwsCredentials, BedrockProvider, CredentialsProvider, resolve_region},
+    fallback::FallbackProvider,
+    gemini::GeminiProvider,
     openai::OpenAIProvider,
+    vertex::{ServiceAccountKey, VertexProvider},
     Provider,
 };
 use crate::config::{Config, ProviderConfig};
@@ -12,8 +16,44 @@ use anyhow::Result;
 use std::sync::Arc;
 
 /// Create a provider based on config.toml
-/// No hardcoded priority - providers are enabled/disabled in config
+///
+/// When `[providers.fallback]` is enabled, the selected provider is wrapped in
+/// a `FallbackProvider` with the configured chain behind it.
 pub fn create_provider(config: &Config) -> Result<Arc<dyn Provider>> {
+    let primary = create_primary_provider(config)?;
+    Ok(with_fallback_chain(config, primary))
+}
+
+/// Wrap `primary` in a failover chain if one is configured
+fn with_fallback_chain(config: &Config, primary: Arc<dyn Provider>) -> Arc<dyn Provider> {
+    let Some(fallback) = config.providers.fallback.as_ref().filter(|f| f.enabled) else {
+        return primary;
+    };
+
+    let mut chain: Vec<Arc<dyn Provider>> = vec![primary.clone()];
+    for name in fallback.chain() {
+        match create_fallback(config, &name) {
+            // Skip the entry that is already serving as primary
+            Ok(provider)
+                if provider.name() == primary.name()
+                    && provider.default_model() == primary.default_model() => {}
+            Ok(provider) => chain.push(provider),
+            Err(e) => tracing::warn!("Skipping fallback provider '{}': {}", name, e),
+        }
+    }
+
+    if chain.len() == 1 {
+        return primary;
+    }
+
+    let provider = FallbackProvider::new(chain);
+    tracing::info!("Provider failover chain: {}", provider.chain().join(" → "));
+    Arc::new(provider)
+}
+
+/// Create the highest-priority enabled provider
+/// No hardcoded priority - providers are enabled/disabled in config
+fn create_primary_provider(config: &Config) -> Result<Arc<dyn Provider>> {
     // Check which providers are enabled in config.toml
     
     // Try Minimax first
@@ -47,15 +87,28 @@ pub fn create_provider(config: &Config) -> Result<Arc<dyn Provider>> {
     // Try Gemini
     if config.providers.gemini.as_ref().is_some_and(|p| p.enabled) {
         tracing::info!("Using enabled provider: Google Gemini");
-        return Err(anyhow::anyhow!(
-            "Gemini provider not yet implemented"
-        ));
+        return try_create_gemini(config)?
+            .ok_or_else(|| anyhow::anyhow!("Gemini enabled but failed to create"));
+    }
+
+    // Try AWS Bedrock
+    if config.providers.bedrock.as_ref().is_some_and(|p| p.enabled) {
+        tracing::info!("Using enabled provider: AWS Bedrock");
+        return try_create_bedrock(config)?
+            .ok_or_else(|| anyhow::anyhow!("Bedrock enabled but no AWS credentials found"));
     }
 
-    // Try fallback if primary fails
+    // Try Google Vertex AI
+    if config.providers.vertex.as_ref().is_some_and(|p| p.enabled) {
+        tracing::info!("Using enabled provider: Google Vertex AI");
+        return try_create_vertex(config)?
+            .ok_or_else(|| anyhow::anyhow!("Vertex enabled but no service-account key found"));
+    }
+
+    // No primary enabled: start the chain at the first fallback entry
     if let Some(fallback) = &config.providers.fallback
         && fallback.enabled
-            && let Some(fallback_type) = &fallback.provider {
+            && let Some(fallback_type) = fallback.chain().first() {
                 tracing::warn!("No primary provider enabled, trying fallback: {}", fallback_type);
                 return create_fallback(config, fallback_type);
             }
@@ -94,6 +147,21 @@ fn create_fallback(config: &Config, fallback_type: &str) -> Result<Arc<dyn Provi
             try_create_openai(config)?
                 .ok_or_else(|| anyhow::anyhow!("OpenAI not configured"))
         }
+        "gemini" => {
+            tracing::info!("Using fallback: Google Gemini");
+            try_create_gemini(config)?
+                .ok_or_else(|| anyhow::anyhow!("Gemini not configured"))
+        }
+        "bedrock" => {
+            tracing::info!("Using fallback: AWS Bedrock");
+            try_create_bedrock(config)?
+                .ok_or_else(|| anyhow::anyhow!("Bedrock not configured"))
+        }
+        "vertex" => {
+            tracing::info!("Using fallback: Google Vertex AI");
+            try_create_vertex(config)?
+                .ok_or_else(|| anyhow::anyhow!("Vertex not configured"))
+        }
         _ => Err(anyhow::anyhow!("Unknown fallback provider: {}", fallback_type)),
     }
 }
@@ -210,10 +278,125 @@ fn try_create_anthropic(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
     Ok(Some(Arc::new(provider)))
 }
 
+/// Try to create Gemini provider if configured
+fn try_create_gemini(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let gemini_config = match &config.providers.gemini {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    let Some(api_key) = &gemini_config.api_key else {
+        return Ok(None);
+    };
+
+    let mut provider = match &gemini_config.base_url {
+        Some(base_url) => GeminiProvider::with_base_url(api_key.clone(), base_url.clone()),
+        None => GeminiProvider::new(api_key.clone()),
+    };
+
+    if let Some(model) = &gemini_config.default_model {
+        tracing::info!("Using custom default model: {}", model);
+        provider = provider.with_default_model(model.clone());
+    }
+
+    tracing::info!("Using Gemini provider");
+
+    Ok(Some(Arc::new(provider)))
+}
+
+/// Try to create AWS Bedrock provider if configured
+///
+/// Credentials come from the AWS env vars, shared profile files or instance
+/// metadata, never from `api_key`, and are refreshed before they expire.
+/// `base_url` overrides the regional endpoint (e.g. VPC endpoints).
+fn try_create_bedrock(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let bedrock_config = match &config.providers.bedrock {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    // Instance metadata is only checked on the first request
+    let initial = AwsCredentials::load();
+    if initial.is_none() {
+        tracing::warn!(
+            "Bedrock configured but no AWS credentials found in env, ~/.aws or credential_process; trying instance metadata"
+        );
+    }
+    let credentials = CredentialsProvider::chain(initial);
+
+    let region = resolve_region();
+    let mut provider = match &bedrock_config.base_url {
+        Some(endpoint) => BedrockProvider::with_endpoint(credentials, region.clone(), endpoint.clone()),
+        None => BedrockProvider::new(credentials, region.clone()),
+    };
+
+    if let Some(model) = &bedrock_config.default_model {
+        tracing::info!("Using custom default model: {}", model);
+        provider = provider.with_default_model(model.clone());
+    }
+
+    tracing::info!("Using Bedrock provider (region: {})", region);
+
+    Ok(Some(Arc::new(provider)))
+}
+
+/// Try to create Google Vertex AI provider if configured
+///
+/// The service-account key comes from `api_key` (a path to the JSON key, or
+/// the JSON itself) or `GOOGLE_APPLICATION_CREDENTIALS`. The project defaults
+/// to the key's `project_id` (override with `GOOGLE_CLOUD_PROJECT`) and the
+/// location to `global` (override with `GOOGLE_CLOUD_LOCATION`).
+fn try_create_vertex(config: &Config) -> Result<Option<Arc<dyn Provider>>> {
+    let vertex_config = match &config.providers.vertex {
+        Some(cfg) => cfg,
+        None => return Ok(None),
+    };
+
+    let key = match vertex_config.api_key.as_deref().map(str::trim) {
+        Some(json) if json.starts_with('{') => ServiceAccountKey::from_json(json)?,
+        Some(path) if !path.is_empty() => ServiceAccountKey::from_file(std::path::Path::new(path))?,
+        _ => match std::env::var("GOOGLE_APPLICATION_CREDENTIALS") {
+            Ok(path) => ServiceAccountKey::from_file(std::path::Path::new(&path))?,
+            Err(_) => {
+                tracing::warn!("Vertex configured but no service-account key (api_key or GOOGLE_APPLICATION_CREDENTIALS)");
+                return Ok(None);
+            }
+        },
+    };
+
+    let Some(project_id) = std::env::var("GOOGLE_CLOUD_PROJECT")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .or_else(|| key.project_id.clone())
+    else {
+        return Err(anyhow::anyhow!(
+            "Vertex: no project_id in service-account key and GOOGLE_CLOUD_PROJECT not set"
+        ));
+    };
+    let location = std::env::var("GOOGLE_CLOUD_LOCATION")
+        .ok()
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| "global".to_string());
+
+    tracing::info!("Using Vertex provider (project: {}, location: {})", project_id, location);
+
+    let mut provider = match &vertex_config.base_url {
+        Some(base_url) => VertexProvider::with_base_url(key, project_id, location, base_url.clone()),
+        None => VertexProvider::new(key, project_id, location),
+    };
+
+    if let Some(model) = &vertex_config.default_model {
+        tracing::info!("Using custom default model: {}", model);
+        provider = provider.with_default_model(model.clone());
+    }
+
+    Ok(Some(Arc::new(provider)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Config, ProviderConfig, ProviderConfigs};
+    use crate::config::{Config, FallbackProviderConfig, ProviderConfig, ProviderConfigs};
 
     #[test]
     fn test_create_provider_with_anthropic() {
@@ -284,6 +467,100 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_create_provider_with_gemini() {
+        let config = Config {
+            providers: ProviderConfigs {
+                gemini: Some(ProviderConfig {
+                    enabled: true,
+                    api_key: Some("test-key".to_string()),
+                    base_url: None,
+                    default_model: Some("gemini-2.5-pro".to_string()),
+                    models: vec![],
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let provider = create_provider(&config).unwrap();
+        assert_eq!(provider.name(), "gemini");
+        assert_eq!(provider.default_model(), "gemini-2.5-pro");
+    }
+
+    #[test]
+    fn test_fallback_chain_wraps_primary() {
+        let config = Config {
+            providers: ProviderConfigs {
+                anthropic: Some(ProviderConfig {
+                    enabled: true,
+                    api_key: Some("anthropic-key".to_string()),
+                    base_url: None,
+                    default_model: None,
+                    models: vec![],
+                }),
+                gemini: Some(ProviderConfig {
+                    enabled: false,
+                    api_key: Some("gemini-key".to_string()),
+                    base_url: None,
+                    default_model: None,
+                    models: vec![],
+                }),
+                fallback: Some(FallbackProviderConfig {
+                    enabled: true,
+                    provider: None,
+                    // "anthropic" is the primary and must not be added twice;
+                    // "openai" has no config and is skipped
+                    providers: vec!["anthropic".to_string(), "openai".to_string(), "gemini".to_string()],
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let primary = create_primary_provider(&config).unwrap();
+        let provider = with_fallback_chain(&config, primary.clone());
+        // Wrapped in a chain, but still reports the primary
+        assert!(!Arc::ptr_eq(&primary, &provider));
+        assert_eq!(provider.name(), "anthropic");
+    }
+
+    #[test]
+    fn test_fallback_chain_without_usable_entries_is_not_wrapped() {
+        let config = Config {
+            providers: ProviderConfigs {
+                anthropic: Some(ProviderConfig {
+                    enabled: true,
+                    api_key: Some("anthropic-key".to_string()),
+                    base_url: None,
+                    default_model: None,
+                    models: vec![],
+                }),
+                fallback: Some(FallbackProviderConfig {
+                    enabled: true,
+                    provider: Some("anthropic".to_string()),
+                    providers: vec![],
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let primary = create_primary_provider(&config).unwrap();
+        let provider = with_fallback_chain(&config, primary.clone());
+        assert!(Arc::ptr_eq(&primary, &provider));
+    }
+
+    #[test]
+    fn test_legacy_single_fallback_chain() {
+        let fallback = FallbackProviderConfig {
+            enabled: true,
+            provider: Some("openrouter".to_string()),
+            providers: vec![],
+        };
+        assert_eq!(fallback.chain(), vec!["openrouter".to_string()]);
+    }
+
     #[test]
     fn test_create_provider_no_credentials() {
         let config = Config {
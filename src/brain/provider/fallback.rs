@@ -0,0 +1,446 @@
+//! Fallback Provider
+//!
+//! Wraps an ordered chain of providers and fails over to the next one when a
+//! request is rate-limited, hits a 5xx, or times out. A provider that failed
+//! over is put on cooldown (its `Retry-After` hint via
+//! [`extract_retry_after`], or [`DEFAULT_COOLDOWN`]); once the cooldown
+//! expires, requests go back to the highest-priority provider again.
+//!
+//! Failover happens before a response starts — for streaming requests that
+//! means while opening the stream. Errors mid-stream are returned as-is.
+//!
+//! Every provider but the last in the attempt order runs under
+//! [`fail_fast`], so its own retry backoff doesn't delay the failover.
+//!
+//! Switches between backends are reported as [`ProviderSwitch`] records to
+//! the call that caused them, via [`collect_switches`]; the agent turns them
+//! into `ProgressEvent::ProviderSwitched` for the UI. Agents sharing a chain
+//! (e.g. delegates) only see their own switches.
+
+use super::error::{ProviderError, Result};
+use super::retry::{extract_retry_after, fail_fast};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::{LLMRequest, LLMResponse};
+use async_trait::async_trait;
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cooldown applied when an error carries no retry hint
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+tokio::task_local! {
+    /// Switches made while serving the enclosing `collect_switches` call
+    static SWITCHES: RefCell<Vec<ProviderSwitch>>;
+}
+
+/// Run a provider call and return the backend switches it caused along with
+/// its result. Outside this scope switches are only logged.
+pub async fn collect_switches<F: Future>(call: F) -> (F::Output, Vec<ProviderSwitch>) {
+    SWITCHES
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = call.await;
+            (output, SWITCHES.with(RefCell::take))
+        })
+        .await
+}
+
+/// A change of the backend that answers requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderSwitch {
+    pub from: String,
+    pub to: String,
+    pub reason: String,
+}
+
+struct ChainState {
+    /// Index of the provider that answered last
+    active: usize,
+    /// Per-provider cooldown deadline
+    cooldown_until: Vec<Option<Instant>>,
+}
+
+/// Provider that fails over along an ordered chain
+pub struct FallbackProvider {
+    providers: Vec<Arc<dyn Provider>>,
+    state: Mutex<ChainState>,
+}
+
+impl FallbackProvider {
+    /// Create a chain; the first provider is the primary.
+    ///
+    /// # Panics
+    /// Panics if `providers` is empty.
+    pub fn new(providers: Vec<Arc<dyn Provider>>) -> Self {
+        assert!(!providers.is_empty(), "FallbackProvider needs at least one provider");
+        let count = providers.len();
+        Self {
+            providers,
+            state: Mutex::new(ChainState {
+                active: 0,
+                cooldown_until: vec![None; count],
+            }),
+        }
+    }
+
+    /// Names of the providers in the chain, in priority order
+    pub fn chain(&self) -> Vec<String> {
+        self.providers.iter().map(|p| p.name().to_string()).collect()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ChainState> {
+        self.state.lock().expect("fallback state lock poisoned")
+    }
+
+    /// Provider indices to try, in order: available providers by priority,
+    /// then the ones still cooling down (better a likely failure than none)
+    fn attempt_order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let state = self.state();
+        let (ready, cooling): (Vec<usize>, Vec<usize>) = (0..self.providers.len())
+            .partition(|&i| state.cooldown_until[i].is_none_or(|until| until <= now));
+        ready.into_iter().chain(cooling).collect()
+    }
+
+    /// Rewrite the model for non-primary providers that don't serve it
+    fn request_for(&self, index: usize, request: &LLMRequest) -> LLMRequest {
+        let provider = &self.providers[index];
+        let mut request = request.clone();
+        if index > 0 && !provider.validate_model(&request.model) {
+            request.model = provider.default_model().to_string();
+        }
+        request
+    }
+
+    fn mark_failed(&self, index: usize, error: &ProviderError) {
+        let cooldown = extract_retry_after(error).unwrap_or(DEFAULT_COOLDOWN);
+        tracing::warn!(
+            "Provider '{}' failed ({}), cooling down for {}s",
+            self.providers[index].name(),
+            error,
+            cooldown.as_secs()
+        );
+        self.state().cooldown_until[index] = Some(Instant::now() + cooldown);
+    }
+
+    /// Record which provider answered, reporting a switch if it changed
+    fn mark_answered(&self, index: usize, last_error: Option<&ProviderError>) {
+        let mut state = self.state();
+        state.cooldown_until[index] = None;
+        if state.active == index {
+            return;
+        }
+
+        let reason = match last_error {
+            Some(err) => err.to_string(),
+            None if index < state.active => "preferred provider available again".to_string(),
+            None => "preferred provider cooling down".to_string(),
+        };
+        let switch = ProviderSwitch {
+            from: self.providers[state.active].name().to_string(),
+            to: self.providers[index].name().to_string(),
+            reason,
+        };
+        tracing::info!("🔀 Provider switch: {} → {} ({})", switch.from, switch.to, switch.reason);
+        state.active = index;
+        let _ = SWITCHES.try_with(|switches| switches.borrow_mut().push(switch));
+    }
+
+    /// Run `call` against the chain until one provider succeeds
+    async fn with_failover<T, F, Fut>(&self, request: &LLMRequest, call: F) -> Result<T>
+    where
+        F: Fn(Arc<dyn Provider>, LLMRequest) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut last_error: Option<ProviderError> = None;
+        let order = self.attempt_order();
+        let attempts = order.len();
+
+        for (n, index) in order.into_iter().enumerate() {
+            let provider = self.providers[index].clone();
+            let attempt = call(provider, self.request_for(index, request));
+            // Only the last resort gets the provider's full retry schedule
+            let result = if n + 1 < attempts {
+                fail_fast(attempt).await
+            } else {
+                attempt.await
+            };
+            match result {
+                Ok(value) => {
+                    self.mark_answered(index, last_error.as_ref());
+                    return Ok(value);
+                }
+                Err(err) if should_fail_over(&err) => {
+                    self.mark_failed(index, &err);
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::Internal("fallback chain exhausted".to_string())
+        }))
+    }
+}
+
+/// Errors that mean "this backend can't serve right now", not "bad request"
+pub fn should_fail_over(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::RateLimitExceeded(_) | ProviderError::Timeout(_) => true,
+        ProviderError::ApiError { status, .. } => *status == 429 || *status >= 500,
+        ProviderError::HttpError(e) => e.is_timeout() || e.is_connect(),
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl Provider for FallbackProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        self.with_failover(&request, |provider, request| async move {
+            provider.complete(request).await
+        })
+        .await
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        self.with_failover(&request, |provider, request| async move {
+            provider.stream(request).await
+        })
+        .await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.providers.iter().all(|p| p.supports_streaming())
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.providers.iter().all(|p| p.supports_tools())
+    }
+
+    fn supports_vision(&self) -> bool {
+        self.providers[0].supports_vision()
+    }
+
+    /// Reports the primary's name; see `ProviderSwitch` for the live backend
+    fn name(&self) -> &str {
+        self.providers[0].name()
+    }
+
+    fn default_model(&self) -> &str {
+        self.providers[0].default_model()
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        self.providers[0].supported_models()
+    }
+
+    async fn fetch_models(&self) -> Vec<String> {
+        self.providers[0].fetch_models().await
+    }
+
+    fn validate_model(&self, model: &str) -> bool {
+        self.providers[0].validate_model(model)
+    }
+
+    /// Responses may come from any provider in the chain, so ask each in turn
+    fn context_window(&self, model: &str) -> Option<u32> {
+        self.providers.iter().find_map(|p| p.context_window(model))
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        self.providers
+            .iter()
+            .map(|p| p.calculate_cost(model, input_tokens, output_tokens))
+            .find(|cost| *cost > 0.0)
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brain::provider::types::{ContentBlock, Message, StopReason, TokenUsage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Provider that fails `failures` times with `error`, then answers
+    struct ScriptedProvider {
+        name: String,
+        failures: AtomicUsize,
+        error: fn() -> ProviderError,
+        calls: AtomicUsize,
+    }
+
+    impl ScriptedProvider {
+        fn new(name: &str, failures: usize, error: fn() -> ProviderError) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.to_string(),
+                failures: AtomicUsize::new(failures),
+                error,
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err((self.error)());
+            }
+            Ok(LLMResponse {
+                id: "r".to_string(),
+                model: request.model,
+                content: vec![ContentBlock::Text {
+                    text: self.name.clone(),
+                }],
+                stop_reason: Some(StopReason::EndTurn),
+                usage: TokenUsage {
+                    input_tokens: 1,
+                    output_tokens: 1,
+                    reasoning_tokens: 0,
+                },
+            })
+        }
+
+        async fn stream(&self, _request: LLMRequest) -> Result<ProviderStream> {
+            Err(ProviderError::StreamingNotSupported)
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn default_model(&self) -> &str {
+            "default"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec![format!("{}-model", self.name)]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            None
+        }
+
+        fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+            0.0
+        }
+    }
+
+    fn rate_limited() -> ProviderError {
+        ProviderError::RateLimitExceeded("slow down (retry after 120 seconds)".to_string())
+    }
+
+    fn server_error() -> ProviderError {
+        ProviderError::ApiError {
+            status: 503,
+            message: "overloaded".to_string(),
+            error_type: None,
+        }
+    }
+
+    fn bad_request() -> ProviderError {
+        ProviderError::InvalidRequest("bad".to_string())
+    }
+
+    fn answered_by(response: &LLMResponse) -> &str {
+        match &response.content[0] {
+            ContentBlock::Text { text } => text,
+            _ => "",
+        }
+    }
+
+    fn request() -> LLMRequest {
+        LLMRequest::new("primary-model", vec![Message::user("hi")])
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_on_rate_limit_and_reports_switch() {
+        let primary = ScriptedProvider::new("primary", 1, rate_limited);
+        let backup = ScriptedProvider::new("backup", 0, bad_request);
+        let chain = FallbackProvider::new(vec![primary.clone(), backup.clone()]);
+
+        let (response, switches) = collect_switches(chain.complete(request())).await;
+        let response = response.unwrap();
+        assert_eq!(answered_by(&response), "backup");
+        // Backup doesn't serve the primary's model, so its default is used
+        assert_eq!(response.model, "default");
+
+        assert_eq!(switches.len(), 1);
+        assert_eq!(switches[0].from, "primary");
+        assert_eq!(switches[0].to, "backup");
+        assert!(switches[0].reason.contains("Rate limit"));
+
+        let (_, switches) = collect_switches(chain.complete(request())).await;
+        assert!(switches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_primary_skipped_during_cooldown_then_restored() {
+        let primary = ScriptedProvider::new("primary", 1, rate_limited);
+        let backup = ScriptedProvider::new("backup", 0, bad_request);
+        let chain = FallbackProvider::new(vec![primary.clone(), backup.clone()]);
+
+        chain.complete(request()).await.unwrap();
+        // Still within the 120s Retry-After: primary isn't tried again
+        chain.complete(request()).await.unwrap();
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 2);
+
+        // Cooldown expires → back to primary, reported as a switch
+        chain.state().cooldown_until[0] = Some(Instant::now());
+        let (response, switches) = collect_switches(chain.complete(request())).await;
+        assert_eq!(answered_by(&response.unwrap()), "primary");
+        assert_eq!(switches.last().unwrap().to, "primary");
+        assert_eq!(switches.last().unwrap().reason, "preferred provider available again");
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_on_server_error() {
+        let primary = ScriptedProvider::new("primary", 1, server_error);
+        let backup = ScriptedProvider::new("backup", 0, bad_request);
+        let chain = FallbackProvider::new(vec![primary, backup]);
+
+        let response = chain.complete(request()).await.unwrap();
+        assert_eq!(answered_by(&response), "backup");
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_do_not_fail_over() {
+        let primary = ScriptedProvider::new("primary", 1, bad_request);
+        let backup = ScriptedProvider::new("backup", 0, bad_request);
+        let chain = FallbackProvider::new(vec![primary, backup.clone()]);
+
+        let (result, switches) = collect_switches(chain.complete(request())).await;
+        assert!(matches!(result, Err(ProviderError::InvalidRequest(_))));
+        assert_eq!(backup.calls.load(Ordering::SeqCst), 0);
+        assert!(switches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_chain_returns_last_error() {
+        let primary = ScriptedProvider::new("primary", 5, rate_limited);
+        let backup = ScriptedProvider::new("backup", 5, server_error);
+        let chain = FallbackProvider::new(vec![primary, backup]);
+
+        assert!(matches!(
+            chain.complete(request()).await,
+            Err(ProviderError::ApiError { status: 503, .. })
+        ));
+    }
+
+    #[test]
+    fn test_should_fail_over() {
+        assert!(should_fail_over(&rate_limited()));
+        assert!(should_fail_over(&server_error()));
+        assert!(should_fail_over(&ProviderError::Timeout(30)));
+        assert!(!should_fail_over(&ProviderError::InvalidApiKey));
+        assert!(!should_fail_over(&bad_request()));
+    }
+}
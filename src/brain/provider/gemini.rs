@@ -0,0 +1,1256 @@
+//! Google Gemini Provider Implementation
+//!
+//! Implements the Provider trait against the Generative Language API
+//! (`generateContent` / `streamGenerateContent`).
+//!
+//! ## Supported Models
+//! - gemini-2.5-pro
+//! - gemini-2.5-flash
+//! - gemini-2.5-flash-lite
+//! - gemini-2.0-flash
+//! - gemini-2.0-flash-lite
+//! - gemini-1.5-pro
+//! - gemini-1.5-flash
+//!
+//! Gemini has no tool-call IDs of its own on older models and matches
+//! `functionResponse` parts to calls by *name*, so request conversion keeps an
+//! id → name map built from earlier `ToolUse` blocks in the conversation.
+
+use super::error::{ProviderError, Result};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use async_trait::async_trait;
+use futures::stream::StreamExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const DEFAULT_GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// JSON-schema keys the Gemini function declaration schema rejects
+const UNSUPPORTED_SCHEMA_KEYS: &[&str] = &["$schema", "additionalProperties"];
+
+/// Google Gemini provider
+#[derive(Clone)]
+pub struct GeminiProvider {
+    api_key: String,
+    base_url: String,
+    client: Client,
+    custom_default_model: Option<String>,
+}
+
+impl GeminiProvider {
+    /// Create a new Gemini provider against the public API
+    pub fn new(api_key: String) -> Self {
+        Self::with_base_url(api_key, DEFAULT_GEMINI_API_URL.to_string())
+    }
+
+    /// Create with custom base URL (proxies, mock servers)
+    pub fn with_base_url(api_key: String, base_url: String) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .pool_idle_timeout(DEFAULT_POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(2)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            api_key,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            custom_default_model: None,
+        }
+    }
+
+    /// Set custom default model
+    pub fn with_default_model(mut self, model: String) -> Self {
+        self.custom_default_model = Some(model);
+        self
+    }
+
+    /// Build request headers
+    fn headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "x-goog-api-key",
+            self.api_key.parse().expect("Invalid API key format"),
+        );
+        headers.insert(
+            reqwest::header::CONTENT_TYPE,
+            "application/json".parse().expect("valid content-type"),
+        );
+        headers
+    }
+
+    fn generate_url(&self, model: &str) -> String {
+        format!("{}/models/{}:generateContent", self.base_url, model)
+    }
+
+    fn stream_url(&self, model: &str) -> String {
+        format!(
+            "{}/models/{}:streamGenerateContent?alt=sse",
+            self.base_url, model
+        )
+    }
+}
+
+/// Convert our generic request to Gemini's `GenerateContentRequest`.
+///
+/// Shared with the Vertex provider, which speaks the same body format.
+pub(crate) fn to_gemini_request(request: &LLMRequest) -> GeminiRequest {
+    let mut system_parts: Vec<GeminiPart> = request
+        .system
+        .iter()
+        .map(|s| GeminiPart::text(s.clone()))
+        .collect();
+
+    // functionResponse parts are matched by name, not id
+    let mut tool_names: HashMap<String, String> = HashMap::new();
+    let mut contents: Vec<GeminiContent> = Vec::new();
+
+    for msg in &request.messages {
+        let role = match msg.role {
+            Role::User => "user",
+            Role::Assistant => "model",
+            Role::System => {
+                for block in &msg.content {
+                    if let ContentBlock::Text { text } = block {
+                        system_parts.push(GeminiPart::text(text.clone()));
+                    }
+                }
+                continue;
+            }
+        };
+
+        let mut parts = Vec::new();
+        for block in &msg.content {
+            match block {
+                ContentBlock::Text { text } => {
+                    if !text.is_empty() {
+                        parts.push(GeminiPart::text(text.clone()));
+                    }
+                }
+                ContentBlock::Image { source } => parts.push(match source {
+                    ImageSource::Base64 { media_type, data } => GeminiPart {
+                        inline_data: Some(GeminiBlob {
+                            mime_type: media_type.clone(),
+                            data: data.clone(),
+                        }),
+                        ..Default::default()
+                    },
+                    ImageSource::Url { url } => GeminiPart {
+                        file_data: Some(GeminiFileData {
+                            mime_type: mime_from_url(url).to_string(),
+                            file_uri: url.clone(),
+                        }),
+                        ..Default::default()
+                    },
+                }),
+                ContentBlock::ToolUse { id, name, input } => {
+                    tool_names.insert(id.clone(), name.clone());
+                    parts.push(GeminiPart {
+                        function_call: Some(GeminiFunctionCall {
+                            id: None,
+                            name: name.clone(),
+                            args: input.clone(),
+                        }),
+                        ..Default::default()
+                    });
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => {
+                    let name = tool_names.get(tool_use_id).cloned().unwrap_or_else(|| {
+                        tracing::warn!(
+                            "Gemini: no ToolUse found for result {}, sending id as name",
+                            tool_use_id
+                        );
+                        tool_use_id.clone()
+                    });
+                    let key = if is_error.unwrap_or(false) { "error" } else { "output" };
+                    parts.push(GeminiPart {
+                        function_response: Some(GeminiFunctionResponse {
+                            name,
+                            response: serde_json::json!({ key: content }),
+                        }),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+
+        if parts.is_empty() {
+            continue;
+        }
+
+        // Gemini requires alternating roles — fold consecutive same-role turns
+        match contents.last_mut() {
+            Some(last) if last.role == role => last.parts.extend(parts),
+            _ => contents.push(GeminiContent {
+                role: role.to_string(),
+                parts,
+            }),
+        }
+    }
+
+    let tools = request.tools.as_ref().filter(|t| !t.is_empty()).map(|tools| {
+        vec![GeminiTool {
+            function_declarations: tools
+                .iter()
+                .map(|tool| GeminiFunctionDeclaration {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: sanitize_schema(tool.input_schema.clone()),
+                })
+                .collect(),
+        }]
+    });
+
+    GeminiRequest {
+        contents,
+        system_instruction: if system_parts.is_empty() {
+            None
+        } else {
+            Some(GeminiContent {
+                role: "user".to_string(),
+                parts: system_parts,
+            })
+        },
+        tools,
+        generation_config: Some(GeminiGenerationConfig {
+            temperature: request.temperature,
+            max_output_tokens: request.max_tokens,
+        }),
+    }
+}
+
+/// Convert a (non-streaming) Gemini response to our generic format
+pub(crate) fn from_gemini_response(response: GeminiResponse, model: &str) -> LLMResponse {
+    let candidate = response.candidates.into_iter().next();
+    let finish_reason = candidate.as_ref().and_then(|c| c.finish_reason.clone());
+
+    let mut content = Vec::new();
+    for part in candidate
+        .and_then(|c| c.content)
+        .map(|c| c.parts)
+        .unwrap_or_default()
+    {
+        if part.thought.unwrap_or(false) {
+            continue;
+        }
+        if let Some(call) = part.function_call {
+            content.push(ContentBlock::ToolUse {
+                id: call.id.unwrap_or_else(new_tool_call_id),
+                name: call.name,
+                input: call.args,
+            });
+        } else if let Some(text) = part.text
+            && !text.is_empty()
+        {
+            content.push(ContentBlock::Text { text });
+        }
+    }
+
+    let has_tool_use = content
+        .iter()
+        .any(|b| matches!(b, ContentBlock::ToolUse { .. }));
+
+    LLMResponse {
+        id: response.response_id.unwrap_or_default(),
+        model: response.model_version.unwrap_or_else(|| model.to_string()),
+        content,
+        stop_reason: map_finish_reason(finish_reason.as_deref(), has_tool_use),
+        usage: response.usage_metadata.map(Into::into).unwrap_or(TokenUsage {
+            input_tokens: 0,
+            output_tokens: 0,
+            reasoning_tokens: 0,
+        }),
+    }
+}
+
+/// Gemini reports `STOP` for tool calls too, so tool use is inferred from content
+fn map_finish_reason(reason: Option<&str>, has_tool_use: bool) -> Option<StopReason> {
+    if has_tool_use {
+        return Some(StopReason::ToolUse);
+    }
+    match reason? {
+        "STOP" => Some(StopReason::EndTurn),
+        "MAX_TOKENS" => Some(StopReason::MaxTokens),
+        _ => Some(StopReason::EndTurn),
+    }
+}
+
+fn new_tool_call_id() -> String {
+    format!("call_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Best-effort MIME type for `fileData` parts (Gemini requires one)
+fn mime_from_url(url: &str) -> &'static str {
+    let path = url.split(['?', '#']).next().unwrap_or(url).to_lowercase();
+    if path.ends_with(".png") {
+        "image/png"
+    } else if path.ends_with(".gif") {
+        "image/gif"
+    } else if path.ends_with(".webp") {
+        "image/webp"
+    } else {
+        "image/jpeg"
+    }
+}
+
+/// Strip JSON-schema keywords Gemini rejects, recursively
+fn sanitize_schema(mut schema: serde_json::Value) -> serde_json::Value {
+    match &mut schema {
+        serde_json::Value::Object(map) => {
+            for key in UNSUPPORTED_SCHEMA_KEYS {
+                map.remove(*key);
+            }
+            for value in map.values_mut() {
+                *value = sanitize_schema(value.take());
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for value in items.iter_mut() {
+                *value = sanitize_schema(value.take());
+            }
+        }
+        _ => {}
+    }
+    schema
+}
+
+/// Translates streamed `GenerateContentResponse` chunks into our `StreamEvent`s.
+///
+/// Gemini sends whole parts per chunk (function calls are never split), so each
+/// `functionCall` becomes a complete start/stop pair while text parts share a
+/// single open text block until something else interrupts it.
+#[derive(Default)]
+pub(crate) struct GeminiStreamState {
+    model: String,
+    started: bool,
+    finished: bool,
+    next_index: usize,
+    open_text_index: Option<usize>,
+    saw_tool_use: bool,
+}
+
+impl GeminiStreamState {
+    pub(crate) fn new(model: String) -> Self {
+        Self {
+            model,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn handle_chunk(&mut self, chunk: GeminiResponse) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+
+        if !self.started {
+            self.started = true;
+            events.push(StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: chunk.response_id.clone().unwrap_or_default(),
+                    model: chunk
+                        .model_version
+                        .clone()
+                        .unwrap_or_else(|| self.model.clone()),
+                    role: Role::Assistant,
+                    usage: TokenUsage {
+                        input_tokens: chunk
+                            .usage_metadata
+                            .as_ref()
+                            .map(|u| u.prompt_token_count)
+                            .unwrap_or(0),
+                        output_tokens: 0,
+                        reasoning_tokens: 0,
+                    },
+                },
+            });
+        }
+
+        let usage = chunk.usage_metadata;
+        let Some(candidate) = chunk.candidates.into_iter().next() else {
+            return events;
+        };
+
+        for part in candidate.content.map(|c| c.parts).unwrap_or_default() {
+            if part.thought.unwrap_or(false) {
+                continue;
+            }
+            if let Some(call) = part.function_call {
+                self.close_text(&mut events);
+                let index = self.next_index;
+                self.next_index += 1;
+                self.saw_tool_use = true;
+                events.push(StreamEvent::ContentBlockStart {
+                    index,
+                    content_block: ContentBlock::ToolUse {
+                        id: call.id.unwrap_or_else(new_tool_call_id),
+                        name: call.name,
+                        input: call.args,
+                    },
+                });
+                events.push(StreamEvent::ContentBlockStop { index });
+            } else if let Some(text) = part.text
+                && !text.is_empty()
+            {
+                let index = match self.open_text_index {
+                    Some(index) => index,
+                    None => {
+                        let index = self.next_index;
+                        self.next_index += 1;
+                        self.open_text_index = Some(index);
+                        events.push(StreamEvent::ContentBlockStart {
+                            index,
+                            content_block: ContentBlock::Text {
+                                text: String::new(),
+                            },
+                        });
+                        index
+                    }
+                };
+                events.push(StreamEvent::ContentBlockDelta {
+                    index,
+                    delta: ContentDelta::TextDelta { text },
+                });
+            }
+        }
+
+        if let Some(reason) = candidate.finish_reason {
+            self.close_text(&mut events);
+            self.finished = true;
+            events.push(StreamEvent::MessageDelta {
+                delta: MessageDelta {
+                    stop_reason: map_finish_reason(Some(reason.as_str()), self.saw_tool_use),
+                    stop_sequence: None,
+                },
+                usage: usage.map(Into::into).unwrap_or(TokenUsage {
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    reasoning_tokens: 0,
+                }),
+            });
+            events.push(StreamEvent::MessageStop);
+        }
+
+        events
+    }
+
+    /// Whether a chunk carrying `finishReason` has been seen
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn close_text(&mut self, events: &mut Vec<StreamEvent>) {
+        if let Some(index) = self.open_text_index.take() {
+            events.push(StreamEvent::ContentBlockStop { index });
+        }
+    }
+}
+
+/// Turn a Gemini SSE byte stream into a `ProviderStream`
+pub(crate) fn gemini_sse_stream(response: reqwest::Response, model: String) -> ProviderStream {
+    let byte_stream = response.bytes_stream();
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+    let state = std::sync::Arc::new(std::sync::Mutex::new(GeminiStreamState::new(model)));
+
+    let event_stream = byte_stream
+        .map(
+            move |chunk_result| -> Vec<std::result::Result<StreamEvent, ProviderError>> {
+                match chunk_result {
+                    Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+                    Ok(chunk) => {
+                        let text = String::from_utf8_lossy(&chunk);
+                        let mut buf = buffer.lock().expect("SSE buffer lock poisoned");
+                        buf.push_str(&text);
+                        let mut state = state.lock().expect("SSE state lock poisoned");
+
+                        let mut events = Vec::new();
+
+                        // Process complete lines (terminated by \n)
+                        while let Some(newline_pos) = buf.find('\n') {
+                            let line = buf[..newline_pos].trim().to_string();
+                            buf.drain(..=newline_pos);
+
+                            if let Some(json_str) = line.strip_prefix("data:") {
+                                let json_str = json_str.trim();
+                                match serde_json::from_str::<GeminiResponse>(json_str) {
+                                    Ok(chunk) => {
+                                        events.extend(state.handle_chunk(chunk).into_iter().map(Ok))
+                                    }
+                                    Err(e) => {
+                                        tracing::warn!(
+                                            "Failed to parse Gemini SSE chunk: {}. Data: {}",
+                                            e,
+                                            json_str.chars().take(200).collect::<String>()
+                                        );
+                                    }
+                                }
+                            }
+                        }
+
+                        if events.is_empty() {
+                            vec![Ok(StreamEvent::Ping)]
+                        } else {
+                            events
+                        }
+                    }
+                }
+            },
+        )
+        .flat_map(futures::stream::iter);
+
+    Box::pin(event_stream)
+}
+
+/// Map a Gemini error response (`{"error": {"code", "message", "status"}}`)
+pub(crate) async fn gemini_error(response: reqwest::Response) -> ProviderError {
+    let status = response.status().as_u16();
+
+    let retry_after = response
+        .headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok());
+
+    let body = response.json::<GeminiErrorResponse>().await.ok();
+    let message = body
+        .as_ref()
+        .map(|b| b.error.message.clone())
+        .unwrap_or_else(|| "Unknown error".to_string());
+
+    if status == 429 {
+        return ProviderError::RateLimitExceeded(match retry_after {
+            Some(secs) => format!("{} (retry after {} seconds)", message, secs),
+            None => format!("{} (rate limited, please retry later)", message),
+        });
+    }
+
+    ProviderError::ApiError {
+        status,
+        message,
+        error_type: body.and_then(|b| b.error.status),
+    }
+}
+
+#[async_trait]
+impl Provider for GeminiProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Gemini API request: model={}, messages={}",
+            model,
+            request.messages.len()
+        );
+
+        let gemini_request = to_gemini_request(&request);
+        let url = self.generate_url(&model);
+        let retry_config = RetryConfig::default();
+
+        let result = retry_with_backoff(
+            || async {
+                let response = self
+                    .client
+                    .post(&url)
+                    .headers(self.headers())
+                    .json(&gemini_request)
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(gemini_error(response).await);
+                }
+
+                let gemini_response: GeminiResponse = response.json().await?;
+                let llm_response = from_gemini_response(gemini_response, &model);
+
+                tracing::info!(
+                    "Gemini API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+                    llm_response.usage.input_tokens,
+                    llm_response.usage.output_tokens,
+                    llm_response.stop_reason
+                );
+
+                Ok(llm_response)
+            },
+            &retry_config,
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("Gemini API request failed: {}", e);
+        }
+
+        result
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        tracing::info!(
+            "Gemini streaming request: model={}, messages={}",
+            model,
+            request.messages.len()
+        );
+
+        let gemini_request = to_gemini_request(&request);
+        let url = self.stream_url(&model);
+        let retry_config = RetryConfig::default();
+
+        let response = retry_with_backoff(
+            || async {
+                let response = self
+                    .client
+                    .post(&url)
+                    .headers(self.headers())
+                    .json(&gemini_request)
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(gemini_error(response).await);
+                }
+
+                Ok(response)
+            },
+            &retry_config,
+        )
+        .await?;
+
+        Ok(gemini_sse_stream(response, model))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    fn default_model(&self) -> &str {
+        self.custom_default_model
+            .as_deref()
+            .unwrap_or("gemini-2.5-flash")
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec![
+            "gemini-2.5-pro".to_string(),
+            "gemini-2.5-flash".to_string(),
+            "gemini-2.5-flash-lite".to_string(),
+            "gemini-2.0-flash".to_string(),
+            "gemini-2.0-flash-lite".to_string(),
+            "gemini-1.5-pro".to_string(),
+            "gemini-1.5-flash".to_string(),
+        ]
+    }
+
+    async fn fetch_models(&self) -> Vec<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ModelEntry {
+            name: String,
+            #[serde(default)]
+            supported_generation_methods: Vec<String>,
+        }
+        #[derive(Deserialize)]
+        struct ModelsResponse {
+            #[serde(default)]
+            models: Vec<ModelEntry>,
+        }
+
+        match self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .header("x-goog-api-key", &self.api_key)
+            .send()
+            .await
+        {
+            Ok(resp) if resp.status().is_success() => match resp.json::<ModelsResponse>().await {
+                Ok(body) => {
+                    let mut models: Vec<String> = body
+                        .models
+                        .into_iter()
+                        .filter(|m| {
+                            m.supported_generation_methods
+                                .iter()
+                                .any(|g| g == "generateContent")
+                        })
+                        .map(|m| m.name.trim_start_matches("models/").to_string())
+                        .collect();
+                    models.sort();
+                    if models.is_empty() {
+                        return self.supported_models();
+                    }
+                    models
+                }
+                Err(_) => self.supported_models(),
+            },
+            _ => self.supported_models(),
+        }
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        gemini_context_window(model)
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        gemini_cost(model, input_tokens, output_tokens)
+    }
+}
+
+/// Context window for Gemini models (also used by Vertex)
+pub(crate) fn gemini_context_window(model: &str) -> Option<u32> {
+    match model {
+        "gemini-2.5-pro" => Some(1_048_576),
+        "gemini-2.5-flash" => Some(1_048_576),
+        "gemini-2.5-flash-lite" => Some(1_048_576),
+        "gemini-2.0-flash" => Some(1_048_576),
+        "gemini-2.0-flash-lite" => Some(1_048_576),
+        "gemini-1.5-pro" => Some(2_097_152),
+        "gemini-1.5-flash" => Some(1_048_576),
+        _ => None,
+    }
+}
+
+/// Cost in USD for Gemini models (also used by Vertex)
+pub(crate) fn gemini_cost(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+    // Costs per million tokens (standard tier, prompts <= 200k)
+    let (input_cost, output_cost) = match model {
+        "gemini-2.5-pro" => (1.25, 10.0),
+        "gemini-2.5-flash" => (0.30, 2.50),
+        "gemini-2.5-flash-lite" => (0.10, 0.40),
+        "gemini-2.0-flash" => (0.10, 0.40),
+        "gemini-2.0-flash-lite" => (0.075, 0.30),
+        "gemini-1.5-pro" => (1.25, 5.0),
+        "gemini-1.5-flash" => (0.075, 0.30),
+        _ => return 0.0,
+    };
+
+    let input_cost_total = (input_tokens as f64 / 1_000_000.0) * input_cost;
+    let output_cost_total = (output_tokens as f64 / 1_000_000.0) * output_cost;
+
+    input_cost_total + output_cost_total
+}
+
+// ============================================================================
+// Gemini API Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<GeminiTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generation_config: Option<GeminiGenerationConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct GeminiContent {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GeminiPart {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inline_data: Option<GeminiBlob>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_data: Option<GeminiFileData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function_response: Option<GeminiFunctionResponse>,
+    /// Set on thinking-model summary parts; never shown as answer text
+    #[serde(skip_serializing)]
+    thought: Option<bool>,
+}
+
+impl GeminiPart {
+    fn text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiBlob {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiFileData {
+    mime_type: String,
+    file_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiFunctionCall {
+    #[serde(default, skip_serializing)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeminiFunctionResponse {
+    name: String,
+    response: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiTool {
+    function_declarations: Vec<GeminiFunctionDeclaration>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct GeminiFunctionDeclaration {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    usage_metadata: Option<GeminiUsage>,
+    model_version: Option<String>,
+    response_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    content: Option<GeminiContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    /// Thinking tokens are billed as output
+    #[serde(default)]
+    thoughts_token_count: u32,
+}
+
+impl From<GeminiUsage> for TokenUsage {
+    fn from(usage: GeminiUsage) -> Self {
+        TokenUsage {
+            input_tokens: usage.prompt_token_count,
+            output_tokens: usage.candidates_token_count + usage.thoughts_token_count,
+            reasoning_tokens: usage.thoughts_token_count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeminiErrorResponse {
+    error: GeminiError,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct GeminiError {
+    message: String,
+    status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool_request() -> LLMRequest {
+        LLMRequest::new(
+            "gemini-2.5-flash".to_string(),
+            vec![
+                Message::user("list files"),
+                Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::ToolUse {
+                        id: "call_1".to_string(),
+                        name: "ls".to_string(),
+                        input: json!({ "path": "." }),
+                    }],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        content: "Cargo.toml\nsrc".to_string(),
+                        is_error: None,
+                    }],
+                },
+            ],
+        )
+        .with_system("You are helpful.".to_string())
+        .with_tools(vec![Tool {
+            name: "ls".to_string(),
+            description: "List a directory".to_string(),
+            input_schema: json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "additionalProperties": false
+            }),
+        }])
+    }
+
+    #[test]
+    fn test_gemini_provider_creation() {
+        let provider = GeminiProvider::new("test-key".to_string());
+        assert_eq!(provider.name(), "gemini");
+        assert_eq!(provider.default_model(), "gemini-2.5-flash");
+        assert_eq!(provider.base_url, DEFAULT_GEMINI_API_URL);
+    }
+
+    #[test]
+    fn test_custom_default_model() {
+        let provider = GeminiProvider::new("test-key".to_string())
+            .with_default_model("gemini-2.5-pro".to_string());
+        assert_eq!(provider.default_model(), "gemini-2.5-pro");
+    }
+
+    #[test]
+    fn test_request_translates_tools_and_results() {
+        let body = serde_json::to_value(to_gemini_request(&tool_request())).unwrap();
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "You are helpful.");
+        assert_eq!(body["contents"][0]["role"], "user");
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(body["contents"][1]["parts"][0]["functionCall"]["name"], "ls");
+        assert_eq!(body["contents"][1]["parts"][0]["functionCall"]["args"]["path"], ".");
+        // Result is matched back to the call by name
+        let response = &body["contents"][2]["parts"][0]["functionResponse"];
+        assert_eq!(response["name"], "ls");
+        assert_eq!(response["response"]["output"], "Cargo.toml\nsrc");
+
+        let params = &body["tools"][0]["functionDeclarations"][0]["parameters"];
+        assert!(params.get("$schema").is_none());
+        assert!(params.get("additionalProperties").is_none());
+        assert_eq!(params["properties"]["path"]["type"], "string");
+    }
+
+    #[test]
+    fn test_request_translates_images() {
+        let request = LLMRequest::new(
+            "gemini-2.5-flash".to_string(),
+            vec![Message {
+                role: Role::User,
+                content: vec![
+                    ContentBlock::Text {
+                        text: "what is this?".to_string(),
+                    },
+                    ContentBlock::Image {
+                        source: ImageSource::Base64 {
+                            media_type: "image/png".to_string(),
+                            data: "aGVsbG8=".to_string(),
+                        },
+                    },
+                    ContentBlock::Image {
+                        source: ImageSource::Url {
+                            url: "gs://bucket/cat.webp".to_string(),
+                        },
+                    },
+                ],
+            }],
+        );
+
+        let body = serde_json::to_value(to_gemini_request(&request)).unwrap();
+        let parts = &body["contents"][0]["parts"];
+        assert_eq!(parts[1]["inlineData"]["mimeType"], "image/png");
+        assert_eq!(parts[1]["inlineData"]["data"], "aGVsbG8=");
+        assert_eq!(parts[2]["fileData"]["fileUri"], "gs://bucket/cat.webp");
+        assert_eq!(parts[2]["fileData"]["mimeType"], "image/webp");
+    }
+
+    #[test]
+    fn test_consecutive_roles_are_merged() {
+        let request = LLMRequest::new(
+            "gemini-2.5-flash".to_string(),
+            vec![Message::user("one"), Message::user("two")],
+        );
+        let body = serde_json::to_value(to_gemini_request(&request)).unwrap();
+        assert_eq!(body["contents"].as_array().unwrap().len(), 1);
+        assert_eq!(body["contents"][0]["parts"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_response_function_call_becomes_tool_use() {
+        let response: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [
+                    { "text": "Let me look." },
+                    { "functionCall": { "name": "ls", "args": { "path": "src" } } }
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": { "promptTokenCount": 12, "candidatesTokenCount": 8 }
+        }))
+        .unwrap();
+
+        let llm = from_gemini_response(response, "gemini-2.5-flash");
+        assert_eq!(llm.stop_reason, Some(StopReason::ToolUse));
+        assert_eq!(llm.usage.input_tokens, 12);
+        assert_eq!(llm.usage.output_tokens, 8);
+        assert!(matches!(&llm.content[0], ContentBlock::Text { text } if text == "Let me look."));
+        match &llm.content[1] {
+            ContentBlock::ToolUse { id, name, input } => {
+                assert!(id.starts_with("call_"));
+                assert_eq!(name, "ls");
+                assert_eq!(input["path"], "src");
+            }
+            other => panic!("expected tool use, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stream_state_text_then_tool_call() {
+        let mut state = GeminiStreamState::new("gemini-2.5-flash".to_string());
+
+        let first: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Hel" }] } }],
+            "usageMetadata": { "promptTokenCount": 3 }
+        }))
+        .unwrap();
+        let events = state.handle_chunk(first);
+        assert!(matches!(events[0], StreamEvent::MessageStart { ref message } if message.usage.input_tokens == 3));
+        assert!(matches!(events[1], StreamEvent::ContentBlockStart { index: 0, .. }));
+        assert!(matches!(events[2], StreamEvent::ContentBlockDelta { index: 0, .. }));
+
+        let second: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": { "role": "model", "parts": [
+                    { "text": "lo" },
+                    { "functionCall": { "name": "ls", "args": {} } }
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": { "promptTokenCount": 3, "candidatesTokenCount": 5 }
+        }))
+        .unwrap();
+        let events = state.handle_chunk(second);
+        assert!(matches!(events[0], StreamEvent::ContentBlockDelta { index: 0, .. }));
+        assert!(matches!(events[1], StreamEvent::ContentBlockStop { index: 0 }));
+        assert!(matches!(
+            events[2],
+            StreamEvent::ContentBlockStart { index: 1, content_block: ContentBlock::ToolUse { .. } }
+        ));
+        assert!(matches!(events[3], StreamEvent::ContentBlockStop { index: 1 }));
+        assert!(matches!(
+            events[4],
+            StreamEvent::MessageDelta { ref delta, ref usage }
+                if delta.stop_reason == Some(StopReason::ToolUse) && usage.output_tokens == 5
+        ));
+        assert!(matches!(events[5], StreamEvent::MessageStop));
+        assert!(state.is_finished());
+    }
+
+    #[test]
+    fn test_thought_parts_are_skipped() {
+        let mut state = GeminiStreamState::new("gemini-2.5-pro".to_string());
+        let chunk: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{ "content": { "parts": [{ "text": "thinking...", "thought": true }] } }]
+        }))
+        .unwrap();
+        let events = state.handle_chunk(chunk);
+        assert_eq!(events.len(), 1); // MessageStart only
+    }
+
+    #[test]
+    fn test_context_window() {
+        let provider = GeminiProvider::new("test-key".to_string());
+        assert_eq!(provider.context_window("gemini-2.5-pro"), Some(1_048_576));
+        assert_eq!(provider.context_window("gemini-1.5-pro"), Some(2_097_152));
+        assert_eq!(provider.context_window("unknown"), None);
+    }
+
+    #[test]
+    fn test_calculate_cost() {
+        let provider = GeminiProvider::new("test-key".to_string());
+        let cost = provider.calculate_cost("gemini-2.5-pro", 1_000_000, 1_000_000);
+        assert!((cost - 11.25).abs() < 1e-9); // $1.25 input + $10 output
+        assert_eq!(provider.calculate_cost("unknown", 1000, 1000), 0.0);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let provider = GeminiProvider::new("test-key".to_string());
+        assert!(provider.supports_streaming());
+        assert!(provider.supports_tools());
+        assert!(provider.supports_vision());
+    }
+
+    // --- HTTP tests with mock server ---
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .match_header("x-goog-api-key", "test-gemini-key")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"generationConfig":{"maxOutputTokens":256}}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "candidates": [{
+                        "content": { "role": "model", "parts": [{ "text": "Hi there" }] },
+                        "finishReason": "STOP"
+                    }],
+                    "usageMetadata": { "promptTokenCount": 4, "candidatesTokenCount": 2 },
+                    "modelVersion": "gemini-2.5-flash",
+                    "responseId": "resp-1"
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let provider = GeminiProvider::with_base_url("test-gemini-key".to_string(), server.url());
+        let request = LLMRequest::new("gemini-2.5-flash".to_string(), vec![Message::user("hi")])
+            .with_max_tokens(256);
+        let response = provider.complete(request).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.id, "resp-1");
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+        assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "Hi there"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_against_mock_server() {
+        let sse = [
+            json!({
+                "candidates": [{ "content": { "role": "model", "parts": [{ "text": "Hello" }] } }],
+                "usageMetadata": { "promptTokenCount": 4 }
+            }),
+            json!({
+                "candidates": [{
+                    "content": { "role": "model", "parts": [{ "text": " world" }] },
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": { "promptTokenCount": 4, "candidatesTokenCount": 2 }
+            }),
+        ]
+        .iter()
+        .map(|c| format!("data: {}\r\n\r\n", c))
+        .collect::<String>();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/models/gemini-2.5-flash:streamGenerateContent")
+            .match_query(mockito::Matcher::UrlEncoded("alt".into(), "sse".into()))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(sse)
+            .create_async()
+            .await;
+
+        let provider = GeminiProvider::with_base_url("key".to_string(), server.url());
+        let request = LLMRequest::new("gemini-2.5-flash".to_string(), vec![Message::user("hi")]);
+        let events: Vec<StreamEvent> = provider
+            .stream(request)
+            .await
+            .unwrap()
+            .filter_map(|e| async move { e.ok() })
+            .collect()
+            .await;
+
+        mock.assert_async().await;
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentBlockDelta {
+                    delta: ContentDelta::TextDelta { text },
+                    ..
+                } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello world");
+        assert!(events.iter().any(|e| matches!(e, StreamEvent::MessageStop)));
+    }
+
+    #[tokio::test]
+    async fn test_api_error_is_mapped() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/models/gemini-2.5-flash:generateContent")
+            .with_status(400)
+            .with_body(
+                r#"{"error":{"code":400,"message":"API key not valid","status":"INVALID_ARGUMENT"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let provider = GeminiProvider::with_base_url("bad".to_string(), server.url());
+        let request = LLMRequest::new("gemini-2.5-flash".to_string(), vec![Message::user("hi")]);
+        let err = provider.complete(request).await.unwrap_err();
+
+        mock.assert_async().await;
+        match err {
+            ProviderError::ApiError {
+                status,
+                message,
+                error_type,
+            } => {
+                assert_eq!(status, 400);
+                assert_eq!(message, "API key not valid");
+                assert_eq!(error_type.as_deref(), Some("INVALID_ARGUMENT"));
+            }
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+    }
+}
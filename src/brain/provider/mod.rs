@@ -15,9 +15,17 @@ pub use types::*;
 
 // Provider implementations
 pub mod anthropic;
+pub mod bedrock;
 pub mod factory;
+pub mod fallback;
+pub mod gemini;
 pub mod openai;
+pub mod vertex;
 
 pub use anthropic::AnthropicProvider;
+pub use bedrock::BedrockProvider;
 pub use factory::create_provider;
+pub use fallback::{FallbackProvider, ProviderSwitch, collect_switches};
+pub use gemini::GeminiProvider;
 pub use openai::OpenAIProvider;
+pub use vertex::VertexProvider;
@@ -14,6 +14,13 @@
 //! - Local LLMs via LM Studio (http://localhost:1234/v1)
 //! - Ollama with OpenAI compatibility (http://localhost:11434/v1)
 //! - LocalAI and other compatible APIs
+//!
+//! ## Responses API
+//! Models that are only served by `/v1/responses` (o1-pro, o3-pro, the codex
+//! models, ...) are routed there automatically on the official API; see
+//! [`responses`].
+
+mod responses;
 
 use super::error::{ProviderError, Result};
 use super::r#trait::{Provider, ProviderStream};
@@ -22,6 +29,8 @@ use async_trait::async_trait;
 use futures::stream::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 const DEFAULT_OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
@@ -37,6 +46,10 @@ pub struct OpenAIProvider {
     client: Client,
     custom_default_model: Option<String>,
     name: String,
+    /// Send every request through the Responses API
+    responses_api: bool,
+    /// Image support per model, learned from the `/models` listing
+    model_vision: Arc<RwLock<HashMap<String, bool>>>,
 }
 
 impl OpenAIProvider {
@@ -56,6 +69,8 @@ impl OpenAIProvider {
             client,
             custom_default_model: None,
             name: "openai".to_string(),
+            responses_api: false,
+            model_vision: Arc::default(),
         }
     }
 
@@ -75,6 +90,8 @@ impl OpenAIProvider {
             client,
             custom_default_model: None,
             name: "openai-compatible".to_string(),
+            responses_api: false,
+            model_vision: Arc::default(),
         }
     }
 
@@ -94,6 +111,8 @@ impl OpenAIProvider {
             client,
             custom_default_model: None,
             name: "openai-compatible".to_string(),
+            responses_api: false,
+            model_vision: Arc::default(),
         }
     }
 
@@ -109,6 +128,41 @@ impl OpenAIProvider {
         self
     }
 
+    /// Use the Responses API for every model, not just the ones that require it
+    pub fn with_responses_api(mut self, enabled: bool) -> Self {
+        self.responses_api = enabled;
+        self
+    }
+
+    /// Whether this talks to api.openai.com (not a compatible server)
+    fn is_official(&self) -> bool {
+        self.base_url.starts_with("https://api.openai.com/")
+    }
+
+    /// Whether requests for `model` go to `/v1/responses`
+    fn uses_responses_api(&self, model: &str) -> bool {
+        self.responses_api || (self.is_official() && responses::requires_responses_api(model))
+    }
+
+    /// Responses endpoint next to the configured chat completions endpoint
+    fn responses_url(&self) -> String {
+        match self.base_url.strip_suffix("/chat/completions") {
+            Some(base) => format!("{}/responses", base),
+            None => format!("{}/responses", self.base_url.trim_end_matches('/')),
+        }
+    }
+
+    /// Whether `model` accepts image input: `/models` metadata when we have
+    /// it, otherwise what is known about the model family. `None` if unknown.
+    fn vision_support(&self, model: &str) -> Option<bool> {
+        let known = self
+            .model_vision
+            .read()
+            .ok()
+            .and_then(|models| models.get(model).copied());
+        known.or_else(|| model_vision_support(model))
+    }
+
     /// Build request headers
     fn headers(&self) -> reqwest::header::HeaderMap {
         let mut headers = reqwest::header::HeaderMap::new();
@@ -134,6 +188,8 @@ impl OpenAIProvider {
     /// Convert our generic request to OpenAI-specific format
     fn to_openai_request(&self, request: LLMRequest) -> OpenAIRequest {
         let mut messages = Vec::new();
+        // Unknown models get the images; only drop them when we know they'd be rejected
+        let accepts_images = self.vision_support(&request.model) != Some(false);
 
         // Debug: log system brain
         if let Some(ref system) = request.system {
@@ -146,7 +202,7 @@ impl OpenAIProvider {
         if let Some(system) = request.system {
             messages.push(OpenAIMessage {
                 role: "system".to_string(),
-                content: Some(system),
+                content: Some(OpenAIContent::Text(system)),
                 tool_calls: None,
                 tool_call_id: None,
             });
@@ -162,6 +218,7 @@ impl OpenAIProvider {
 
             // Separate content blocks by type
             let mut text_parts = Vec::new();
+            let mut image_urls = Vec::new();
             let mut tool_uses = Vec::new();
             let mut tool_results = Vec::new();
 
@@ -180,9 +237,11 @@ impl OpenAIProvider {
                     } => {
                         tool_results.push((tool_use_id, content));
                     }
+                    ContentBlock::Image { source } if accepts_images => {
+                        image_urls.push(image_url(source));
+                    }
                     ContentBlock::Image { .. } => {
-                        // Skip images for now (OpenAI needs special handling)
-                        tracing::warn!("Image content blocks not yet supported for OpenAI");
+                        text_parts.push(omitted_image_note(&request.model));
                     }
                 }
             }
@@ -209,7 +268,7 @@ impl OpenAIProvider {
 
                 messages.push(OpenAIMessage {
                     role: role.to_string(),
-                    content: content_str,
+                    content: content_str.map(OpenAIContent::Text),
                     tool_calls: Some(openai_tool_calls),
                     tool_call_id: None,
                 });
@@ -219,12 +278,29 @@ impl OpenAIProvider {
                 for (tool_use_id, content) in tool_results {
                     messages.push(OpenAIMessage {
                         role: "tool".to_string(),
-                        content: Some(content),
+                        content: Some(OpenAIContent::Text(content)),
                         tool_calls: None,
                         tool_call_id: Some(tool_use_id),
                     });
                 }
             }
+            // Handle messages with image attachments: text first, then images
+            else if !image_urls.is_empty() {
+                let parts = text_parts
+                    .into_iter()
+                    .map(|text| OpenAIContentPart::Text { text })
+                    .chain(image_urls.into_iter().map(|url| OpenAIContentPart::ImageUrl {
+                        image_url: OpenAIImageUrl { url },
+                    }))
+                    .collect();
+
+                messages.push(OpenAIMessage {
+                    role: role.to_string(),
+                    content: Some(OpenAIContent::Parts(parts)),
+                    tool_calls: None,
+                    tool_call_id: None,
+                });
+            }
             // Handle regular text messages
             else {
                 let content_str = if text_parts.is_empty() {
@@ -235,7 +311,7 @@ impl OpenAIProvider {
 
                 messages.push(OpenAIMessage {
                     role: role.to_string(),
-                    content: content_str,
+                    content: content_str.map(OpenAIContent::Text),
                     tool_calls: None,
                     tool_call_id: None,
                 });
@@ -263,6 +339,7 @@ impl OpenAIProvider {
             temperature: request.temperature,
             max_tokens: request.max_tokens,
             stream: Some(request.stream),
+            stream_options: None,
             tools,
         }
     }
@@ -278,7 +355,7 @@ impl OpenAIProvider {
                 index: 0,
                 message: OpenAIMessage {
                     role: "assistant".to_string(),
-                    content: Some(String::new()),
+                    content: Some(OpenAIContent::Text(String::new())),
                     tool_calls: None,
                     tool_call_id: None,
                 },
@@ -289,7 +366,7 @@ impl OpenAIProvider {
         let mut content_blocks = Vec::new();
 
         // Add text content if present
-        if let Some(content) = choice.message.content
+        if let Some(content) = choice.message.content.map(OpenAIContent::into_text)
             && !content.is_empty() {
                 content_blocks.push(ContentBlock::Text { text: content });
             }
@@ -327,25 +404,94 @@ impl OpenAIProvider {
         }
 
         // Map finish_reason to StopReason
-        let stop_reason = choice
-            .finish_reason
-            .and_then(|reason| match reason.as_str() {
-                "stop" => Some(StopReason::EndTurn),
-                "length" => Some(StopReason::MaxTokens),
-                "tool_calls" | "function_call" => Some(StopReason::ToolUse),
-                _ => None,
-            });
+        let stop_reason = choice.finish_reason.as_deref().and_then(map_finish_reason);
 
         LLMResponse {
             id: response.id,
             model: response.model,
             content: content_blocks,
             stop_reason,
-            usage: TokenUsage {
-                input_tokens: response.usage.prompt_tokens,
-                output_tokens: response.usage.completion_tokens,
+            usage: response.usage.into(),
+        }
+    }
+
+    /// Non-streaming request through the Responses API
+    async fn complete_responses(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        tracing::info!(
+            "OpenAI Responses API request: model={}, messages={}",
+            request.model,
+            request.messages.len()
+        );
+        let accepts_images = self.vision_support(&request.model) != Some(false);
+        let body = responses::to_responses_request(request, accepts_images);
+        let url = self.responses_url();
+
+        let result = retry_with_backoff(
+            || async {
+                let response = self
+                    .client
+                    .post(&url)
+                    .headers(self.headers())
+                    .json(&body)
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(self.handle_error(response).await);
+                }
+
+                let parsed: responses::ResponsesResponse = response.json().await?;
+                Ok(responses::from_responses_response(parsed))
             },
+            &RetryConfig::default(),
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("OpenAI Responses API request failed: {}", e);
         }
+
+        result
+    }
+
+    /// Streaming request through the Responses API
+    async fn stream_responses(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        tracing::info!(
+            "{} Responses API streaming request: model={}, messages={}",
+            self.name(),
+            request.model,
+            request.messages.len()
+        );
+        let accepts_images = self.vision_support(&request.model) != Some(false);
+        let mut body = responses::to_responses_request(request, accepts_images);
+        body.stream = true;
+        let url = self.responses_url();
+
+        let response = retry_with_backoff(
+            || async {
+                let response = self
+                    .client
+                    .post(&url)
+                    .headers(self.headers())
+                    .json(&body)
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(self.handle_error(response).await);
+                }
+
+                Ok(response)
+            },
+            &RetryConfig::default(),
+        )
+        .await?;
+
+        Ok(responses::responses_sse_stream(response))
     }
 
     /// Handle API error response
@@ -414,6 +560,10 @@ impl Provider for OpenAIProvider {
     async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
         use super::retry::{retry_with_backoff, RetryConfig};
 
+        if self.uses_responses_api(&request.model) {
+            return self.complete_responses(request).await;
+        }
+
         let model = request.model.clone();
         let message_count = request.messages.len();
         let openai_request = self.to_openai_request(request);
@@ -478,6 +628,10 @@ impl Provider for OpenAIProvider {
     async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
         use super::retry::{retry_with_backoff, RetryConfig};
 
+        if self.uses_responses_api(&request.model) {
+            return self.stream_responses(request).await;
+        }
+
         let model = request.model.clone();
         let message_count = request.messages.len();
         
@@ -489,6 +643,13 @@ impl Provider for OpenAIProvider {
 
         let mut openai_request = self.to_openai_request(request);
         openai_request.stream = Some(true);
+        // Compatible servers don't all accept stream_options; the official API
+        // only reports usage (and reasoning tokens) in a stream when asked
+        if self.is_official() {
+            openai_request.stream_options = Some(OpenAIStreamOptions {
+                include_usage: true,
+            });
+        }
         
         let tools_count = openai_request.tools.as_ref().map(|t| t.len()).unwrap_or(0);
         tracing::debug!("OpenAI request has {} tools", tools_count);
@@ -529,6 +690,7 @@ impl Provider for OpenAIProvider {
             false, // emitted_message_start
             false, // emitted_content_start
             std::collections::HashMap::<String, String>::new(), // accumulated tool args: index -> json string
+            None::<StopReason>, // stop reason from finish_reason, reported again with usage
         )));
         
         // Keep old state reference for now, use tool_state for accumulation
@@ -586,11 +748,31 @@ impl Provider for OpenAIProvider {
                                                     usage: crate::brain::provider::types::TokenUsage {
                                                         input_tokens: 0,
                                                         output_tokens: 0,
+                                                        reasoning_tokens: 0,
                                                     },
                                                 },
                                             }));
                                         }
                                         
+                                        // finish_reason and usage may come in separate chunks
+                                        // (usage last, with no choices); report both each time
+                                        let finish_reason = chunk.choices.first()
+                                            .and_then(|c| c.finish_reason.as_deref())
+                                            .map(map_finish_reason);
+                                        if finish_reason.is_some() || chunk.usage.is_some() {
+                                            let mut s = state.lock().expect("SSE state lock");
+                                            if finish_reason.is_some() {
+                                                s.3 = finish_reason.flatten();
+                                            }
+                                            events.push(Ok(StreamEvent::MessageDelta {
+                                                delta: MessageDelta {
+                                                    stop_reason: s.3.clone(),
+                                                    stop_sequence: None,
+                                                },
+                                                usage: chunk.usage.clone().map(Into::into).unwrap_or_default(),
+                                            }));
+                                        }
+
                                         // Get content
                                         let content = chunk.choices.first()
                                             .and_then(|c| c.delta.as_ref())
@@ -717,8 +899,7 @@ impl Provider for OpenAIProvider {
     }
 
     fn supports_vision(&self) -> bool {
-        // Only GPT-4 Vision models support vision
-        false
+        self.vision_support(self.default_model()).unwrap_or(false)
     }
 
     fn name(&self) -> &str {
@@ -748,7 +929,17 @@ impl Provider for OpenAIProvider {
             .replace("/chat/completions", "/models");
 
         #[derive(Deserialize)]
-        struct ModelEntry { id: String }
+        struct ModelEntry {
+            id: String,
+            /// OpenRouter describes each model's input/output modalities
+            #[serde(default)]
+            architecture: Option<ModelArchitecture>,
+        }
+        #[derive(Deserialize)]
+        struct ModelArchitecture {
+            #[serde(default)]
+            input_modalities: Vec<String>,
+        }
         #[derive(Deserialize)]
         struct ModelsResponse { data: Vec<ModelEntry> }
 
@@ -761,6 +952,16 @@ impl Provider for OpenAIProvider {
             Ok(resp) if resp.status().is_success() => {
                 match resp.json::<ModelsResponse>().await {
                     Ok(body) => {
+                        if let Ok(mut vision) = self.model_vision.write() {
+                            for model in &body.data {
+                                if let Some(arch) = &model.architecture
+                                    && !arch.input_modalities.is_empty()
+                                {
+                                    let image = arch.input_modalities.iter().any(|m| m == "image");
+                                    vision.insert(model.id.clone(), image);
+                                }
+                            }
+                        }
                         let mut models: Vec<String> = body.data
                             .into_iter()
                             .map(|m| m.id)
@@ -807,6 +1008,82 @@ impl Provider for OpenAIProvider {
     }
 }
 
+/// Map a chat completions `finish_reason` to our stop reason
+fn map_finish_reason(reason: &str) -> Option<StopReason> {
+    match reason {
+        "stop" => Some(StopReason::EndTurn),
+        "length" => Some(StopReason::MaxTokens),
+        "tool_calls" | "function_call" => Some(StopReason::ToolUse),
+        _ => None,
+    }
+}
+
+/// `image_url` value for an image: the URL itself, or a base64 data URL
+fn image_url(source: ImageSource) -> String {
+    match source {
+        ImageSource::Base64 { media_type, data } => {
+            format!("data:{};base64,{}", media_type, data)
+        }
+        ImageSource::Url { url } => url,
+    }
+}
+
+/// Stands in for an image the model can't take, so it can tell the user
+fn omitted_image_note(model: &str) -> String {
+    tracing::warn!("{} does not accept images, dropping attachment", model);
+    format!("[image omitted: {} does not accept image input]", model)
+}
+
+/// Image support by model family, for when `/models` doesn't say.
+/// Accepts OpenRouter-style `vendor/model` ids. `None` if unknown.
+fn model_vision_support(model: &str) -> Option<bool> {
+    let model = model.to_lowercase();
+    let model = model.rsplit('/').next().unwrap_or(&model);
+
+    const TEXT_ONLY: &[&str] = &[
+        "gpt-3.5",
+        "gpt-4-32k",
+        "gpt-4-0",
+        "gpt-4-1106",
+        "gpt-4-turbo-preview",
+        "o1-mini",
+        "o1-preview",
+        "o3-mini",
+        "text-embedding",
+    ];
+    const VISION: &[&str] = &[
+        "gpt-4o",
+        "chatgpt-4o",
+        "gpt-4.1",
+        "gpt-4.5",
+        "gpt-4-turbo",
+        "gpt-4-vision",
+        "gpt-5",
+        "o1",
+        "o3",
+        "o4",
+        "codex-mini",
+        "computer-use",
+        "claude-3",
+        "claude-sonnet-4",
+        "claude-opus-4",
+        "claude-haiku-4",
+        "gemini",
+        "pixtral",
+        "llava",
+    ];
+
+    if model.contains("vision") || model.contains("-vl") {
+        Some(true)
+    } else if model == "gpt-4" || TEXT_ONLY.iter().any(|p| model.starts_with(p)) {
+        Some(false)
+    } else if VISION.iter().any(|p| model.starts_with(p)) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
 // ============================================================================
 // OpenAI API Types
 // ============================================================================
@@ -822,20 +1099,63 @@ struct OpenAIRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<OpenAIStreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<OpenAITool>>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct OpenAIStreamOptions {
+    include_usage: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct OpenAIMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<OpenAIContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAIToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
 }
 
+/// Message content: plain text, or typed parts when images are attached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<OpenAIContentPart>),
+}
+
+impl OpenAIContent {
+    fn into_text(self) -> String {
+        match self {
+            OpenAIContent::Text(text) => text,
+            OpenAIContent::Parts(parts) => parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    OpenAIContentPart::Text { text } => Some(text),
+                    OpenAIContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenAIContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAIImageUrl },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenAIImageUrl {
+    url: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct OpenAIToolCall {
     id: String,
@@ -882,6 +1202,26 @@ struct OpenAIChoice {
 struct OpenAIUsage {
     prompt_tokens: u32,
     completion_tokens: u32,
+    #[serde(default)]
+    completion_tokens_details: Option<OpenAICompletionTokensDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OpenAICompletionTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u32,
+}
+
+impl From<OpenAIUsage> for TokenUsage {
+    fn from(usage: OpenAIUsage) -> Self {
+        TokenUsage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+            reasoning_tokens: usage
+                .completion_tokens_details
+                .map_or(0, |details| details.reasoning_tokens),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -890,6 +1230,9 @@ struct OpenAIStreamChunk {
     id: String,
     model: Option<String>,
     choices: Vec<OpenAIStreamChoice>,
+    /// Only on the last chunk, when usage was requested (or the server always sends it)
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -965,4 +1308,128 @@ mod tests {
         let cost = provider.calculate_cost("gpt-3.5-turbo", 1000, 1000);
         assert!((cost - 0.002).abs() < 0.0001);
     }
+
+    fn image_request(model: &str) -> LLMRequest {
+        LLMRequest::new(
+            model,
+            vec![Message {
+                role: Role::User,
+                content: vec![
+                    ContentBlock::Text {
+                        text: "what is this?".to_string(),
+                    },
+                    ContentBlock::Image {
+                        source: ImageSource::Base64 {
+                            media_type: "image/png".to_string(),
+                            data: "aGVsbG8=".to_string(),
+                        },
+                    },
+                    ContentBlock::Image {
+                        source: ImageSource::Url {
+                            url: "https://example.com/cat.jpg".to_string(),
+                        },
+                    },
+                ],
+            }],
+        )
+    }
+
+    #[test]
+    fn test_request_sends_image_parts() {
+        let provider = OpenAIProvider::new("test-key".to_string());
+        let body =
+            serde_json::to_value(provider.to_openai_request(image_request("gpt-4o"))).unwrap();
+
+        let parts = &body["messages"][0]["content"];
+        assert_eq!(parts[0]["type"], "text");
+        assert_eq!(parts[0]["text"], "what is this?");
+        assert_eq!(parts[1]["type"], "image_url");
+        assert_eq!(parts[1]["image_url"]["url"], "data:image/png;base64,aGVsbG8=");
+        assert_eq!(parts[2]["image_url"]["url"], "https://example.com/cat.jpg");
+
+        // Text-only messages stay plain strings for compatible servers
+        let body = serde_json::to_value(
+            provider.to_openai_request(LLMRequest::new("gpt-4o", vec![Message::user("hi")])),
+        )
+        .unwrap();
+        assert_eq!(body["messages"][0]["content"], "hi");
+    }
+
+    #[test]
+    fn test_request_drops_images_for_text_only_models() {
+        let provider = OpenAIProvider::new("test-key".to_string());
+        let body =
+            serde_json::to_value(provider.to_openai_request(image_request("gpt-3.5-turbo")))
+                .unwrap();
+
+        let content = body["messages"][0]["content"].as_str().unwrap();
+        assert!(content.starts_with("what is this?"));
+        assert!(content.contains("[image omitted: gpt-3.5-turbo does not accept image input]"));
+    }
+
+    #[test]
+    fn test_vision_support() {
+        assert_eq!(model_vision_support("gpt-4o-mini"), Some(true));
+        assert_eq!(model_vision_support("openai/gpt-4.1"), Some(true));
+        assert_eq!(model_vision_support("gpt-4-turbo-preview"), Some(false));
+        assert_eq!(model_vision_support("o3-mini"), Some(false));
+        assert_eq!(model_vision_support("qwen2.5-vl-72b-instruct"), Some(true));
+        assert_eq!(model_vision_support("MiniMax-M2.5"), None);
+
+        let provider = OpenAIProvider::new("test-key".to_string());
+        assert!(!provider.supports_vision());
+        let provider = provider.with_default_model("gpt-4o".to_string());
+        assert!(provider.supports_vision());
+
+        // /models metadata wins over the name
+        let provider = OpenAIProvider::with_base_url("k".to_string(), "http://x".to_string())
+            .with_default_model("acme/seer".to_string());
+        assert!(!provider.supports_vision());
+        provider
+            .model_vision
+            .write()
+            .unwrap()
+            .insert("acme/seer".to_string(), true);
+        assert!(provider.supports_vision());
+    }
+
+    #[test]
+    fn test_usage_reports_reasoning_tokens() {
+        let response: OpenAIResponse = serde_json::from_value(serde_json::json!({
+            "id": "chatcmpl-1",
+            "model": "o3",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "42"},
+                "finish_reason": "stop"
+            }],
+            "usage": {
+                "prompt_tokens": 10,
+                "completion_tokens": 300,
+                "completion_tokens_details": {"reasoning_tokens": 256}
+            }
+        }))
+        .unwrap();
+
+        let provider = OpenAIProvider::new("test-key".to_string());
+        let response = provider.from_openai_response(response);
+        assert_eq!(response.usage.output_tokens, 300);
+        assert_eq!(response.usage.reasoning_tokens, 256);
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+    }
+
+    #[test]
+    fn test_responses_api_routing() {
+        let provider = OpenAIProvider::new("test-key".to_string());
+        assert!(provider.uses_responses_api("o3-pro"));
+        assert!(!provider.uses_responses_api("gpt-4o"));
+        assert_eq!(provider.responses_url(), "https://api.openai.com/v1/responses");
+
+        // Compatible servers only use it when asked
+        let local = OpenAIProvider::local("http://localhost:1234/v1/chat/completions".to_string());
+        assert!(!local.uses_responses_api("o3-pro"));
+        let local = local.with_responses_api(true);
+        assert!(local.uses_responses_api("gpt-4o"));
+        assert_eq!(local.responses_url(), "http://localhost:1234/v1/responses");
+    }
 }
@@ -0,0 +1,683 @@
+//! OpenAI Responses API
+//!
+//! Some models (o1-pro, o3-pro, the codex models, computer-use) are only
+//! served by `/v1/responses`, which uses typed input/output items instead of
+//! chat messages. This translates our requests and streams to and from it.
+
+use super::super::error::ProviderError;
+use super::super::r#trait::ProviderStream;
+use super::super::types::*;
+use futures::stream::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Model families only available through the Responses API
+const RESPONSES_ONLY_MODELS: &[&str] = &[
+    "o1-pro",
+    "o3-pro",
+    "o3-deep-research",
+    "o4-mini-deep-research",
+    "codex-mini",
+    "computer-use-preview",
+];
+
+/// Whether `model` can't be used with chat completions
+pub(super) fn requires_responses_api(model: &str) -> bool {
+    RESPONSES_ONLY_MODELS.iter().any(|m| model.starts_with(m)) || model.contains("-codex")
+}
+
+/// Reasoning models reject sampling parameters like `temperature`
+fn is_reasoning_model(model: &str) -> bool {
+    ["o1", "o3", "o4", "gpt-5", "codex"]
+        .iter()
+        .any(|prefix| model.starts_with(prefix))
+}
+
+/// Convert our generic request to a Responses API request
+pub(super) fn to_responses_request(request: LLMRequest, accepts_images: bool) -> ResponsesRequest {
+    let mut input = Vec::new();
+
+    for msg in request.messages {
+        let role = match msg.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        };
+        let mut content = Vec::new();
+
+        for block in msg.content {
+            match block {
+                ContentBlock::Text { text } if msg.role == Role::Assistant => {
+                    content.push(InputContent::OutputText { text });
+                }
+                ContentBlock::Text { text } => content.push(InputContent::InputText { text }),
+                ContentBlock::Image { source } if accepts_images => {
+                    content.push(InputContent::InputImage {
+                        image_url: super::image_url(source),
+                    });
+                }
+                ContentBlock::Image { .. } => content.push(InputContent::InputText {
+                    text: super::omitted_image_note(&request.model),
+                }),
+                // Calls and results are items of their own, so flush the message so far
+                ContentBlock::ToolUse {
+                    id,
+                    name,
+                    input: args,
+                } => {
+                    flush_message(&mut input, role, &mut content);
+                    input.push(InputItem::FunctionCall {
+                        call_id: id,
+                        name,
+                        arguments: serde_json::to_string(&args).unwrap_or_default(),
+                    });
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content: output,
+                    ..
+                } => {
+                    flush_message(&mut input, role, &mut content);
+                    input.push(InputItem::FunctionCallOutput {
+                        call_id: tool_use_id,
+                        output,
+                    });
+                }
+            }
+        }
+        flush_message(&mut input, role, &mut content);
+    }
+
+    let tools = request
+        .tools
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tool| ResponsesTool {
+            r#type: "function".to_string(),
+            name: tool.name,
+            description: tool.description,
+            parameters: tool.input_schema,
+        })
+        .collect();
+
+    let temperature = request
+        .temperature
+        .filter(|_| !is_reasoning_model(&request.model));
+
+    ResponsesRequest {
+        model: request.model,
+        instructions: request.system,
+        input,
+        tools,
+        max_output_tokens: request.max_tokens,
+        temperature,
+        stream: request.stream,
+        store: false,
+    }
+}
+
+fn flush_message(input: &mut Vec<InputItem>, role: &str, content: &mut Vec<InputContent>) {
+    if !content.is_empty() {
+        input.push(InputItem::Message {
+            role: role.to_string(),
+            content: std::mem::take(content),
+        });
+    }
+}
+
+/// Convert a Responses API response to our generic format
+pub(super) fn from_responses_response(response: ResponsesResponse) -> LLMResponse {
+    let mut content = Vec::new();
+
+    for item in response.output {
+        match item {
+            OutputItem::Message { content: parts } => {
+                let text: String = parts
+                    .into_iter()
+                    .filter_map(|part| match part {
+                        OutputContent::OutputText { text } => Some(text),
+                        OutputContent::Refusal { refusal } => Some(refusal),
+                        OutputContent::Other => None,
+                    })
+                    .collect();
+                if !text.is_empty() {
+                    content.push(ContentBlock::Text { text });
+                }
+            }
+            OutputItem::FunctionCall {
+                call_id,
+                name,
+                arguments,
+            } => {
+                let input = serde_json::from_str(&arguments).unwrap_or_else(|e| {
+                    tracing::warn!("Failed to parse tool arguments for {}: {}", name, e);
+                    serde_json::json!({})
+                });
+                content.push(ContentBlock::ToolUse {
+                    id: call_id,
+                    name,
+                    input,
+                });
+            }
+            OutputItem::Other => {}
+        }
+    }
+
+    let saw_tool_use = content
+        .iter()
+        .any(|block| matches!(block, ContentBlock::ToolUse { .. }));
+
+    LLMResponse {
+        id: response.id,
+        model: response.model,
+        stop_reason: stop_reason(response.incomplete_details.as_ref(), saw_tool_use),
+        content,
+        usage: response.usage.map(Into::into).unwrap_or_default(),
+    }
+}
+
+fn stop_reason(incomplete: Option<&IncompleteDetails>, saw_tool_use: bool) -> Option<StopReason> {
+    if incomplete.and_then(|d| d.reason.as_deref()) == Some("max_output_tokens") {
+        Some(StopReason::MaxTokens)
+    } else if saw_tool_use {
+        Some(StopReason::ToolUse)
+    } else {
+        Some(StopReason::EndTurn)
+    }
+}
+
+/// Translates Responses API stream events into our `StreamEvent`s.
+///
+/// Each output item keeps its `output_index` as the content block index.
+/// Reasoning items produce no block; the gaps they leave are empty text
+/// blocks that the agent drops.
+#[derive(Default)]
+struct ResponsesStreamState {
+    open_blocks: HashSet<usize>,
+    saw_tool_use: bool,
+}
+
+impl ResponsesStreamState {
+    fn handle_event(&mut self, event: ResponsesStreamEvent) -> Vec<StreamEvent> {
+        match event {
+            ResponsesStreamEvent::Created { response } => vec![StreamEvent::MessageStart {
+                message: StreamMessage {
+                    id: response.id,
+                    model: response.model,
+                    role: Role::Assistant,
+                    usage: TokenUsage::default(),
+                },
+            }],
+            ResponsesStreamEvent::OutputItemAdded {
+                output_index,
+                item: OutputItem::FunctionCall { call_id, name, .. },
+            } => {
+                self.open_blocks.insert(output_index);
+                self.saw_tool_use = true;
+                vec![StreamEvent::ContentBlockStart {
+                    index: output_index,
+                    content_block: ContentBlock::ToolUse {
+                        id: call_id,
+                        name,
+                        input: serde_json::json!({}),
+                    },
+                }]
+            }
+            ResponsesStreamEvent::OutputItemAdded { .. } => Vec::new(),
+            ResponsesStreamEvent::OutputTextDelta {
+                output_index,
+                delta,
+            } => {
+                let mut events = Vec::new();
+                if self.open_blocks.insert(output_index) {
+                    events.push(StreamEvent::ContentBlockStart {
+                        index: output_index,
+                        content_block: ContentBlock::Text {
+                            text: String::new(),
+                        },
+                    });
+                }
+                events.push(StreamEvent::ContentBlockDelta {
+                    index: output_index,
+                    delta: ContentDelta::TextDelta { text: delta },
+                });
+                events
+            }
+            ResponsesStreamEvent::FunctionCallArgumentsDelta {
+                output_index,
+                delta,
+            } => vec![StreamEvent::ContentBlockDelta {
+                index: output_index,
+                delta: ContentDelta::InputJsonDelta {
+                    partial_json: delta,
+                },
+            }],
+            ResponsesStreamEvent::OutputItemDone { output_index } => {
+                if self.open_blocks.remove(&output_index) {
+                    vec![StreamEvent::ContentBlockStop {
+                        index: output_index,
+                    }]
+                } else {
+                    Vec::new()
+                }
+            }
+            ResponsesStreamEvent::Completed { response }
+            | ResponsesStreamEvent::Incomplete { response } => vec![
+                StreamEvent::MessageDelta {
+                    delta: MessageDelta {
+                        stop_reason: stop_reason(
+                            response.incomplete_details.as_ref(),
+                            self.saw_tool_use,
+                        ),
+                        stop_sequence: None,
+                    },
+                    usage: response.usage.map(Into::into).unwrap_or_default(),
+                },
+                StreamEvent::MessageStop,
+            ],
+            ResponsesStreamEvent::Failed { response } => vec![StreamEvent::Error {
+                error: response
+                    .error
+                    .map(|e| e.message)
+                    .unwrap_or_else(|| "Response failed".to_string()),
+            }],
+            ResponsesStreamEvent::Error { message } => vec![StreamEvent::Error { error: message }],
+            ResponsesStreamEvent::Other => Vec::new(),
+        }
+    }
+}
+
+/// Turn a Responses API SSE byte stream into a `ProviderStream`
+pub(super) fn responses_sse_stream(response: reqwest::Response) -> ProviderStream {
+    let byte_stream = response.bytes_stream();
+    let buffer = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+    let state = std::sync::Arc::new(std::sync::Mutex::new(ResponsesStreamState::default()));
+
+    let event_stream = byte_stream
+        .map(
+            move |chunk_result| -> Vec<std::result::Result<StreamEvent, ProviderError>> {
+                match chunk_result {
+                    Err(e) => vec![Err(ProviderError::StreamError(e.to_string()))],
+                    Ok(chunk) => {
+                        let text = String::from_utf8_lossy(&chunk);
+                        let mut buf = buffer.lock().expect("SSE buffer lock poisoned");
+                        buf.push_str(&text);
+                        let mut state = state.lock().expect("SSE state lock poisoned");
+
+                        let mut events = Vec::new();
+
+                        // Process complete lines; the `event:` lines repeat the data's `type`
+                        while let Some(newline_pos) = buf.find('\n') {
+                            let line = buf[..newline_pos].trim().to_string();
+                            buf.drain(..=newline_pos);
+
+                            if let Some(json_str) = line.strip_prefix("data:") {
+                                let json_str = json_str.trim();
+                                match serde_json::from_str::<ResponsesStreamEvent>(json_str) {
+                                    Ok(event) => {
+                                        events.extend(state.handle_event(event).into_iter().map(Ok))
+                                    }
+                                    Err(e) => {
+                                        tracing::warn!(
+                                            "Failed to parse Responses SSE event: {}. Data: {}",
+                                            e,
+                                            json_str.chars().take(200).collect::<String>()
+                                        );
+                                    }
+                                }
+                            }
+                        }
+
+                        if events.is_empty() {
+                            vec![Ok(StreamEvent::Ping)]
+                        } else {
+                            events
+                        }
+                    }
+                }
+            },
+        )
+        .flat_map(futures::stream::iter);
+
+    Box::pin(event_stream)
+}
+
+// ============================================================================
+// Responses API Types
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct ResponsesRequest {
+    model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instructions: Option<String>,
+    input: Vec<InputItem>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ResponsesTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    pub(super) stream: bool,
+    /// The whole conversation is sent every turn; nothing to keep server-side
+    store: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InputItem {
+    Message {
+        role: String,
+        content: Vec<InputContent>,
+    },
+    FunctionCall {
+        call_id: String,
+        name: String,
+        arguments: String,
+    },
+    FunctionCallOutput {
+        call_id: String,
+        output: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum InputContent {
+    InputText {
+        text: String,
+    },
+    /// Earlier assistant turns are sent back as output text
+    OutputText {
+        text: String,
+    },
+    InputImage {
+        image_url: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ResponsesTool {
+    r#type: String,
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(super) struct ResponsesResponse {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    output: Vec<OutputItem>,
+    #[serde(default)]
+    usage: Option<ResponsesUsage>,
+    #[serde(default)]
+    incomplete_details: Option<IncompleteDetails>,
+    #[serde(default)]
+    error: Option<ResponsesError>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OutputItem {
+    Message {
+        #[serde(default)]
+        content: Vec<OutputContent>,
+    },
+    FunctionCall {
+        call_id: String,
+        name: String,
+        #[serde(default)]
+        arguments: String,
+    },
+    /// Reasoning summaries, web searches, ...
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OutputContent {
+    OutputText {
+        text: String,
+    },
+    Refusal {
+        refusal: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IncompleteDetails {
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ResponsesError {
+    message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ResponsesUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    #[serde(default)]
+    output_tokens_details: Option<OutputTokensDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct OutputTokensDetails {
+    #[serde(default)]
+    reasoning_tokens: u32,
+}
+
+impl From<ResponsesUsage> for TokenUsage {
+    fn from(usage: ResponsesUsage) -> Self {
+        TokenUsage {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            reasoning_tokens: usage
+                .output_tokens_details
+                .map_or(0, |details| details.reasoning_tokens),
+        }
+    }
+}
+
+/// The stream events we act on; the rest (reasoning summaries, content part
+/// bookkeeping, ...) are ignored
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type")]
+enum ResponsesStreamEvent {
+    #[serde(rename = "response.created")]
+    Created { response: ResponsesResponse },
+    #[serde(rename = "response.output_item.added")]
+    OutputItemAdded {
+        output_index: usize,
+        item: OutputItem,
+    },
+    #[serde(rename = "response.output_text.delta")]
+    OutputTextDelta { output_index: usize, delta: String },
+    #[serde(rename = "response.function_call_arguments.delta")]
+    FunctionCallArgumentsDelta { output_index: usize, delta: String },
+    #[serde(rename = "response.output_item.done")]
+    OutputItemDone { output_index: usize },
+    #[serde(rename = "response.completed")]
+    Completed { response: ResponsesResponse },
+    #[serde(rename = "response.incomplete")]
+    Incomplete { response: ResponsesResponse },
+    #[serde(rename = "response.failed")]
+    Failed { response: ResponsesResponse },
+    #[serde(rename = "error")]
+    Error { message: String },
+    #[serde(other)]
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_requires_responses_api() {
+        assert!(requires_responses_api("o3-pro"));
+        assert!(requires_responses_api("gpt-5-codex"));
+        assert!(requires_responses_api("codex-mini-latest"));
+        assert!(!requires_responses_api("gpt-4o"));
+        assert!(!requires_responses_api("o3"));
+    }
+
+    #[test]
+    fn test_request_items_and_images() {
+        let request = LLMRequest::new(
+            "o3-pro",
+            vec![
+                Message {
+                    role: Role::User,
+                    content: vec![
+                        ContentBlock::Text {
+                            text: "what is this?".to_string(),
+                        },
+                        ContentBlock::Image {
+                            source: ImageSource::Base64 {
+                                media_type: "image/png".to_string(),
+                                data: "aGVsbG8=".to_string(),
+                            },
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::Assistant,
+                    content: vec![
+                        ContentBlock::Text {
+                            text: "Let me look.".to_string(),
+                        },
+                        ContentBlock::ToolUse {
+                            id: "call_1".to_string(),
+                            name: "ls".to_string(),
+                            input: json!({"path": "."}),
+                        },
+                    ],
+                },
+                Message {
+                    role: Role::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "call_1".to_string(),
+                        content: "src".to_string(),
+                        is_error: None,
+                    }],
+                },
+            ],
+        )
+        .with_system("You are helpful.")
+        .with_temperature(0.7);
+
+        let body = serde_json::to_value(to_responses_request(request, true)).unwrap();
+        assert_eq!(body["instructions"], "You are helpful.");
+        assert!(body.get("temperature").is_none());
+        assert_eq!(body["store"], false);
+
+        let input = &body["input"];
+        assert_eq!(input[0]["content"][0]["type"], "input_text");
+        assert_eq!(input[0]["content"][1]["type"], "input_image");
+        assert_eq!(
+            input[0]["content"][1]["image_url"],
+            "data:image/png;base64,aGVsbG8="
+        );
+        assert_eq!(input[1]["role"], "assistant");
+        assert_eq!(input[1]["content"][0]["type"], "output_text");
+        assert_eq!(input[2]["type"], "function_call");
+        assert_eq!(input[2]["call_id"], "call_1");
+        assert_eq!(input[2]["arguments"], r#"{"path":"."}"#);
+        assert_eq!(input[3]["type"], "function_call_output");
+        assert_eq!(input[3]["output"], "src");
+    }
+
+    #[test]
+    fn test_response_with_reasoning_and_tool_call() {
+        let response: ResponsesResponse = serde_json::from_value(json!({
+            "id": "resp_1",
+            "model": "o3-pro",
+            "output": [
+                {"type": "reasoning", "id": "rs_1", "summary": []},
+                {"type": "message", "role": "assistant", "content": [
+                    {"type": "output_text", "text": "Listing.", "annotations": []}
+                ]},
+                {"type": "function_call", "id": "fc_1", "call_id": "call_1",
+                 "name": "ls", "arguments": "{\"path\":\".\"}"}
+            ],
+            "usage": {
+                "input_tokens": 50,
+                "output_tokens": 120,
+                "output_tokens_details": {"reasoning_tokens": 100}
+            }
+        }))
+        .unwrap();
+
+        let response = from_responses_response(response);
+        assert_eq!(response.stop_reason, Some(StopReason::ToolUse));
+        assert_eq!(response.content.len(), 2);
+        assert!(matches!(&response.content[1],
+            ContentBlock::ToolUse { name, input, .. } if name == "ls" && input["path"] == "."));
+        assert_eq!(response.usage.output_tokens, 120);
+        assert_eq!(response.usage.reasoning_tokens, 100);
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let events = [
+            json!({"type": "response.created", "response": {"id": "resp_1", "model": "o3-pro"}}),
+            json!({"type": "response.output_item.added", "output_index": 0,
+                   "item": {"type": "reasoning", "id": "rs_1"}}),
+            json!({"type": "response.output_item.done", "output_index": 0,
+                   "item": {"type": "reasoning", "id": "rs_1"}}),
+            json!({"type": "response.output_text.delta", "output_index": 1, "delta": "Hi"}),
+            json!({"type": "response.output_item.added", "output_index": 2,
+                   "item": {"type": "function_call", "call_id": "call_1", "name": "ls", "arguments": ""}}),
+            json!({"type": "response.function_call_arguments.delta", "output_index": 2, "delta": "{}"}),
+            json!({"type": "response.output_item.done", "output_index": 2,
+                   "item": {"type": "function_call", "call_id": "call_1", "name": "ls", "arguments": "{}"}}),
+            json!({"type": "response.completed", "response": {"id": "resp_1", "model": "o3-pro",
+                   "usage": {"input_tokens": 5, "output_tokens": 9,
+                             "output_tokens_details": {"reasoning_tokens": 4}}}}),
+        ];
+
+        let mut state = ResponsesStreamState::default();
+        let out: Vec<StreamEvent> = events
+            .into_iter()
+            .flat_map(|e| state.handle_event(serde_json::from_value(e).unwrap()))
+            .collect();
+
+        assert!(matches!(&out[0], StreamEvent::MessageStart { message } if message.id == "resp_1"));
+        assert!(matches!(
+            &out[1],
+            StreamEvent::ContentBlockStart { index: 1, .. }
+        ));
+        assert!(matches!(&out[3],
+            StreamEvent::ContentBlockStart { index: 2, content_block: ContentBlock::ToolUse { id, .. } }
+            if id == "call_1"));
+        assert!(matches!(
+            &out[4],
+            StreamEvent::ContentBlockDelta {
+                index: 2,
+                delta: ContentDelta::InputJsonDelta { .. }
+            }
+        ));
+        assert!(matches!(
+            &out[5],
+            StreamEvent::ContentBlockStop { index: 2 }
+        ));
+        match &out[6] {
+            StreamEvent::MessageDelta { delta, usage } => {
+                assert_eq!(delta.stop_reason, Some(StopReason::ToolUse));
+                assert_eq!(usage.reasoning_tokens, 4);
+            }
+            other => panic!("expected MessageDelta, got {:?}", other),
+        }
+        assert!(matches!(&out[7], StreamEvent::MessageStop));
+    }
+}
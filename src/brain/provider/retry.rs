@@ -13,6 +13,18 @@ use std::future::Future;
 use std::time::Duration;
 use tokio::time::sleep;
 
+tokio::task_local! {
+    /// Set while another provider in a fallback chain can take over
+    static FAIL_FAST: ();
+}
+
+/// Run a provider call without backing off on errors a fallback chain fails
+/// over on, so the next provider is tried right away instead of after the
+/// full retry schedule. Other retryable errors are still retried.
+pub async fn fail_fast<F: Future>(call: F) -> F::Output {
+    FAIL_FAST.scope((), call).await
+}
+
 /// Retry configuration
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
@@ -133,6 +145,11 @@ where
                     return Err(err);
                 }
 
+                if FAIL_FAST.try_with(|_| ()).is_ok() && super::fallback::should_fail_over(&err) {
+                    tracing::debug!("Fallback provider available, not retrying: {}", err);
+                    return Err(err);
+                }
+
                 // Check if we've exhausted attempts
                 if attempt >= config.max_attempts {
                     tracing::warn!(
@@ -391,6 +408,31 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 1); // Should not retry
     }
 
+    #[tokio::test]
+    async fn test_fail_fast_skips_backoff_for_failover_errors() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        let config = RetryConfig::new(3, Duration::from_millis(10));
+        let call_count = Arc::new(AtomicU32::new(0));
+        let call_count_clone = call_count.clone();
+
+        let result = fail_fast(retry_with_backoff(
+            move || {
+                let count = call_count_clone.clone();
+                async move {
+                    count.fetch_add(1, Ordering::SeqCst);
+                    Err::<i32, _>(ProviderError::Timeout(10))
+                }
+            },
+            &config,
+        ))
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(call_count.load(Ordering::SeqCst), 1); // Left to the next provider
+    }
+
     #[test]
     fn test_extract_retry_after() {
         let err = ProviderError::RateLimitExceeded(
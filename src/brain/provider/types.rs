@@ -199,12 +199,15 @@ pub enum StopReason {
 }
 
 /// Token usage information
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct TokenUsage {
     /// Input tokens
     pub input_tokens: u32,
-    /// Output tokens
+    /// Output tokens (including any reasoning tokens)
     pub output_tokens: u32,
+    /// Output tokens spent on hidden reasoning (already counted in `output_tokens`)
+    #[serde(default)]
+    pub reasoning_tokens: u32,
 }
 
 impl TokenUsage {
@@ -302,7 +305,9 @@ mod tests {
         let usage = TokenUsage {
             input_tokens: 100,
             output_tokens: 200,
+            reasoning_tokens: 50,
         };
+        // Reasoning is part of the output, not on top of it
         assert_eq!(usage.total(), 300);
     }
 }
@@ -0,0 +1,327 @@
+//! Service-account OAuth2 for Vertex AI
+//!
+//! Implements the JWT-bearer grant (RFC 7523): a short-lived JWT signed with
+//! the service account's RSA key is exchanged at `token_uri` for an access
+//! token. Tokens are cached and refreshed shortly before they expire.
+
+use super::super::error::{ProviderError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+const JWT_BEARER_GRANT: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+/// Lifetime requested for the signed assertion (Google's maximum)
+const ASSERTION_LIFETIME_SECS: i64 = 3600;
+/// Refresh this long before the token actually expires
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// The fields we need from a service-account JSON key file
+#[derive(Clone, Deserialize)]
+pub struct ServiceAccountKey {
+    pub client_email: String,
+    pub private_key: String,
+    #[serde(default)]
+    pub private_key_id: Option<String>,
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default = "default_token_uri")]
+    pub token_uri: String,
+}
+
+fn default_token_uri() -> String {
+    DEFAULT_TOKEN_URI.to_string()
+}
+
+impl std::fmt::Debug for ServiceAccountKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServiceAccountKey")
+            .field("client_email", &self.client_email)
+            .field("private_key", &"[REDACTED]")
+            .field("project_id", &self.project_id)
+            .field("token_uri", &self.token_uri)
+            .finish()
+    }
+}
+
+impl ServiceAccountKey {
+    /// Parse a key from its JSON contents
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            ProviderError::InvalidRequest(format!("Invalid service-account key: {}", e))
+        })
+    }
+
+    /// Load a key from a JSON file on disk
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            ProviderError::InvalidRequest(format!(
+                "Failed to read service-account key {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Build the signed JWT assertion for the token request
+    fn signed_assertion(&self, issued_at: i64) -> Result<String> {
+        let mut header = serde_json::json!({ "alg": "RS256", "typ": "JWT" });
+        if let Some(kid) = &self.private_key_id {
+            header["kid"] = serde_json::Value::String(kid.clone());
+        }
+        let claims = serde_json::json!({
+            "iss": self.client_email,
+            "scope": CLOUD_PLATFORM_SCOPE,
+            "aud": self.token_uri,
+            "iat": issued_at,
+            "exp": issued_at + ASSERTION_LIFETIME_SECS,
+        });
+
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+
+        let signature = rs256_sign(&self.private_key, signing_input.as_bytes())
+            .map_err(|e| ProviderError::InvalidRequest(format!("Failed to sign JWT: {}", e)))?;
+
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+    }
+}
+
+fn rs256_sign(pem: &str, data: &[u8]) -> std::result::Result<Vec<u8>, openssl::error::ErrorStack> {
+    let key = PKey::private_key_from_pem(pem.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(data)?;
+    signer.sign_to_vec()
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default = "default_expires_in")]
+    expires_in: u64,
+}
+
+fn default_expires_in() -> u64 {
+    ASSERTION_LIFETIME_SECS as u64
+}
+
+/// Mints and caches access tokens for one service account
+pub struct TokenSource {
+    key: ServiceAccountKey,
+    client: reqwest::Client,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+impl TokenSource {
+    pub fn new(key: ServiceAccountKey, client: reqwest::Client) -> Self {
+        Self {
+            key,
+            client,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Return a valid access token, minting a new one if the cache is stale.
+    ///
+    /// The lock is held across the refresh so concurrent callers share a
+    /// single token request instead of stampeding the endpoint.
+    pub async fn access_token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(token) = cached.as_ref()
+            && Instant::now() + REFRESH_MARGIN < token.expires_at
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = self.fetch_token().await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    async fn fetch_token(&self) -> Result<CachedToken> {
+        tracing::debug!("Minting Vertex access token for {}", self.key.client_email);
+
+        let assertion = self.key.signed_assertion(chrono::Utc::now().timestamp())?;
+        let response = self
+            .client
+            .post(&self.key.token_uri)
+            .form(&[("grant_type", JWT_BEARER_GRANT), ("assertion", assertion.as_str())])
+            .send()
+            .await?;
+
+        let status = response.status().as_u16();
+        if !(200..300).contains(&status) {
+            let body = response.text().await.unwrap_or_default();
+            tracing::error!("Vertex token exchange failed ({}): {}", status, body);
+            return Err(if status == 400 || status == 401 {
+                ProviderError::InvalidApiKey
+            } else {
+                ProviderError::ApiError {
+                    status,
+                    message: format!("Token exchange failed: {}", body),
+                    error_type: None,
+                }
+            });
+        }
+
+        let token: TokenResponse = response.json().await?;
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: Instant::now() + Duration::from_secs(token.expires_in),
+        })
+    }
+
+    #[cfg(test)]
+    async fn expire_cached_token(&self) {
+        if let Some(token) = self.cached.lock().await.as_mut() {
+            token.expires_at = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use openssl::rsa::Rsa;
+    use openssl::sign::Verifier;
+
+    /// A throwaway service-account key pointing at `token_uri`
+    pub(crate) fn test_key(token_uri: &str) -> (ServiceAccountKey, PKey<openssl::pkey::Private>) {
+        let rsa = Rsa::generate(2048).unwrap();
+        let pkey = PKey::from_rsa(rsa).unwrap();
+        let pem = String::from_utf8(pkey.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        let key = ServiceAccountKey::from_json(
+            &serde_json::json!({
+                "type": "service_account",
+                "project_id": "test-project",
+                "private_key_id": "kid-1",
+                "private_key": pem,
+                "client_email": "bot@test-project.iam.gserviceaccount.com",
+                "token_uri": token_uri,
+            })
+            .to_string(),
+        )
+        .unwrap();
+        (key, pkey)
+    }
+
+    fn decode_segment(segment: &str) -> serde_json::Value {
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(segment).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_assertion_is_valid_rs256_jwt() {
+        let (key, pkey) = test_key("https://oauth2.example/token");
+        let jwt = key.signed_assertion(1_700_000_000).unwrap();
+        let parts: Vec<&str> = jwt.split('.').collect();
+        assert_eq!(parts.len(), 3);
+
+        let header = decode_segment(parts[0]);
+        assert_eq!(header["alg"], "RS256");
+        assert_eq!(header["kid"], "kid-1");
+
+        let claims = decode_segment(parts[1]);
+        assert_eq!(claims["iss"], "bot@test-project.iam.gserviceaccount.com");
+        assert_eq!(claims["aud"], "https://oauth2.example/token");
+        assert_eq!(claims["scope"], CLOUD_PLATFORM_SCOPE);
+        assert_eq!(claims["exp"], 1_700_003_600);
+
+        let signature = URL_SAFE_NO_PAD.decode(parts[2]).unwrap();
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey).unwrap();
+        verifier
+            .update(format!("{}.{}", parts[0], parts[1]).as_bytes())
+            .unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+    }
+
+    #[test]
+    fn test_debug_redacts_private_key() {
+        let (key, _) = test_key("https://oauth2.example/token");
+        assert!(!format!("{:?}", key).contains("PRIVATE KEY"));
+    }
+
+    #[test]
+    fn test_invalid_key_json() {
+        assert!(ServiceAccountKey::from_json("{\"client_email\": \"x\"}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_is_minted_and_cached() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .match_body(mockito::Matcher::Regex(
+                "grant_type=urn%3Aietf%3Aparams%3Aoauth%3Agrant-type%3Ajwt-bearer&assertion=".to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token":"ya29.first","expires_in":3599,"token_type":"Bearer"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let (key, _) = test_key(&format!("{}/token", server.url()));
+        let source = TokenSource::new(key, reqwest::Client::new());
+
+        assert_eq!(source.access_token().await.unwrap(), "ya29.first");
+        assert_eq!(source.access_token().await.unwrap(), "ya29.first");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_refreshed() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .with_status(200)
+            .with_body(r#"{"access_token":"ya29.token","expires_in":3599}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let (key, _) = test_key(&format!("{}/token", server.url()));
+        let source = TokenSource::new(key, reqwest::Client::new());
+
+        source.access_token().await.unwrap();
+        source.expire_cached_token().await;
+        source.access_token().await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rejected_assertion_maps_to_invalid_key() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("POST", "/token")
+            .with_status(400)
+            .with_body(r#"{"error":"invalid_grant","error_description":"Invalid JWT Signature."}"#)
+            .create_async()
+            .await;
+
+        let (key, _) = test_key(&format!("{}/token", server.url()));
+        let source = TokenSource::new(key, reqwest::Client::new());
+
+        assert!(matches!(
+            source.access_token().await,
+            Err(ProviderError::InvalidApiKey)
+        ));
+    }
+}
@@ -0,0 +1,548 @@
+//! Google Vertex AI Provider Implementation
+//!
+//! Calls publisher models on Vertex AI with service-account credentials:
+//! - Anthropic Claude via `rawPredict` / `streamRawPredict` (Messages API body)
+//! - Google Gemini via `generateContent` / `streamGenerateContent`
+//!
+//! The publisher is picked from the model ID (`claude-*` → Anthropic,
+//! everything else → Google). Request/response translation is shared with the
+//! native Anthropic and Gemini providers; only auth and URLs differ.
+//!
+//! ## Supported Models
+//! - claude-opus-4-1@20250805
+//! - claude-sonnet-4-5@20250929
+//! - claude-sonnet-4@20250514
+//! - claude-haiku-4-5@20251001
+//! - claude-3-7-sonnet@20250219
+//! - claude-3-5-haiku@20241022
+//! - gemini-2.5-pro / gemini-2.5-flash / gemini-2.5-flash-lite
+//! - gemini-2.0-flash / gemini-2.0-flash-lite
+
+mod auth;
+
+pub use auth::{ServiceAccountKey, TokenSource};
+
+use super::anthropic::{anthropic_error, anthropic_sse_stream};
+use super::error::{ProviderError, Result};
+use super::gemini::{
+    GeminiResponse, from_gemini_response, gemini_context_window, gemini_cost, gemini_error,
+    gemini_sse_stream, to_gemini_request,
+};
+use super::r#trait::{Provider, ProviderStream};
+use super::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `anthropic_version` value required by Claude on Vertex
+const VERTEX_ANTHROPIC_VERSION: &str = "vertex-2023-10-16";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Model publisher on Vertex — decides the endpoint and body format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Publisher {
+    Anthropic,
+    Google,
+}
+
+impl Publisher {
+    fn for_model(model: &str) -> Self {
+        if model.starts_with("claude") {
+            Publisher::Anthropic
+        } else {
+            Publisher::Google
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Publisher::Anthropic => "anthropic",
+            Publisher::Google => "google",
+        }
+    }
+}
+
+/// Google Vertex AI provider
+#[derive(Clone)]
+pub struct VertexProvider {
+    tokens: Arc<TokenSource>,
+    project_id: String,
+    location: String,
+    base_url: String,
+    client: Client,
+    custom_default_model: Option<String>,
+}
+
+impl VertexProvider {
+    /// Create a provider for `project_id` in `location` (e.g. `us-east5`, `global`)
+    pub fn new(key: ServiceAccountKey, project_id: String, location: String) -> Self {
+        let base_url = if location == "global" {
+            "https://aiplatform.googleapis.com".to_string()
+        } else {
+            format!("https://{}-aiplatform.googleapis.com", location)
+        };
+        Self::with_base_url(key, project_id, location, base_url)
+    }
+
+    /// Create with a custom API endpoint (private endpoints, mock servers)
+    pub fn with_base_url(
+        key: ServiceAccountKey,
+        project_id: String,
+        location: String,
+        base_url: String,
+    ) -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .pool_idle_timeout(DEFAULT_POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(2)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            tokens: Arc::new(TokenSource::new(key, client.clone())),
+            project_id,
+            location,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            custom_default_model: None,
+        }
+    }
+
+    /// Set custom default model
+    pub fn with_default_model(mut self, model: String) -> Self {
+        self.custom_default_model = Some(model);
+        self
+    }
+
+    fn model_url(&self, model: &str, method: &str) -> String {
+        format!(
+            "{}/v1/projects/{}/locations/{}/publishers/{}/models/{}:{}",
+            self.base_url,
+            self.project_id,
+            self.location,
+            Publisher::for_model(model).as_str(),
+            model,
+            method
+        )
+    }
+
+    /// Serialize the publisher-specific request body
+    fn request_body(&self, request: &LLMRequest, stream: bool) -> Result<serde_json::Value> {
+        let body = match Publisher::for_model(&request.model) {
+            Publisher::Anthropic => serde_json::to_value(VertexAnthropicRequest {
+                anthropic_version: VERTEX_ANTHROPIC_VERSION,
+                messages: &request.messages,
+                system: request.system.as_deref(),
+                max_tokens: request.max_tokens.unwrap_or(16384),
+                temperature: request.temperature,
+                tools: request.tools.as_deref(),
+                stream,
+            })?,
+            Publisher::Google => serde_json::to_value(to_gemini_request(request))?,
+        };
+        Ok(body)
+    }
+
+    /// POST a body with a fresh bearer token, mapping errors per publisher
+    async fn post(&self, url: &str, body: &serde_json::Value, publisher: Publisher) -> Result<reqwest::Response> {
+        let token = self.tokens.access_token().await?;
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        Err(match publisher {
+            Publisher::Anthropic => anthropic_error(response).await,
+            Publisher::Google => gemini_error(response).await,
+        })
+    }
+}
+
+#[async_trait]
+impl Provider for VertexProvider {
+    async fn complete(&self, request: LLMRequest) -> Result<LLMResponse> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        let publisher = Publisher::for_model(&model);
+        tracing::info!(
+            "Vertex API request: model={}, publisher={}, messages={}",
+            model,
+            publisher.as_str(),
+            request.messages.len()
+        );
+
+        let body = self.request_body(&request, false)?;
+        let url = match publisher {
+            Publisher::Anthropic => self.model_url(&model, "rawPredict"),
+            Publisher::Google => self.model_url(&model, "generateContent"),
+        };
+        let retry_config = RetryConfig::default();
+
+        let result = retry_with_backoff(
+            || async {
+                let response = self.post(&url, &body, publisher).await?;
+
+                let llm_response = match publisher {
+                    // Claude on Vertex returns the native Messages response
+                    Publisher::Anthropic => response.json::<LLMResponse>().await?,
+                    Publisher::Google => {
+                        let gemini_response: GeminiResponse = response.json().await?;
+                        from_gemini_response(gemini_response, &model)
+                    }
+                };
+
+                tracing::info!(
+                    "Vertex API response: input_tokens={}, output_tokens={}, stop_reason={:?}",
+                    llm_response.usage.input_tokens,
+                    llm_response.usage.output_tokens,
+                    llm_response.stop_reason
+                );
+
+                Ok(llm_response)
+            },
+            &retry_config,
+        )
+        .await;
+
+        if let Err(ref e) = result {
+            tracing::error!("Vertex API request failed: {}", e);
+        }
+
+        result
+    }
+
+    async fn stream(&self, request: LLMRequest) -> Result<ProviderStream> {
+        use super::retry::{retry_with_backoff, RetryConfig};
+
+        let model = request.model.clone();
+        let publisher = Publisher::for_model(&model);
+        tracing::info!(
+            "Vertex streaming request: model={}, publisher={}, messages={}",
+            model,
+            publisher.as_str(),
+            request.messages.len()
+        );
+
+        let body = self.request_body(&request, true)?;
+        let url = match publisher {
+            Publisher::Anthropic => self.model_url(&model, "streamRawPredict"),
+            Publisher::Google => format!("{}?alt=sse", self.model_url(&model, "streamGenerateContent")),
+        };
+        let retry_config = RetryConfig::default();
+
+        let response = retry_with_backoff(
+            || async { self.post(&url, &body, publisher).await },
+            &retry_config,
+        )
+        .await?;
+
+        Ok(match publisher {
+            Publisher::Anthropic => anthropic_sse_stream(response),
+            Publisher::Google => gemini_sse_stream(response, model),
+        })
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_tools(&self) -> bool {
+        true
+    }
+
+    fn supports_vision(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "vertex"
+    }
+
+    fn default_model(&self) -> &str {
+        self.custom_default_model
+            .as_deref()
+            .unwrap_or("claude-sonnet-4-5@20250929")
+    }
+
+    fn supported_models(&self) -> Vec<String> {
+        vec![
+            "claude-opus-4-1@20250805".to_string(),
+            "claude-sonnet-4-5@20250929".to_string(),
+            "claude-sonnet-4@20250514".to_string(),
+            "claude-haiku-4-5@20251001".to_string(),
+            "claude-3-7-sonnet@20250219".to_string(),
+            "claude-3-5-haiku@20241022".to_string(),
+            "gemini-2.5-pro".to_string(),
+            "gemini-2.5-flash".to_string(),
+            "gemini-2.5-flash-lite".to_string(),
+            "gemini-2.0-flash".to_string(),
+            "gemini-2.0-flash-lite".to_string(),
+        ]
+    }
+
+    fn context_window(&self, model: &str) -> Option<u32> {
+        match Publisher::for_model(model) {
+            Publisher::Anthropic => claude_pricing(model).map(|_| 200_000),
+            Publisher::Google => gemini_context_window(model),
+        }
+    }
+
+    fn calculate_cost(&self, model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+        match Publisher::for_model(model) {
+            Publisher::Anthropic => {
+                let Some((input_cost, output_cost)) = claude_pricing(model) else {
+                    return 0.0;
+                };
+                let input_cost_total = (input_tokens as f64 / 1_000_000.0) * input_cost;
+                let output_cost_total = (output_tokens as f64 / 1_000_000.0) * output_cost;
+                input_cost_total + output_cost_total
+            }
+            Publisher::Google => gemini_cost(model, input_tokens, output_tokens),
+        }
+    }
+}
+
+/// Claude on Vertex pricing, per million tokens (input, output)
+fn claude_pricing(model: &str) -> Option<(f64, f64)> {
+    match model {
+        "claude-opus-4-1@20250805" => Some((15.0, 75.0)),
+        "claude-sonnet-4-5@20250929" => Some((3.0, 15.0)),
+        "claude-sonnet-4@20250514" => Some((3.0, 15.0)),
+        "claude-haiku-4-5@20251001" => Some((1.0, 5.0)),
+        "claude-3-7-sonnet@20250219" => Some((3.0, 15.0)),
+        "claude-3-5-haiku@20241022" => Some((0.80, 4.0)),
+        _ => None,
+    }
+}
+
+/// Messages API body for Claude on Vertex: no `model` (it's in the URL) and
+/// an `anthropic_version` field instead of the header
+#[derive(Debug, Serialize)]
+struct VertexAnthropicRequest<'a> {
+    anthropic_version: &'static str,
+    messages: &'a [Message],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [Tool]>,
+    stream: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    async fn token_server() -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .with_status(200)
+            .with_body(r#"{"access_token":"ya29.test","expires_in":3599}"#)
+            .create_async()
+            .await;
+        (server, mock)
+    }
+
+    fn provider(server_url: &str) -> VertexProvider {
+        let (key, _) = auth::tests::test_key(&format!("{}/token", server_url));
+        VertexProvider::with_base_url(
+            key,
+            "test-project".to_string(),
+            "us-east5".to_string(),
+            server_url.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_publisher_routing() {
+        assert_eq!(Publisher::for_model("claude-sonnet-4-5@20250929"), Publisher::Anthropic);
+        assert_eq!(Publisher::for_model("gemini-2.5-pro"), Publisher::Google);
+    }
+
+    #[test]
+    fn test_regional_and_global_endpoints() {
+        let (key, _) = auth::tests::test_key("https://oauth2.example/token");
+        let regional = VertexProvider::new(key.clone(), "p".to_string(), "us-east5".to_string());
+        assert_eq!(
+            regional.model_url("claude-sonnet-4-5@20250929", "rawPredict"),
+            "https://us-east5-aiplatform.googleapis.com/v1/projects/p/locations/us-east5/publishers/anthropic/models/claude-sonnet-4-5@20250929:rawPredict"
+        );
+
+        let global = VertexProvider::new(key, "p".to_string(), "global".to_string());
+        assert_eq!(
+            global.model_url("gemini-2.5-flash", "generateContent"),
+            "https://aiplatform.googleapis.com/v1/projects/p/locations/global/publishers/google/models/gemini-2.5-flash:generateContent"
+        );
+    }
+
+    #[test]
+    fn test_anthropic_body_has_version_and_no_model() {
+        let provider = provider("http://localhost");
+        let request = LLMRequest::new("claude-haiku-4-5@20251001", vec![Message::user("hi")])
+            .with_system("be brief");
+        let body = provider.request_body(&request, false).unwrap();
+        assert_eq!(body["anthropic_version"], VERTEX_ANTHROPIC_VERSION);
+        assert_eq!(body["system"], "be brief");
+        assert!(body.get("model").is_none());
+    }
+
+    #[test]
+    fn test_cost_and_context() {
+        let provider = provider("http://localhost");
+        assert_eq!(provider.context_window("claude-sonnet-4-5@20250929"), Some(200_000));
+        assert_eq!(provider.context_window("gemini-2.5-pro"), Some(1_048_576));
+        assert_eq!(provider.calculate_cost("claude-sonnet-4-5@20250929", 1_000_000, 0), 3.0);
+        assert_eq!(provider.calculate_cost("unknown", 1000, 1000), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_complete_claude_on_vertex() {
+        let (mut server, token_mock) = token_server().await;
+        let mock = server
+            .mock(
+                "POST",
+                "/v1/projects/test-project/locations/us-east5/publishers/anthropic/models/claude-haiku-4-5@20251001:rawPredict",
+            )
+            .match_header("authorization", "Bearer ya29.test")
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "id": "msg_1",
+                    "type": "message",
+                    "role": "assistant",
+                    "model": "claude-haiku-4-5-20251001",
+                    "content": [{ "type": "text", "text": "Hello from Vertex" }],
+                    "stop_reason": "end_turn",
+                    "usage": { "input_tokens": 8, "output_tokens": 4 }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let response = provider(&server.url())
+            .complete(LLMRequest::new("claude-haiku-4-5@20251001", vec![Message::user("hi")]))
+            .await
+            .unwrap();
+
+        token_mock.assert_async().await;
+        mock.assert_async().await;
+        assert_eq!(response.usage.output_tokens, 4);
+        assert!(matches!(&response.content[0], ContentBlock::Text { text } if text == "Hello from Vertex"));
+    }
+
+    #[tokio::test]
+    async fn test_complete_gemini_on_vertex() {
+        let (mut server, _token_mock) = token_server().await;
+        let mock = server
+            .mock(
+                "POST",
+                "/v1/projects/test-project/locations/us-east5/publishers/google/models/gemini-2.5-flash:generateContent",
+            )
+            .match_header("authorization", "Bearer ya29.test")
+            .with_status(200)
+            .with_body(
+                serde_json::json!({
+                    "candidates": [{
+                        "content": { "role": "model", "parts": [{ "text": "Hi!" }] },
+                        "finishReason": "STOP"
+                    }],
+                    "usageMetadata": { "promptTokenCount": 5, "candidatesTokenCount": 2 }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let response = provider(&server.url())
+            .complete(LLMRequest::new("gemini-2.5-flash", vec![Message::user("hi")]))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(response.stop_reason, Some(StopReason::EndTurn));
+        assert_eq!(response.usage.input_tokens, 5);
+    }
+
+    #[tokio::test]
+    async fn test_stream_claude_on_vertex() {
+        let (mut server, _token_mock) = token_server().await;
+        let sse = [
+            r#"data: {"type":"message_start","message":{"id":"msg_1","model":"claude-haiku-4-5","role":"assistant","usage":{"input_tokens":8,"output_tokens":0}}}"#,
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hey"}}"#,
+            r#"data: {"type":"content_block_stop","index":0}"#,
+            r#"data: {"type":"message_delta","delta":{"stop_reason":"end_turn","stop_sequence":null},"usage":{"input_tokens":0,"output_tokens":1}}"#,
+            r#"data: {"type":"message_stop"}"#,
+        ]
+        .join("\n\n");
+        let _mock = server
+            .mock(
+                "POST",
+                "/v1/projects/test-project/locations/us-east5/publishers/anthropic/models/claude-haiku-4-5@20251001:streamRawPredict",
+            )
+            .match_body(mockito::Matcher::PartialJsonString(r#"{"stream":true}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(format!("{}\n\n", sse))
+            .create_async()
+            .await;
+
+        let text: String = provider(&server.url())
+            .stream(LLMRequest::new("claude-haiku-4-5@20251001", vec![Message::user("hi")]))
+            .await
+            .unwrap()
+            .filter_map(|e| async move {
+                match e {
+                    Ok(StreamEvent::ContentBlockDelta {
+                        delta: ContentDelta::TextDelta { text },
+                        ..
+                    }) => Some(text),
+                    _ => None,
+                }
+            })
+            .collect()
+            .await;
+
+        assert_eq!(text, "Hey");
+    }
+
+    #[tokio::test]
+    async fn test_vertex_error_is_mapped() {
+        let (mut server, _token_mock) = token_server().await;
+        let _mock = server
+            .mock(
+                "POST",
+                "/v1/projects/test-project/locations/us-east5/publishers/google/models/gemini-2.5-pro:generateContent",
+            )
+            .with_status(404)
+            .with_body(
+                r#"{"error":{"code":404,"message":"Publisher model not found","status":"NOT_FOUND"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = provider(&server.url())
+            .complete(LLMRequest::new("gemini-2.5-pro", vec![Message::user("hi")]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Publisher model not found"));
+    }
+}
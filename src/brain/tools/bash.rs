@@ -4,6 +4,7 @@
 
 use super::error::{Result, ToolError};
 use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use super::sandbox::Sandbox;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -12,7 +13,17 @@ use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
 /// Bash execution tool
-pub struct BashTool;
+#[derive(Default)]
+pub struct BashTool {
+    sandbox: Option<Sandbox>,
+}
+
+impl BashTool {
+    /// Run commands inside `sandbox` (unconfined when `None`)
+    pub fn new(sandbox: Option<Sandbox>) -> Self {
+        Self { sandbox }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 struct BashInput {
@@ -261,15 +272,20 @@ impl Tool for BashTool {
                 input.command.replacen("sudo ", "sudo -S ", 1)
             };
 
+            let mut command = Command::new(shell);
+            command
+                .arg(shell_arg)
+                .arg(&sudo_cmd)
+                .current_dir(&working_dir)
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped());
+            if let Some(ref sandbox) = self.sandbox {
+                sandbox.apply(&mut command, &context.working_directory)?;
+            }
+
             let command_future = async {
-                let mut child = Command::new(shell)
-                    .arg(shell_arg)
-                    .arg(&sudo_cmd)
-                    .current_dir(&working_dir)
-                    .stdin(std::process::Stdio::piped())
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::piped())
-                    .spawn()?;
+                let mut child = command.spawn()?;
 
                 // Write password to stdin and close it
                 if let Some(mut stdin) = child.stdin.take() {
@@ -282,6 +298,12 @@ impl Tool for BashTool {
 
             match timeout(Duration::from_secs(effective_timeout), command_future).await {
                 Ok(Ok(output)) => output,
+                Ok(Err(e)) if self.sandbox.is_some() => {
+                    return Err(ToolError::SandboxUnavailable(format!(
+                        "failed to start sandboxed command: {}",
+                        e
+                    )));
+                }
                 Ok(Err(e)) => {
                     return Ok(ToolResult::error(format!(
                         "Command execution failed: {}",
@@ -294,14 +316,25 @@ impl Tool for BashTool {
             }
         } else {
             // Normal execution (no sudo password needed)
-            let command_future = Command::new(shell)
+            let mut command = Command::new(shell);
+            command
                 .arg(shell_arg)
                 .arg(&input.command)
-                .current_dir(&working_dir)
-                .output();
+                .current_dir(&working_dir);
+            if let Some(ref sandbox) = self.sandbox {
+                // Writes are confined to the session's directory, not `working_dir`
+                sandbox.apply(&mut command, &context.working_directory)?;
+            }
+            let command_future = command.output();
 
             match timeout(Duration::from_secs(effective_timeout), command_future).await {
                 Ok(Ok(output)) => output,
+                Ok(Err(e)) if self.sandbox.is_some() => {
+                    return Err(ToolError::SandboxUnavailable(format!(
+                        "failed to start sandboxed command: {}",
+                        e
+                    )));
+                }
                 Ok(Err(e)) => {
                     return Ok(ToolResult::error(format!(
                         "Command execution failed: {}",
@@ -340,15 +373,26 @@ impl Tool for BashTool {
         }
 
         let success = output.status.success();
+        // A blocked write or connection replaces the exit-code error, keeping the output
+        let violation = (!success)
+            .then(|| {
+                self.sandbox
+                    .as_ref()?
+                    .violation(&stderr, &context.working_directory, &working_dir)
+            })
+            .flatten();
 
         let result = if success {
             ToolResult::success(result_text)
+        } else if let Some(ToolError::SandboxViolation { kind, detail }) = violation {
+            ToolResult::sandbox_violation(result_text, kind, detail)
         } else {
             ToolResult {
                 success: false,
                 output: result_text,
                 error: Some(format!("Command exited with code {}", exit_code)),
                 metadata: std::collections::HashMap::new(),
+                sandbox_violation: None,
             }
         };
 
@@ -365,7 +409,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_bash_simple_command() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         let session_id = Uuid::new_v4();
         let context = ToolExecutionContext::new(session_id).with_auto_approve(true);
 
@@ -386,7 +430,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_bash_with_exit_code() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         let session_id = Uuid::new_v4();
         let context = ToolExecutionContext::new(session_id).with_auto_approve(true);
 
@@ -403,7 +447,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_bash_invalid_command() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         let session_id = Uuid::new_v4();
         let context = ToolExecutionContext::new(session_id).with_auto_approve(true);
 
@@ -418,7 +462,7 @@ mod tests {
     #[tokio::test]
     #[cfg(not(target_os = "windows"))] // Skip on Windows due to cmd.exe limitations
     async fn test_bash_timeout() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         let session_id = Uuid::new_v4();
         let context = ToolExecutionContext::new(session_id)
             .with_auto_approve(true)
@@ -435,7 +479,7 @@ mod tests {
 
     #[test]
     fn test_bash_tool_schema() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         assert_eq!(tool.name(), "bash");
         assert!(tool.requires_approval());
 
@@ -446,7 +490,7 @@ mod tests {
 
     #[test]
     fn test_validate_empty_command() {
-        let tool = BashTool;
+        let tool = BashTool::default();
         let input = serde_json::json!({
             "command": ""
         });
@@ -1,9 +1,11 @@
 //! Code Execution Tool
 //!
-//! Execute code in various languages within a sandboxed environment.
+//! Execute code in various languages, optionally inside an OS-level sandbox
+//! (`[sandbox.execute_code]`).
 
 use super::error::{Result, ToolError};
 use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use super::sandbox::Sandbox;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -12,7 +14,17 @@ use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
 /// Code execution tool
-pub struct CodeExecTool;
+#[derive(Default)]
+pub struct CodeExecTool {
+    sandbox: Option<Sandbox>,
+}
+
+impl CodeExecTool {
+    /// Run code inside `sandbox` (unconfined when `None`)
+    pub fn new(sandbox: Option<Sandbox>) -> Self {
+        Self { sandbox }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 struct CodeExecInput {
@@ -194,6 +206,13 @@ impl Tool for CodeExecTool {
         // Add the temp file path
         cmd.arg(&temp_file);
 
+        if let Some(ref sandbox) = self.sandbox
+            && let Err(e) = sandbox.apply(&mut cmd, &context.working_directory)
+        {
+            let _ = fs::remove_file(&temp_file).await;
+            return Err(e);
+        }
+
         // Execute with timeout
         let exec_timeout = Duration::from_secs(input.timeout_secs);
         let output_future = cmd.output();
@@ -203,6 +222,12 @@ impl Tool for CodeExecTool {
             Ok(Err(e)) => {
                 // Clean up temp file
                 let _ = fs::remove_file(&temp_file).await;
+                if self.sandbox.is_some() {
+                    return Err(ToolError::SandboxUnavailable(format!(
+                        "failed to start sandboxed interpreter: {}",
+                        e
+                    )));
+                }
                 return Ok(ToolResult::error(format!("Code execution failed: {}", e)));
             }
             Err(_) => {
@@ -248,8 +273,17 @@ impl Tool for CodeExecTool {
         }
 
         let success = output.status.success();
+        let violation = (!success)
+            .then(|| {
+                let cwd = &context.working_directory;
+                self.sandbox.as_ref()?.violation(&stderr, cwd, cwd)
+            })
+            .flatten();
         let mut tool_result = if success {
             ToolResult::success(result_text)
+        } else if let Some(ToolError::SandboxViolation { kind, detail }) = violation {
+            // Keep the output so the model can see what ran before the block
+            ToolResult::sandbox_violation(result_text, kind, detail)
         } else {
             ToolResult::error(result_text)
         };
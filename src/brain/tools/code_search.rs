@@ -0,0 +1,156 @@
+//! Code Search Tool
+//!
+//! Searches the working directory's source tree by meaning, not just by text.
+//! Files are chunked at function/type boundaries into the qmd "code" collection
+//! and ranked with hybrid FTS5 + vector search (same engine as memory_search).
+//! The tree is indexed on first use and then kept current by a file watcher,
+//! so a call only searches.
+
+use super::error::Result;
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::Value;
+
+/// Semantic code search over the current working directory.
+pub struct CodeSearchTool;
+
+#[async_trait]
+impl Tool for CodeSearchTool {
+    fn name(&self) -> &str {
+        "code_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the project's source code by natural-language description or identifiers \
+         using hybrid FTS5 + vector semantic search. Use this to find where something is \
+         implemented when you don't know the exact names to grep for. Returns file paths \
+         with line ranges and the matching code."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What the code does, or identifiers it uses (e.g. 'retry with exponential backoff')"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Only return files whose path relative to the working directory contains this (e.g. 'src/tui')"
+                },
+                "n": {
+                    "type": "integer",
+                    "description": "Number of results to return (default: 8)",
+                    "default": 8
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadFiles]
+    }
+
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let query = input
+            .get("query")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        if query.trim().is_empty() {
+            return Ok(ToolResult::error("query parameter is required".to_string()));
+        }
+
+        let n = input.get("n").and_then(|v| v.as_u64()).unwrap_or(8).max(1) as usize;
+        let path_filter = input
+            .get("path")
+            .and_then(|v| v.as_str())
+            .filter(|p| !p.is_empty() && *p != ".");
+
+        let store = match crate::memory::get_store() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Memory store init failed: {}", e);
+                return Ok(ToolResult::error(format!(
+                    "Code search unavailable: {e}. Use grep or glob instead."
+                )));
+            }
+        };
+
+        let root = &context.working_directory;
+        let stats = match crate::memory::ensure_code_index(store, root).await {
+            Ok(stats) => stats,
+            Err(e) => return Ok(ToolResult::error(format!("Code indexing failed: {e}"))),
+        };
+        if stats.files == 0 {
+            return Ok(ToolResult::success(format!(
+                "No source files found under {}.",
+                root.display()
+            )));
+        }
+
+        let results = match crate::memory::search_code(store, root, &query, path_filter, n).await {
+            Ok(results) => results,
+            Err(e) => return Ok(ToolResult::error(format!("Code search failed: {e}"))),
+        };
+
+        if results.is_empty() {
+            return Ok(ToolResult::success("No matching code found.".to_string()));
+        }
+
+        let base = root.canonicalize().unwrap_or_else(|_| root.clone());
+        let mut output = String::new();
+        for (i, r) in results.iter().enumerate() {
+            let rel = r.path.strip_prefix(&base).unwrap_or(&r.path);
+            let lang = r.path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            output.push_str(&format!(
+                "{}. **{}:{}-{}**",
+                i + 1,
+                rel.display(),
+                r.start_line,
+                r.end_line
+            ));
+            if let Some(symbol) = &r.symbol {
+                output.push_str(&format!(" `{}`", symbol));
+            }
+            output.push_str(&format!("\n```{}\n{}\n```\n\n", lang, r.snippet));
+        }
+        if stats.truncated {
+            output.push_str(
+                "Note: the project has more files than the index covers; \
+                 narrow the working directory or use grep for exhaustive results.\n",
+            );
+        }
+        Ok(ToolResult::success(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_metadata() {
+        let tool = CodeSearchTool;
+        assert_eq!(tool.name(), "code_search");
+        assert!(!tool.requires_approval());
+    }
+
+    #[tokio::test]
+    async fn test_empty_query() {
+        let tool = CodeSearchTool;
+        let ctx = ToolExecutionContext::new(uuid::Uuid::new_v4());
+        let result = tool
+            .execute(serde_json::json!({"query": "  "}), &ctx)
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+}
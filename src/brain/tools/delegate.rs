@@ -0,0 +1,665 @@
+//! Delegate Tool
+//!
+//! Hands a self-contained task to a sub-agent. Each call creates a child
+//! session and a child `AgentService` with its own system brain and a
+//! restricted subset of the parent's tools, runs the task to completion and
+//! returns the child's final answer with its token and cost totals. Several
+//! read-only delegate calls in one turn run concurrently. Files a child
+//! writes are saved into the parent turn's checkpoint, so `/undo` in the
+//! parent reverts them.
+
+use super::error::{Result, ToolError};
+use super::registry::ToolRegistry;
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use crate::brain::agent::{AgentService, ApprovalCallback, ProgressCallback, ProgressEvent};
+use crate::brain::provider::Provider;
+use crate::services::{ServiceContext, SessionService};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Tool name, also excluded from every child registry so children can't recurse
+const DELEGATE_TOOL_NAME: &str = "delegate";
+
+/// Tool-loop iterations a child gets unless the caller asks for more
+const DEFAULT_MAX_ITERATIONS: usize = 25;
+
+/// System brain for every sub-agent; task-specific instructions are appended
+const SUBAGENT_BRAIN: &str = "You are a focused sub-agent of OpenCrabs. Another agent delegated \
+a single task to you. Work on it autonomously with the tools you have, then reply with a \
+complete, self-contained answer — the delegating agent sees only your final message, not your \
+tool calls. Do not ask the user questions; if something is ambiguous, state your assumption \
+and continue.";
+
+#[derive(Debug, Deserialize)]
+struct DelegateInput {
+    task: String,
+    #[serde(default)]
+    instructions: Option<String>,
+    #[serde(default)]
+    tools: Option<Vec<String>>,
+    #[serde(default)]
+    max_iterations: Option<usize>,
+}
+
+/// Agent-callable tool that runs a task in a scoped child agent.
+///
+/// Like `ChannelFactory`, the parent registry is set lazily via
+/// [`set_tool_registry`](Self::set_tool_registry) because this tool is itself
+/// part of that registry.
+pub struct DelegateTool {
+    provider: Arc<dyn Provider>,
+    service_context: ServiceContext,
+    tool_registry: OnceLock<Arc<ToolRegistry>>,
+    approval_callback: Option<ApprovalCallback>,
+    progress_callback: Option<ProgressCallback>,
+    next_agent: AtomicUsize,
+}
+
+impl DelegateTool {
+    pub fn new(
+        provider: Arc<dyn Provider>,
+        service_context: ServiceContext,
+        approval_callback: Option<ApprovalCallback>,
+        progress_callback: Option<ProgressCallback>,
+    ) -> Self {
+        Self {
+            provider,
+            service_context,
+            tool_registry: OnceLock::new(),
+            approval_callback,
+            progress_callback,
+            next_agent: AtomicUsize::new(1),
+        }
+    }
+
+    /// Set the parent tool registry (call once, after Arc<ToolRegistry> is created).
+    pub fn set_tool_registry(&self, registry: Arc<ToolRegistry>) {
+        let _ = self.tool_registry.set(registry);
+    }
+
+    /// Pick the child's tools: the requested names, or every read-only tool
+    fn child_registry(&self, requested: Option<&[String]>) -> Result<ToolRegistry> {
+        let parent = self
+            .tool_registry
+            .get()
+            .ok_or_else(|| ToolError::Internal("Delegate tool registry not set".to_string()))?;
+
+        match requested {
+            Some(names) => {
+                let unknown: Vec<&str> = names
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|name| *name == DELEGATE_TOOL_NAME || !parent.has_tool(name))
+                    .collect();
+                if !unknown.is_empty() {
+                    return Err(ToolError::InvalidInput(format!(
+                        "Tools not available to sub-agents: {}",
+                        unknown.join(", ")
+                    )));
+                }
+                Ok(parent.filtered(|tool| names.iter().any(|n| n == tool.name())))
+            }
+            None => Ok(
+                parent.filtered(|tool| tool.name() != DELEGATE_TOOL_NAME && tool.is_read_only())
+            ),
+        }
+    }
+
+    /// Forward child progress to the parent, tagged with the child's label
+    fn child_progress(&self, agent: &str) -> Option<ProgressCallback> {
+        let parent = self.progress_callback.clone()?;
+        let agent = agent.to_string();
+        Some(Arc::new(move |event| {
+            parent(ProgressEvent::Subagent {
+                agent: agent.clone(),
+                event: Box::new(event),
+            })
+        }))
+    }
+}
+
+#[async_trait]
+impl Tool for DelegateTool {
+    fn name(&self) -> &str {
+        DELEGATE_TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        "Delegate a self-contained task to a sub-agent that works in its own session and \
+         returns only its final answer (plus token/cost totals). Use it to parallelise \
+         independent research or analysis: several read-only delegate calls in one turn run \
+         concurrently. \
+         By default the sub-agent gets the read-only tools; pass `tools` to choose a subset. \
+         The sub-agent cannot see this conversation, so put everything it needs in `task`."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "task": {
+                    "type": "string",
+                    "description": "The complete task, including any context the sub-agent needs"
+                },
+                "instructions": {
+                    "type": "string",
+                    "description": "Extra guidance added to the sub-agent's system prompt (e.g. output format)"
+                },
+                "tools": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tool names the sub-agent may use (default: all read-only tools)"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Maximum tool-loop iterations for the sub-agent (default: 25)"
+                }
+            },
+            "required": ["task"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        // The child's own tool calls go through approval individually
+        vec![ToolCapability::Network]
+    }
+
+    fn is_concurrency_safe_for(&self, input: &Value) -> bool {
+        // Children run in their own sessions, but only read-only ones can't
+        // trip over each other's (or the parent's) writes
+        let Some(parent) = self.tool_registry.get() else {
+            return false;
+        };
+        match input.get("tools") {
+            None | Some(Value::Null) => true,
+            Some(Value::Array(names)) => names.iter().all(|name| {
+                name.as_str()
+                    .and_then(|name| parent.get(name))
+                    .is_some_and(|tool| tool.is_read_only())
+            }),
+            Some(_) => false,
+        }
+    }
+
+    fn validate_input(&self, input: &Value) -> Result<()> {
+        let task = input
+            .get("task")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        if task.trim().is_empty() {
+            return Err(ToolError::InvalidInput(
+                "'task' must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, input: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let input: DelegateInput = serde_json::from_value(input)?;
+        let registry = self.child_registry(input.tools.as_deref())?;
+
+        let agent = format!("agent-{}", self.next_agent.fetch_add(1, Ordering::Relaxed));
+        let tool_count = registry.count();
+
+        let mut brain = SUBAGENT_BRAIN.to_string();
+        brain.push_str(&format!(
+            "\n\nWorking directory: {}",
+            context.working_directory.display()
+        ));
+        if let Some(instructions) = input
+            .instructions
+            .as_deref()
+            .filter(|s| !s.trim().is_empty())
+        {
+            brain.push_str("\n\n");
+            brain.push_str(instructions);
+        }
+
+        let child = AgentService::new(self.provider.clone(), self.service_context.clone())
+            .with_system_brain(brain)
+            .with_tool_registry(Arc::new(registry))
+            .with_max_tool_iterations(input.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS))
+            .with_auto_approve_tools(context.auto_approve)
+            .with_approval_callback(self.approval_callback.clone())
+            .with_progress_callback(self.child_progress(&agent))
+            .with_working_directory(context.working_directory.clone())
+            .with_checkpoint(context.checkpoint.clone());
+
+        let sessions = SessionService::new(self.service_context.clone());
+        let title: String = input.task.chars().take(60).collect();
+        let session = sessions
+            .create_session(Some(format!("↳ {}: {}", agent, title)))
+            .await
+            .map_err(|e| ToolError::Internal(format!("Failed to create child session: {}", e)))?;
+
+        tracing::info!(
+            "🦀 {} started in session {} with {} tools",
+            agent,
+            session.id,
+            tool_count
+        );
+
+        let outcome = child
+            .send_message_with_tools_and_mode(
+                session.id,
+                input.task,
+                None,
+                context.read_only_mode,
+                None,
+            )
+            .await;
+
+        // Child sessions are kept for inspection but hidden from the session list
+        if let Err(e) = sessions.archive_session(session.id).await {
+            tracing::warn!("Failed to archive child session {}: {}", session.id, e);
+        }
+
+        let response = match outcome {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("{} failed: {}", agent, e);
+                return Ok(
+                    ToolResult::error(format!("Sub-agent {} failed: {}", agent, e))
+                        .with_metadata("child_session_id".to_string(), session.id.to_string()),
+                );
+            }
+        };
+
+        // The child's spend is already recorded on its own session; the
+        // totals below are for the parent's answer, not its session usage
+        let input_tokens = response.usage.input_tokens;
+        let output_tokens = response.usage.output_tokens;
+
+        tracing::info!(
+            "🦀 {} finished: {} in / {} out tokens, ${:.4}",
+            agent,
+            input_tokens,
+            output_tokens,
+            response.cost
+        );
+
+        let output = format!(
+            "{}\n\n---\n[{} | {} tools | {} input + {} output tokens | ${:.4}]",
+            response.content.trim_end(),
+            agent,
+            tool_count,
+            input_tokens,
+            output_tokens,
+            response.cost
+        );
+
+        Ok(ToolResult::success(output)
+            .with_metadata("child_session_id".to_string(), session.id.to_string())
+            .with_metadata("input_tokens".to_string(), input_tokens.to_string())
+            .with_metadata("output_tokens".to_string(), output_tokens.to_string())
+            .with_metadata("cost".to_string(), format!("{:.6}", response.cost)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brain::agent::service::tests::stream_from_response;
+    use crate::brain::provider::{
+        ContentBlock, LLMRequest, LLMResponse, ProviderStream, StopReason, TokenUsage,
+    };
+    use crate::db::Database;
+    use crate::services::checkpoint::{CheckpointService, TurnCheckpoint};
+    use std::sync::Mutex;
+
+    /// Answers every request with a fixed text and usage, recording system
+    /// prompts. With a `tool_call`, the first `times` requests call that tool
+    /// instead of answering.
+    struct AnswerProvider {
+        systems: Mutex<Vec<String>>,
+        tools_seen: Mutex<Vec<Vec<String>>>,
+        tool_call: Option<(&'static str, Value, usize)>,
+    }
+
+    impl AnswerProvider {
+        fn new() -> Self {
+            Self {
+                systems: Mutex::new(Vec::new()),
+                tools_seen: Mutex::new(Vec::new()),
+                tool_call: None,
+            }
+        }
+
+        fn calling(tool: &'static str, input: Value, times: usize) -> Self {
+            Self {
+                tool_call: Some((tool, input, times)),
+                ..Self::new()
+            }
+        }
+
+        fn answer(&self, request: &LLMRequest) -> LLMResponse {
+            let calls = {
+                let mut systems = self.systems.lock().unwrap();
+                systems.push(request.system.clone().unwrap_or_default());
+                systems.len()
+            };
+            self.tools_seen.lock().unwrap().push(
+                request
+                    .tools
+                    .iter()
+                    .flatten()
+                    .map(|t| t.name.clone())
+                    .collect(),
+            );
+            if let Some((tool, input, times)) = &self.tool_call
+                && calls <= *times
+            {
+                return LLMResponse {
+                    id: format!("child-call-{}", calls),
+                    model: "mock-model".to_string(),
+                    content: vec![ContentBlock::ToolUse {
+                        id: format!("tool-{}", calls),
+                        name: tool.to_string(),
+                        input: input.clone(),
+                    }],
+                    stop_reason: Some(StopReason::ToolUse),
+                    usage: TokenUsage {
+                        input_tokens: 10,
+                        output_tokens: 5,
+                        reasoning_tokens: 0,
+                    },
+                };
+            }
+            LLMResponse {
+                id: "child-1".to_string(),
+                model: "mock-model".to_string(),
+                content: vec![ContentBlock::Text {
+                    text: "The answer is 42.".to_string(),
+                }],
+                stop_reason: Some(StopReason::EndTurn),
+                usage: TokenUsage {
+                    input_tokens: 100,
+                    output_tokens: 50,
+                    reasoning_tokens: 0,
+                },
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Provider for AnswerProvider {
+        async fn complete(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<LLMResponse> {
+            Ok(self.answer(&request))
+        }
+
+        async fn stream(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<ProviderStream> {
+            Ok(stream_from_response(self.answer(&request)))
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["mock-model".to_string()]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            Some(200_000)
+        }
+
+        fn calculate_cost(&self, _model: &str, input: u32, output: u32) -> f64 {
+            (input + output) as f64 * 0.0001
+        }
+    }
+
+    struct NamedTool {
+        name: &'static str,
+        capabilities: Vec<ToolCapability>,
+    }
+
+    #[async_trait]
+    impl Tool for NamedTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn description(&self) -> &str {
+            "test tool"
+        }
+        fn input_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+        fn capabilities(&self) -> Vec<ToolCapability> {
+            self.capabilities.clone()
+        }
+        async fn execute(&self, input: Value, ctx: &ToolExecutionContext) -> Result<ToolResult> {
+            // Writes to `path`, checkpointing it first like the real write tool
+            if let Some(path) = input.get("path").and_then(Value::as_str) {
+                ctx.checkpoint_file(std::path::Path::new(path)).await;
+                std::fs::write(path, "written by child")?;
+            }
+            Ok(ToolResult::success(String::new()))
+        }
+    }
+
+    async fn setup(
+        progress: Option<ProgressCallback>,
+    ) -> (
+        Arc<DelegateTool>,
+        Arc<AnswerProvider>,
+        ServiceContext,
+        uuid::Uuid,
+    ) {
+        setup_with(AnswerProvider::new(), progress).await
+    }
+
+    async fn setup_with(
+        provider: AnswerProvider,
+        progress: Option<ProgressCallback>,
+    ) -> (
+        Arc<DelegateTool>,
+        Arc<AnswerProvider>,
+        ServiceContext,
+        uuid::Uuid,
+    ) {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+        let provider = Arc::new(provider);
+
+        let delegate = Arc::new(DelegateTool::new(
+            provider.clone(),
+            context.clone(),
+            None,
+            progress,
+        ));
+
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(NamedTool {
+            name: "read_file",
+            capabilities: vec![ToolCapability::ReadFiles],
+        }));
+        registry.register(Arc::new(NamedTool {
+            name: "write_file",
+            capabilities: vec![ToolCapability::WriteFiles],
+        }));
+        registry.register(delegate.clone());
+        delegate.set_tool_registry(Arc::new(registry));
+
+        let parent = SessionService::new(context.clone())
+            .create_session(Some("Parent".to_string()))
+            .await
+            .unwrap();
+
+        (delegate, provider, context, parent.id)
+    }
+
+    #[tokio::test]
+    async fn test_default_child_tools_are_read_only_without_delegate() {
+        let (delegate, _, _, _) = setup(None).await;
+        let registry = delegate.child_registry(None).unwrap();
+        assert_eq!(registry.list_tools(), vec!["read_file".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_requested_tools_are_validated() {
+        let (delegate, _, _, _) = setup(None).await;
+
+        let registry = delegate
+            .child_registry(Some(&["write_file".to_string()]))
+            .unwrap();
+        assert!(registry.has_tool("write_file"));
+
+        let err = delegate
+            .child_registry(Some(&["delegate".to_string(), "nope".to_string()]))
+            .err()
+            .unwrap();
+        assert!(matches!(err, ToolError::InvalidInput(msg) if msg.contains("delegate, nope")));
+    }
+
+    #[tokio::test]
+    async fn test_only_read_only_delegations_run_concurrently() {
+        let (delegate, _, _, _) = setup(None).await;
+
+        assert!(delegate.is_concurrency_safe_for(&serde_json::json!({ "task": "t" })));
+        assert!(delegate.is_concurrency_safe_for(&serde_json::json!({
+            "task": "t",
+            "tools": ["read_file"]
+        })));
+        assert!(!delegate.is_concurrency_safe_for(&serde_json::json!({
+            "task": "t",
+            "tools": ["read_file", "write_file"]
+        })));
+        assert!(!delegate.is_concurrency_safe_for(&serde_json::json!({
+            "task": "t",
+            "tools": ["nope"]
+        })));
+    }
+
+    #[tokio::test]
+    async fn test_runaway_child_stops_at_max_iterations() {
+        let looping = AnswerProvider::calling("read_file", serde_json::json!({}), usize::MAX);
+        let (delegate, provider, _, parent_id) = setup_with(looping, None).await;
+
+        let ctx = ToolExecutionContext::new(parent_id);
+        let result = delegate
+            .execute(
+                serde_json::json!({ "task": "Loop forever", "max_iterations": 3 }),
+                &ctx,
+            )
+            .await
+            .expect("delegate reports child failures as tool errors");
+
+        assert!(!result.success);
+        assert!(
+            result
+                .error
+                .as_deref()
+                .is_some_and(|e| e.contains("Maximum tool iterations exceeded: 3"))
+        );
+        assert_eq!(provider.systems.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_delegate_returns_answer_and_records_usage_once() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let progress: ProgressCallback = Arc::new(move |event| {
+            sink.lock().unwrap().push(event);
+        });
+        let (delegate, provider, context, parent_id) = setup(Some(progress)).await;
+
+        let ctx = ToolExecutionContext::new(parent_id);
+        let result = delegate
+            .execute(
+                serde_json::json!({
+                    "task": "What is six times seven?",
+                    "instructions": "Answer in one sentence."
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.output.starts_with("The answer is 42."));
+        assert!(result.output.contains("100 input + 50 output tokens"));
+        assert_eq!(result.metadata["input_tokens"], "100");
+        assert_eq!(result.metadata["output_tokens"], "50");
+
+        // Child ran with its own brain and only the read-only tool
+        let system = provider.systems.lock().unwrap()[0].clone();
+        assert!(system.starts_with("You are a focused sub-agent"));
+        assert!(system.ends_with("Answer in one sentence."));
+        assert_eq!(
+            provider.tools_seen.lock().unwrap()[0],
+            vec!["read_file".to_string()]
+        );
+
+        // The archived child session carries the spend, counted only there
+        let sessions = SessionService::new(context);
+        let parent = sessions.get_session(parent_id).await.unwrap().unwrap();
+        assert_eq!(parent.token_count, 0);
+        let child_id = uuid::Uuid::parse_str(&result.metadata["child_session_id"]).unwrap();
+        let child = sessions.get_session(child_id).await.unwrap().unwrap();
+        assert_eq!(child.token_count, 150);
+        assert!(child.archived_at.is_some());
+
+        // Child progress reached the parent wrapped as Subagent events
+        assert!(events.lock().unwrap().iter().all(|e| matches!(
+            e,
+            ProgressEvent::Subagent { agent, .. } if agent == "agent-1"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_undo_reverts_delegated_writes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "original").expect("write");
+
+        let provider = AnswerProvider::calling(
+            "write_file",
+            serde_json::json!({ "path": path.to_string_lossy() }),
+            1,
+        );
+        let (delegate, _, context, parent_id) = setup_with(provider, None).await;
+
+        let mut ctx = ToolExecutionContext::new(parent_id).with_auto_approve(true);
+        ctx.checkpoint = Some(TurnCheckpoint::new(
+            context.clone(),
+            parent_id,
+            "delegate an edit".to_string(),
+        ));
+        let result = delegate
+            .execute(
+                serde_json::json!({ "task": "Edit the notes", "tools": ["write_file"] }),
+                &ctx,
+            )
+            .await
+            .expect("delegate");
+        assert!(result.success);
+        assert_eq!(
+            std::fs::read_to_string(&path).expect("read"),
+            "written by child"
+        );
+
+        // The child's write landed in the parent's turn checkpoint
+        let summary = CheckpointService::new(context)
+            .undo(parent_id)
+            .await
+            .expect("undo")
+            .expect("a checkpoint to undo");
+        assert_eq!(summary.files, 1);
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), "original");
+    }
+}
@@ -1,12 +1,15 @@
 //! Edit File Tool
 //!
-//! Intelligently modify portions of files (find/replace, line-based edits).
+//! Intelligently modify portions of files (find/replace, line-based edits,
+//! unified-diff patches spanning several files).
 
-use super::error::{validate_file_path, Result, ToolError};
+use super::error::{validate_file_path, validate_path_safety, Result, ToolError};
+use super::patch::{self, FilePatch};
 use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
 use tokio::fs;
 
 /// Edit file tool
@@ -41,11 +44,17 @@ enum EditOperation {
         pattern: String,
         replacement: String,
     },
+
+    /// Apply a unified diff (one or more files), all hunks or nothing
+    #[serde(rename = "apply_patch")]
+    ApplyPatch { patch: String },
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 struct EditInput {
-    /// Path to the file to edit
+    /// Path to the file to edit. For `apply_patch`: the file a header-less
+    /// patch applies to, or the directory patch paths are relative to.
+    #[serde(default)]
     path: String,
 
     /// Edit operation to perform
@@ -68,7 +77,8 @@ impl Tool for EditTool {
     }
 
     fn description(&self) -> &str {
-        "Edit a file intelligently using various operations: replace text, replace lines, insert lines, delete lines, or regex replace."
+        "Edit a file intelligently using various operations: replace text, replace lines, insert lines, delete lines, regex replace, \
+         or apply_patch to apply a unified diff (may span multiple files, create or delete files) atomically in one call."
     }
 
     fn input_schema(&self) -> Value {
@@ -77,12 +87,16 @@ impl Tool for EditTool {
             "properties": {
                 "path": {
                     "type": "string",
-                    "description": "Path to the file to edit"
+                    "description": "Path to the file to edit. For 'apply_patch', optional: the target file of a patch without ---/+++ headers, or the directory patch paths are relative to (default: working directory)"
                 },
                 "operation": {
                     "type": "string",
                     "description": "Type of edit operation",
-                    "enum": ["replace", "replace_lines", "insert_line", "delete_lines", "regex_replace"]
+                    "enum": ["replace", "replace_lines", "insert_line", "delete_lines", "regex_replace", "apply_patch"]
+                },
+                "patch": {
+                    "type": "string",
+                    "description": "Unified diff for 'apply_patch' (git diff / diff -u format, multiple files allowed, /dev/null to create or delete). Hunks are matched with fuzzy context; if any hunk fails nothing is changed"
                 },
                 "old_text": {
                     "type": "string",
@@ -125,7 +139,7 @@ impl Tool for EditTool {
                     "default": true
                 }
             },
-            "required": ["path", "operation"]
+            "required": ["operation"]
         })
     }
 
@@ -159,6 +173,10 @@ impl Tool for EditTool {
 
         let input: EditInput = serde_json::from_value(input)?;
 
+        if let EditOperation::ApplyPatch { patch } = &input.operation {
+            return apply_patch(&input.path, patch, input.create_backup, context).await;
+        }
+
         // Validate path: safety check, existence, and file type
         let path = match validate_file_path(&input.path, &context.working_directory) {
             Ok(p) => p,
@@ -180,111 +198,13 @@ impl Tool for EditTool {
         }
 
         // Perform edit operation
-        let new_content = match input.operation {
-            EditOperation::Replace { old_text, new_text } => {
-                if !content.contains(&old_text) {
-                    return Ok(ToolResult::error(format!(
-                        "Text not found in file: '{}'",
-                        old_text
-                    )));
-                }
-                content.replace(&old_text, &new_text)
-            }
-
-            EditOperation::ReplaceLines {
-                start_line,
-                end_line,
-                new_text,
-            } => {
-                let lines: Vec<&str> = content.lines().collect();
-                if start_line >= lines.len() || end_line >= lines.len() {
-                    return Ok(ToolResult::error(format!(
-                        "Line range {}-{} out of bounds (file has {} lines)",
-                        start_line,
-                        end_line,
-                        lines.len()
-                    )));
-                }
-                if start_line > end_line {
-                    return Ok(ToolResult::error(
-                        "start_line must be <= end_line".to_string(),
-                    ));
-                }
-
-                let mut new_lines = Vec::new();
-                new_lines.extend_from_slice(&lines[..start_line]);
-                new_lines.push(&new_text);
-                if end_line + 1 < lines.len() {
-                    new_lines.extend_from_slice(&lines[end_line + 1..]);
-                }
-                new_lines.join("\n")
-            }
-
-            EditOperation::InsertLine { line, text } => {
-                let lines: Vec<&str> = content.lines().collect();
-                if line > lines.len() {
-                    return Ok(ToolResult::error(format!(
-                        "Line {} out of bounds (file has {} lines)",
-                        line,
-                        lines.len()
-                    )));
-                }
-
-                let mut new_lines = Vec::new();
-                new_lines.extend_from_slice(&lines[..line]);
-                new_lines.push(&text);
-                new_lines.extend_from_slice(&lines[line..]);
-                new_lines.join("\n")
-            }
-
-            EditOperation::DeleteLines {
-                start_line,
-                end_line,
-            } => {
-                let lines: Vec<&str> = content.lines().collect();
-                if start_line >= lines.len() || end_line >= lines.len() {
-                    return Ok(ToolResult::error(format!(
-                        "Line range {}-{} out of bounds (file has {} lines)",
-                        start_line,
-                        end_line,
-                        lines.len()
-                    )));
-                }
-                if start_line > end_line {
-                    return Ok(ToolResult::error(
-                        "start_line must be <= end_line".to_string(),
-                    ));
-                }
-
-                let mut new_lines = Vec::new();
-                new_lines.extend_from_slice(&lines[..start_line]);
-                if end_line + 1 < lines.len() {
-                    new_lines.extend_from_slice(&lines[end_line + 1..]);
-                }
-                new_lines.join("\n")
-            }
-
-            EditOperation::RegexReplace {
-                pattern,
-                replacement,
-            } => {
-                let regex = regex::Regex::new(&pattern)
-                    .map_err(|e| ToolError::InvalidInput(format!("Invalid regex: {}", e)))?;
-
-                if !regex.is_match(&content) {
-                    return Ok(ToolResult::error(format!(
-                        "Pattern not found in file: '{}'",
-                        pattern
-                    )));
-                }
-
-                regex
-                    .replace_all(&content, replacement.as_str())
-                    .to_string()
-            }
+        let new_content = match apply_operation(&content, input.operation) {
+            Ok(new_content) => new_content,
+            Err(msg) => return Ok(ToolResult::error(msg)),
         };
 
         // Write modified content
+        context.checkpoint_file(&path).await;
         fs::write(&path, &new_content)
             .await
             .map_err(ToolError::Io)?;
@@ -306,6 +226,429 @@ impl Tool for EditTool {
     }
 }
 
+/// Run a single-file edit operation on `content`. `Err` carries a message
+/// for the model (text not found, bad line range, ...).
+fn apply_operation(content: &str, operation: EditOperation) -> std::result::Result<String, String> {
+    let new_content = match operation {
+        EditOperation::Replace { old_text, new_text } => {
+            if !content.contains(&old_text) {
+                return Err(format!("Text not found in file: '{}'", old_text));
+            }
+            content.replace(&old_text, &new_text)
+        }
+
+        EditOperation::ReplaceLines {
+            start_line,
+            end_line,
+            new_text,
+        } => {
+            let lines: Vec<&str> = content.lines().collect();
+            if start_line >= lines.len() || end_line >= lines.len() {
+                return Err(format!(
+                    "Line range {}-{} out of bounds (file has {} lines)",
+                    start_line,
+                    end_line,
+                    lines.len()
+                ));
+            }
+            if start_line > end_line {
+                return Err("start_line must be <= end_line".to_string());
+            }
+
+            let mut new_lines = Vec::new();
+            new_lines.extend_from_slice(&lines[..start_line]);
+            new_lines.push(&new_text);
+            if end_line + 1 < lines.len() {
+                new_lines.extend_from_slice(&lines[end_line + 1..]);
+            }
+            new_lines.join("\n")
+        }
+
+        EditOperation::InsertLine { line, text } => {
+            let lines: Vec<&str> = content.lines().collect();
+            if line > lines.len() {
+                return Err(format!(
+                    "Line {} out of bounds (file has {} lines)",
+                    line,
+                    lines.len()
+                ));
+            }
+
+            let mut new_lines = Vec::new();
+            new_lines.extend_from_slice(&lines[..line]);
+            new_lines.push(&text);
+            new_lines.extend_from_slice(&lines[line..]);
+            new_lines.join("\n")
+        }
+
+        EditOperation::DeleteLines {
+            start_line,
+            end_line,
+        } => {
+            let lines: Vec<&str> = content.lines().collect();
+            if start_line >= lines.len() || end_line >= lines.len() {
+                return Err(format!(
+                    "Line range {}-{} out of bounds (file has {} lines)",
+                    start_line,
+                    end_line,
+                    lines.len()
+                ));
+            }
+            if start_line > end_line {
+                return Err("start_line must be <= end_line".to_string());
+            }
+
+            let mut new_lines = Vec::new();
+            new_lines.extend_from_slice(&lines[..start_line]);
+            if end_line + 1 < lines.len() {
+                new_lines.extend_from_slice(&lines[end_line + 1..]);
+            }
+            new_lines.join("\n")
+        }
+
+        EditOperation::RegexReplace {
+            pattern,
+            replacement,
+        } => {
+            let regex = regex::Regex::new(&pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+
+            if !regex.is_match(content) {
+                return Err(format!("Pattern not found in file: '{}'", pattern));
+            }
+
+            regex.replace_all(content, replacement.as_str()).to_string()
+        }
+
+        EditOperation::ApplyPatch { .. } => {
+            return Err("apply_patch edits are planned from the patch text".to_string());
+        }
+    };
+    Ok(new_content)
+}
+
+/// A file an edit would change
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    /// Content before the edit, `None` if the file is new
+    pub before: Option<String>,
+    /// Content after the edit, `None` if the file is deleted
+    pub after: Option<String>,
+}
+
+/// Compute what an `edit_file` call would change without writing anything,
+/// e.g. to show a diff before approving it
+pub fn preview(input: &Value, working_dir: &Path) -> std::result::Result<Vec<FileChange>, String> {
+    let input: EditInput =
+        serde_json::from_value(input.clone()).map_err(|e| format!("Invalid input: {}", e))?;
+
+    if let EditOperation::ApplyPatch { patch } = &input.operation {
+        return plan_patch(&input.path, patch, working_dir).map(|plan| plan.changes);
+    }
+
+    let path = validate_file_path(&input.path, working_dir)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let after = apply_operation(&content, input.operation)?;
+    Ok(vec![FileChange {
+        path,
+        before: Some(content),
+        after: Some(after),
+    }])
+}
+
+/// Every change a patch makes, computed up front
+struct PatchPlan {
+    changes: Vec<FileChange>,
+    hunks: usize,
+    /// Hunks that needed an offset or fuzz to apply
+    notes: Vec<String>,
+}
+
+/// Work out every file change of a patch. Fails without side effects if any
+/// hunk doesn't apply, naming the file and hunk.
+fn plan_patch(
+    path: &str,
+    patch_text: &str,
+    working_dir: &Path,
+) -> std::result::Result<PatchPlan, String> {
+    let files = patch::parse(patch_text).map_err(|e| format!("Invalid patch: {}", e))?;
+
+    // `path` is either the single target file or the base for patch paths
+    let requested = if path.is_empty() {
+        working_dir.to_path_buf()
+    } else {
+        validate_path_safety(path, working_dir).map_err(|e| format!("Invalid path: {}", e))?
+    };
+    // A missing path must not fall back to the working directory: approval
+    // and policy were checked against `path`, not the whole workspace
+    if !requested.is_file() && !requested.is_dir() {
+        return Err(format!(
+            "Invalid path: {} is neither an existing file nor a directory",
+            path
+        ));
+    }
+    let single_target = requested.is_file().then(|| requested.clone());
+    if single_target.is_some() && files.len() > 1 {
+        return Err(format!(
+            "Patch touches {} files but path is a single file; pass a directory (or omit path)",
+            files.len()
+        ));
+    }
+    let base = requested;
+
+    let mut plan = PatchPlan {
+        changes: Vec::new(),
+        hunks: 0,
+        notes: Vec::new(),
+    };
+    for file in &files {
+        let label = file.display_path();
+        let (source, target) = match &single_target {
+            Some(target) => (Some(target.clone()), Some(target.clone())),
+            None => resolve_targets(file, &base)
+                .map_err(|reason| patch_failed(label, &reason))?,
+        };
+        let (changes, fuzz) = plan_file(file, source, target, &plan.changes)
+            .map_err(|reason| patch_failed(label, &reason))?;
+
+        plan.notes
+            .extend(fuzz.into_iter().map(|n| format!("{}: {}", label, n)));
+        for change in changes {
+            // A later section for the same file builds on the earlier one
+            match plan.changes.iter().position(|c| c.path == change.path) {
+                Some(i) => plan.changes[i].after = change.after,
+                None => plan.changes.push(change),
+            }
+        }
+        plan.hunks += file.hunks.len();
+    }
+    Ok(plan)
+}
+
+fn patch_failed(file: &str, reason: &str) -> String {
+    format!(
+        "Patch not applied (no files were changed). {}: {}",
+        file, reason
+    )
+}
+
+/// Apply a unified diff atomically: every hunk of every file applies, or no
+/// file is touched and the error names the failing file and hunk.
+async fn apply_patch(
+    path: &str,
+    patch_text: &str,
+    create_backup: bool,
+    context: &ToolExecutionContext,
+) -> Result<ToolResult> {
+    let working_dir = &context.working_directory;
+    let plan = match plan_patch(path, patch_text, working_dir) {
+        Ok(plan) => plan,
+        Err(msg) => return Ok(ToolResult::error(msg)),
+    };
+
+    // Write everything; undo earlier writes if a later one fails
+    for (index, change) in plan.changes.iter().enumerate() {
+        if let Err(e) = write_change(change, create_backup, context).await {
+            for done in plan.changes[..index].iter().rev() {
+                let revert = FileChange {
+                    path: done.path.clone(),
+                    before: done.after.clone(),
+                    after: done.before.clone(),
+                };
+                if let Err(err) = write_change(&revert, false, context).await {
+                    tracing::error!("Failed to revert {}: {}", done.path.display(), err);
+                }
+            }
+            return Err(e);
+        }
+    }
+
+    let mut output = format!(
+        "Applied patch: {} file(s), {} hunk(s)\n",
+        plan.changes.len(),
+        plan.hunks
+    );
+    for change in &plan.changes {
+        let display = change
+            .path
+            .strip_prefix(working_dir)
+            .unwrap_or(&change.path)
+            .display();
+        let (marker, old, new) = match (&change.before, &change.after) {
+            (None, Some(new)) => ("A", "", new.as_str()),
+            (Some(old), None) => ("D", old.as_str(), ""),
+            (Some(old), Some(new)) => ("M", old.as_str(), new.as_str()),
+            (None, None) => continue,
+        };
+        output.push_str(&format!("{} {}\n", marker, display));
+        output.push_str(&build_edit_diff(old, new));
+    }
+    for note in plan.notes {
+        output.push_str(&format!("note: {}\n", note));
+    }
+
+    Ok(ToolResult::success(output))
+}
+
+/// Compute the changes for one file section of a patch
+fn plan_file(
+    file: &FilePatch,
+    source: Option<PathBuf>,
+    target: Option<PathBuf>,
+    planned: &[FileChange],
+) -> std::result::Result<(Vec<FileChange>, Vec<patch::FuzzNote>), String> {
+    let before = match &source {
+        Some(path) => match current_content(path, planned)? {
+            Some(content) => Some(content),
+            None => return Err(format!("file not found: {}", path.display())),
+        },
+        None => None,
+    };
+    let (after, notes) = patch::apply(before.as_deref().unwrap_or_default(), &file.hunks)
+        .map_err(|e| e.to_string())?;
+
+    let changes = match (source, target) {
+        (Some(source), Some(target)) if source == target => vec![FileChange {
+            path: target,
+            before,
+            after: Some(after),
+        }],
+        // Rename
+        (Some(source), Some(target)) => {
+            if current_content(&target, planned)?.is_some() {
+                return Err(format!(
+                    "rename target already exists: {}",
+                    target.display()
+                ));
+            }
+            vec![
+                FileChange {
+                    path: source,
+                    before,
+                    after: None,
+                },
+                FileChange {
+                    path: target,
+                    before: None,
+                    after: Some(after),
+                },
+            ]
+        }
+        (None, Some(target)) => {
+            if current_content(&target, planned)?.is_some() {
+                return Err(format!("file already exists: {}", target.display()));
+            }
+            vec![FileChange {
+                path: target,
+                before: None,
+                after: Some(after),
+            }]
+        }
+        (Some(source), None) => {
+            if !after.trim().is_empty() {
+                return Err("deletion hunks don't remove the whole file".to_string());
+            }
+            vec![FileChange {
+                path: source,
+                before,
+                after: None,
+            }]
+        }
+        (None, None) => {
+            return Err("patch has no file headers; pass the target file as path".to_string());
+        }
+    };
+    Ok((changes, notes))
+}
+
+/// Content of `path` as earlier sections of the patch left it
+fn current_content(
+    path: &Path,
+    planned: &[FileChange],
+) -> std::result::Result<Option<String>, String> {
+    if let Some(change) = planned.iter().find(|c| c.path == path) {
+        return Ok(change.after.clone());
+    }
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("cannot read {}: {}", path.display(), e)),
+    }
+}
+
+/// Resolve a section's old/new paths below `base`, keeping them inside it
+/// (including files that don't exist yet). `base` is the already validated
+/// `path` argument, so header paths can't reach files the permission policy
+/// only checked the directory for.
+fn resolve_targets(
+    file: &FilePatch,
+    base: &Path,
+) -> std::result::Result<(Option<PathBuf>, Option<PathBuf>), String> {
+    let resolve = |rel: &Option<String>| -> std::result::Result<Option<PathBuf>, String> {
+        let Some(rel) = rel else {
+            return Ok(None);
+        };
+        // Absolute paths would replace `base` in the join, `..` climb out of it
+        if Path::new(rel)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(format!(
+                "path must be relative to {} without '..': {}",
+                base.display(),
+                rel
+            ));
+        }
+        let path = base.join(rel);
+        let existing = path
+            .ancestors()
+            .find(|p| p.exists())
+            .ok_or_else(|| format!("invalid path: {}", rel))?;
+        // Catches symlinks that lead out of `base`
+        validate_path_safety(&existing.to_string_lossy(), base)
+            .map_err(|e| format!("{}: {}", rel, e))?;
+        Ok(Some(path))
+    };
+    Ok((resolve(&file.old_path)?, resolve(&file.new_path)?))
+}
+
+/// Write (or delete) one planned file, checkpointing it first
+async fn write_change(
+    change: &FileChange,
+    create_backup: bool,
+    context: &ToolExecutionContext,
+) -> Result<()> {
+    context.checkpoint_file(&change.path).await;
+    if create_backup && let Some(before) = &change.before {
+        let backup_path = change.path.with_extension(format!(
+            "{}.backup",
+            change
+                .path
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("txt")
+        ));
+        fs::write(&backup_path, before)
+            .await
+            .map_err(ToolError::Io)?;
+    }
+
+    match &change.after {
+        Some(content) => {
+            if let Some(parent) = change.path.parent() {
+                fs::create_dir_all(parent).await.map_err(ToolError::Io)?;
+            }
+            fs::write(&change.path, content)
+                .await
+                .map_err(ToolError::Io)
+        }
+        None => match fs::remove_file(&change.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(ToolError::Io(e)),
+            _ => Ok(()),
+        },
+    }
+}
+
 /// Build a compact unified-style diff between old and new content.
 /// Shows only changed lines with `-`/`+` prefixes (capped at 40 diff lines).
 fn build_edit_diff(old: &str, new: &str) -> String {
@@ -387,3 +730,175 @@ fn build_edit_diff(old: &str, new: &str) -> String {
 
     diff
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn context_in(dir: &TempDir) -> ToolExecutionContext {
+        ToolExecutionContext::new(Uuid::new_v4()).with_working_directory(dir.path().to_path_buf())
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_multi_file() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("lib.rs"), "mod a;\n\nfn one() {}\n").unwrap();
+
+        let patch = "\
+--- a/lib.rs
++++ b/lib.rs
+@@ -1,3 +1,4 @@
+ mod a;
++mod b;
+ 
+ fn one() {}
+--- /dev/null
++++ b/src/b.rs
+@@ -0,0 +1 @@
++pub fn two() {}
+";
+        let input = serde_json::json!({
+            "operation": "apply_patch",
+            "patch": patch,
+            "create_backup": false
+        });
+        let result = EditTool
+            .execute(input, &context_in(&temp_dir))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(
+            result
+                .output
+                .starts_with("Applied patch: 2 file(s), 2 hunk(s)")
+        );
+
+        let lib = std::fs::read_to_string(temp_dir.path().join("lib.rs")).unwrap();
+        assert_eq!(lib, "mod a;\nmod b;\n\nfn one() {}\n");
+        let created = std::fs::read_to_string(temp_dir.path().join("src/b.rs")).unwrap();
+        assert_eq!(created, "pub fn two() {}\n");
+    }
+
+    #[test]
+    fn test_preview_does_not_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.md");
+        std::fs::write(&file, "# Title\ndraft\n").unwrap();
+
+        let input = serde_json::json!({
+            "path": "notes.md",
+            "operation": "replace",
+            "old_text": "draft",
+            "new_text": "final"
+        });
+        let changes = preview(&input, temp_dir.path()).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].before.as_deref(), Some("# Title\ndraft\n"));
+        assert_eq!(changes[0].after.as_deref(), Some("# Title\nfinal\n"));
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "# Title\ndraft\n");
+
+        let missing = serde_json::json!({
+            "path": "notes.md",
+            "operation": "replace",
+            "old_text": "nope",
+            "new_text": "x"
+        });
+        assert!(preview(&missing, temp_dir.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_is_atomic() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.txt"), "alpha\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "beta\n").unwrap();
+
+        let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-alpha
++ALPHA
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-gamma
++GAMMA
+";
+        let input = serde_json::json!({
+            "operation": "apply_patch",
+            "patch": patch,
+            "create_backup": false
+        });
+        let result = EditTool
+            .execute(input, &context_in(&temp_dir))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(
+            error.contains("b.txt: hunk #1: context not found"),
+            "{}",
+            error
+        );
+
+        // The first file's hunk matched, but nothing may be written
+        let a = std::fs::read_to_string(temp_dir.path().join("a.txt")).unwrap();
+        assert_eq!(a, "alpha\n");
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_stays_inside_path() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        let secret = temp_dir.path().join("secret.txt");
+        std::fs::write(&secret, "keep\n").unwrap();
+
+        // Header paths must not escape the `path` the policy checked
+        let absolute = secret.display().to_string();
+        for header in [absolute.as_str(), "../secret.txt"] {
+            let patch = format!("--- {header}\n+++ {header}\n@@ -1 +1 @@\n-keep\n+pwned\n");
+            let input = serde_json::json!({
+                "operation": "apply_patch",
+                "path": "docs",
+                "patch": patch,
+                "create_backup": false
+            });
+            let result = EditTool
+                .execute(input, &context_in(&temp_dir))
+                .await
+                .unwrap();
+            assert!(!result.success, "{}", header);
+            assert!(result.error.unwrap().contains("path must be relative"));
+        }
+        assert_eq!(std::fs::read_to_string(&secret).unwrap(), "keep\n");
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_rejects_missing_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.txt");
+        std::fs::write(&file, "alpha\n").unwrap();
+
+        // A typo'd directory must not widen the patch to the working directory
+        let input = serde_json::json!({
+            "operation": "apply_patch",
+            "path": "sr",
+            "patch": "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-alpha\n+ALPHA\n",
+            "create_backup": false
+        });
+        let result = EditTool
+            .execute(input, &context_in(&temp_dir))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(
+            result
+                .error
+                .unwrap()
+                .contains("sr is neither an existing file nor a directory")
+        );
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "alpha\n");
+    }
+}
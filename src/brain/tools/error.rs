@@ -41,6 +41,17 @@ pub enum ToolError {
     #[error("Tool execution timed out after {0}s")]
     Timeout(u64),
 
+    /// A sandboxed command was blocked
+    #[error("Sandbox violation ({kind}): {detail}")]
+    SandboxViolation {
+        kind: super::sandbox::SandboxViolationKind,
+        detail: String,
+    },
+
+    /// The sandbox is enabled but can't be enforced here
+    #[error("Sandbox unavailable: {0}")]
+    SandboxUnavailable(String),
+
     /// Internal error
     #[error("Internal error: {0}")]
     Internal(String),
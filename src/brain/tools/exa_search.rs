@@ -401,6 +401,10 @@ impl Tool for ExaSearchTool {
         vec![ToolCapability::Network]
     }
 
+    fn is_read_only(&self) -> bool {
+        true // Search queries have no side effects
+    }
+
     fn requires_approval(&self) -> bool {
         false
     }
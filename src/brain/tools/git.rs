@@ -0,0 +1,1001 @@
+//! Git Tool
+//!
+//! Structured access to the repository in the working directory: status,
+//! per-file diffs with hunks, log and blame come back as JSON, so the agent
+//! doesn't have to scrape `git` output through `bash`. Staging, commits,
+//! branches and stashes are write operations: they need approval and are
+//! refused in Plan mode.
+
+use super::error::{Result, ToolError};
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+use tokio::time::{Duration, timeout};
+
+/// Diff lines returned before the rest is cut off
+const MAX_DIFF_LINES: usize = 2000;
+
+/// Git tool
+pub struct GitTool;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BranchAction {
+    #[default]
+    List,
+    Create,
+    Switch,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StashAction {
+    List,
+    #[default]
+    Push,
+    Pop,
+    Apply,
+    Drop,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+enum GitOperation {
+    /// Branch, upstream and changed files
+    Status,
+
+    /// Working tree (or staged) changes, optionally against a base revision
+    Diff {
+        #[serde(default)]
+        staged: bool,
+        #[serde(default)]
+        base: Option<String>,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+
+    /// Recent commits
+    Log {
+        #[serde(default = "default_log_limit")]
+        limit: usize,
+        #[serde(default)]
+        rev: Option<String>,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+
+    /// Who last changed each line of a file
+    Blame {
+        path: String,
+        #[serde(default)]
+        start_line: Option<usize>,
+        #[serde(default)]
+        end_line: Option<usize>,
+    },
+
+    Stage {
+        paths: Vec<String>,
+    },
+
+    Unstage {
+        paths: Vec<String>,
+    },
+
+    Commit {
+        message: String,
+        /// Stage all tracked changes first (`git commit -a`)
+        #[serde(default)]
+        all: bool,
+    },
+
+    Branch {
+        #[serde(default)]
+        action: BranchAction,
+        #[serde(default)]
+        name: Option<String>,
+    },
+
+    Stash {
+        #[serde(default)]
+        action: StashAction,
+        #[serde(default)]
+        message: Option<String>,
+        #[serde(default)]
+        index: Option<usize>,
+    },
+}
+
+fn default_log_limit() -> usize {
+    20
+}
+
+impl GitOperation {
+    /// The revision or branch name that git receives as a bare argument
+    fn revision_argument(&self) -> Option<(&'static str, &str)> {
+        match self {
+            GitOperation::Diff { base, .. } => base.as_deref().map(|b| ("base", b)),
+            GitOperation::Log { rev, .. } => rev.as_deref().map(|r| ("rev", r)),
+            GitOperation::Branch { name, .. } => name.as_deref().map(|n| ("name", n)),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            GitOperation::Status => "status",
+            GitOperation::Diff { .. } => "diff",
+            GitOperation::Log { .. } => "log",
+            GitOperation::Blame { .. } => "blame",
+            GitOperation::Stage { .. } => "stage",
+            GitOperation::Unstage { .. } => "unstage",
+            GitOperation::Commit { .. } => "commit",
+            GitOperation::Branch { .. } => "branch",
+            GitOperation::Stash { .. } => "stash",
+        }
+    }
+
+    /// Whether the operation changes the repository
+    fn is_write(&self) -> bool {
+        match self {
+            GitOperation::Status
+            | GitOperation::Diff { .. }
+            | GitOperation::Log { .. }
+            | GitOperation::Blame { .. } => false,
+            GitOperation::Branch { action, .. } => *action != BranchAction::List,
+            GitOperation::Stash { action, .. } => *action != StashAction::List,
+            GitOperation::Stage { .. }
+            | GitOperation::Unstage { .. }
+            | GitOperation::Commit { .. } => true,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect and change the git repository in the working directory. Prefer this over \
+         running git through bash. Read operations return JSON: 'status' (branch, upstream, \
+         ahead/behind, changed files), 'diff' (per-file hunks; staged or against a base \
+         revision), 'log' (commits with authors and dates) and 'blame' (line ranges with the \
+         commit that last changed them). Write operations need approval and are unavailable \
+         in Plan mode: 'stage', 'unstage', 'commit' (write the message yourself from what \
+         changed in this session), 'branch' (list/create/switch/delete) and 'stash' \
+         (push/pop/apply/drop/list)."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["status", "diff", "log", "blame", "stage", "unstage", "commit", "branch", "stash"],
+                    "description": "Git operation to perform"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files to limit diff/log to, or to stage/unstage (required for stage and unstage)"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "For diff: show staged changes instead of unstaged ones",
+                    "default": false
+                },
+                "base": {
+                    "type": "string",
+                    "description": "For diff: revision to compare the working tree (or index, with staged) against"
+                },
+                "rev": {
+                    "type": "string",
+                    "description": "For log: revision or range to list (default: HEAD)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "For log: maximum number of commits",
+                    "default": 20
+                },
+                "path": {
+                    "type": "string",
+                    "description": "For blame: file to blame"
+                },
+                "start_line": {
+                    "type": "integer",
+                    "description": "For blame: first line (1-based)"
+                },
+                "end_line": {
+                    "type": "integer",
+                    "description": "For blame: last line (inclusive)"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Commit message (required for commit), or stash message for stash push"
+                },
+                "all": {
+                    "type": "boolean",
+                    "description": "For commit: stage all tracked changes first",
+                    "default": false
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "create", "switch", "delete", "push", "pop", "apply", "drop"],
+                    "description": "For branch: list (default), create, switch or delete. For stash: push (default), pop, apply, drop or list"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "For branch: branch name (required unless listing)"
+                },
+                "index": {
+                    "type": "integer",
+                    "description": "For stash pop/apply/drop: stash entry (default: latest)"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadFiles, ToolCapability::WriteFiles]
+    }
+
+    fn requires_approval_for(&self, input: &Value) -> bool {
+        serde_json::from_value::<GitOperation>(input.clone()).map_or(true, |op| op.is_write())
+    }
+
+    fn validate_input(&self, input: &Value) -> Result<()> {
+        let operation: GitOperation = serde_json::from_value(input.clone())
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid input: {}", e)))?;
+
+        match &operation {
+            GitOperation::Stage { paths } | GitOperation::Unstage { paths } if paths.is_empty() => {
+                Err(ToolError::InvalidInput(
+                    "paths must name at least one file".to_string(),
+                ))
+            }
+            GitOperation::Commit { message, .. } if message.trim().is_empty() => Err(
+                ToolError::InvalidInput("Commit message cannot be empty".to_string()),
+            ),
+            GitOperation::Branch { action, name: None } if *action != BranchAction::List => {
+                Err(ToolError::InvalidInput(
+                    "name is required to create, switch or delete a branch".to_string(),
+                ))
+            }
+            _ => match operation.revision_argument() {
+                // Read operations skip approval, so a "revision" like
+                // `--output=<file>` must not reach git as an option
+                Some((field, value)) if value.starts_with('-') => Err(ToolError::InvalidInput(
+                    format!("{} must not start with '-': {}", field, value),
+                )),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    async fn execute(&self, input: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let operation: GitOperation = serde_json::from_value(input)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid input: {}", e)))?;
+
+        if context.read_only_mode && operation.is_write() {
+            return Ok(ToolResult::error(format!(
+                "git {} is not allowed in Plan mode. Read operations (status, diff, log, blame) \
+                 are available; approve the plan (Ctrl+A) to change the repository.",
+                operation.name()
+            )));
+        }
+
+        let git = Git {
+            dir: &context.working_directory,
+            timeout: Duration::from_secs(context.timeout_secs),
+        };
+        let name = operation.name();
+        match run(&git, operation).await {
+            Ok(value) => Ok(ToolResult::success(
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+            )),
+            Err(e) => Ok(ToolResult::error(format!("git {} failed: {}", name, e))),
+        }
+    }
+}
+
+/// Runs `git` in the working directory
+struct Git<'a> {
+    dir: &'a Path,
+    timeout: Duration,
+}
+
+impl Git<'_> {
+    /// Run git and return stdout, or stderr as the error
+    async fn run<S: AsRef<std::ffi::OsStr>>(
+        &self,
+        args: &[S],
+    ) -> std::result::Result<String, String> {
+        let mut cmd = Command::new("git");
+        cmd.args(args)
+            .current_dir(self.dir)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_PAGER", "cat")
+            .kill_on_drop(true);
+
+        let output = match timeout(self.timeout, cmd.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Err(format!("could not run git: {}", e)),
+            Err(_) => return Err(format!("timed out after {}s", self.timeout.as_secs())),
+        };
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let message = if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            };
+            Err(message.trim().to_string())
+        }
+    }
+}
+
+async fn run(git: &Git<'_>, operation: GitOperation) -> std::result::Result<Value, String> {
+    match operation {
+        GitOperation::Status => status(git).await,
+
+        GitOperation::Diff {
+            staged,
+            base,
+            paths,
+        } => {
+            let mut args = vec!["diff", "--no-color", "--no-ext-diff", "-M"];
+            if staged {
+                args.push("--cached");
+            }
+            if let Some(ref base) = base {
+                args.push(base);
+            }
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+            let (files, truncated) = parse_diff(&git.run(&args).await?);
+            Ok(json!({ "files": files, "truncated": truncated }))
+        }
+
+        GitOperation::Log { limit, rev, paths } => {
+            let limit = format!("-n{}", limit.max(1));
+            let mut args = vec![
+                "log",
+                "--no-color",
+                limit.as_str(),
+                "--date=iso-strict",
+                "--format=%H%x1f%an%x1f%ae%x1f%ad%x1f%s%x1e",
+            ];
+            if let Some(ref rev) = rev {
+                args.push(rev);
+            }
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+            Ok(json!({ "commits": parse_log(&git.run(&args).await?) }))
+        }
+
+        GitOperation::Blame {
+            path,
+            start_line,
+            end_line,
+        } => {
+            let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+            match (start_line, end_line) {
+                (None, None) => {}
+                (start, end) => args.push(format!(
+                    "-L{},{}",
+                    start.unwrap_or(1).max(1),
+                    end.map(|e| e.to_string()).unwrap_or_default()
+                )),
+            }
+            args.push("--".to_string());
+            args.push(path.clone());
+            Ok(json!({ "path": path, "ranges": parse_blame(&git.run(&args).await?) }))
+        }
+
+        GitOperation::Stage { paths } => {
+            let mut args = vec!["add", "--"];
+            args.extend(paths.iter().map(String::as_str));
+            git.run(&args).await?;
+            status(git).await
+        }
+
+        GitOperation::Unstage { paths } => {
+            let mut args = vec!["restore", "--staged", "--"];
+            args.extend(paths.iter().map(String::as_str));
+            git.run(&args).await?;
+            status(git).await
+        }
+
+        GitOperation::Commit { message, all } => {
+            let mut args = vec!["commit", "-m", message.as_str()];
+            if all {
+                args.push("-a");
+            }
+            git.run(&args).await?;
+            let head = git.run(&["log", "-1", "--format=%H%x1f%s"]).await?;
+            let (hash, subject) = head.trim().split_once('\x1f').unwrap_or((head.trim(), ""));
+            let stat = git
+                .run(&["show", "--shortstat", "--format=", "HEAD"])
+                .await?;
+            Ok(json!({
+                "commit": hash,
+                "subject": subject,
+                "stats": stat.trim(),
+            }))
+        }
+
+        GitOperation::Branch { action, name } => {
+            let name = name.unwrap_or_default();
+            let args: Vec<&str> = match action {
+                BranchAction::List => {
+                    let out = git
+                        .run(&[
+                            "branch",
+                            "--format=%(HEAD)\t%(refname:short)\t%(upstream:short)\t%(objectname:short)",
+                        ])
+                        .await?;
+                    return Ok(json!({ "branches": parse_branches(&out) }));
+                }
+                BranchAction::Create => vec!["branch", "--", &name],
+                BranchAction::Switch => vec!["switch", "--", &name],
+                BranchAction::Delete => vec!["branch", "-d", "--", &name],
+            };
+            let out = git.run(&args).await?;
+            Ok(json!({ "branch": name, "output": out.trim() }))
+        }
+
+        GitOperation::Stash {
+            action,
+            message,
+            index,
+        } => {
+            let entry = index.map(|i| format!("stash@{{{}}}", i));
+            let mut args: Vec<&str> = match action {
+                StashAction::List => {
+                    let out = git.run(&["stash", "list", "--format=%gd%x1f%s"]).await?;
+                    let entries: Vec<Value> = out
+                        .lines()
+                        .filter_map(|line| line.split_once('\x1f'))
+                        .map(|(entry, message)| json!({ "entry": entry, "message": message }))
+                        .collect();
+                    return Ok(json!({ "stashes": entries }));
+                }
+                StashAction::Push => {
+                    let mut args = vec!["stash", "push"];
+                    if let Some(ref message) = message {
+                        args.extend(["-m", message.as_str()]);
+                    }
+                    args
+                }
+                StashAction::Pop => vec!["stash", "pop"],
+                StashAction::Apply => vec!["stash", "apply"],
+                StashAction::Drop => vec!["stash", "drop"],
+            };
+            if action != StashAction::Push
+                && let Some(ref entry) = entry
+            {
+                args.push(entry);
+            }
+            let out = git.run(&args).await?;
+            Ok(json!({ "output": out.trim() }))
+        }
+    }
+}
+
+async fn status(git: &Git<'_>) -> std::result::Result<Value, String> {
+    let out = git
+        .run(&["status", "--porcelain=v1", "-z", "--branch"])
+        .await?;
+    Ok(serde_json::to_value(parse_status(&out)).unwrap_or_default())
+}
+
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+struct Status {
+    branch: Option<String>,
+    upstream: Option<String>,
+    ahead: usize,
+    behind: usize,
+    clean: bool,
+    entries: Vec<StatusEntry>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct StatusEntry {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    orig_path: Option<String>,
+    /// Change in the index, `None` when nothing is staged
+    staged: Option<&'static str>,
+    /// Change in the working tree, `None` when nothing is unstaged
+    unstaged: Option<&'static str>,
+}
+
+fn status_code(code: char) -> Option<&'static str> {
+    Some(match code {
+        'M' => "modified",
+        'T' => "type_changed",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'U' => "unmerged",
+        '?' => "untracked",
+        '!' => "ignored",
+        _ => return None,
+    })
+}
+
+/// Parse `git status --porcelain=v1 -z --branch`
+fn parse_status(out: &str) -> Status {
+    let mut status = Status::default();
+    let mut records = out.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("## ") {
+            parse_branch_header(header, &mut status);
+            continue;
+        }
+        let mut chars = record.chars();
+        let (Some(x), Some(y)) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        let path = record.get(3..).unwrap_or_default().to_string();
+        // Renames and copies are followed by the original path
+        let orig_path = matches!(x, 'R' | 'C')
+            .then(|| records.next().map(str::to_string))
+            .flatten();
+        let untracked = x == '?' || x == '!';
+        status.entries.push(StatusEntry {
+            path,
+            orig_path,
+            staged: if untracked { None } else { status_code(x) },
+            unstaged: status_code(y),
+        });
+    }
+    status.clean = status.entries.is_empty();
+    status
+}
+
+/// `main...origin/main [ahead 1, behind 2]`, `No commits yet on main`,
+/// `HEAD (no branch)`
+fn parse_branch_header(header: &str, status: &mut Status) {
+    if let Some(branch) = header
+        .strip_prefix("No commits yet on ")
+        .or_else(|| header.strip_prefix("Initial commit on "))
+    {
+        status.branch = Some(branch.to_string());
+        return;
+    }
+    if header.starts_with("HEAD (no branch)") {
+        return;
+    }
+
+    let (refs, tracking) = match header.split_once(" [") {
+        Some((refs, rest)) => (refs, rest.trim_end_matches(']')),
+        None => (header, ""),
+    };
+    match refs.split_once("...") {
+        Some((branch, upstream)) => {
+            status.branch = Some(branch.to_string());
+            status.upstream = Some(upstream.to_string());
+        }
+        None => status.branch = Some(refs.to_string()),
+    }
+    for part in tracking.split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            status.ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            status.behind = n.parse().unwrap_or(0);
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, PartialEq, Eq)]
+struct DiffFile {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_path: Option<String>,
+    status: &'static str,
+    binary: bool,
+    additions: usize,
+    deletions: usize,
+    hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct DiffHunk {
+    header: String,
+    old_start: usize,
+    old_lines: usize,
+    new_start: usize,
+    new_lines: usize,
+    /// Hunk body with the ` `/`+`/`-` prefixes kept
+    lines: Vec<String>,
+}
+
+/// Parse `git diff` output into files and hunks. The second value is true
+/// when hunk lines were cut off after `MAX_DIFF_LINES`.
+fn parse_diff(out: &str) -> (Vec<DiffFile>, bool) {
+    let mut files: Vec<DiffFile> = Vec::new();
+    let mut in_hunk = false;
+    let mut budget = MAX_DIFF_LINES;
+    let mut truncated = false;
+
+    for line in out.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            in_hunk = false;
+            // Paths from `a/x b/x`; the ---/+++ lines refine them below
+            let path = rest.rsplit_once(" b/").map_or(rest, |(_, b)| b).to_string();
+            files.push(DiffFile {
+                path,
+                status: "modified",
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if line.starts_with("@@") {
+            in_hunk = true;
+            let (old_start, old_lines, new_start, new_lines) = parse_hunk_range(line);
+            file.hunks.push(DiffHunk {
+                header: line.to_string(),
+                old_start,
+                old_lines,
+                new_start,
+                new_lines,
+                lines: Vec::new(),
+            });
+        } else if in_hunk && line.starts_with(['+', '-', ' ', '\\']) {
+            match line.as_bytes()[0] {
+                b'+' => file.additions += 1,
+                b'-' => file.deletions += 1,
+                _ => {}
+            }
+            if budget == 0 {
+                truncated = true;
+            } else if let Some(hunk) = file.hunks.last_mut() {
+                budget -= 1;
+                hunk.lines.push(line.to_string());
+            }
+        } else if line.starts_with("new file mode") {
+            file.status = "added";
+        } else if line.starts_with("deleted file mode") {
+            file.status = "deleted";
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.status = "renamed";
+            file.old_path = Some(from.to_string());
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            file.path = to.to_string();
+        } else if line.starts_with("Binary files ") {
+            file.binary = true;
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        }
+    }
+    (files, truncated)
+}
+
+/// `@@ -12,7 +12,9 @@ fn x()` → (12, 7, 12, 9); a missing count means 1
+fn parse_hunk_range(header: &str) -> (usize, usize, usize, usize) {
+    let range = |prefix: char| {
+        header
+            .split_whitespace()
+            .find_map(|part| part.strip_prefix(prefix))
+            .map(|r| {
+                let (start, count) = r.split_once(',').unwrap_or((r, "1"));
+                (start.parse().unwrap_or(0), count.parse().unwrap_or(0))
+            })
+            .unwrap_or((0, 0))
+    };
+    let (old_start, old_lines) = range('-');
+    let (new_start, new_lines) = range('+');
+    (old_start, old_lines, new_start, new_lines)
+}
+
+/// Parse `--format=%H%x1f%an%x1f%ae%x1f%ad%x1f%s%x1e`
+fn parse_log(out: &str) -> Vec<Value> {
+    out.split('\x1e')
+        .map(str::trim)
+        .filter(|record| !record.is_empty())
+        .filter_map(|record| {
+            let fields: Vec<&str> = record.split('\x1f').collect();
+            let [hash, author, email, date, subject] = fields.as_slice() else {
+                return None;
+            };
+            Some(json!({
+                "hash": hash,
+                "author": author,
+                "email": email,
+                "date": date,
+                "subject": subject,
+            }))
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct BlameRange {
+    start_line: usize,
+    end_line: usize,
+    commit: String,
+    author: String,
+    date: String,
+    summary: String,
+}
+
+/// Parse `git blame --porcelain` into runs of lines from the same commit
+fn parse_blame(out: &str) -> Vec<BlameRange> {
+    #[derive(Default, Clone)]
+    struct CommitInfo {
+        author: String,
+        time: i64,
+        summary: String,
+    }
+
+    let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+
+    for line in out.lines() {
+        if line.starts_with('\t') {
+            // Line content ends the entry
+            if let Some(entry) = current.take() {
+                lines.push((entry.1, entry.0));
+            }
+            continue;
+        }
+        let mut parts = line.split(' ');
+        let first = parts.next().unwrap_or_default();
+        if first.len() == 40 && first.bytes().all(|b| b.is_ascii_hexdigit()) {
+            let final_line = parts.nth(1).and_then(|n| n.parse().ok()).unwrap_or(0);
+            current = Some((first.to_string(), final_line));
+            commits.entry(first.to_string()).or_default();
+        } else if let Some((hash, _)) = &current
+            && let Some(info) = commits.get_mut(hash)
+        {
+            let value = line.split_once(' ').map_or("", |(_, v)| v);
+            match first {
+                "author" => info.author = value.to_string(),
+                "author-time" => info.time = value.parse().unwrap_or(0),
+                "summary" => info.summary = value.to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    let mut ranges: Vec<BlameRange> = Vec::new();
+    for (line_no, hash) in lines {
+        if let Some(last) = ranges.last_mut()
+            && last.end_line + 1 == line_no
+            && last.commit == hash
+        {
+            last.end_line = line_no;
+            continue;
+        }
+        let info = commits.get(&hash).cloned().unwrap_or_default();
+        ranges.push(BlameRange {
+            start_line: line_no,
+            end_line: line_no,
+            commit: hash,
+            author: info.author,
+            date: chrono::DateTime::from_timestamp(info.time, 0)
+                .map(|d| d.to_rfc3339())
+                .unwrap_or_default(),
+            summary: info.summary,
+        });
+    }
+    for range in &mut ranges {
+        range.commit.truncate(12);
+    }
+    ranges
+}
+
+/// Parse `git branch --format=%(HEAD)\t%(refname:short)\t%(upstream:short)\t%(objectname:short)`
+fn parse_branches(out: &str) -> Vec<Value> {
+    out.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let head = fields.next()?;
+            let name = fields.next()?;
+            let upstream = fields.next().filter(|u| !u.is_empty());
+            let commit = fields.next().unwrap_or_default();
+            Some(json!({
+                "name": name,
+                "current": head == "*",
+                "upstream": upstream,
+                "commit": commit,
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_parse_status() {
+        let out = "## main...origin/main [ahead 2, behind 1]\0M  src/lib.rs\0 M README.md\0\
+                   R  new.rs\0old.rs\0?? notes.txt\0";
+        let status = parse_status(out);
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.upstream.as_deref(), Some("origin/main"));
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert!(!status.clean);
+        assert_eq!(
+            status.entries[0],
+            StatusEntry {
+                path: "src/lib.rs".to_string(),
+                orig_path: None,
+                staged: Some("modified"),
+                unstaged: None,
+            }
+        );
+        assert_eq!(status.entries[1].unstaged, Some("modified"));
+        assert_eq!(status.entries[2].orig_path.as_deref(), Some("old.rs"));
+        assert_eq!(status.entries[3].staged, None);
+        assert_eq!(status.entries[3].unstaged, Some("untracked"));
+
+        let fresh = parse_status("## No commits yet on main\0");
+        assert_eq!(fresh.branch.as_deref(), Some("main"));
+        assert!(fresh.clean);
+    }
+
+    #[test]
+    fn test_parse_diff() {
+        let out = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@ mod a;
+ fn a() {}
+-fn b() {}
++fn b() { todo!() }
++fn c() {}
+ fn d() {}
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1 @@
++hello
+diff --git a/logo.png b/logo.png
+index 4444444..5555555 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        let (files, truncated) = parse_diff(out);
+        assert!(!truncated);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!((files[0].additions, files[0].deletions), (2, 1));
+        assert_eq!(files[0].hunks[0].old_start, 1);
+        assert_eq!(files[0].hunks[0].new_lines, 4);
+        assert_eq!(files[0].hunks[0].lines[1], "-fn b() {}");
+        assert_eq!(files[1].status, "added");
+        assert_eq!(files[1].hunks[0].new_lines, 1);
+        assert!(files[2].binary);
+        assert!(files[2].hunks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_blame_merges_consecutive_lines() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let out = format!(
+            "{a} 1 1 2\nauthor Ada\nauthor-time 1700000000\nsummary First\nfilename f\n\tline 1\n\
+             {a} 2 2\n\tline 2\n\
+             {b} 3 3 1\nauthor Bo\nauthor-time 1700000100\nsummary Second\nfilename f\n\tline 3\n"
+        );
+        let ranges = parse_blame(&out);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!((ranges[0].start_line, ranges[0].end_line), (1, 2));
+        assert_eq!(ranges[0].author, "Ada");
+        assert_eq!(ranges[0].commit, "a".repeat(12));
+        assert!(ranges[0].date.starts_with("2023-11-14"));
+        assert_eq!(ranges[1].summary, "Second");
+    }
+
+    #[test]
+    fn test_write_operations_need_approval() {
+        let tool = GitTool;
+        assert!(!tool.requires_approval_for(&json!({"operation": "status"})));
+        assert!(!tool.requires_approval_for(&json!({"operation": "branch"})));
+        assert!(!tool.requires_approval_for(&json!({"operation": "stash", "action": "list"})));
+        assert!(tool.requires_approval_for(&json!({"operation": "stash"})));
+        assert!(tool.requires_approval_for(&json!({"operation": "commit", "message": "x"})));
+        assert!(tool.requires_approval_for(&json!({"operation": "bogus"})));
+        assert!(
+            tool.validate_input(&json!({"operation": "stage", "paths": []}))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_option_like_revisions_are_rejected() {
+        let tool = GitTool;
+        for input in [
+            json!({"operation": "diff", "base": "--output=/tmp/x"}),
+            json!({"operation": "log", "rev": "-p"}),
+            json!({"operation": "branch", "action": "switch", "name": "--orphan=x"}),
+        ] {
+            assert!(tool.validate_input(&input).is_err(), "{}", input);
+        }
+        assert!(
+            tool.validate_input(&json!({"operation": "diff", "base": "main~2"}))
+                .is_ok()
+        );
+        assert!(
+            tool.validate_input(&json!({"operation": "log", "rev": "v1.0..HEAD"}))
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commit_flow_in_repo() {
+        let dir = TempDir::new().unwrap();
+        let sh = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap()
+        };
+        sh(&["init", "-q", "-b", "main"]);
+        sh(&["config", "user.name", "Test"]);
+        sh(&["config", "user.email", "test@example.com"]);
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+
+        let tool = GitTool;
+        let ctx = ToolExecutionContext::new(Uuid::new_v4())
+            .with_working_directory(dir.path().to_path_buf());
+        let call = |input: Value| {
+            let ctx = ctx.clone();
+            async move {
+                let result = GitTool.execute(input, &ctx).await.unwrap();
+                assert!(result.success, "{:?}", result.error);
+                serde_json::from_str::<Value>(&result.output).unwrap()
+            }
+        };
+
+        let staged = call(json!({"operation": "stage", "paths": ["a.txt"]})).await;
+        assert_eq!(staged["entries"][0]["staged"], "added");
+
+        let commit = call(json!({"operation": "commit", "message": "Add a"})).await;
+        assert_eq!(commit["subject"], "Add a");
+
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let diff = call(json!({"operation": "diff"})).await;
+        assert_eq!(diff["files"][0]["additions"], 1);
+
+        let log = call(json!({"operation": "log"})).await;
+        assert_eq!(log["commits"][0]["author"], "Test");
+
+        let read_only = ctx.clone().with_read_only_mode(true);
+        let refused = tool
+            .execute(
+                json!({"operation": "commit", "message": "x", "all": true}),
+                &read_only,
+            )
+            .await
+            .unwrap();
+        assert!(!refused.success);
+    }
+}
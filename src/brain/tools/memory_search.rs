@@ -20,7 +20,8 @@ impl Tool for MemorySearchTool {
     fn description(&self) -> &str {
         "Search past conversation memory logs for relevant context. \
          Use this when you need to recall decisions, files, errors, or context \
-         from previous sessions. Returns matching excerpts from daily memory logs."
+         from previous sessions. Returns matching excerpts from daily memory logs, \
+         brain files and entries saved with memory_write."
     }
 
     fn input_schema(&self) -> Value {
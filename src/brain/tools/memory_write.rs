@@ -0,0 +1,339 @@
+//! Memory Write Tool
+//!
+//! Lets the agent curate long-term memory: typed entries (fact, preference,
+//! decision, project-note) with tags and an optional expiry. Entries are stored
+//! in `~/.opencrabs/memory/entries.json`, indexed into the qmd "entries"
+//! collection and returned by memory_search. Near-duplicates are rejected
+//! unless `force` is set.
+
+use super::error::{Result, ToolError};
+use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use crate::memory::{AddOutcome, EntryKind, EntryUpdate, MemoryEntry, NewEntry};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Long-term memory curation tool.
+pub struct MemoryWriteTool;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "operation")]
+enum MemoryWriteOperation {
+    #[serde(rename = "add")]
+    Add {
+        kind: String,
+        content: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        expires: Option<String>,
+        #[serde(default)]
+        force: bool,
+    },
+
+    #[serde(rename = "update")]
+    Update {
+        id: String,
+        kind: Option<String>,
+        content: Option<String>,
+        tags: Option<Vec<String>>,
+        expires: Option<String>,
+    },
+
+    #[serde(rename = "forget")]
+    Forget { id: String },
+
+    #[serde(rename = "list")]
+    List {
+        kind: Option<String>,
+        tag: Option<String>,
+    },
+}
+
+impl MemoryWriteOperation {
+    /// Whether the operation changes the stored entries
+    fn is_write(&self) -> bool {
+        !matches!(self, MemoryWriteOperation::List { .. })
+    }
+}
+
+fn parse_kind(kind: &str) -> Result<EntryKind> {
+    EntryKind::parse(kind).ok_or_else(|| {
+        ToolError::InvalidInput(format!(
+            "Unknown kind '{kind}' (expected fact, preference, decision or project-note)"
+        ))
+    })
+}
+
+fn parse_expires(expires: &str) -> Result<Option<chrono::DateTime<Utc>>> {
+    crate::memory::parse_expiry(expires, Utc::now()).map_err(ToolError::InvalidInput)
+}
+
+fn format_entry(entry: &MemoryEntry) -> String {
+    let mut line = format!("[{}] {}: {}", entry.id, entry.kind.as_str(), entry.content);
+    if !entry.tags.is_empty() {
+        line.push_str(&format!(" (tags: {})", entry.tags.join(", ")));
+    }
+    if let Some(expires_at) = entry.expires_at {
+        line.push_str(&format!(
+            " (expires {})",
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+    }
+    line
+}
+
+#[async_trait]
+impl Tool for MemoryWriteTool {
+    fn name(&self) -> &str {
+        "memory_write"
+    }
+
+    fn description(&self) -> &str {
+        "Save, update or forget long-term memories that persist across sessions. \
+         Use this for durable facts about the user or project, stated preferences, \
+         and decisions worth remembering — not for transient task state. Saved entries \
+         are returned by memory_search. Adding an entry that nearly duplicates an \
+         existing one is rejected with the existing entry's id; update it instead."
+    }
+
+    fn input_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "description": "Operation to perform",
+                    "enum": ["add", "update", "forget", "list"]
+                },
+                "kind": {
+                    "type": "string",
+                    "description": "Entry type (required for add; filter for list)",
+                    "enum": ["fact", "preference", "decision", "project-note"]
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The memory itself, as a self-contained statement (for add, update)"
+                },
+                "tags": {
+                    "type": "array",
+                    "description": "Short labels such as a project or topic name (for add, update)",
+                    "items": {
+                        "type": "string"
+                    }
+                },
+                "expires": {
+                    "type": "string",
+                    "description": "When the entry stops being relevant: '12h', '30d', '2w', a date (YYYY-MM-DD), or 'never' (for add, update)"
+                },
+                "force": {
+                    "type": "boolean",
+                    "description": "Add even if a near-duplicate exists (for add)",
+                    "default": false
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Entry id (for update, forget)"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Filter by tag (for list)"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        vec![ToolCapability::ReadFiles, ToolCapability::WriteFiles]
+    }
+
+    fn requires_approval(&self) -> bool {
+        false // Only touches the agent's own memory store
+    }
+
+    fn validate_input(&self, input: &Value) -> Result<()> {
+        let _: MemoryWriteOperation = serde_json::from_value(input.clone())
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid input: {}", e)))?;
+        Ok(())
+    }
+
+    async fn execute(&self, input: Value, context: &ToolExecutionContext) -> Result<ToolResult> {
+        let operation: MemoryWriteOperation = serde_json::from_value(input)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid input: {}", e)))?;
+
+        if context.read_only_mode && operation.is_write() {
+            return Ok(ToolResult::error(
+                "Changing memory is not allowed in Plan mode. Listing entries is available; \
+                 approve the plan (Ctrl+A) to save, update or forget memories."
+                    .to_string(),
+            ));
+        }
+
+        let store = match crate::memory::get_store() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Memory store init failed: {}", e);
+                return Ok(ToolResult::error(format!("Memory unavailable: {e}")));
+            }
+        };
+        let session = Some(context.session_id.to_string());
+
+        let output = match operation {
+            MemoryWriteOperation::Add {
+                kind,
+                content,
+                tags,
+                expires,
+                force,
+            } => {
+                if content.trim().is_empty() {
+                    return Ok(ToolResult::error(
+                        "content parameter is required".to_string(),
+                    ));
+                }
+                let new = NewEntry {
+                    kind: parse_kind(&kind)?,
+                    content,
+                    tags,
+                    source_session: session,
+                    expires_at: expires.as_deref().map(parse_expires).transpose()?.flatten(),
+                };
+                match crate::memory::add_entry(store, new, force)
+                    .await
+                    .map_err(ToolError::Execution)?
+                {
+                    AddOutcome::Added(entry) => format!("Saved {}", format_entry(&entry)),
+                    AddOutcome::Duplicate {
+                        existing,
+                        similarity,
+                    } => {
+                        return Ok(ToolResult::error(format!(
+                            "Not saved: {:.0}% similar to an existing entry:\n{}\n\
+                             Use operation \"update\" with id \"{}\" to change it, \
+                             or add with \"force\": true if it is genuinely different.",
+                            similarity * 100.0,
+                            format_entry(&existing),
+                            existing.id
+                        )));
+                    }
+                }
+            }
+
+            MemoryWriteOperation::Update {
+                id,
+                kind,
+                content,
+                tags,
+                expires,
+            } => {
+                let update = EntryUpdate {
+                    kind: kind.as_deref().map(parse_kind).transpose()?,
+                    content: content.filter(|c| !c.trim().is_empty()),
+                    tags,
+                    source_session: session,
+                    expires_at: expires.as_deref().map(parse_expires).transpose()?,
+                };
+                let entry = crate::memory::update_entry(store, &id, update)
+                    .await
+                    .map_err(ToolError::Execution)?;
+                format!("Updated {}", format_entry(&entry))
+            }
+
+            MemoryWriteOperation::Forget { id } => {
+                let entry = crate::memory::forget_entry(store, &id)
+                    .await
+                    .map_err(ToolError::Execution)?;
+                format!("Forgot {}", format_entry(&entry))
+            }
+
+            MemoryWriteOperation::List { kind, tag } => {
+                let kind = kind.as_deref().map(parse_kind).transpose()?;
+                let entries = crate::memory::list_entries(kind, tag)
+                    .await
+                    .map_err(ToolError::Execution)?;
+                if entries.is_empty() {
+                    return Ok(ToolResult::success("No memory entries found.".to_string()));
+                }
+                let mut output = format!("Found {} memory entries:\n\n", entries.len());
+                for entry in &entries {
+                    output.push_str(&format_entry(entry));
+                    output.push('\n');
+                }
+                output
+            }
+        };
+
+        Ok(ToolResult::success(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_metadata() {
+        let tool = MemoryWriteTool;
+        assert_eq!(tool.name(), "memory_write");
+        assert!(!tool.requires_approval());
+    }
+
+    #[test]
+    fn test_validate_input() {
+        let tool = MemoryWriteTool;
+        assert!(
+            tool.validate_input(&serde_json::json!({
+                "operation": "add",
+                "kind": "preference",
+                "content": "Prefers tabs over spaces",
+                "tags": ["style"],
+                "expires": "30d"
+            }))
+            .is_ok()
+        );
+        assert!(
+            tool.validate_input(&serde_json::json!({"operation": "forget", "id": "a1b2c3d4"}))
+                .is_ok()
+        );
+        // add without content, update without id, unknown operation
+        assert!(
+            tool.validate_input(&serde_json::json!({"operation": "add", "kind": "fact"}))
+                .is_err()
+        );
+        assert!(
+            tool.validate_input(&serde_json::json!({"operation": "update", "content": "x"}))
+                .is_err()
+        );
+        assert!(
+            tool.validate_input(&serde_json::json!({"operation": "remember"}))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_mode_refuses_writes() {
+        let context = ToolExecutionContext::new(uuid::Uuid::new_v4()).with_read_only_mode(true);
+        for input in [
+            serde_json::json!({"operation": "add", "kind": "fact", "content": "x"}),
+            serde_json::json!({"operation": "update", "id": "a1b2c3d4", "content": "x"}),
+            serde_json::json!({"operation": "forget", "id": "a1b2c3d4"}),
+        ] {
+            let result = MemoryWriteTool
+                .execute(input, &context)
+                .await
+                .expect("refusal is a tool result");
+            assert!(!result.success);
+            assert!(result.error.is_some_and(|e| e.contains("Plan mode")));
+        }
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        assert!(matches!(
+            parse_kind("project_note"),
+            Ok(EntryKind::ProjectNote)
+        ));
+        assert!(parse_kind("opinion").is_err());
+    }
+}
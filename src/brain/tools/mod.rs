@@ -4,12 +4,16 @@
 //! including file operations, shell commands, and more.
 
 pub mod error;
+pub mod patch;
+pub mod policy;
 pub mod registry;
+pub mod sandbox;
 mod r#trait;
 
 // Tool implementations - Phase 1: Essential File Operations
 pub mod bash;
 pub mod edit;
+pub mod git;
 pub mod glob;
 pub mod grep;
 pub mod ls;
@@ -19,6 +23,7 @@ pub mod write;
 // Tool implementations - Phase 2: Advanced Features
 pub mod brave_search;
 pub mod code_exec;
+pub mod code_search;
 pub mod doc_parser;
 pub mod exa_search;
 pub mod notebook;
@@ -27,8 +32,10 @@ pub mod web_search;
 // Tool implementations - Phase 3: Workflow & Integration
 pub mod config_tool;
 pub mod context;
+pub mod delegate;
 pub mod http;
 pub mod memory_search;
+pub mod memory_write;
 pub mod plan_tool;
 pub mod rebuild;
 pub mod session_search;
@@ -68,4 +75,5 @@ pub use web3_auto_repair::Web3AutoRepairTool;
 // Re-exports
 pub use error::{Result, ToolError};
 pub use r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
+pub use policy::{PolicyAction, ToolPolicy};
 pub use registry::ToolRegistry;
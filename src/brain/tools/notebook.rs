@@ -312,6 +312,7 @@ impl Tool for NotebookEditTool {
         let new_content = serde_json::to_string_pretty(&notebook)
             .map_err(|e| ToolError::Execution(format!("Failed to serialize notebook: {}", e)))?;
 
+        context.checkpoint_file(&path).await;
         fs::write(&path, new_content).await.map_err(ToolError::Io)?;
 
         Ok(ToolResult::success(format!(
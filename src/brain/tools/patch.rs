@@ -0,0 +1,539 @@
+//! Unified Diff Patches
+//!
+//! Parses standard unified diffs (single- or multi-file, `git diff` style or
+//! plain `diff -u`) and applies their hunks with fuzzy context matching:
+//! a hunk may land away from its stated line number, trailing whitespace is
+//! ignored when exact context doesn't match, and up to `MAX_FUZZ` outer
+//! context lines may be dropped, much like GNU `patch`.
+//!
+//! Hunk headers without line numbers (`@@ @@`) are accepted too; such
+//! hunks are located purely by their context.
+
+use std::fmt;
+
+/// Outer context lines that may be ignored when a hunk doesn't match
+const MAX_FUZZ: usize = 2;
+
+/// One line of a hunk body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// A single `@@` hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based start line in the original file, if the header had one
+    pub old_start: Option<usize>,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Lines the hunk expects to find (context + removed)
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Number of added and removed lines
+    pub fn stats(&self) -> (usize, usize) {
+        self.lines.iter().fold((0, 0), |(add, del), l| match l {
+            HunkLine::Add(_) => (add + 1, del),
+            HunkLine::Remove(_) => (add, del + 1),
+            HunkLine::Context(_) => (add, del),
+        })
+    }
+}
+
+/// All hunks for one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path before the change, `None` for a new file (`/dev/null`)
+    pub old_path: Option<String>,
+    /// Path after the change, `None` for a deleted file (`/dev/null`)
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// Path to show for this file
+    pub fn display_path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or("?")
+    }
+
+    /// Number of added and removed lines across all hunks
+    pub fn stats(&self) -> (usize, usize) {
+        self.hunks.iter().fold((0, 0), |(add, del), h| {
+            let (a, d) = h.stats();
+            (add + a, del + d)
+        })
+    }
+}
+
+/// Why a patch could not be parsed or applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    /// 1-based hunk number, if the error belongs to one hunk
+    pub hunk: Option<usize>,
+    pub reason: String,
+}
+
+impl PatchError {
+    fn new(reason: impl Into<String>) -> Self {
+        Self {
+            hunk: None,
+            reason: reason.into(),
+        }
+    }
+
+    fn hunk(hunk: usize, reason: impl Into<String>) -> Self {
+        Self {
+            hunk: Some(hunk),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.hunk {
+            Some(n) => write!(f, "hunk #{}: {}", n, self.reason),
+            None => f.write_str(&self.reason),
+        }
+    }
+}
+
+/// Parse a unified diff into per-file patches.
+///
+/// A patch without `---`/`+++` headers is accepted as a single file patch
+/// with no paths, for callers that already know the target file.
+pub fn parse(text: &str) -> std::result::Result<Vec<FilePatch>, PatchError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ ")) {
+            files.push(FilePatch {
+                old_path: header_path(&line[4..]),
+                new_path: header_path(&lines[i + 1][4..]),
+                hunks: Vec::new(),
+            });
+            i += 2;
+        } else if line.starts_with("@@") {
+            let old_start = parse_hunk_header(line)?;
+            i += 1;
+            let start = i;
+            while i < lines.len() && !is_section_start(&lines, i) {
+                i += 1;
+            }
+            let hunk = parse_hunk_body(old_start, &lines[start..i])?;
+            if files.is_empty() {
+                files.push(FilePatch {
+                    old_path: None,
+                    new_path: None,
+                    hunks: Vec::new(),
+                });
+            }
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(hunk);
+            }
+        } else {
+            // `diff --git`, `index`, mode lines and free text between files
+            i += 1;
+        }
+    }
+
+    if files.is_empty() {
+        return Err(PatchError::new(
+            "no hunks found (expected unified diff with @@ headers)",
+        ));
+    }
+    if let Some(empty) = files.iter().find(|f| f.hunks.is_empty()) {
+        return Err(PatchError::new(format!(
+            "no hunks for {}",
+            empty.display_path()
+        )));
+    }
+    Ok(files)
+}
+
+/// `a/src/lib.rs\t2024-01-01 ...` → `src/lib.rs`, `/dev/null` → `None`
+fn header_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or(raw).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+/// Old start line from `@@ -12,7 +12,9 @@ fn x()`; bare `@@ @@` gives `None`
+fn parse_hunk_header(line: &str) -> std::result::Result<Option<usize>, PatchError> {
+    let Some(range) = line.split_whitespace().find(|part| part.starts_with('-')) else {
+        return Ok(None);
+    };
+    let malformed = || PatchError::new(format!("malformed hunk header: {}", line));
+    let mut parts = range[1..].split(',');
+    let start: usize = parts
+        .next()
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| malformed())?;
+    let count: usize = match parts.next() {
+        Some(count) => count.parse().map_err(|_| malformed())?,
+        None => 1,
+    };
+    // An empty old range names the line *after which* the hunk inserts
+    Ok(Some(if count == 0 { start + 1 } else { start }))
+}
+
+/// Whether line `i` starts a new hunk or file section
+fn is_section_start(lines: &[&str], i: usize) -> bool {
+    let line = lines[i];
+    line.starts_with("@@")
+        || line.starts_with("diff ")
+        || (line.starts_with("--- ") && lines.get(i + 1).is_some_and(|l| l.starts_with("+++ ")))
+}
+
+fn parse_hunk_body(
+    old_start: Option<usize>,
+    body: &[&str],
+) -> std::result::Result<Hunk, PatchError> {
+    let mut lines = Vec::new();
+    for raw in body {
+        let line = match raw.chars().next() {
+            Some(' ') => HunkLine::Context(raw[1..].to_string()),
+            Some('-') => HunkLine::Remove(raw[1..].to_string()),
+            Some('+') => HunkLine::Add(raw[1..].to_string()),
+            // "\ No newline at end of file"
+            Some('\\') => continue,
+            // Editors and models often strip the space from empty context lines
+            None => HunkLine::Context(String::new()),
+            Some(_) => {
+                return Err(PatchError::new(format!(
+                    "unexpected line in hunk (must start with ' ', '-' or '+'): {}",
+                    raw
+                )));
+            }
+        };
+        lines.push(line);
+    }
+
+    // Blank lines after the last hunk are usually just the end of the text
+    while matches!(lines.last(), Some(HunkLine::Context(s)) if s.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return Err(PatchError::new("empty hunk"));
+    }
+    Ok(Hunk { old_start, lines })
+}
+
+/// A hunk that only matched after relaxing the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzNote {
+    /// 1-based hunk number
+    pub hunk: usize,
+    /// Lines away from the position in the header
+    pub offset: isize,
+    /// Outer context lines ignored
+    pub fuzz: usize,
+    /// Matched ignoring whitespace differences
+    pub whitespace: bool,
+}
+
+impl fmt::Display for FuzzNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hunk #{} applied", self.hunk)?;
+        if self.offset != 0 {
+            write!(f, " at offset {:+}", self.offset)?;
+        }
+        if self.fuzz > 0 {
+            write!(f, " with fuzz {}", self.fuzz)?;
+        }
+        if self.whitespace {
+            f.write_str(" ignoring whitespace")?;
+        }
+        Ok(())
+    }
+}
+
+/// Apply all hunks to `content`. Either every hunk applies or an error
+/// names the first one that failed; `content` itself is never modified.
+pub fn apply(
+    content: &str,
+    hunks: &[Hunk],
+) -> std::result::Result<(String, Vec<FuzzNote>), PatchError> {
+    let trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut notes = Vec::new();
+    // Shift from earlier hunks, and where the next hunk may start at the earliest
+    let mut delta: isize = 0;
+    let mut min_pos = 0usize;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let number = index + 1;
+        let expected = hunk
+            .old_start
+            .map(|s| (s.saturating_sub(1) as isize + delta).max(0) as usize);
+
+        let found = locate(&lines, &hunk.lines, expected, min_pos).ok_or_else(|| {
+            PatchError::hunk(number, describe_mismatch(&hunk.old_lines(), hunk.old_start))
+        })?;
+
+        // Context lines keep the file's text, so whitespace-relaxed matches
+        // don't rewrite indentation the hunk got wrong
+        let mut file_index = found.start;
+        let mut replacement = Vec::new();
+        for line in found.core {
+            match line {
+                HunkLine::Context(_) => {
+                    replacement.push(lines[file_index].clone());
+                    file_index += 1;
+                }
+                HunkLine::Remove(_) => file_index += 1,
+                HunkLine::Add(text) => replacement.push(text.clone()),
+            }
+        }
+        let replace_len = file_index - found.start;
+
+        let offset = expected.map_or(0, |e| found.start as isize - (e + found.skipped) as isize);
+        if offset != 0 || found.fuzz > 0 || found.whitespace {
+            notes.push(FuzzNote {
+                hunk: number,
+                offset,
+                fuzz: found.fuzz,
+                whitespace: found.whitespace,
+            });
+        }
+
+        min_pos = found.start + replacement.len();
+        delta += offset + replacement.len() as isize - replace_len as isize;
+        lines.splice(found.start..found.start + replace_len, replacement);
+    }
+
+    let mut result = lines.join("\n");
+    if trailing_newline && !lines.is_empty() {
+        result.push('\n');
+    }
+    Ok((result, notes))
+}
+
+/// Where (and how loosely) a hunk matched
+struct Located<'a> {
+    /// File line where `core` starts
+    start: usize,
+    /// Hunk lines that apply, without the context dropped by fuzz
+    core: &'a [HunkLine],
+    /// Leading context lines dropped
+    skipped: usize,
+    fuzz: usize,
+    whitespace: bool,
+}
+
+fn locate<'a>(
+    lines: &[String],
+    hunk: &'a [HunkLine],
+    expected: Option<usize>,
+    min_pos: usize,
+) -> Option<Located<'a>> {
+    // Pure insertion: trust the header, or append
+    if hunk.iter().all(|l| matches!(l, HunkLine::Add(_))) {
+        return Some(Located {
+            start: expected.unwrap_or(lines.len()).clamp(min_pos, lines.len()),
+            core: hunk,
+            skipped: 0,
+            fuzz: 0,
+            whitespace: false,
+        });
+    }
+
+    let leading_total = leading_context(hunk);
+    let trailing_total = trailing_context(hunk);
+    let max_fuzz = MAX_FUZZ.min(leading_total.max(trailing_total));
+    for fuzz in 0..=max_fuzz {
+        let leading = leading_total.min(fuzz);
+        let trailing = trailing_total.min(fuzz);
+        let core = &hunk[leading..hunk.len() - trailing];
+        let needle: Vec<&str> = core
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+        if needle.is_empty() {
+            break;
+        }
+        for whitespace in [false, true] {
+            let target = expected.map(|e| e + leading);
+            if let Some(start) = search(lines, &needle, target, min_pos, whitespace) {
+                return Some(Located {
+                    start,
+                    core,
+                    skipped: leading,
+                    fuzz,
+                    whitespace,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Nearest position to `expected` (at or after `min_pos`) where `needle` matches
+fn search(
+    lines: &[String],
+    needle: &[&str],
+    expected: Option<usize>,
+    min_pos: usize,
+    relaxed: bool,
+) -> Option<usize> {
+    let last = lines.len().checked_sub(needle.len())?;
+    if min_pos > last {
+        return None;
+    }
+    let expected = expected.unwrap_or(min_pos).clamp(min_pos, last);
+    let matches = |pos: usize| {
+        needle.iter().enumerate().all(|(i, want)| {
+            let have = lines[pos + i].as_str();
+            if relaxed {
+                have.split_whitespace().eq(want.split_whitespace())
+            } else {
+                have == *want
+            }
+        })
+    };
+
+    // Spiral outwards from the expected position
+    for distance in 0..=last - min_pos {
+        if expected + distance <= last && matches(expected + distance) {
+            return Some(expected + distance);
+        }
+        if distance > 0
+            && let Some(pos) = expected.checked_sub(distance)
+            && pos >= min_pos
+            && matches(pos)
+        {
+            return Some(pos);
+        }
+    }
+    None
+}
+
+fn leading_context(hunk: &[HunkLine]) -> usize {
+    hunk.iter()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count()
+}
+
+fn trailing_context(hunk: &[HunkLine]) -> usize {
+    hunk.iter()
+        .rev()
+        .take_while(|l| matches!(l, HunkLine::Context(_)))
+        .count()
+}
+
+fn describe_mismatch(old: &[&str], old_start: Option<usize>) -> String {
+    let first = old.first().map(|s| s.trim()).unwrap_or_default();
+    match old_start {
+        Some(line) => format!(
+            "context not found near line {} (expected {} line(s) starting with {:?})",
+            line,
+            old.len(),
+            first
+        ),
+        None => format!(
+            "context not found (expected {} line(s) starting with {:?})",
+            old.len(),
+            first
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINAL: &str =
+        "fn main() {\n    let a = 1;\n    let b = 2;\n    println!(\"{}\", a + b);\n}\n";
+
+    #[test]
+    fn test_parse_multi_file_git_diff() {
+        let patch = "diff --git a/src/a.rs b/src/a.rs\nindex 123..456 100644\n--- a/src/a.rs\n+++ b/src/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n ctx\n@@ -10 +10 @@\n-x\n+y\ndiff --git a/b.txt b/b.txt\nnew file mode 100644\n--- /dev/null\n+++ b/b.txt\n@@ -0,0 +1 @@\n+hello\n";
+        let files = parse(patch).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].old_path.as_deref(), Some("src/a.rs"));
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[0].hunks[1].old_start, Some(10));
+        assert_eq!(files[0].stats(), (2, 2));
+        assert_eq!(files[1].old_path, None);
+        assert_eq!(files[1].display_path(), "b.txt");
+    }
+
+    #[test]
+    fn test_apply_at_offset_and_new_file() {
+        // Header says line 1, but two lines were added above since
+        let content = format!("// header\n// more\n{}", ORIGINAL);
+        let files = parse(
+            "--- a/main.rs\n+++ b/main.rs\n@@ -2,3 +2,3 @@\n     let a = 1;\n-    let b = 2;\n+    let b = 3;\n     println!(\"{}\", a + b);\n",
+        )
+        .unwrap();
+        let (patched, notes) = apply(&content, &files[0].hunks).unwrap();
+        assert!(patched.contains("let b = 3;"));
+        assert!(patched.ends_with("}\n"));
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].offset, 2);
+
+        let created = parse("--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n").unwrap();
+        assert_eq!(apply("", &created[0].hunks).unwrap().0, "one\ntwo\n");
+    }
+
+    #[test]
+    fn test_apply_with_whitespace_and_fuzz() {
+        // Context line has different indentation, and the leading context is stale
+        let files =
+            parse("@@ @@\n fn main() { // stale\n let a = 1;\n-    let b = 2;\n+    let b = 4;\n")
+                .unwrap();
+        let (patched, notes) = apply(ORIGINAL, &files[0].hunks).unwrap();
+        assert!(patched.contains("    let b = 4;"));
+        assert!(patched.starts_with("fn main() {\n"));
+        assert_eq!(notes[0].fuzz, 1);
+        assert!(notes[0].whitespace);
+    }
+
+    #[test]
+    fn test_failed_hunk_is_reported_and_nothing_applies() {
+        let files = parse(
+            "@@ -2 +2 @@\n-    let a = 1;\n+    let a = 10;\n@@ -9 +9 @@\n-    missing();\n+    found();\n",
+        )
+        .unwrap();
+        let err = apply(ORIGINAL, &files[0].hunks).unwrap_err();
+        assert_eq!(err.hunk, Some(2));
+        assert!(
+            err.to_string()
+                .starts_with("hunk #2: context not found near line 9")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(parse("just some text").is_err());
+        assert!(parse("@@ -1 +1 @@\n*bad\n").is_err());
+    }
+}
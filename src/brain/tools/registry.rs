@@ -3,6 +3,7 @@
 //! Manages the collection of available tools that can be invoked by agents.
 
 use super::error::{Result, ToolError};
+use super::policy::{PolicyAction, PolicyDecision, ToolPolicy};
 use super::r#trait::{Tool, ToolExecutionContext, ToolResult};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -11,6 +12,7 @@ use std::sync::Arc;
 /// Registry of available tools
 pub struct ToolRegistry {
     tools: HashMap<String, Arc<dyn Tool>>,
+    policy: Arc<ToolPolicy>,
 }
 
 impl ToolRegistry {
@@ -18,9 +20,31 @@ impl ToolRegistry {
     pub fn new() -> Self {
         Self {
             tools: HashMap::new(),
+            policy: Arc::new(ToolPolicy::default()),
         }
     }
 
+    /// Set the permission policy enforced by `execute`
+    pub fn set_policy(&mut self, policy: Arc<ToolPolicy>) {
+        self.policy = policy;
+    }
+
+    /// Evaluate the permission policy for a call, without executing it
+    pub fn check_policy(
+        &self,
+        name: &str,
+        input: &Value,
+        context: &ToolExecutionContext,
+    ) -> Option<PolicyDecision> {
+        let tool = self.tools.get(name)?;
+        Some(self.policy.evaluate(
+            name,
+            tool.requires_approval_for(input),
+            input,
+            &context.working_directory,
+        ))
+    }
+
     /// Register a tool
     pub fn register(&mut self, tool: Arc<dyn Tool>) {
         let name = tool.name().to_string();
@@ -43,6 +67,19 @@ impl ToolRegistry {
         self.tools.keys().cloned().collect()
     }
 
+    /// Build a new registry holding only the tools that match `keep`
+    pub fn filtered(&self, keep: impl Fn(&dyn Tool) -> bool) -> Self {
+        Self {
+            tools: self
+                .tools
+                .iter()
+                .filter(|(_, tool)| keep(tool.as_ref()))
+                .map(|(name, tool)| (name.clone(), tool.clone()))
+                .collect(),
+            policy: self.policy.clone(),
+        }
+    }
+
     /// Get tool definitions in LLM format
     pub fn get_tool_definitions(&self) -> Vec<crate::brain::provider::Tool> {
         self.tools
@@ -69,12 +106,34 @@ impl ToolRegistry {
         // Validate input
         tool.validate_input(&input)?;
 
-        // Check if approval is required
-        if tool.requires_approval() && !context.auto_approve {
-            return Err(ToolError::ApprovalRequired(format!(
-                "Tool '{}' requires approval before execution",
-                name
-            )));
+        // Enforce the permission policy; `ask` passes once approved
+        let decision = self.policy.evaluate(
+            name,
+            tool.requires_approval_for(&input),
+            &input,
+            &context.working_directory,
+        );
+        tracing::info!(
+            "[POLICY] tool='{}' decision={} approved={}",
+            name,
+            decision,
+            context.auto_approve
+        );
+        match decision.action {
+            PolicyAction::Allow => {}
+            PolicyAction::Ask if context.auto_approve => {}
+            PolicyAction::Ask => {
+                return Err(ToolError::ApprovalRequired(format!(
+                    "Tool '{}' requires approval before execution",
+                    name
+                )));
+            }
+            PolicyAction::Deny => {
+                return Err(ToolError::PermissionDenied(format!(
+                    "Tool '{}' is denied by policy ({})",
+                    name, decision
+                )));
+            }
         }
 
         // Execute the tool
@@ -198,6 +257,23 @@ mod tests {
         assert!(tools.contains(&"tool2".to_string()));
     }
 
+    #[test]
+    fn test_filtered_registry() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(MockTool {
+            name: "safe".to_string(),
+            requires_approval: false,
+        }));
+        registry.register(Arc::new(MockTool {
+            name: "dangerous".to_string(),
+            requires_approval: true,
+        }));
+
+        let subset = registry.filtered(|tool| !tool.requires_approval());
+        assert_eq!(subset.list_tools(), vec!["safe".to_string()]);
+        assert_eq!(registry.count(), 2);
+    }
+
     #[tokio::test]
     async fn test_execute_tool() {
         let mut registry = ToolRegistry::new();
@@ -274,4 +350,39 @@ mod tests {
             .unwrap();
         assert!(result.success);
     }
+
+    #[tokio::test]
+    async fn test_policy_deny_overrides_auto_approve() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(MockTool {
+            name: "safe_tool".to_string(),
+            requires_approval: false,
+        }));
+        registry.set_policy(Arc::new(
+            ToolPolicy::from_toml(
+                "[[rules]]\naction = \"deny\"\ntool = \"safe_*\"\ncommand = \"*\"",
+            )
+            .unwrap(),
+        ));
+
+        let context = ToolExecutionContext::new(Uuid::new_v4()).with_auto_approve(true);
+        let denied = registry
+            .execute("safe_tool", serde_json::json!({ "command": "x" }), &context)
+            .await;
+        assert!(matches!(denied, Err(ToolError::PermissionDenied(_))));
+
+        // Rule needs a command argument, so other calls keep the tool default
+        let allowed = registry
+            .execute("safe_tool", serde_json::json!({ "message": "hi" }), &context)
+            .await;
+        assert!(allowed.unwrap().success);
+
+        // Sub-registries keep the policy
+        let sub = registry.filtered(|_| true);
+        assert_eq!(
+            sub.check_policy("safe_tool", &serde_json::json!({ "command": "x" }), &context)
+                .map(|d| d.action),
+            Some(PolicyAction::Deny)
+        );
+    }
 }
@@ -0,0 +1,358 @@
+//! OS-level Sandbox
+//!
+//! Optional confinement for tools that run arbitrary code (`bash`,
+//! `execute_code`), configured per tool under `[sandbox.<tool>]`. On Linux
+//! the child enters fresh user and mount namespaces (plus a network
+//! namespace when networking is off) and a landlock ruleset that keeps the
+//! filesystem readable but only lets it write to the working directory, the
+//! temp dir, `/dev` and any configured extra paths.
+//!
+//! Enabling a sandbox where it can't be enforced is an error, never a silent
+//! no-op, so auto-approved commands don't run unconfined by accident.
+
+use super::error::{Result, ToolError};
+use super::policy::normalize;
+use crate::config::ToolSandboxConfig;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// What a sandboxed command tried to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxViolationKind {
+    /// Write outside the writable paths
+    Filesystem,
+    /// Network access with networking disabled
+    Network,
+}
+
+impl std::fmt::Display for SandboxViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SandboxViolationKind::Filesystem => "filesystem",
+            SandboxViolationKind::Network => "network",
+        })
+    }
+}
+
+/// stderr fragments for the errors a blocked write produces: landlock
+/// reports EACCES, a read-only mount EROFS
+const FILESYSTEM_DENIALS: &[&str] = &["Permission denied", "Read-only file system"];
+
+/// stderr fragments that mean the network namespace blocked a connection
+const NETWORK_DENIALS: &[&str] = &[
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+    "Could not resolve host",
+    "Name or service not known",
+    "nodename nor servname provided",
+];
+
+/// Confinement applied to a tool's child processes
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    network: bool,
+    writable_paths: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// Build the sandbox for a tool, `None` when it is disabled
+    pub fn from_config(config: &ToolSandboxConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            network: config.network,
+            writable_paths: config
+                .writable_paths
+                .iter()
+                .map(|p| match p.strip_prefix("~/") {
+                    Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                    None => PathBuf::from(p),
+                })
+                .collect(),
+        })
+    }
+
+    /// Confine `cmd` so it may only write below `working_dir` (and the
+    /// other writable paths)
+    pub fn apply(&self, cmd: &mut Command, working_dir: &Path) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            linux::apply(self, cmd, working_dir)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (cmd, working_dir);
+            Err(ToolError::SandboxUnavailable(
+                "OS sandboxing is only supported on Linux".to_string(),
+            ))
+        }
+    }
+
+    /// Turn the stderr of a failed sandboxed run into a violation, if the
+    /// failure looks like the sandbox blocked it. A filesystem denial only
+    /// counts when it names a path outside the writable roots; denials inside
+    /// them are ordinary permission errors. `working_dir` is the one given to
+    /// [`apply`](Self::apply), `cwd` the directory the command ran in.
+    pub fn violation(&self, stderr: &str, working_dir: &Path, cwd: &Path) -> Option<ToolError> {
+        let find = |matches: &dyn Fn(&str) -> bool| {
+            stderr
+                .lines()
+                .find(|line| matches(line))
+                .map(|line| line.trim().to_string())
+        };
+
+        if !self.network
+            && let Some(detail) = find(&|line| NETWORK_DENIALS.iter().any(|m| line.contains(m)))
+        {
+            return Some(ToolError::SandboxViolation {
+                kind: SandboxViolationKind::Network,
+                detail,
+            });
+        }
+
+        let roots: Vec<PathBuf> = self
+            .writable_roots(working_dir)
+            .iter()
+            .map(|root| normalize(root))
+            .collect();
+        let blocked_write = |line: &str| {
+            FILESYSTEM_DENIALS.iter().any(|m| line.contains(m))
+                && mentioned_paths(line, cwd)
+                    .iter()
+                    .any(|path| !roots.iter().any(|root| path.starts_with(root)))
+        };
+        find(&blocked_write).map(|detail| ToolError::SandboxViolation {
+            kind: SandboxViolationKind::Filesystem,
+            detail,
+        })
+    }
+
+    /// Paths the sandboxed process may write to
+    fn writable_roots(&self, working_dir: &Path) -> Vec<PathBuf> {
+        let mut roots = vec![
+            working_dir.to_path_buf(),
+            std::env::temp_dir(),
+            PathBuf::from("/dev"),
+        ];
+        roots.extend(self.writable_paths.iter().cloned());
+        roots
+    }
+}
+
+/// Paths named in an error line, e.g. `touch: cannot touch '/etc/x': ...`
+/// or `PermissionError: [Errno 13] Permission denied: '/etc/x'`. Relative
+/// paths are resolved against the command's `cwd`.
+fn mentioned_paths(line: &str, cwd: &Path) -> Vec<PathBuf> {
+    line.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| matches!(c, '\'' | '"' | '`' | '‘' | '’' | ':' | ','))
+        })
+        .filter(|word| word.starts_with('/') || word.starts_with("./") || word.starts_with("../"))
+        .map(|word| normalize(&cwd.join(word)))
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use landlock::{
+        ABI, Access, AccessFs, CompatLevel, Compatible, PathBeneath, PathFd, Ruleset, RulesetAttr,
+        RulesetCreatedAttr, RulesetStatus,
+    };
+    use std::ffi::CStr;
+    use std::io;
+
+    /// Newest filesystem rights we know of; kernels that predate some of
+    /// them (`Refer`, `Truncate`, device ioctls) enforce the rest
+    const ABI_LATEST: ABI = ABI::V5;
+
+    fn unavailable(e: impl std::fmt::Display) -> ToolError {
+        ToolError::SandboxUnavailable(e.to_string())
+    }
+
+    pub(super) fn apply(sandbox: &Sandbox, cmd: &mut Command, working_dir: &Path) -> Result<()> {
+        // Everything is prepared before fork: the pre_exec hook must not allocate
+        // The first ABI's write rights are the floor: without them the
+        // sandbox would be a no-op, so they stay a hard requirement
+        let abi = ABI_LATEST;
+        let mut ruleset = Ruleset::default()
+            .set_compatibility(CompatLevel::HardRequirement)
+            .handle_access(AccessFs::from_all(ABI::V1))
+            .map(|r| r.set_compatibility(CompatLevel::BestEffort))
+            .and_then(|r| r.handle_access(AccessFs::from_all(abi)))
+            .and_then(|r| r.create())
+            .map_err(|e| unavailable(format!("landlock: {}", e)))?
+            .add_rule(PathBeneath::new(
+                PathFd::new("/").map_err(unavailable)?,
+                AccessFs::from_read(abi),
+            ))
+            .map_err(unavailable)?;
+        for root in sandbox.writable_roots(working_dir) {
+            if !root.exists() {
+                continue;
+            }
+            let access = if root.is_dir() {
+                AccessFs::from_all(abi)
+            } else {
+                AccessFs::from_file(abi)
+            };
+            ruleset = ruleset
+                .add_rule(PathBeneath::new(
+                    PathFd::new(&root).map_err(unavailable)?,
+                    access,
+                ))
+                .map_err(unavailable)?;
+        }
+        let ruleset = std::sync::Mutex::new(Some(ruleset));
+
+        let mut flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS;
+        if !sandbox.network {
+            flags |= libc::CLONE_NEWNET;
+        }
+        // SAFETY: getuid/getgid cannot fail
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        // Map ourselves onto the same ids so file ownership looks unchanged
+        let uid_map = format!("{0} {0} 1", uid);
+        let gid_map = format!("{0} {0} 1", gid);
+
+        // SAFETY: the hook runs between fork and exec and only issues
+        // syscalls on data prepared above
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::unshare(flags) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                write_proc(c"/proc/self/setgroups", b"deny")?;
+                write_proc(c"/proc/self/uid_map", uid_map.as_bytes())?;
+                write_proc(c"/proc/self/gid_map", gid_map.as_bytes())?;
+
+                // Keep any mounts made inside from propagating back out
+                if libc::mount(
+                    c"none".as_ptr(),
+                    c"/".as_ptr(),
+                    std::ptr::null(),
+                    libc::MS_REC | libc::MS_PRIVATE,
+                    std::ptr::null(),
+                ) != 0
+                {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let ruleset = ruleset
+                    .lock()
+                    .ok()
+                    .and_then(|mut r| r.take())
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::Other))?;
+                match ruleset.restrict_self() {
+                    Ok(status) if status.ruleset != RulesetStatus::NotEnforced => Ok(()),
+                    _ => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// `write(2)` to a procfs file without allocating
+    fn write_proc(path: &CStr, data: &[u8]) -> io::Result<()> {
+        // SAFETY: path is NUL-terminated and data outlives the call
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let written = libc::write(fd, data.as_ptr().cast(), data.len());
+            let err = io::Error::last_os_error();
+            libc::close(fd);
+            if written < 0 {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(network: bool) -> Sandbox {
+        Sandbox::from_config(&ToolSandboxConfig {
+            enabled: true,
+            network,
+            writable_paths: vec!["/opt/cache".to_string()],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_disabled_config_has_no_sandbox() {
+        assert!(Sandbox::from_config(&ToolSandboxConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_writable_roots_include_working_dir_and_extras() {
+        let roots = sandbox(true).writable_roots(Path::new("/work/repo"));
+        assert_eq!(roots[0], PathBuf::from("/work/repo"));
+        assert!(roots.contains(&PathBuf::from("/opt/cache")));
+        assert!(roots.contains(&std::env::temp_dir()));
+    }
+
+    #[test]
+    fn test_violation_classification() {
+        let offline = sandbox(false);
+        let cwd = Path::new("/work/repo");
+        assert!(matches!(
+            offline.violation("curl: (6) Could not resolve host: example.com", cwd, cwd),
+            Some(ToolError::SandboxViolation {
+                kind: SandboxViolationKind::Network,
+                ..
+            })
+        ));
+        match offline.violation(
+            "touch: cannot touch '/etc/x': Permission denied\n",
+            cwd,
+            cwd,
+        ) {
+            Some(ToolError::SandboxViolation { kind, detail }) => {
+                assert_eq!(kind, SandboxViolationKind::Filesystem);
+                assert_eq!(detail, "touch: cannot touch '/etc/x': Permission denied");
+            }
+            other => panic!("unexpected: {:?}", other),
+        }
+        assert!(matches!(
+            offline.violation(
+                "PermissionError: [Errno 30] Read-only file system: '../x'",
+                cwd,
+                cwd
+            ),
+            Some(ToolError::SandboxViolation {
+                kind: SandboxViolationKind::Filesystem,
+                ..
+            })
+        ));
+        assert!(offline.violation("error: test failed", cwd, cwd).is_none());
+
+        // With networking allowed, DNS failures are the network's fault
+        assert!(
+            sandbox(true)
+                .violation("Could not resolve host: example.com", cwd, cwd)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_denials_inside_writable_roots_are_not_violations() {
+        let sandbox = sandbox(true);
+        let cwd = Path::new("/work/repo");
+        for stderr in [
+            "bash: ./deploy.sh: Permission denied",
+            "cp: cannot create regular file '/work/repo/out.bin': Permission denied",
+            "mkdir: cannot create directory '/opt/cache/x': Permission denied",
+            "sudo: a terminal is required: Operation not permitted",
+            "npm ERR! Error: EACCES: permission denied",
+        ] {
+            assert!(sandbox.violation(stderr, cwd, cwd).is_none(), "{stderr}");
+        }
+    }
+}
@@ -2,18 +2,65 @@
 //!
 //! Indexes session message history into the qmd "sessions" collection and
 //! searches it using hybrid FTS5 + vector search (same engine as memory_search).
-//! Sessions are indexed on-demand with hash-based deduplication — unchanged
-//! sessions are skipped instantly.
+//! Sessions are indexed on-demand: only messages added since the last pass are
+//! read, and unchanged documents are skipped by hash.
 
 use super::error::Result;
 use super::r#trait::{Tool, ToolCapability, ToolExecutionContext, ToolResult};
 use async_trait::async_trait;
+use crate::db::models::{Message, Session};
+use once_cell::sync::Lazy;
 use qmd::{Store, hybrid_search_rrf};
 use serde_json::Value;
 use sqlx::SqlitePool;
+use std::collections::HashMap;
+use uuid::Uuid;
 
 const COLLECTION: &str = "sessions";
 
+/// Rendered history of each indexed session, so a pass only reads the
+/// messages added since the previous one.
+static INDEXED: Lazy<std::sync::Mutex<HashMap<Uuid, IndexedHistory>>> =
+    Lazy::new(|| std::sync::Mutex::new(HashMap::new()));
+
+/// Every message of a session but the last, already rendered. The last one
+/// is re-read on every pass because the agent updates it in place while a
+/// turn is running.
+#[derive(Debug, Default)]
+struct IndexedHistory {
+    /// Sequence of the last message in `text`
+    sequence: Option<i32>,
+    text: String,
+}
+
+impl IndexedHistory {
+    /// Settle all but the last of `new` (messages after `sequence`, oldest
+    /// first) and return the full rendered history. `None` when there is
+    /// nothing after `sequence`, i.e. the history was cut and must be rebuilt.
+    fn extend(&mut self, new: &[Message]) -> Option<String> {
+        let (last, settled) = new.split_last()?;
+        for msg in settled {
+            self.text.push_str(&render_message(msg));
+            self.sequence = Some(msg.sequence);
+        }
+        Some(format!("{}{}", self.text, render_message(last)))
+    }
+}
+
+fn render_message(msg: &Message) -> String {
+    let role = if msg.role == "user" {
+        "[user]"
+    } else {
+        "[assistant]"
+    };
+    // Cap individual messages to avoid huge documents
+    let content = match msg.content.char_indices().nth(2000) {
+        Some((cut, _)) => format!("{}...", &msg.content[..cut]),
+        None => msg.content.clone(),
+    };
+    format!("{} {}\n\n", role, content)
+}
+
 /// Tool for listing and searching session message history via QMD hybrid search.
 pub struct SessionSearchTool {
     pool: SqlitePool,
@@ -155,10 +202,9 @@ impl SessionSearchTool {
         session_filter: Option<&str>,
         n: usize,
     ) -> Result<ToolResult> {
-        use crate::db::repository::{MessageRepository, SessionListOptions, SessionRepository};
+        use crate::db::repository::{SessionListOptions, SessionRepository};
 
         let session_repo = SessionRepository::new(self.pool.clone());
-        let message_repo = MessageRepository::new(self.pool.clone());
 
         // Load all sessions (most-recent-first) to resolve filter
         let all_sessions = session_repo
@@ -213,53 +259,7 @@ impl SessionSearchTool {
         };
 
         // Index target sessions into QMD — hash-skipped if content unchanged
-        for session in &target_sessions {
-            let messages = message_repo
-                .find_by_session(session.id)
-                .await
-                .unwrap_or_default();
-
-            if messages.is_empty() {
-                continue;
-            }
-
-            let title = session
-                .title
-                .clone()
-                .unwrap_or_else(|| "Untitled".to_string());
-            let date = session.updated_at.format("%Y-%m-%d").to_string();
-            let mut body =
-                format!("# {}\nDate: {}\nSession: {}\n\n", title, date, session.id);
-
-            for msg in &messages {
-                let role = if msg.role == "user" {
-                    "[user]"
-                } else {
-                    "[assistant]"
-                };
-                // Cap individual messages to avoid huge documents
-                let content = if msg.content.len() > 2000 {
-                    format!("{}...", &msg.content[..2000])
-                } else {
-                    msg.content.clone()
-                };
-                body.push_str(&format!("{} {}\n\n", role, content));
-            }
-
-            let doc_path = format!("{}.md", session.id);
-            let title_owned = title.clone();
-            let body_owned = body;
-
-            if let Err(e) = tokio::task::spawn_blocking(move || {
-                index_session_body(store, &doc_path, &title_owned, body_owned)
-            })
-            .await
-            .map_err(|e| e.to_string())
-            .and_then(|r| r)
-            {
-                tracing::warn!("Failed to index session {}: {}", session.id, e);
-            }
-        }
+        index_sessions(&self.pool, store, &target_sessions).await;
 
         // Session doc paths for post-filter
         let target_paths: Vec<String> = target_sessions
@@ -311,6 +311,65 @@ impl SessionSearchTool {
     }
 }
 
+/// Index sessions' message history into the "sessions" collection. Only
+/// messages added since the last call are read; sessions whose content hasn't
+/// changed are skipped by hash. Failures are logged per session and don't
+/// stop the rest.
+pub async fn index_sessions(
+    pool: &SqlitePool,
+    store: &'static std::sync::Mutex<Store>,
+    sessions: &[Session],
+) {
+    use crate::db::repository::MessageRepository;
+
+    let message_repo = MessageRepository::new(pool.clone());
+    for session in sessions {
+        let mut history = INDEXED
+            .lock()
+            .ok()
+            .and_then(|mut indexed| indexed.remove(&session.id))
+            .unwrap_or_default();
+        let new = match history.sequence {
+            Some(sequence) => {
+                message_repo
+                    .find_by_session_after(session.id, sequence)
+                    .await
+            }
+            None => message_repo.find_by_session(session.id).await,
+        }
+        .unwrap_or_default();
+
+        // Without a cached entry the next pass reads the whole session again
+        let Some(messages) = history.extend(&new) else {
+            continue;
+        };
+        if let Ok(mut indexed) = INDEXED.lock() {
+            indexed.insert(session.id, history);
+        }
+
+        let title = session
+            .title
+            .clone()
+            .unwrap_or_else(|| "Untitled".to_string());
+        let date = session.updated_at.format("%Y-%m-%d").to_string();
+        let mut body =
+            format!("# {}\nDate: {}\nSession: {}\n\n", title, date, session.id);
+        body.push_str(&messages);
+
+        let doc_path = format!("{}.md", session.id);
+
+        if let Err(e) = tokio::task::spawn_blocking(move || {
+            index_session_body(store, &doc_path, &title, body)
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r)
+        {
+            tracing::warn!("Failed to index session {}: {}", session.id, e);
+        }
+    }
+}
+
 /// Insert/update a session document in the QMD store. Skips if content unchanged.
 /// Triggers embedding if the engine is already running (non-blocking, FTS-only fallback).
 fn index_session_body(
@@ -357,7 +416,7 @@ fn search_in_sessions(
     let query_embedding = crate::memory::engine_if_ready().and_then(|em| {
         em.lock()
             .ok()
-            .and_then(|mut e| e.embed_query(raw_query).ok().map(|r| r.embedding))
+            .and_then(|mut e| e.embed_query(raw_query).ok())
     });
 
     let s = store
@@ -499,3 +558,35 @@ fn extract_snippet(body: &str, query: &str, max_len: usize) -> String {
 
     snippet
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(seq: i32, role: &str, content: &str) -> Message {
+        Message::new(Uuid::nil(), role.to_string(), content.to_string(), seq)
+    }
+
+    #[test]
+    fn test_history_keeps_last_message_unsettled() {
+        let mut history = IndexedHistory::default();
+
+        let text = history
+            .extend(&[message(1, "user", "hi"), message(2, "assistant", "draft")])
+            .expect("history");
+        assert_eq!(text, "[user] hi\n\n[assistant] draft\n\n");
+        assert_eq!(history.sequence, Some(1));
+
+        // The in-progress reply is read again with what came after it
+        let text = history
+            .extend(&[
+                message(2, "assistant", "final"),
+                message(3, "user", "thanks"),
+            ])
+            .expect("history");
+        assert_eq!(text, "[user] hi\n\n[assistant] final\n\n[user] thanks\n\n");
+        assert_eq!(history.sequence, Some(2));
+
+        assert!(history.extend(&[]).is_none());
+    }
+}
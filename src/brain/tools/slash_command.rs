@@ -171,9 +171,16 @@ impl SlashCommandTool {
         }
 
         // Set policy
-        match policy {
-            "approve-only" | "auto-session" | "auto-always" => {
-                match crate::config::Config::write_key("agent", "approval_policy", policy) {
+        use crate::config::ApprovalPolicy;
+        let parsed = match policy {
+            "approve-only" | "ask" => Some(ApprovalPolicy::Ask),
+            "auto-session" => Some(ApprovalPolicy::AutoSession),
+            "auto-always" => Some(ApprovalPolicy::AutoAlways),
+            _ => None,
+        };
+        match parsed {
+            Some(parsed) => {
+                match crate::config::Config::write_key("agent", "approval_policy", parsed.as_str()) {
                     Ok(()) => Ok(ToolResult::success(format!(
                         "Approval policy set to: {}",
                         policy
@@ -184,7 +191,7 @@ impl SlashCommandTool {
                     ))),
                 }
             }
-            _ => Ok(ToolResult::error(format!(
+            None => Ok(ToolResult::error(format!(
                 "Invalid policy: '{}'. Valid: approve-only, auto-session, auto-always",
                 policy
             ))),
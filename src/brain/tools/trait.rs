@@ -1,6 +1,7 @@
 //! Tool trait definition
 
-use super::error::Result;
+use super::error::{Result, ToolError};
+use super::sandbox::SandboxViolationKind;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -34,6 +35,10 @@ pub struct ToolExecutionContext {
     /// Shared working directory handle — tools can mutate this to change the
     /// working directory at runtime (e.g. config_manager set_working_directory).
     pub shared_working_directory: Option<Arc<std::sync::RwLock<std::path::PathBuf>>>,
+
+    /// Checkpoint of the current agent turn — file-modifying tools save the
+    /// previous content here before writing so the turn can be undone
+    pub checkpoint: Option<crate::services::checkpoint::TurnCheckpoint>,
 }
 
 impl std::fmt::Debug for ToolExecutionContext {
@@ -45,6 +50,7 @@ impl std::fmt::Debug for ToolExecutionContext {
             .field("timeout_secs", &self.timeout_secs)
             .field("read_only_mode", &self.read_only_mode)
             .field("sudo_callback", &self.sudo_callback.is_some())
+            .field("checkpoint", &self.checkpoint)
             .finish()
     }
 }
@@ -61,6 +67,7 @@ impl ToolExecutionContext {
             read_only_mode: false,
             sudo_callback: None,
             shared_working_directory: None,
+            checkpoint: None,
         }
     }
 
@@ -87,6 +94,16 @@ impl ToolExecutionContext {
         self.read_only_mode = read_only;
         self
     }
+
+    /// Save `path` into the turn's checkpoint before it is modified. A
+    /// failed snapshot is logged rather than blocking the write.
+    pub async fn checkpoint_file(&self, path: &std::path::Path) {
+        if let Some(checkpoint) = &self.checkpoint
+            && let Err(e) = checkpoint.snapshot(path).await
+        {
+            tracing::warn!("Failed to checkpoint {}: {}", path.display(), e);
+        }
+    }
 }
 
 /// Tool result
@@ -103,6 +120,9 @@ pub struct ToolResult {
 
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+
+    /// Set when the OS sandbox blocked the run
+    pub sandbox_violation: Option<SandboxViolationKind>,
 }
 
 impl ToolResult {
@@ -113,6 +133,7 @@ impl ToolResult {
             output,
             error: None,
             metadata: HashMap::new(),
+            sandbox_violation: None,
         }
     }
 
@@ -123,6 +144,18 @@ impl ToolResult {
             output: String::new(),
             error: Some(error),
             metadata: HashMap::new(),
+            sandbox_violation: None,
+        }
+    }
+
+    /// Create a result for a run the OS sandbox blocked, keeping its output
+    pub fn sandbox_violation(output: String, kind: SandboxViolationKind, detail: String) -> Self {
+        Self {
+            success: false,
+            output,
+            error: Some(ToolError::SandboxViolation { kind, detail }.to_string()),
+            metadata: HashMap::new(),
+            sandbox_violation: Some(kind),
         }
     }
 
@@ -179,6 +212,35 @@ pub trait Tool: Send + Sync {
             .any(|cap| dangerous_capabilities.contains(cap))
     }
 
+    /// Check if a particular call requires approval. Tools whose operations
+    /// differ in risk (e.g. `git status` vs `git commit`) override this.
+    fn requires_approval_for(&self, _input: &Value) -> bool {
+        self.requires_approval()
+    }
+
+    /// Check if the tool only reads state. By default only tools that do
+    /// nothing but read files qualify; network tools can have side effects
+    /// (sending a message, a POST) and opt in by overriding this when they
+    /// are pure lookups.
+    fn is_read_only(&self) -> bool {
+        let capabilities = self.capabilities();
+        !capabilities.is_empty()
+            && capabilities
+                .iter()
+                .all(|cap| *cap == ToolCapability::ReadFiles)
+    }
+
+    /// Check if several calls of this tool can run alongside other tool calls
+    fn is_concurrency_safe(&self) -> bool {
+        self.is_read_only()
+    }
+
+    /// Check if a particular call can run alongside other tool calls. Tools
+    /// whose side effects depend on the input override this.
+    fn is_concurrency_safe_for(&self, _input: &Value) -> bool {
+        self.is_concurrency_safe()
+    }
+
     /// Execute the tool with given input
     async fn execute(&self, input: Value, context: &ToolExecutionContext) -> Result<ToolResult>;
 
@@ -216,6 +278,53 @@ mod tests {
         assert_eq!(result.metadata.get("duration_ms"), Some(&"123".to_string()));
     }
 
+    struct CapabilityTool(Vec<ToolCapability>);
+
+    #[async_trait]
+    impl Tool for CapabilityTool {
+        fn name(&self) -> &str {
+            "capability_tool"
+        }
+
+        fn description(&self) -> &str {
+            "Tool with fixed capabilities"
+        }
+
+        fn input_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn capabilities(&self) -> Vec<ToolCapability> {
+            self.0.clone()
+        }
+
+        async fn execute(
+            &self,
+            _input: Value,
+            _context: &ToolExecutionContext,
+        ) -> Result<ToolResult> {
+            Ok(ToolResult::success(String::new()))
+        }
+    }
+
+    #[test]
+    fn test_read_only_is_explicit() {
+        assert!(CapabilityTool(vec![ToolCapability::ReadFiles]).is_read_only());
+        assert!(CapabilityTool(vec![ToolCapability::ReadFiles]).is_concurrency_safe());
+
+        // Side effects that aren't file writes still aren't read-only
+        for capabilities in [
+            vec![],
+            vec![ToolCapability::Network],
+            vec![ToolCapability::PlanManagement],
+            vec![ToolCapability::ReadFiles, ToolCapability::Network],
+        ] {
+            let tool = CapabilityTool(capabilities);
+            assert!(!tool.is_read_only());
+            assert!(!tool.is_concurrency_safe());
+        }
+    }
+
     #[test]
     fn test_tool_result_error() {
         let result = ToolResult::error("Something went wrong".to_string());
@@ -223,4 +332,24 @@ mod tests {
         assert!(!result.success);
         assert_eq!(result.error, Some("Something went wrong".to_string()));
     }
+
+    #[test]
+    fn test_tool_result_sandbox_violation() {
+        let result = ToolResult::sandbox_violation(
+            "partial output".to_string(),
+            SandboxViolationKind::Filesystem,
+            "touch: cannot touch '/etc/x': Permission denied".to_string(),
+        );
+
+        assert!(!result.success);
+        assert_eq!(result.output, "partial output");
+        assert_eq!(
+            result.sandbox_violation,
+            Some(SandboxViolationKind::Filesystem)
+        );
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Sandbox violation (filesystem): touch: cannot touch '/etc/x': Permission denied")
+        );
+    }
 }
@@ -259,7 +259,7 @@ impl Tool for Web3AutoRepairTool {
             
             // S5: Apply patch
             let patch_path = working_dir.join("reports").join(format!("repair.round{}.patch", round));
-            let _ = apply_patch(&patch_path, &patch_content).await;
+            let _ = apply_patch(&patch_path, &patch_content, context).await;
             patches.push(patch_path.display().to_string());
             
             // S6: Re-run test
@@ -318,6 +318,7 @@ impl Tool for Web3AutoRepairTool {
             output: serde_json::to_string_pretty(&final_report).unwrap(),
             error: Some("Auto-repair could not fix the failing tests".to_string()),
             metadata: std::collections::HashMap::new(),
+            sandbox_violation: None,
         })
     }
 }
@@ -479,12 +480,18 @@ fn validate_patch(patch: &str, input: &Web3AutoRepairInput) -> Result<()> {
 }
 
 /// Apply patch to file
-async fn apply_patch(patch_path: &PathBuf, content: &str) -> Result<()> {
+async fn apply_patch(
+    patch_path: &PathBuf,
+    content: &str,
+    context: &ToolExecutionContext,
+) -> Result<()> {
     // Ensure reports directory exists
     if let Some(parent) = patch_path.parent() {
         tokio::fs::create_dir_all(parent).await.ok();
     }
-    
+
+    context.checkpoint_file(patch_path).await;
+
     tokio::fs::write(patch_path, content).await
         .map_err(|e| ToolError::Execution(format!("Failed to write patch: {}", e)))?;
     
@@ -233,6 +233,7 @@ impl Tool for Web3DeployTool {
                 output: result_text,
                 error: Some(format!("Deployment failed with exit code {}", exit_code)),
                 metadata: std::collections::HashMap::new(),
+                sandbox_violation: None,
             }
         };
 
@@ -181,6 +181,7 @@ impl Tool for Web3TestTool {
                 output: result_text,
                 error: Some(format!("Tests failed with exit code {}", exit_code)),
                 metadata: std::collections::HashMap::new(),
+                sandbox_violation: None,
             }
         };
 
@@ -12,7 +12,7 @@ use crate::brain::agent::{ProgressCallback, ProgressEvent};
 use crate::channels::whatsapp::handler;
 use async_trait::async_trait;
 use serde_json::Value;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
@@ -163,8 +163,8 @@ impl Tool for WhatsAppConnectTool {
             Arc::new(allowed_phones.iter().cloned().collect());
         let voice_config = Arc::new(factory.voice_config().clone());
         let shared_session = factory.shared_session_id();
-        let extra_sessions: Arc<Mutex<HashMap<String, uuid::Uuid>>> =
-            Arc::new(Mutex::new(HashMap::new()));
+        let channel_sessions =
+            crate::services::ChannelSessionService::new(factory.service_context());
 
         // 4. Build bot with combined event handler (QR + Connected + Messages)
         let qr_tx_clone = qr_tx.clone();
@@ -184,7 +184,7 @@ impl Tool for WhatsAppConnectTool {
                 let agent = agent.clone();
                 let session_svc = session_svc.clone();
                 let allowed = allowed.clone();
-                let extra_sessions = extra_sessions.clone();
+                let channel_sessions = channel_sessions.clone();
                 let voice_config = voice_config.clone();
                 let shared_session = shared_session.clone();
                 let wa_state = wa_state.clone();
@@ -212,7 +212,7 @@ impl Tool for WhatsAppConnectTool {
                                 agent,
                                 session_svc,
                                 allowed,
-                                extra_sessions,
+                                channel_sessions,
                                 voice_config,
                                 shared_session,
                             )
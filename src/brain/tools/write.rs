@@ -153,6 +153,7 @@ impl Tool for WriteTool {
             }
 
         // Write the file
+        context.checkpoint_file(&path).await;
         fs::write(&path, &input.content)
             .await
             .map_err(ToolError::Io)?;
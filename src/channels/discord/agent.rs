@@ -6,8 +6,8 @@ use super::handler;
 use super::DiscordState;
 use crate::config::{RespondTo, VoiceConfig};
 use crate::brain::agent::AgentService;
-use crate::services::{ServiceContext, SessionService};
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, ServiceContext, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -21,6 +21,7 @@ use serenity::prelude::*;
 pub struct DiscordAgent {
     agent_service: Arc<AgentService>,
     session_service: SessionService,
+    channel_sessions: ChannelSessionService,
     allowed_users: Vec<i64>,
     voice_config: VoiceConfig,
     shared_session_id: Arc<Mutex<Option<Uuid>>>,
@@ -43,7 +44,8 @@ impl DiscordAgent {
     ) -> Self {
         Self {
             agent_service,
-            session_service: SessionService::new(service_context),
+            session_service: SessionService::new(service_context.clone()),
+            channel_sessions: ChannelSessionService::new(service_context),
             allowed_users,
             voice_config,
             shared_session_id,
@@ -65,8 +67,6 @@ impl DiscordAgent {
 
             let allowed: Arc<HashSet<i64>> =
                 Arc::new(self.allowed_users.into_iter().collect());
-            let extra_sessions: Arc<Mutex<HashMap<u64, Uuid>>> =
-                Arc::new(Mutex::new(HashMap::new()));
 
             let allowed_channels: HashSet<String> =
                 self.allowed_channels.into_iter().collect();
@@ -78,7 +78,7 @@ impl DiscordAgent {
                 agent: self.agent_service,
                 session_svc: self.session_service,
                 allowed,
-                extra_sessions,
+                channel_sessions: self.channel_sessions,
                 shared_session: self.shared_session_id,
                 discord_state: self.discord_state,
                 respond_to: self.respond_to,
@@ -114,7 +114,7 @@ struct Handler {
     agent: Arc<AgentService>,
     session_svc: SessionService,
     allowed: Arc<HashSet<i64>>,
-    extra_sessions: Arc<Mutex<HashMap<u64, Uuid>>>,
+    channel_sessions: ChannelSessionService,
     shared_session: Arc<Mutex<Option<Uuid>>>,
     discord_state: Arc<DiscordState>,
     respond_to: RespondTo,
@@ -147,7 +147,7 @@ impl EventHandler for Handler {
             self.agent.clone(),
             self.session_svc.clone(),
             self.allowed.clone(),
-            self.extra_sessions.clone(),
+            self.channel_sessions.clone(),
             self.shared_session.clone(),
             self.discord_state.clone(),
             &self.respond_to,
@@ -6,8 +6,8 @@
 use super::DiscordState;
 use crate::config::{RespondTo, VoiceConfig};
 use crate::brain::agent::AgentService;
-use crate::services::SessionService;
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -47,7 +47,7 @@ pub(crate) async fn handle_message(
     agent: Arc<AgentService>,
     session_svc: SessionService,
     allowed: Arc<HashSet<i64>>,
-    extra_sessions: Arc<Mutex<HashMap<u64, Uuid>>>,
+    channel_sessions: ChannelSessionService,
     shared_session: Arc<Mutex<Option<Uuid>>>,
     discord_state: Arc<DiscordState>,
     respond_to: &RespondTo,
@@ -188,22 +188,15 @@ pub(crate) async fn handle_message(
             }
         }
     } else {
-        let mut map = extra_sessions.lock().await;
-        let disc_user_id = msg.author.id.get();
-        match map.get(&disc_user_id) {
-            Some(id) => *id,
-            None => {
-                let title = format!("Discord: {}", msg.author.name);
-                match session_svc.create_session(Some(title)).await {
-                    Ok(session) => {
-                        map.insert(disc_user_id, session.id);
-                        session.id
-                    }
-                    Err(e) => {
-                        tracing::error!("Discord: failed to create session: {}", e);
-                        return;
-                    }
-                }
+        let title = format!("Discord: {}", msg.author.name);
+        match channel_sessions
+            .resolve_session("discord", &msg.author.id.get().to_string(), title)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Discord: failed to resolve session: {}", e);
+                return;
             }
         }
     };
@@ -60,17 +60,25 @@ impl ChannelFactory {
 
     /// Create a new AgentService configured for channel use (auto-approve, no TUI callbacks).
     pub fn create_agent_service(&self) -> Arc<AgentService> {
+        Arc::new(self.build_agent_service())
+    }
+
+    /// Like [`create_agent_service`], but returns the unwrapped service so callers
+    /// can attach extra callbacks (e.g. the gateway's progress broadcaster).
+    pub fn build_agent_service(&self) -> AgentService {
         let mut builder = AgentService::new(self.provider.clone(), self.service_context.clone())
             .with_system_brain(self.shared_brain.clone())
             .with_auto_approve_tools(true)
             .with_working_directory(self.working_directory.clone())
-            .with_brain_path(self.brain_path.clone());
+            .with_brain_path(self.brain_path.clone())
+            // Past sessions belong to other chats; recall only from memory
+            .with_session_recall(false);
 
         if let Some(registry) = self.tool_registry.get() {
             builder = builder.with_tool_registry(registry.clone());
         }
 
-        Arc::new(builder)
+        builder
     }
 
     pub fn shared_session_id(&self) -> Arc<Mutex<Option<Uuid>>> {
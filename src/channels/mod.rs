@@ -1,9 +1,11 @@
 //! Channel Integrations
 //!
-//! Messaging channel integrations (Telegram, WhatsApp, Discord, Slack) and the
-//! shared factory for creating channel-specific agent services.
+//! Messaging channel integrations (Telegram, WhatsApp, Discord, Slack), the
+//! shared factory for creating channel-specific agent services, and the
+//! runtime that starts every configured channel.
 
 mod factory;
+mod runtime;
 pub mod voice;
 
 #[cfg(feature = "discord")]
@@ -16,3 +18,4 @@ pub mod telegram;
 pub mod whatsapp;
 
 pub use factory::ChannelFactory;
+pub use runtime::{ChannelStates, spawn_channels};
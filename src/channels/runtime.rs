@@ -0,0 +1,177 @@
+//! Channel Runtime
+//!
+//! Starts every channel enabled in config.toml. Shared by the TUI startup
+//! (ui.rs) and the headless daemon so both run channels the same way.
+
+use super::ChannelFactory;
+use crate::config::Config;
+#[cfg(any(
+    feature = "telegram",
+    feature = "whatsapp",
+    feature = "discord",
+    feature = "slack"
+))]
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Per-channel shared state, used by the running bots and by the
+/// connect/send tools for proactive messaging.
+#[derive(Clone, Default)]
+pub struct ChannelStates {
+    #[cfg(feature = "telegram")]
+    pub telegram: Arc<super::telegram::TelegramState>,
+    #[cfg(feature = "whatsapp")]
+    pub whatsapp: Arc<super::whatsapp::WhatsAppState>,
+    #[cfg(feature = "discord")]
+    pub discord: Arc<super::discord::DiscordState>,
+    #[cfg(feature = "slack")]
+    pub slack: Arc<super::slack::SlackState>,
+}
+
+impl ChannelStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Spawn every configured channel. Returns the handles of the channels that
+/// started; misconfigured channels are logged and skipped.
+#[allow(unused_variables, unused_mut)]
+pub fn spawn_channels(
+    config: &Config,
+    factory: &ChannelFactory,
+    states: &ChannelStates,
+) -> Vec<JoinHandle<()>> {
+    let mut handles = Vec::new();
+
+    // Telegram bot (token-based)
+    #[cfg(feature = "telegram")]
+    {
+        let tg = &config.channels.telegram;
+        let tg_token = tg
+            .token
+            .clone()
+            .or_else(|| std::env::var("TELEGRAM_BOT_TOKEN").ok());
+        if tg.enabled || tg_token.is_some() {
+            if let Some(token) = tg_token {
+                // Extract OpenAI API key for TTS (from providers.tts.openai)
+                let openai_key = config
+                    .providers
+                    .tts
+                    .as_ref()
+                    .and_then(|t| t.openai.as_ref())
+                    .and_then(|p| p.api_key.clone());
+                // Extract STT/TTS provider config from providers.stt.* / providers.tts.*
+                let mut voice_cfg = config.voice.clone();
+                voice_cfg.stt_provider = config.providers.stt.as_ref().and_then(|s| s.groq.clone());
+                voice_cfg.tts_provider =
+                    config.providers.tts.as_ref().and_then(|t| t.openai.clone());
+                let bot = super::telegram::TelegramAgent::new(
+                    factory.create_agent_service(),
+                    factory.service_context(),
+                    tg.allowed_users.clone(),
+                    voice_cfg,
+                    openai_key,
+                    factory.shared_session_id(),
+                    states.telegram.clone(),
+                    tg.respond_to.clone(),
+                    tg.allowed_channels.clone(),
+                );
+                tracing::info!(
+                    "Spawning Telegram bot ({} allowed users)",
+                    tg.allowed_users.len()
+                );
+                handles.push(bot.start(token));
+            } else {
+                tracing::warn!("Telegram enabled but no token configured");
+            }
+        }
+    }
+
+    // WhatsApp agent (already paired via session.db)
+    #[cfg(feature = "whatsapp")]
+    {
+        let wa = &config.channels.whatsapp;
+        if wa.enabled {
+            let wa_agent = super::whatsapp::WhatsAppAgent::new(
+                factory.create_agent_service(),
+                factory.service_context(),
+                wa.allowed_phones.clone(),
+                config.voice.clone(),
+                factory.shared_session_id(),
+                states.whatsapp.clone(),
+            );
+            tracing::info!(
+                "Spawning WhatsApp agent ({} allowed phones)",
+                wa.allowed_phones.len()
+            );
+            handles.push(wa_agent.start());
+        }
+    }
+
+    // Discord bot (token-based, like Telegram)
+    #[cfg(feature = "discord")]
+    {
+        let dc = &config.channels.discord;
+        let dc_token = dc
+            .token
+            .clone()
+            .or_else(|| std::env::var("DISCORD_BOT_TOKEN").ok());
+        if dc.enabled || dc_token.is_some() {
+            if let Some(token) = dc_token {
+                let dc_agent = super::discord::DiscordAgent::new(
+                    factory.create_agent_service(),
+                    factory.service_context(),
+                    dc.allowed_users.clone(),
+                    config.voice.clone(),
+                    factory.shared_session_id(),
+                    states.discord.clone(),
+                    dc.respond_to.clone(),
+                    dc.allowed_channels.clone(),
+                );
+                tracing::info!(
+                    "Spawning Discord bot ({} allowed users)",
+                    dc.allowed_users.len()
+                );
+                handles.push(dc_agent.start(token));
+            } else {
+                tracing::warn!("Discord enabled but no token configured");
+            }
+        }
+    }
+
+    // Slack bot (needs both bot token + app token for Socket Mode)
+    #[cfg(feature = "slack")]
+    {
+        let sl = &config.channels.slack;
+        let sl_token = sl
+            .token
+            .clone()
+            .or_else(|| std::env::var("SLACK_BOT_TOKEN").ok());
+        let sl_app_token = sl
+            .app_token
+            .clone()
+            .or_else(|| std::env::var("SLACK_APP_TOKEN").ok());
+        if sl.enabled || sl_token.is_some() {
+            if let (Some(bot_tok), Some(app_tok)) = (sl_token, sl_app_token) {
+                let sl_agent = super::slack::SlackAgent::new(
+                    factory.create_agent_service(),
+                    factory.service_context(),
+                    sl.allowed_ids.clone(),
+                    factory.shared_session_id(),
+                    states.slack.clone(),
+                    sl.respond_to.clone(),
+                    sl.allowed_channels.clone(),
+                );
+                tracing::info!("Spawning Slack bot ({} allowed IDs)", sl.allowed_ids.len());
+                handles.push(sl_agent.start(bot_tok, app_tok));
+            } else if sl.enabled {
+                tracing::warn!(
+                    "Slack enabled but missing tokens (need both SLACK_BOT_TOKEN and SLACK_APP_TOKEN)"
+                );
+            }
+        }
+    }
+
+    handles
+}
@@ -7,9 +7,8 @@ use super::handler;
 use super::SlackState;
 use crate::config::RespondTo;
 use crate::brain::agent::AgentService;
-use crate::services::{ServiceContext, SessionService};
+use crate::services::{ChannelSessionService, ServiceContext, SessionService};
 use slack_morphism::prelude::*;
-use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -18,6 +17,7 @@ use uuid::Uuid;
 pub struct SlackAgent {
     agent_service: Arc<AgentService>,
     session_service: SessionService,
+    channel_sessions: ChannelSessionService,
     allowed_ids: Vec<String>,
     shared_session_id: Arc<Mutex<Option<Uuid>>>,
     slack_state: Arc<SlackState>,
@@ -37,7 +37,8 @@ impl SlackAgent {
     ) -> Self {
         Self {
             agent_service,
-            session_service: SessionService::new(service_context),
+            session_service: SessionService::new(service_context.clone()),
+            channel_sessions: ChannelSessionService::new(service_context),
             allowed_ids,
             shared_session_id,
             slack_state,
@@ -89,7 +90,7 @@ impl SlackAgent {
                 agent: self.agent_service,
                 session_svc: self.session_service,
                 allowed: Arc::new(self.allowed_ids.into_iter().collect()),
-                extra_sessions: Arc::new(Mutex::new(HashMap::new())),
+                channel_sessions: self.channel_sessions,
                 shared_session: self.shared_session_id,
                 slack_state: self.slack_state.clone(),
                 bot_token: bot_token.clone(),
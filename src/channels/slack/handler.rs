@@ -9,9 +9,9 @@
 use super::SlackState;
 use crate::config::RespondTo;
 use crate::brain::agent::AgentService;
-use crate::services::SessionService;
+use crate::services::{ChannelSessionService, SessionService};
 use slack_morphism::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::sync::{Arc, OnceLock};
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -25,7 +25,7 @@ pub struct HandlerState {
     pub agent: Arc<AgentService>,
     pub session_svc: SessionService,
     pub allowed: Arc<HashSet<String>>,
-    pub extra_sessions: Arc<Mutex<HashMap<String, Uuid>>>,
+    pub channel_sessions: ChannelSessionService,
     pub shared_session: Arc<Mutex<Option<Uuid>>>,
     pub slack_state: Arc<SlackState>,
     pub bot_token: String,
@@ -206,21 +206,16 @@ async fn handle_message(msg: &SlackMessageEvent, client: Arc<SlackHyperClient>)
             }
         }
     } else {
-        let mut map = state.extra_sessions.lock().await;
-        match map.get(&user_id) {
-            Some(id) => *id,
-            None => {
-                let title = format!("Slack: {}", user_id);
-                match state.session_svc.create_session(Some(title)).await {
-                    Ok(session) => {
-                        map.insert(user_id.clone(), session.id);
-                        session.id
-                    }
-                    Err(e) => {
-                        tracing::error!("Slack: failed to create session: {}", e);
-                        return;
-                    }
-                }
+        let title = format!("Slack: {}", user_id);
+        match state
+            .channel_sessions
+            .resolve_session("slack", &user_id, title)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Slack: failed to resolve session: {}", e);
+                return;
             }
         }
     };
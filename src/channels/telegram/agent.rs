@@ -6,8 +6,8 @@ use super::handler::handle_message;
 use super::TelegramState;
 use crate::config::{RespondTo, VoiceConfig};
 use crate::brain::agent::AgentService;
-use crate::services::{ServiceContext, SessionService};
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, ServiceContext, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use teloxide::prelude::*;
 use tokio::sync::Mutex;
@@ -17,6 +17,7 @@ use uuid::Uuid;
 pub struct TelegramAgent {
     agent_service: Arc<AgentService>,
     session_service: SessionService,
+    channel_sessions: ChannelSessionService,
     allowed_users: HashSet<i64>,
     voice_config: VoiceConfig,
     openai_api_key: Option<String>,
@@ -42,7 +43,8 @@ impl TelegramAgent {
     ) -> Self {
         Self {
             agent_service,
-            session_service: SessionService::new(service_context),
+            session_service: SessionService::new(service_context.clone()),
+            channel_sessions: ChannelSessionService::new(service_context),
             allowed_users: allowed_users.into_iter().collect(),
             voice_config,
             openai_api_key,
@@ -81,8 +83,8 @@ impl TelegramAgent {
                 }
             }
 
-            // Per-user session tracking for non-owner users (owner shares TUI session)
-            let extra_sessions: Arc<Mutex<HashMap<i64, Uuid>>> = Arc::new(Mutex::new(HashMap::new()));
+            // Non-owner users get persistent per-user sessions (owner shares TUI session)
+            let channel_sessions = self.channel_sessions.clone();
             let agent = self.agent_service.clone();
             let session_svc = self.session_service.clone();
             let allowed = Arc::new(self.allowed_users);
@@ -99,7 +101,7 @@ impl TelegramAgent {
                     let agent = agent.clone();
                     let session_svc = session_svc.clone();
                     let allowed = allowed.clone();
-                    let extra_sessions = extra_sessions.clone();
+                    let channel_sessions = channel_sessions.clone();
                     let voice_config = voice_config.clone();
                     let openai_key = openai_key.clone();
                     let bot_token = bot_token.clone();
@@ -109,7 +111,7 @@ impl TelegramAgent {
                     let allowed_channels = allowed_channels.clone();
                     async move {
                         handle_message(
-                            bot, msg, agent, session_svc, allowed, extra_sessions,
+                            bot, msg, agent, session_svc, allowed, channel_sessions,
                             voice_config, openai_key, bot_token, shared_session,
                             telegram_state, &respond_to, &allowed_channels,
                         )
@@ -5,8 +5,8 @@
 use super::TelegramState;
 use crate::config::{RespondTo, VoiceConfig};
 use crate::brain::agent::AgentService;
-use crate::services::SessionService;
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use teloxide::prelude::*;
 use teloxide::types::{ChatKind, InputFile};
@@ -21,7 +21,7 @@ pub(crate) async fn handle_message(
     agent: Arc<AgentService>,
     session_svc: SessionService,
     allowed: Arc<HashSet<i64>>,
-    extra_sessions: Arc<Mutex<HashMap<i64, Uuid>>>,
+    channel_sessions: ChannelSessionService,
     voice_config: Arc<VoiceConfig>,
     openai_key: Arc<Option<String>>,
     bot_token: Arc<String>,
@@ -360,24 +360,18 @@ pub(crate) async fn handle_message(
             }
         }
     } else {
-        // Non-owner users get their own separate sessions
-        let mut map = extra_sessions.lock().await;
-        match map.get(&user_id) {
-            Some(id) => *id,
-            None => {
-                let title = format!("Telegram: {}", user.first_name);
-                match session_svc.create_session(Some(title)).await {
-                    Ok(session) => {
-                        map.insert(user_id, session.id);
-                        session.id
-                    }
-                    Err(e) => {
-                        tracing::error!("Telegram: failed to create session: {}", e);
-                        bot.send_message(msg.chat.id, "Internal error creating session.")
-                            .await?;
-                        return Ok(());
-                    }
-                }
+        // Non-owner users get their own persistent sessions
+        let title = format!("Telegram: {}", user.first_name);
+        match channel_sessions
+            .resolve_session("telegram", &user_id.to_string(), title)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("Telegram: failed to resolve session: {}", e);
+                bot.send_message(msg.chat.id, "Internal error creating session.")
+                    .await?;
+                return Ok(());
             }
         }
     };
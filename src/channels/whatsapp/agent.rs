@@ -6,8 +6,8 @@ use super::handler;
 use super::WhatsAppState;
 use crate::config::VoiceConfig;
 use crate::brain::agent::AgentService;
-use crate::services::{ServiceContext, SessionService};
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, ServiceContext, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -22,6 +22,7 @@ use whatsapp_rust_ureq_http_client::UreqHttpClient;
 pub struct WhatsAppAgent {
     agent_service: Arc<AgentService>,
     session_service: SessionService,
+    channel_sessions: ChannelSessionService,
     allowed_phones: Vec<String>,
     voice_config: VoiceConfig,
     shared_session_id: Arc<Mutex<Option<Uuid>>>,
@@ -39,7 +40,8 @@ impl WhatsAppAgent {
     ) -> Self {
         Self {
             agent_service,
-            session_service: SessionService::new(service_context),
+            session_service: SessionService::new(service_context.clone()),
+            channel_sessions: ChannelSessionService::new(service_context),
             allowed_phones,
             voice_config,
             shared_session_id,
@@ -105,8 +107,7 @@ impl WhatsAppAgent {
             let shared_session = self.shared_session_id.clone();
             let wa_state = self.whatsapp_state.clone();
             let owner_jid_clone = owner_jid.clone();
-            let extra_sessions: Arc<Mutex<HashMap<String, Uuid>>> =
-                Arc::new(Mutex::new(HashMap::new()));
+            let channel_sessions = self.channel_sessions.clone();
 
             let bot_result = Bot::builder()
                 .with_backend(backend)
@@ -116,7 +117,7 @@ impl WhatsAppAgent {
                     let agent = agent.clone();
                     let session_svc = session_svc.clone();
                     let allowed = allowed.clone();
-                    let extra_sessions = extra_sessions.clone();
+                    let channel_sessions = channel_sessions.clone();
                     let voice_config = voice_config.clone();
                     let shared_session = shared_session.clone();
                     let wa_state = wa_state.clone();
@@ -146,7 +147,7 @@ impl WhatsAppAgent {
                                     agent,
                                     session_svc,
                                     allowed,
-                                    extra_sessions,
+                                    channel_sessions,
                                     voice_config,
                                     shared_session,
                                 )
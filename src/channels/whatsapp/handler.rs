@@ -5,8 +5,8 @@
 
 use crate::config::VoiceConfig;
 use crate::brain::agent::AgentService;
-use crate::services::SessionService;
-use std::collections::{HashMap, HashSet};
+use crate::services::{ChannelSessionService, SessionService};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use uuid::Uuid;
@@ -186,7 +186,7 @@ pub(crate) async fn handle_message(
     agent: Arc<AgentService>,
     session_svc: SessionService,
     allowed: Arc<HashSet<String>>,
-    extra_sessions: Arc<Mutex<HashMap<String, Uuid>>>,
+    channel_sessions: ChannelSessionService,
     voice_config: Arc<VoiceConfig>,
     shared_session: Arc<Mutex<Option<Uuid>>>,
 ) {
@@ -305,21 +305,15 @@ pub(crate) async fn handle_message(
             }
         }
     } else {
-        let mut map = extra_sessions.lock().await;
-        match map.get(&phone) {
-            Some(id) => *id,
-            None => {
-                let title = format!("WhatsApp: {}", phone);
-                match session_svc.create_session(Some(title)).await {
-                    Ok(session) => {
-                        map.insert(phone.clone(), session.id);
-                        session.id
-                    }
-                    Err(e) => {
-                        tracing::error!("WhatsApp: failed to create session: {}", e);
-                        return;
-                    }
-                }
+        let title = format!("WhatsApp: {}", phone);
+        match channel_sessions
+            .resolve_session("whatsapp", &phone, title)
+            .await
+        {
+            Ok(id) => id,
+            Err(e) => {
+                tracing::error!("WhatsApp: failed to resolve session: {}", e);
+                return;
             }
         }
     };
@@ -1,12 +1,38 @@
-//! CLI subcommands — run, init, config, db, keyring, logs, and config loading.
+//! CLI subcommands — run, init, config, db, keyring, logs, mcp, channels, and config loading.
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
 
 use crate::brain::prompt_builder::RuntimeInfo;
+use crate::brain::tools::ToolPolicy;
 use crate::brain::BrainLoader;
 
-use super::{DbCommands, KeyringCommands, LogCommands, OutputFormat};
+use super::{
+    ChannelCommands, DbCommands, KeyringCommands, LogCommands, McpCommands, OutputFormat,
+};
+
+/// Flags for a non-interactive `run`
+pub(crate) struct RunOptions {
+    pub auto_approve: bool,
+    pub format: OutputFormat,
+    /// Session ID to continue
+    pub session: Option<String>,
+    /// Continue the most recent session
+    pub continue_last: bool,
+    pub max_iterations: Option<usize>,
+    pub read_only: bool,
+}
+
+/// Read a prompt piped on stdin
+fn read_prompt(mut reader: impl std::io::Read) -> Result<String> {
+    let mut prompt = String::new();
+    reader
+        .read_to_string(&mut prompt)
+        .context("Failed to read prompt from stdin")?;
+    let prompt = prompt.trim();
+    anyhow::ensure!(!prompt.is_empty(), "Prompt from stdin is empty");
+    Ok(prompt.to_string())
+}
 
 /// Load configuration from file or defaults
 pub(crate) async fn load_config(config_path: Option<&str>) -> Result<crate::config::Config> {
@@ -215,53 +241,41 @@ pub(crate) async fn cmd_db(config: &crate::config::Config, operation: DbCommands
     }
 }
 
-/// Run a single command non-interactively
-pub(crate) async fn cmd_run(
+/// Build the registry of built-in tools shared by `run` and `mcp serve`
+pub(super) fn core_tool_registry(
     config: &crate::config::Config,
-    prompt: String,
-    auto_approve: bool,
-    format: OutputFormat,
-) -> Result<()> {
-    use crate::{
-        db::Database,
-        brain::{
-            agent::AgentService,
-            tools::{
-                bash::BashTool, brave_search::BraveSearchTool, code_exec::CodeExecTool,
-                config_tool::ConfigTool, context::ContextTool, doc_parser::DocParserTool,
-                edit::EditTool, exa_search::ExaSearchTool, glob::GlobTool, grep::GrepTool,
-                http::HttpClientTool, ls::LsTool, memory_search::MemorySearchTool,
-                notebook::NotebookEditTool, plan_tool::PlanTool,
-                read::ReadTool, registry::ToolRegistry, session_search::SessionSearchTool,
-                slash_command::SlashCommandTool,
-                task::TaskTool, web_search::WebSearchTool, write::WriteTool,
-            },
-        },
-        services::{ServiceContext, SessionService},
+    db: &crate::db::Database,
+) -> crate::brain::tools::ToolRegistry {
+    use crate::brain::tools::{
+        bash::BashTool, brave_search::BraveSearchTool, code_exec::CodeExecTool,
+        code_search::CodeSearchTool,
+        config_tool::ConfigTool, context::ContextTool, doc_parser::DocParserTool,
+        edit::EditTool, exa_search::ExaSearchTool, git::GitTool, glob::GlobTool, grep::GrepTool,
+        http::HttpClientTool, ls::LsTool, memory_search::MemorySearchTool,
+        memory_write::MemoryWriteTool,
+        notebook::NotebookEditTool, plan_tool::PlanTool,
+        read::ReadTool, registry::ToolRegistry, sandbox::Sandbox,
+        session_search::SessionSearchTool, slash_command::SlashCommandTool,
+        task::TaskTool, web_search::WebSearchTool, write::WriteTool,
     };
 
-    tracing::info!("Running non-interactive command: {}", prompt);
-
-    // Initialize database
-    let db = Database::connect(&config.database.path).await?;
-    db.run_migrations().await?;
-
-    // Select provider based on configuration using factory
-    let provider = crate::brain::provider::create_provider(config)?;
-
-    // Create tool registry
     let mut tool_registry = ToolRegistry::new();
     // Phase 1: Essential file operations
     tool_registry.register(Arc::new(ReadTool));
     tool_registry.register(Arc::new(WriteTool));
     tool_registry.register(Arc::new(EditTool));
-    tool_registry.register(Arc::new(BashTool));
+    tool_registry.register(Arc::new(BashTool::new(Sandbox::from_config(
+        &config.sandbox.bash,
+    ))));
     tool_registry.register(Arc::new(LsTool));
     tool_registry.register(Arc::new(GlobTool));
     tool_registry.register(Arc::new(GrepTool));
+    tool_registry.register(Arc::new(GitTool));
     // Phase 2: Advanced features
     tool_registry.register(Arc::new(WebSearchTool));
-    tool_registry.register(Arc::new(CodeExecTool));
+    tool_registry.register(Arc::new(CodeExecTool::new(Sandbox::from_config(
+        &config.sandbox.execute_code,
+    ))));
     tool_registry.register(Arc::new(NotebookEditTool));
     tool_registry.register(Arc::new(DocParserTool));
     // Phase 3: Workflow & integration
@@ -271,8 +285,12 @@ pub(crate) async fn cmd_run(
     tool_registry.register(Arc::new(PlanTool));
     // Memory search (built-in FTS5, always available)
     tool_registry.register(Arc::new(MemorySearchTool));
+    // Memory write — curated long-term entries, searchable via memory_search
+    tool_registry.register(Arc::new(MemoryWriteTool));
     // Session search — hybrid QMD search across all session message history
     tool_registry.register(Arc::new(SessionSearchTool::new(db.pool().clone())));
+    // Code search — hybrid QMD search over the working directory's source tree
+    tool_registry.register(Arc::new(CodeSearchTool));
     // Config management (read/write config.toml, commands.toml)
     tool_registry.register(Arc::new(ConfigTool));
     // Slash command invocation (agent can call any slash command)
@@ -291,6 +309,61 @@ pub(crate) async fn cmd_run(
     tool_registry.register(Arc::new(crate::brain::tools::Web3DeployTool));
     tool_registry.register(Arc::new(crate::brain::tools::Web3AutoRepairTool));
 
+    tool_registry
+}
+
+/// Run a single command non-interactively
+pub(crate) async fn cmd_run(
+    config: &crate::config::Config,
+    prompt: Option<String>,
+    options: RunOptions,
+) -> Result<()> {
+    use super::stream::RunEvent;
+    use crate::{
+        db::Database,
+        brain::agent::AgentService,
+        services::{ServiceContext, SessionService},
+    };
+
+    let RunOptions {
+        auto_approve,
+        format,
+        session,
+        continue_last,
+        max_iterations,
+        read_only,
+    } = options;
+
+    let prompt = match prompt.filter(|p| p != "-") {
+        Some(prompt) => prompt,
+        None => {
+            use std::io::IsTerminal;
+            let stdin = std::io::stdin();
+            if stdin.is_terminal() {
+                anyhow::bail!("No prompt given: pass it as an argument or pipe it via stdin");
+            }
+            read_prompt(stdin.lock())?
+        }
+    };
+
+    tracing::info!("Running non-interactive command: {}", prompt);
+
+    // Initialize database
+    let db = Database::connect(&config.database.path).await?;
+    db.run_migrations().await?;
+
+    // Select provider based on configuration using factory
+    let provider = crate::brain::provider::create_provider(config)?;
+
+    // Create tool registry
+    let mut tool_registry = core_tool_registry(config, &db);
+    tool_registry.set_policy(Arc::new(ToolPolicy::load(
+        config.agent.policy_file.as_deref(),
+    )?));
+
+    // External MCP tool servers from [mcp.servers]
+    crate::mcp::register_servers(&config.mcp, &mut tool_registry).await;
+
     // Build dynamic system brain from workspace files
     let brain_path = BrainLoader::resolve_path();
     let brain_loader = BrainLoader::new(brain_path.clone());
@@ -309,24 +382,70 @@ pub(crate) async fn cmd_run(
 
     // Create service context and agent service
     let service_context = ServiceContext::new(db.pool().clone());
-    let agent_service = AgentService::new(provider.clone(), service_context.clone())
+    let streaming = matches!(format, OutputFormat::StreamJson);
+    let mut agent_service = AgentService::new(provider.clone(), service_context.clone())
         .with_tool_registry(Arc::new(tool_registry))
         .with_system_brain(system_brain)
-;
+        .with_auto_approve_tools(auto_approve)
+        .with_progress_callback(streaming.then(super::stream::progress_callback));
+    if let Some(max) = max_iterations {
+        agent_service = agent_service.with_max_tool_iterations(max);
+    }
 
     // Create or get session
     let session_service = SessionService::new(service_context);
 
-    let session = session_service
-        .create_session(Some("CLI Run".to_string()))
-        .await?;
+    let existing = if let Some(id) = session {
+        let id = uuid::Uuid::parse_str(&id).with_context(|| format!("Invalid session ID: {}", id))?;
+        Some(session_service.get_session_required(id).await?)
+    } else if continue_last {
+        let recent = session_service.get_most_recent_session().await?;
+        if recent.is_none() {
+            tracing::info!("No previous session to continue, starting a new one");
+        }
+        recent
+    } else {
+        None
+    };
+    let session = match existing {
+        Some(session) => session,
+        None => {
+            session_service
+                .create_session(Some("CLI Run".to_string()))
+                .await?
+        }
+    };
 
     // Send message
-    println!("🤔 Processing...\n");
-    let response = agent_service.send_message(session.id, prompt, None).await?;
+    if streaming {
+        super::stream::emit(&RunEvent::Started {
+            session_id: session.id,
+            provider: provider.name(),
+            model: provider.default_model(),
+        });
+    } else if !matches!(format, OutputFormat::Json) {
+        println!("🤔 Processing...\n");
+    }
+    let response = match agent_service
+        .send_message_with_tools_and_mode(session.id, prompt, None, read_only, None)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            if streaming {
+                super::stream::emit(&RunEvent::Error {
+                    message: e.to_string(),
+                });
+            }
+            return Err(e.into());
+        }
+    };
 
     // Format and display output
     match format {
+        OutputFormat::StreamJson => {
+            super::stream::emit(&RunEvent::result(session.id, &response));
+        }
         OutputFormat::Text => {
             println!("{}", response.content);
             println!();
@@ -335,13 +454,16 @@ pub(crate) async fn cmd_run(
                 response.usage.input_tokens + response.usage.output_tokens
             );
             println!("💰 Cost: ${:.6}", response.cost);
+            println!("🔖 Session: {}", session.id);
         }
         OutputFormat::Json => {
             let output = serde_json::json!({
+                "session_id": session.id,
                 "content": response.content,
                 "usage": {
                     "input_tokens": response.usage.input_tokens,
                     "output_tokens": response.usage.output_tokens,
+                    "reasoning_tokens": response.usage.reasoning_tokens,
                 },
                 "cost": response.cost,
                 "model": response.model,
@@ -357,16 +479,107 @@ pub(crate) async fn cmd_run(
                 response.usage.input_tokens + response.usage.output_tokens
             );
             println!("**Cost:** ${:.6}", response.cost);
+            println!("**Session:** {}", session.id);
         }
     }
 
-    if auto_approve {
+    if auto_approve && !streaming {
         println!("\n⚠️  Auto-approve mode was enabled");
     }
 
     Ok(())
 }
 
+/// MCP commands
+pub(crate) async fn cmd_mcp(config: &crate::config::Config, operation: McpCommands) -> Result<()> {
+    use crate::{brain::tools::ToolExecutionContext, db::Database, mcp::McpServer};
+
+    match operation {
+        McpCommands::Serve { allow_tools } => {
+            // stdout carries the protocol — everything else goes to the log
+            let db = Database::connect(&config.database.path).await?;
+            db.run_migrations().await?;
+
+            let mut registry = core_tool_registry(config, &db);
+            registry.set_policy(Arc::new(ToolPolicy::load(
+                config.agent.policy_file.as_deref(),
+            )?));
+            let registry = Arc::new(registry);
+            let context = ToolExecutionContext::new(uuid::Uuid::new_v4());
+
+            if !allow_tools.is_empty() {
+                tracing::warn!("MCP serve: approval bypassed for {:?}", allow_tools);
+            }
+            tracing::info!("Serving {} tools over MCP stdio", registry.count());
+
+            let server = Arc::new(McpServer::new(registry, context).with_allowed_tools(allow_tools));
+            server
+                .serve_stdio()
+                .await
+                .context("MCP stdio server failed")?;
+            Ok(())
+        }
+    }
+}
+
+/// Channel session mapping commands
+pub(crate) async fn cmd_channels(
+    config: &crate::config::Config,
+    operation: ChannelCommands,
+) -> Result<()> {
+    use crate::{
+        db::Database,
+        services::{ChannelSessionService, ServiceContext, SessionService},
+    };
+
+    let db = Database::connect(&config.database.path).await?;
+    db.run_migrations().await?;
+    let context = ServiceContext::new(db.pool().clone());
+    let channel_sessions = ChannelSessionService::new(context.clone());
+
+    match operation {
+        ChannelCommands::List { channel } => {
+            let mappings = channel_sessions
+                .list_mappings(channel.as_deref().map(str::trim))
+                .await?;
+            if mappings.is_empty() {
+                println!("No channel sessions linked");
+                return Ok(());
+            }
+
+            let sessions = SessionService::new(context);
+            println!("🔗 Channel Sessions\n");
+            for mapping in mappings {
+                let title = sessions
+                    .get_session(mapping.session_id)
+                    .await?
+                    .and_then(|s| s.title)
+                    .unwrap_or_else(|| "Untitled".to_string());
+                println!(
+                    "{:<9} {:<24} {}  {}  (last seen {})",
+                    mapping.channel,
+                    mapping.external_id,
+                    mapping.session_id,
+                    title,
+                    mapping.last_seen.format("%Y-%m-%d %H:%M")
+                );
+            }
+            Ok(())
+        }
+        ChannelCommands::Unlink { channel, user_id } => {
+            if channel_sessions.unlink(&channel, &user_id).await? {
+                println!(
+                    "✅ Unlinked {} user {} — their next message starts a new session",
+                    channel, user_id
+                );
+            } else {
+                println!("No session linked for {} user {}", channel, user_id);
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Keyring management commands
 pub(crate) async fn cmd_keyring(operation: KeyringCommands) -> Result<()> {
     use crate::config::secrets::SecretString;
@@ -612,3 +825,19 @@ pub(crate) async fn cmd_logs(operation: LogCommands) -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_prompt_trims_piped_input() {
+        let prompt = read_prompt("  fix the build\n\n".as_bytes()).unwrap();
+        assert_eq!(prompt, "fix the build");
+    }
+
+    #[test]
+    fn test_read_prompt_rejects_empty_input() {
+        assert!(read_prompt(" \n".as_bytes()).is_err());
+    }
+}
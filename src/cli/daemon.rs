@@ -0,0 +1,220 @@
+//! Headless daemon — runs every enabled channel plus memory indexing without
+//! the TUI, guarded by a PID/lock file and stopped gracefully on SIGTERM.
+
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::brain::BrainLoader;
+use crate::brain::prompt_builder::RuntimeInfo;
+use crate::daemon::{PidFile, default_pid_path, is_process_alive, read_pid, running_daemon};
+
+use super::DaemonCommands;
+
+/// How long `daemon stop` waits for the process to exit
+const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Daemon lifecycle commands
+pub(crate) async fn cmd_daemon(
+    config: &crate::config::Config,
+    operation: DaemonCommands,
+) -> Result<()> {
+    let pid_path = default_pid_path();
+
+    match operation {
+        DaemonCommands::Start => run_daemon(config).await,
+        DaemonCommands::Status => {
+            match read_pid(&pid_path) {
+                Some(pid) if is_process_alive(pid) => {
+                    println!("🦀 OpenCrabs daemon is running (pid {})", pid);
+                }
+                Some(pid) => {
+                    println!(
+                        "OpenCrabs daemon is not running (stale PID file for {})",
+                        pid
+                    );
+                }
+                None => println!("OpenCrabs daemon is not running"),
+            }
+            Ok(())
+        }
+        DaemonCommands::Stop => {
+            let Some(pid) = running_daemon(&pid_path) else {
+                println!("OpenCrabs daemon is not running");
+                return Ok(());
+            };
+
+            println!("Stopping OpenCrabs daemon (pid {})...", pid);
+            crate::daemon::terminate(pid)?;
+
+            let deadline = tokio::time::Instant::now() + STOP_TIMEOUT;
+            while is_process_alive(pid) {
+                if tokio::time::Instant::now() >= deadline {
+                    anyhow::bail!(
+                        "Daemon (pid {}) did not exit within {}s",
+                        pid,
+                        STOP_TIMEOUT.as_secs()
+                    );
+                }
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            println!("✅ Daemon stopped");
+            Ok(())
+        }
+        DaemonCommands::Install => {
+            let path = crate::daemon::install_service().map_err(|e| anyhow::anyhow!(e))?;
+            println!("✅ Installed service: {}", path.display());
+            #[cfg(target_os = "linux")]
+            println!("   Start it now with: systemctl --user start opencrabs");
+            Ok(())
+        }
+    }
+}
+
+/// Run channels headless until SIGTERM / Ctrl+C
+async fn run_daemon(config: &crate::config::Config) -> Result<()> {
+    use crate::{
+        brain::tools::delegate::DelegateTool, channels::ChannelFactory, db::Database,
+        services::ServiceContext,
+    };
+
+    let pid_file = PidFile::acquire(default_pid_path())?;
+    tracing::info!(
+        "OpenCrabs daemon starting (pid {}, lock {})",
+        std::process::id(),
+        pid_file.path().display()
+    );
+
+    // Initialize database
+    let db = Database::connect(&config.database.path)
+        .await
+        .context("Failed to connect to database")?;
+    db.run_migrations()
+        .await
+        .context("Failed to run database migrations")?;
+
+    // Select provider based on configuration using factory
+    let provider = crate::brain::provider::create_provider(config)?;
+    let service_context = ServiceContext::new(db.pool().clone());
+    let working_directory = std::env::current_dir().unwrap_or_default();
+
+    // Build dynamic system brain from workspace files
+    let brain_path = BrainLoader::resolve_path();
+    let runtime_info = RuntimeInfo {
+        model: Some(provider.default_model().to_string()),
+        provider: Some(provider.name().to_string()),
+        working_directory: Some(working_directory.to_string_lossy().to_string()),
+    };
+    let system_brain =
+        BrainLoader::new(brain_path.clone()).build_system_brain(Some(&runtime_info), None);
+
+    // Channels answer on their own, so no shared TUI session to start from
+    let channel_factory = Arc::new(ChannelFactory::new(
+        provider.clone(),
+        service_context.clone(),
+        system_brain,
+        working_directory,
+        brain_path,
+        Arc::new(tokio::sync::Mutex::new(None)),
+        config.voice.clone(),
+    ));
+    let channel_states = crate::channels::ChannelStates::new();
+
+    // Same tools as the TUI, minus the ones that need a terminal
+    let mut tool_registry = super::commands::core_tool_registry(config, &db);
+    tool_registry.set_policy(Arc::new(crate::brain::tools::ToolPolicy::load(
+        config.agent.policy_file.as_deref(),
+    )?));
+    let delegate_tool = Arc::new(DelegateTool::new(
+        provider.clone(),
+        service_context.clone(),
+        None,
+        None,
+    ));
+    tool_registry.register(delegate_tool.clone());
+    #[cfg(feature = "telegram")]
+    tool_registry.register(Arc::new(
+        crate::brain::tools::telegram_send::TelegramSendTool::new(channel_states.telegram.clone()),
+    ));
+    #[cfg(feature = "whatsapp")]
+    tool_registry.register(Arc::new(
+        crate::brain::tools::whatsapp_send::WhatsAppSendTool::new(channel_states.whatsapp.clone()),
+    ));
+    #[cfg(feature = "discord")]
+    tool_registry.register(Arc::new(
+        crate::brain::tools::discord_send::DiscordSendTool::new(channel_states.discord.clone()),
+    ));
+    #[cfg(feature = "slack")]
+    tool_registry.register(Arc::new(
+        crate::brain::tools::slack_send::SlackSendTool::new(channel_states.slack.clone()),
+    ));
+    crate::mcp::register_servers(&config.mcp, &mut tool_registry).await;
+
+    let shared_tool_registry = Arc::new(tool_registry);
+    channel_factory.set_tool_registry(shared_tool_registry.clone());
+    delegate_tool.set_tool_registry(shared_tool_registry);
+
+    // Index existing memory files and warm up embedding engine in the background
+    let memory_handle = crate::memory::spawn_startup_index();
+
+    let mut handles = crate::channels::spawn_channels(config, &channel_factory, &channel_states);
+
+    // Start HTTP/WebSocket gateway if enabled
+    if config.gateway.enabled {
+        match crate::gateway::GatewayServer::new(&config.gateway, channel_factory.clone()) {
+            Ok(server) => match server.start().await {
+                Ok((addr, handle)) => {
+                    tracing::info!("Gateway listening on {}", addr);
+                    handles.push(handle);
+                }
+                Err(e) => tracing::error!("Failed to start gateway: {}", e),
+            },
+            Err(e) => tracing::error!("Gateway misconfigured: {}", e),
+        }
+    }
+
+    if handles.is_empty() {
+        tracing::warn!("Daemon: no channels or gateway enabled — only memory indexing will run");
+    }
+    tracing::info!("🦀 OpenCrabs daemon running ({} services)", handles.len());
+
+    shutdown_signal().await;
+
+    tracing::info!("OpenCrabs daemon shutting down");
+    memory_handle.abort();
+    for handle in &handles {
+        handle.abort();
+    }
+    for handle in handles {
+        let _ = handle.await;
+    }
+
+    drop(pid_file);
+    tracing::info!("OpenCrabs daemon stopped");
+    Ok(())
+}
+
+/// Resolve on SIGTERM (service manager / `daemon stop`) or Ctrl+C
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = sigterm.recv() => tracing::info!("Received SIGTERM"),
+                    _ = tokio::signal::ctrl_c() => tracing::info!("Received Ctrl+C"),
+                }
+                return;
+            }
+            Err(e) => tracing::warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        tracing::error!("Failed to listen for Ctrl+C: {}", e);
+        // Without any signal source, park forever rather than exit immediately
+        std::future::pending::<()>().await;
+    }
+}
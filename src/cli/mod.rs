@@ -3,6 +3,8 @@
 //! Command-line interface for OpenCrabs using Clap v4.
 
 mod commands;
+mod daemon;
+mod stream;
 mod ui;
 
 use anyhow::Result;
@@ -44,16 +46,33 @@ pub enum Commands {
 
     /// Run a single command non-interactively
     Run {
-        /// The prompt to execute
-        prompt: String,
+        /// The prompt to execute (read from stdin when omitted or "-")
+        prompt: Option<String>,
 
-        /// Auto-approve all tool executions (dangerous!)
+        /// Auto-approve all tool executions (dangerous!). Without it, tool
+        /// calls that need approval are refused.
         #[arg(long, alias = "yolo")]
         auto_approve: bool,
 
-        /// Output format
-        #[arg(short, long, default_value = "text")]
+        /// Output format (stream-json: one JSON event per line, for CI/wrappers)
+        #[arg(short, long, alias = "output", default_value = "text")]
         format: OutputFormat,
+
+        /// Session ID to continue instead of starting a new one
+        #[arg(short, long, conflicts_with = "continue_last")]
+        session: Option<String>,
+
+        /// Continue the most recent session (long-only: `-c` is the global `--config`)
+        #[arg(long = "continue")]
+        continue_last: bool,
+
+        /// Maximum tool-call iterations; the run fails when they run out (0 = unlimited)
+        #[arg(long)]
+        max_iterations: Option<usize>,
+
+        /// Only allow read-only tools
+        #[arg(long)]
+        read_only: bool,
     },
 
     /// Initialize configuration
@@ -87,6 +106,65 @@ pub enum Commands {
         #[command(subcommand)]
         operation: KeyringCommands,
     },
+
+    /// Model Context Protocol integration
+    Mcp {
+        #[command(subcommand)]
+        operation: McpCommands,
+    },
+
+    /// Run channels and memory indexing headless (no TUI)
+    Daemon {
+        #[command(subcommand)]
+        operation: Option<DaemonCommands>,
+    },
+
+    /// Manage channel user → session mappings (Telegram, Discord, Slack, WhatsApp)
+    Channels {
+        #[command(subcommand)]
+        operation: ChannelCommands,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DaemonCommands {
+    /// Start the daemon in the foreground (default)
+    Start,
+    /// Show whether the daemon is running
+    Status,
+    /// Stop a running daemon gracefully
+    Stop,
+    /// Write and enable a systemd user unit (LaunchAgent on macOS)
+    Install,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ChannelCommands {
+    /// List which session each channel user is linked to
+    List {
+        /// Only show one channel (telegram, discord, slack, whatsapp).
+        /// Long-only: `-c` is the global `--config`.
+        #[arg(long)]
+        channel: Option<String>,
+    },
+    /// Unlink a user so their next message starts a fresh session
+    Unlink {
+        /// Channel name (telegram, discord, slack, whatsapp)
+        channel: String,
+        /// Channel-specific user ID (as shown by `channels list`)
+        user_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum McpCommands {
+    /// Serve OpenCrabs tools to an MCP host over stdio
+    Serve {
+        /// Expose a tool the permission policy would ask about (repeatable or
+        /// comma-separated; "*" exposes all of them). Policy denials still apply.
+        #[arg(long = "allow-tool", value_name = "TOOL", value_delimiter = ',')]
+        allow_tools: Vec<String>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -151,6 +229,8 @@ pub enum OutputFormat {
     Text,
     Json,
     Markdown,
+    /// Newline-delimited JSON: every progress event, then the final result
+    StreamJson,
 }
 
 /// Main CLI entry point
@@ -199,11 +279,32 @@ pub async fn run() -> Result<()> {
         Some(Commands::Db { operation }) => commands::cmd_db(&config, operation).await,
         Some(Commands::Logs { operation }) => commands::cmd_logs(operation).await,
         Some(Commands::Keyring { operation }) => commands::cmd_keyring(operation).await,
+        Some(Commands::Mcp { operation }) => commands::cmd_mcp(&config, operation).await,
+        Some(Commands::Daemon { operation }) => {
+            daemon::cmd_daemon(&config, operation.unwrap_or(DaemonCommands::Start)).await
+        }
+        Some(Commands::Channels { operation }) => {
+            commands::cmd_channels(&config, operation).await
+        }
         Some(Commands::Run {
             prompt,
             auto_approve,
             format,
-        }) => commands::cmd_run(&config, prompt, auto_approve, format).await,
+            session,
+            continue_last,
+            max_iterations,
+            read_only,
+        }) => {
+            let options = commands::RunOptions {
+                auto_approve,
+                format,
+                session,
+                continue_last,
+                max_iterations,
+                read_only,
+            };
+            commands::cmd_run(&config, prompt, options).await
+        }
     }
 }
 
@@ -216,4 +317,70 @@ mod tests {
         use clap::CommandFactory;
         Cli::command().debug_assert();
     }
+
+    #[test]
+    fn test_run_accepts_stream_json_output() {
+        let cli = Cli::try_parse_from(["opencrabs", "run", "--output", "stream-json", "hi"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Run {
+                format: OutputFormat::StreamJson,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_run_resume_flags() {
+        let cli = Cli::try_parse_from([
+            "opencrabs",
+            "run",
+            "--continue",
+            "--read-only",
+            "--max-iterations",
+            "5",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Run {
+                prompt: None,
+                continue_last: true,
+                read_only: true,
+                max_iterations: Some(5),
+                ..
+            })
+        ));
+
+        // -c stays the global config flag
+        let cli = Cli::try_parse_from(["opencrabs", "run", "--continue", "-c", "custom.toml"]).unwrap();
+        assert_eq!(cli.config.as_deref(), Some("custom.toml"));
+
+        // A session can't be both explicit and "most recent"
+        assert!(
+            Cli::try_parse_from(["opencrabs", "run", "--session", "abc", "--continue", "hi"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_channels_list_filter_leaves_c_for_config() {
+        let cli = Cli::try_parse_from([
+            "opencrabs",
+            "channels",
+            "list",
+            "--channel",
+            "telegram",
+            "-c",
+            "custom.toml",
+        ])
+        .unwrap();
+        assert_eq!(cli.config.as_deref(), Some("custom.toml"));
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Channels {
+                operation: ChannelCommands::List { channel: Some(ref c) }
+            }) if c == "telegram"
+        ));
+    }
 }
@@ -0,0 +1,141 @@
+//! NDJSON event stream for `run --output stream-json`.
+//!
+//! Every `ProgressEvent` is written to stdout as one JSON object per line,
+//! framed by a `started` event and a final `result` (or `error`) event that
+//! carries usage and cost. Logs never go to stdout, so the stream stays
+//! machine-readable.
+
+use crate::brain::agent::{AgentResponse, ProgressCallback};
+use crate::brain::provider::StopReason;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Events that frame a run, interleaved with serialized `ProgressEvent`s
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum RunEvent<'a> {
+    /// The run has a session and is about to call the model
+    Started {
+        session_id: Uuid,
+        provider: &'a str,
+        model: &'a str,
+    },
+    /// Final answer with usage accumulated across all tool iterations
+    Result {
+        session_id: Uuid,
+        content: &'a str,
+        stop_reason: Option<&'a StopReason>,
+        usage: Usage,
+        cost: f64,
+        model: &'a str,
+    },
+    /// The run failed; no `result` follows
+    Error { message: String },
+}
+
+/// Token usage in the final `result` event
+#[derive(Debug, Serialize)]
+pub(crate) struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Only reported by reasoning models; part of `output_tokens`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub reasoning_tokens: u32,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl<'a> RunEvent<'a> {
+    pub(crate) fn result(session_id: Uuid, response: &'a AgentResponse) -> Self {
+        RunEvent::Result {
+            session_id,
+            content: &response.content,
+            stop_reason: response.stop_reason.as_ref(),
+            usage: Usage {
+                input_tokens: response.usage.input_tokens,
+                output_tokens: response.usage.output_tokens,
+                reasoning_tokens: response.usage.reasoning_tokens,
+            },
+            cost: response.cost,
+            model: &response.model,
+        }
+    }
+}
+
+/// Write one event as a single line on stdout
+pub(crate) fn emit<T: Serialize>(event: &T) {
+    match serde_json::to_string(event) {
+        Ok(line) => {
+            let mut stdout = std::io::stdout().lock();
+            // A closed pipe just means nobody is listening any more
+            let _ = writeln!(stdout, "{}", line).and_then(|_| stdout.flush());
+        }
+        Err(e) => tracing::error!("Failed to serialize stream event: {}", e),
+    }
+}
+
+/// Progress callback that streams every agent event as NDJSON
+pub(crate) fn progress_callback() -> ProgressCallback {
+    Arc::new(|event| emit(&event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brain::agent::ProgressEvent;
+    use crate::brain::provider::TokenUsage;
+    use serde_json::json;
+
+    #[test]
+    fn test_progress_events_are_tagged_by_type() {
+        let event = ProgressEvent::ToolStarted {
+            tool_name: "read_file".to_string(),
+            tool_input: json!({"path": "src/main.rs"}),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "tool_started",
+                "tool_name": "read_file",
+                "tool_input": {"path": "src/main.rs"}
+            })
+        );
+    }
+
+    #[test]
+    fn test_result_event_carries_usage_and_cost() {
+        let session_id = Uuid::new_v4();
+        let response = AgentResponse {
+            message_id: Uuid::new_v4(),
+            content: "Done.".to_string(),
+            stop_reason: Some(StopReason::EndTurn),
+            usage: TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                reasoning_tokens: 0,
+            },
+            context_tokens: 120,
+            cost: 0.0015,
+            model: "mock-model".to_string(),
+        };
+
+        let line = serde_json::to_string(&RunEvent::result(session_id, &response)).unwrap();
+        assert!(!line.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&line).unwrap(),
+            json!({
+                "type": "result",
+                "session_id": session_id,
+                "content": "Done.",
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 120, "output_tokens": 30},
+                "cost": 0.0015,
+                "model": "mock-model"
+            })
+        );
+    }
+}
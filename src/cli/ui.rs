@@ -1,4 +1,4 @@
-//! TUI chat startup — provider init, tool registry, approval callbacks, Telegram spawn.
+//! TUI chat startup — provider init, tool registry, approval callbacks, channel spawn.
 
 use anyhow::{Context, Result};
 use std::sync::Arc;
@@ -18,12 +18,14 @@ pub(crate) async fn cmd_chat(
             agent::AgentService,
             tools::{
                 bash::BashTool, brave_search::BraveSearchTool, code_exec::CodeExecTool,
+                code_search::CodeSearchTool,
                 config_tool::ConfigTool, context::ContextTool, doc_parser::DocParserTool,
-                edit::EditTool, exa_search::ExaSearchTool, glob::GlobTool, grep::GrepTool,
-                http::HttpClientTool, ls::LsTool, memory_search::MemorySearchTool,
+                edit::EditTool, exa_search::ExaSearchTool, git::GitTool, glob::GlobTool,
+                grep::GrepTool, http::HttpClientTool, ls::LsTool, memory_search::MemorySearchTool,
+                memory_write::MemoryWriteTool,
                 notebook::NotebookEditTool, plan_tool::PlanTool,
-                read::ReadTool, registry::ToolRegistry, session_search::SessionSearchTool,
-                slash_command::SlashCommandTool,
+                read::ReadTool, registry::ToolRegistry, sandbox::Sandbox,
+                session_search::SessionSearchTool, slash_command::SlashCommandTool,
                 task::TaskTool, web_search::WebSearchTool, write::WriteTool,
             },
         },
@@ -69,17 +71,25 @@ pub(crate) async fn cmd_chat(
     // Create tool registry
     tracing::debug!("Setting up tool registry");
     let mut tool_registry = ToolRegistry::new();
+    tool_registry.set_policy(Arc::new(crate::brain::tools::ToolPolicy::load(
+        config.agent.policy_file.as_deref(),
+    )?));
     // Phase 1: Essential file operations
     tool_registry.register(Arc::new(ReadTool));
     tool_registry.register(Arc::new(WriteTool));
     tool_registry.register(Arc::new(EditTool));
-    tool_registry.register(Arc::new(BashTool));
+    tool_registry.register(Arc::new(BashTool::new(Sandbox::from_config(
+        &config.sandbox.bash,
+    ))));
     tool_registry.register(Arc::new(LsTool));
     tool_registry.register(Arc::new(GlobTool));
     tool_registry.register(Arc::new(GrepTool));
+    tool_registry.register(Arc::new(GitTool));
     // Phase 2: Advanced features
     tool_registry.register(Arc::new(WebSearchTool));
-    tool_registry.register(Arc::new(CodeExecTool));
+    tool_registry.register(Arc::new(CodeExecTool::new(Sandbox::from_config(
+        &config.sandbox.execute_code,
+    ))));
     tool_registry.register(Arc::new(NotebookEditTool));
     tool_registry.register(Arc::new(DocParserTool));
     // Phase 3: Workflow & integration
@@ -89,8 +99,12 @@ pub(crate) async fn cmd_chat(
     tool_registry.register(Arc::new(PlanTool));
     // Memory search (built-in FTS5, always available)
     tool_registry.register(Arc::new(MemorySearchTool));
+    // Memory write — curated long-term entries, searchable via memory_search
+    tool_registry.register(Arc::new(MemoryWriteTool));
     // Session search — hybrid QMD search across all session message history
     tool_registry.register(Arc::new(SessionSearchTool::new(db.pool().clone())));
+    // Code search — hybrid QMD search over the working directory's source tree
+    tool_registry.register(Arc::new(CodeSearchTool));
     // Config management (read/write config.toml, commands.toml)
     tool_registry.register(Arc::new(ConfigTool));
     // Slash command invocation (agent can call any slash command)
@@ -113,26 +127,14 @@ pub(crate) async fn cmd_chat(
     tool_registry.register(Arc::new(crate::brain::tools::Web3AutoRepairTool));
     tracing::info!("Registered Web3 tools (test, report_read, deploy, auto_repair)");
 
+    // External MCP tool servers from [mcp.servers]
+    let mcp_tools = crate::mcp::register_servers(&config.mcp, &mut tool_registry).await;
+    if mcp_tools > 0 {
+        tracing::info!("Registered {} MCP tools", mcp_tools);
+    }
+
     // Index existing memory files and warm up embedding engine in the background
-    tokio::spawn(async {
-        match crate::memory::get_store() {
-            Ok(store) => {
-                match crate::memory::reindex(store).await {
-                    Ok(n) => tracing::info!("Startup memory reindex: {n} files"),
-                    Err(e) => tracing::warn!("Startup memory reindex failed: {e}"),
-                }
-            }
-            Err(e) => tracing::warn!("Memory store init failed at startup: {e}"),
-        }
-        // Warm up embedding engine so first search doesn't pay model download cost.
-        // reindex() already calls get_engine() during backfill, but if all docs were
-        // already embedded, this ensures the engine is ready for search.
-        match tokio::task::spawn_blocking(crate::memory::get_engine).await {
-            Ok(Ok(_)) => tracing::info!("Embedding engine warmed up"),
-            Ok(Err(e)) => tracing::warn!("Embedding engine init skipped: {e}"),
-            Err(e) => tracing::warn!("Embedding engine warmup failed: {e}"),
-        }
-    });
+    crate::memory::spawn_startup_index();
 
     // Create service context
     let service_context = ServiceContext::new(db.pool().clone());
@@ -229,7 +231,7 @@ pub(crate) async fn cmd_chat(
                 )
             })?;
 
-            Ok(response.approved)
+            Ok(response.decision())
         })
     });
 
@@ -262,6 +264,41 @@ pub(crate) async fn cmd_chat(
             ProgressEvent::RestartReady { status } => {
                 progress_sender.send(TuiEvent::RestartReady(status))
             }
+            ProgressEvent::ProviderSwitched { from, to, reason } => {
+                progress_sender.send(TuiEvent::SystemMessage(format!(
+                    "Provider switched: {} → {} ({})",
+                    from, to, reason
+                )))
+            }
+            ProgressEvent::MemoryRecalled { memories } => {
+                let mut text = format!("Recalled from memory ({}):", memories.len());
+                for memory in &memories {
+                    let mut preview: String = memory.snippet.chars().take(80).collect();
+                    if preview.len() < memory.snippet.len() {
+                        preview.push('…');
+                    }
+                    text.push_str(&format!("\n  • {}: {}", memory.source, preview));
+                }
+                progress_sender.send(TuiEvent::SystemMessage(text))
+            }
+            ProgressEvent::SandboxViolation { tool_name, detail, .. } => {
+                progress_sender.send(TuiEvent::SystemMessage(format!("🛡️ {}: {}", tool_name, detail)))
+            }
+            ProgressEvent::Subagent { agent, event } => match *event {
+                ProgressEvent::ToolStarted { tool_name, tool_input } => progress_sender
+                    .send(TuiEvent::SubagentToolCallStarted { agent, tool_name, tool_input }),
+                ProgressEvent::ToolCompleted { tool_name, tool_input, success, summary } => {
+                    progress_sender.send(TuiEvent::SubagentToolCallCompleted {
+                        agent,
+                        tool_name,
+                        tool_input,
+                        success,
+                        summary,
+                    })
+                }
+                // The child's text and status stay out of the parent's transcript
+                _ => return,
+            },
         };
         if let Err(e) = result {
             tracing::error!("Progress event channel closed: {}", e);
@@ -280,6 +317,19 @@ pub(crate) async fn cmd_chat(
         crate::brain::tools::rebuild::RebuildTool::new(Some(progress_callback.clone())),
     ));
 
+    // Register delegate tool (sub-agents share the approval + progress callbacks).
+    // Its registry is set lazily after Arc wrapping, like the channel factory's.
+    let delegate_tool = Arc::new(crate::brain::tools::delegate::DelegateTool::new(
+        provider.clone(),
+        service_context.clone(),
+        Some(approval_callback.clone()),
+        Some(progress_callback.clone()),
+    ));
+    tool_registry.register(delegate_tool.clone());
+
+    // Shared per-channel state for proactive messaging (connect + send tools + running bots)
+    let channel_states = crate::channels::ChannelStates::new();
+
     // Create ChannelFactory (shared by static channel spawn + WhatsApp connect tool).
     // Tool registry is set lazily after Arc wrapping to break circular dependency.
     let channel_factory = Arc::new(crate::channels::ChannelFactory::new(
@@ -292,81 +342,65 @@ pub(crate) async fn cmd_chat(
         config.voice.clone(),
     ));
 
-    // Shared Telegram state for proactive messaging
-    #[cfg(feature = "telegram")]
-    let telegram_state = Arc::new(crate::channels::telegram::TelegramState::new());
-
     // Register Telegram connect tool (agent-callable bot setup)
     #[cfg(feature = "telegram")]
     tool_registry.register(Arc::new(
         crate::brain::tools::telegram_connect::TelegramConnectTool::new(
             channel_factory.clone(),
-            telegram_state.clone(),
+            channel_states.telegram.clone(),
         ),
     ));
 
     // Register Telegram send tool (proactive messaging)
     #[cfg(feature = "telegram")]
     tool_registry.register(Arc::new(
-        crate::brain::tools::telegram_send::TelegramSendTool::new(telegram_state.clone()),
+        crate::brain::tools::telegram_send::TelegramSendTool::new(channel_states.telegram.clone()),
     ));
 
-    // Shared WhatsApp state for proactive messaging (connect + send tools + static agent)
-    #[cfg(feature = "whatsapp")]
-    let whatsapp_state = Arc::new(crate::channels::whatsapp::WhatsAppState::new());
-
     // Register WhatsApp connect tool (agent-callable QR pairing)
     #[cfg(feature = "whatsapp")]
     tool_registry.register(Arc::new(
         crate::brain::tools::whatsapp_connect::WhatsAppConnectTool::new(
             Some(progress_callback.clone()),
             channel_factory.clone(),
-            whatsapp_state.clone(),
+            channel_states.whatsapp.clone(),
         ),
     ));
 
     // Register WhatsApp send tool (proactive messaging)
     #[cfg(feature = "whatsapp")]
     tool_registry.register(Arc::new(
-        crate::brain::tools::whatsapp_send::WhatsAppSendTool::new(whatsapp_state.clone()),
+        crate::brain::tools::whatsapp_send::WhatsAppSendTool::new(channel_states.whatsapp.clone()),
     ));
 
-    // Shared Discord state for proactive messaging
-    #[cfg(feature = "discord")]
-    let discord_state = Arc::new(crate::channels::discord::DiscordState::new());
-
     // Register Discord connect tool (agent-callable bot setup)
     #[cfg(feature = "discord")]
     tool_registry.register(Arc::new(
         crate::brain::tools::discord_connect::DiscordConnectTool::new(
             channel_factory.clone(),
-            discord_state.clone(),
+            channel_states.discord.clone(),
         ),
     ));
 
     // Register Discord send tool (proactive messaging)
     #[cfg(feature = "discord")]
     tool_registry.register(Arc::new(
-        crate::brain::tools::discord_send::DiscordSendTool::new(discord_state.clone()),
+        crate::brain::tools::discord_send::DiscordSendTool::new(channel_states.discord.clone()),
     ));
 
-    // Shared Slack state for proactive messaging
-    #[cfg(feature = "slack")]
-    let slack_state = Arc::new(crate::channels::slack::SlackState::new());
-
     // Register Slack connect tool (agent-callable bot setup)
     #[cfg(feature = "slack")]
     tool_registry.register(Arc::new(
         crate::brain::tools::slack_connect::SlackConnectTool::new(
             channel_factory.clone(),
-            slack_state.clone(),
+            channel_states.slack.clone(),
         ),
     ));
 
     // Register Slack send tool (proactive messaging)
     #[cfg(feature = "slack")]
     tool_registry.register(Arc::new(
-        crate::brain::tools::slack_send::SlackSendTool::new(slack_state.clone()),
+        crate::brain::tools::slack_send::SlackSendTool::new(channel_states.slack.clone()),
     ));
 
     // Create sudo password callback that sends requests to TUI
@@ -421,6 +455,7 @@ pub(crate) async fn cmd_chat(
 
     // Now that the registry is Arc'd, give it to the channel factory
     channel_factory.set_tool_registry(shared_tool_registry.clone());
+    delegate_tool.set_tool_registry(shared_tool_registry.clone());
 
     let agent_service = Arc::new(
         AgentService::new(provider.clone(), service_context.clone())
@@ -449,136 +484,40 @@ pub(crate) async fn cmd_chat(
         app.resume_session_id = Some(uuid);
     }
 
-    // Spawn Telegram bot if configured
-    #[cfg(feature = "telegram")]
-    let _telegram_handle = {
-        let tg = &config.channels.telegram;
-        let tg_token = tg.token.clone().or_else(|| std::env::var("TELEGRAM_BOT_TOKEN").ok());
-        if tg.enabled || tg_token.is_some() {
-            if let Some(ref token) = tg_token {
-                let tg_agent = channel_factory.create_agent_service();
-                // Extract OpenAI API key for TTS (from providers.tts.openai)
-                let openai_key = config.providers.tts.as_ref()
-                    .and_then(|t| t.openai.as_ref())
-                    .and_then(|p| p.api_key.clone());
-                // Extract STT provider config from providers.stt.*
-                let mut voice_cfg = config.voice.clone();
-                voice_cfg.stt_provider = config.providers.stt.as_ref()
-                    .and_then(|s| s.groq.clone());
-                voice_cfg.tts_provider = config.providers.tts.as_ref()
-                    .and_then(|t| t.openai.clone());
-                let bot = crate::channels::telegram::TelegramAgent::new(
-                    tg_agent,
-                    service_context.clone(),
-                    tg.allowed_users.clone(),
-                    voice_cfg,
-                    openai_key,
-                    app.shared_session_id(),
-                    telegram_state.clone(),
-                    tg.respond_to.clone(),
-                    tg.allowed_channels.clone(),
-                );
-                tracing::info!("Spawning Telegram bot ({} allowed users)", tg.allowed_users.len());
-                Some(bot.start(token.clone()))
-            } else {
-                tracing::warn!("Telegram enabled but no token configured");
-                None
-            }
-        } else {
-            None
-        }
-    };
-
-    // Spawn WhatsApp agent if configured (already paired via session.db)
-    #[cfg(feature = "whatsapp")]
-    let _whatsapp_handle = {
-        let wa = &config.channels.whatsapp;
-        if wa.enabled {
-            let wa_agent = crate::channels::whatsapp::WhatsAppAgent::new(
-                channel_factory.create_agent_service(),
-                service_context.clone(),
-                wa.allowed_phones.clone(),
-                config.voice.clone(),
-                app.shared_session_id(),
-                whatsapp_state.clone(),
-            );
-            tracing::info!(
-                "Spawning WhatsApp agent ({} allowed phones)",
-                wa.allowed_phones.len()
-            );
-            Some(wa_agent.start())
-        } else {
-            None
-        }
-    };
+    // A running daemon already serves the channels and gateway; starting them
+    // again would answer every message twice
+    let daemon_pid = crate::daemon::running_daemon(&crate::daemon::default_pid_path());
+    if let Some(pid) = daemon_pid {
+        tracing::info!(
+            "OpenCrabs daemon is running (pid {}), leaving channels and gateway to it",
+            pid
+        );
+    }
 
-    // Spawn Discord bot if configured (token-based, like Telegram)
-    #[cfg(feature = "discord")]
-    let _discord_handle = {
-        let dc = &config.channels.discord;
-        let dc_token = dc.token.clone().or_else(|| std::env::var("DISCORD_BOT_TOKEN").ok());
-        if dc.enabled || dc_token.is_some() {
-            if let Some(ref token) = dc_token {
-                let dc_agent = crate::channels::discord::DiscordAgent::new(
-                    channel_factory.create_agent_service(),
-                    service_context.clone(),
-                    dc.allowed_users.clone(),
-                    config.voice.clone(),
-                    app.shared_session_id(),
-                    discord_state.clone(),
-                    dc.respond_to.clone(),
-                    dc.allowed_channels.clone(),
-                );
-                tracing::info!(
-                    "Spawning Discord bot ({} allowed users)",
-                    dc.allowed_users.len()
-                );
-                Some(dc_agent.start(token.clone()))
-            } else {
-                tracing::warn!("Discord enabled but no token configured");
-                None
-            }
-        } else {
-            None
-        }
+    // Spawn every configured channel (Telegram, WhatsApp, Discord, Slack)
+    let _channel_handles = if daemon_pid.is_none() {
+        crate::channels::spawn_channels(config, &channel_factory, &channel_states)
+    } else {
+        Vec::new()
     };
 
-    // Spawn Slack bot if configured (needs both bot token + app token for Socket Mode)
-    #[cfg(feature = "slack")]
-    let _slack_handle = {
-        let sl = &config.channels.slack;
-        let sl_token = sl.token.clone().or_else(|| std::env::var("SLACK_BOT_TOKEN").ok());
-        let sl_app_token = sl
-            .app_token
-            .clone()
-            .or_else(|| std::env::var("SLACK_APP_TOKEN").ok());
-        if sl.enabled || sl_token.is_some() {
-            if let (Some(bot_tok), Some(app_tok)) = (sl_token, sl_app_token) {
-                let sl_agent = crate::channels::slack::SlackAgent::new(
-                    channel_factory.create_agent_service(),
-                    service_context.clone(),
-                    sl.allowed_ids.clone(),
-                    app.shared_session_id(),
-                    slack_state.clone(),
-                    sl.respond_to.clone(),
-                    sl.allowed_channels.clone(),
-                );
-                tracing::info!(
-                    "Spawning Slack bot ({} allowed IDs)",
-                    sl.allowed_ids.len()
-                );
-                Some(sl_agent.start(bot_tok, app_tok))
-            } else {
-                if sl.enabled {
-                    tracing::warn!(
-                        "Slack enabled but missing tokens (need both SLACK_BOT_TOKEN and SLACK_APP_TOKEN)"
-                    );
+    // Start HTTP/WebSocket gateway if enabled
+    let _gateway_handle = if config.gateway.enabled && daemon_pid.is_none() {
+        match crate::gateway::GatewayServer::new(&config.gateway, channel_factory.clone()) {
+            Ok(server) => match server.start().await {
+                Ok((_, handle)) => Some(handle),
+                Err(e) => {
+                    tracing::error!("Failed to start gateway: {}", e);
+                    None
                 }
+            },
+            Err(e) => {
+                tracing::error!("Gateway misconfigured: {}", e);
                 None
             }
-        } else {
-            None
         }
+    } else {
+        None
     };
 
     // Run TUI
@@ -3,6 +3,7 @@
 use super::crabrace::CrabraceConfig;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -44,6 +45,18 @@ pub struct Config {
     /// Agent behaviour configuration
     #[serde(default)]
     pub agent: AgentConfig,
+
+    /// External MCP tool servers
+    #[serde(default)]
+    pub mcp: McpConfig,
+
+    /// OS-level sandbox for bash / execute_code
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+
+    /// Memory search configuration
+    #[serde(default)]
+    pub memory: MemoryConfig,
 }
 
 /// HTTP API gateway configuration
@@ -64,6 +77,16 @@ pub struct GatewayConfig {
     /// Whether the gateway is enabled
     #[serde(default)]
     pub enabled: bool,
+
+    /// Bearer token required when `auth_mode = "token"`.
+    /// Falls back to the `OPENCRABS_GATEWAY_TOKEN` env var when unset.
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Run tools the policy marks "ask" (bash, write_file, ...) without
+    /// approval. Off by default: nobody is there to approve, so such calls fail.
+    #[serde(default)]
+    pub auto_approve_tools: bool,
 }
 
 fn default_gateway_port() -> u16 {
@@ -85,6 +108,8 @@ impl Default for GatewayConfig {
             bind: default_gateway_bind(),
             auth_mode: default_gateway_auth(),
             enabled: false,
+            token: None,
+            auto_approve_tools: false,
         }
     }
 }
@@ -196,12 +221,68 @@ impl Default for VoiceConfig {
     }
 }
 
+/// How the TUI answers `ask` decisions from the tool policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApprovalPolicy {
+    /// Prompt for every call
+    #[default]
+    Ask,
+    /// Approve everything until the session changes
+    AutoSession,
+    /// Never prompt (denied calls stay denied)
+    AutoAlways,
+}
+
+impl ApprovalPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalPolicy::Ask => "ask",
+            ApprovalPolicy::AutoSession => "auto-session",
+            ApprovalPolicy::AutoAlways => "auto-always",
+        }
+    }
+}
+
+impl std::fmt::Display for ApprovalPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// `approval_policy` used to be a free-form string, so an unrecognised value
+// falls back to prompting instead of failing the whole config load
+impl<'de> Deserialize<'de> for ApprovalPolicy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Ok(match value.as_str() {
+            "ask" | "approve-only" => ApprovalPolicy::Ask,
+            "auto-session" => ApprovalPolicy::AutoSession,
+            "auto-always" => ApprovalPolicy::AutoAlways,
+            other => {
+                tracing::warn!(
+                    "Unknown approval_policy '{}', using 'ask' (expected ask, auto-session or auto-always)",
+                    other
+                );
+                ApprovalPolicy::Ask
+            }
+        })
+    }
+}
+
 /// Agent behaviour configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentConfig {
     /// Approval policy: "ask", "auto-session", "auto-always"
-    #[serde(default = "default_approval_policy")]
-    pub approval_policy: String,
+    #[serde(default)]
+    pub approval_policy: ApprovalPolicy,
+
+    /// Tool permission rules (default: ~/.opencrabs/policy.toml)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_file: Option<String>,
 
     /// Maximum concurrent tool calls
     #[serde(default = "default_max_concurrent")]
@@ -216,10 +297,6 @@ pub struct AgentConfig {
     pub max_tokens: u32,
 }
 
-fn default_approval_policy() -> String {
-    "ask".to_string()
-}
-
 fn default_max_concurrent() -> u32 {
     4
 }
@@ -235,7 +312,8 @@ fn default_max_tokens() -> u32 {
 impl Default for AgentConfig {
     fn default() -> Self {
         Self {
-            approval_policy: default_approval_policy(),
+            approval_policy: ApprovalPolicy::default(),
+            policy_file: None,
             max_concurrent: default_max_concurrent(),
             context_limit: default_context_limit(),
             max_tokens: default_max_tokens(),
@@ -243,6 +321,202 @@ impl Default for AgentConfig {
     }
 }
 
+/// MCP (Model Context Protocol) client configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct McpConfig {
+    /// Servers keyed by name; the name prefixes every tool they expose
+    #[serde(default)]
+    pub servers: HashMap<String, McpServerConfig>,
+}
+
+/// A single MCP server, reached either over stdio or streamable HTTP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Whether to connect to this server at startup
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Executable to spawn for a stdio server
+    #[serde(default)]
+    pub command: Option<String>,
+
+    /// Arguments passed to `command`
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Extra environment variables for the spawned process
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// Endpoint of a streamable HTTP server (used when `command` is unset)
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Extra HTTP headers, e.g. `Authorization`
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// Per-request timeout in seconds
+    #[serde(default = "default_mcp_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Believe the server's `readOnlyHint` annotations: tools it marks
+    /// read-only skip approval and may run alongside other tools. Off by
+    /// default, since a server can mislabel a destructive tool.
+    #[serde(default)]
+    pub trust_annotations: bool,
+}
+
+fn default_mcp_timeout_secs() -> u64 {
+    60
+}
+
+/// OS-level sandboxing for tools that run arbitrary code (Linux only)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SandboxConfig {
+    /// Sandbox for the `bash` tool
+    #[serde(default)]
+    pub bash: ToolSandboxConfig,
+
+    /// Sandbox for the `execute_code` tool
+    #[serde(default)]
+    pub execute_code: ToolSandboxConfig,
+}
+
+/// Sandbox settings for a single tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSandboxConfig {
+    /// Run the tool inside user/mount/network namespaces with landlock
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Allow network access from inside the sandbox
+    #[serde(default = "default_true")]
+    pub network: bool,
+
+    /// Extra writable paths besides the working directory and temp dir
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+}
+
+impl Default for ToolSandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            network: true,
+            writable_paths: Vec::new(),
+        }
+    }
+}
+
+/// Memory search configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MemoryConfig {
+    /// Where vector embeddings for hybrid search come from
+    #[serde(default)]
+    pub embedding: EmbeddingConfig,
+
+    /// Automatic recall of relevant memories into the system brain each turn
+    #[serde(default)]
+    pub recall: RecallConfig,
+}
+
+/// Automatic memory recall settings (`[memory.recall]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecallConfig {
+    /// Search memory for each incoming message and inject the best matches
+    /// (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Maximum snippets injected per turn (default: 5)
+    #[serde(default = "default_recall_max_results")]
+    pub max_results: usize,
+
+    /// Token budget for all injected snippets together (default: 800)
+    #[serde(default = "default_recall_token_budget")]
+    pub token_budget: usize,
+
+    /// Also search past sessions (default: true; never for channel agents)
+    #[serde(default = "default_true")]
+    pub sessions: bool,
+}
+
+fn default_recall_max_results() -> usize {
+    5
+}
+
+fn default_recall_token_budget() -> usize {
+    800
+}
+
+impl Default for RecallConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_results: default_recall_max_results(),
+            token_budget: default_recall_token_budget(),
+            sessions: true,
+        }
+    }
+}
+
+/// Which embedding backend memory, session and code search use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbeddingBackendKind {
+    /// Bundled GGUF model via llama.cpp, downloaded on first use
+    #[default]
+    Local,
+    /// A GGUF model file already on disk (air-gapped machines)
+    Path,
+    /// Any OpenAI-compatible `/embeddings` endpoint (OpenAI, Ollama, llama.cpp server)
+    #[serde(alias = "openai-compatible")]
+    Openai,
+    /// No embeddings — keyword (FTS) search only
+    None,
+}
+
+impl EmbeddingBackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmbeddingBackendKind::Local => "local",
+            EmbeddingBackendKind::Path => "path",
+            EmbeddingBackendKind::Openai => "openai",
+            EmbeddingBackendKind::None => "none",
+        }
+    }
+}
+
+/// Embedding backend settings (`[memory.embedding]`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EmbeddingConfig {
+    /// "local", "path", "openai" or "none" (default: "local")
+    #[serde(default)]
+    pub backend: EmbeddingBackendKind,
+
+    /// `local`: model URI (default: embeddinggemma-300M). `openai`: model name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// `path`: GGUF model file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// `openai`: API base URL, e.g. "http://localhost:11434/v1"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+
+    /// `openai`: API key. Falls back to the `OPENCRABS_EMBEDDING_API_KEY` env var when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// Vector size. Sent as `dimensions` to OpenAI-compatible endpoints when set
+    /// (default: 768)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
+}
+
 /// Debug configuration options
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DebugConfig {
@@ -313,6 +587,23 @@ pub struct FallbackProviderConfig {
     /// Fallback provider type
     #[serde(default)]
     pub provider: Option<String>,
+
+    /// Ordered failover chain tried after the primary when it is rate-limited,
+    /// erroring (5xx) or timing out, e.g. `["anthropic", "openrouter"]`.
+    /// When empty, `provider` is used as a single-entry chain.
+    #[serde(default)]
+    pub providers: Vec<String>,
+}
+
+impl FallbackProviderConfig {
+    /// The failover chain, honouring the legacy single `provider` key
+    pub fn chain(&self) -> Vec<String> {
+        if !self.providers.is_empty() {
+            self.providers.clone()
+        } else {
+            self.provider.iter().cloned().collect()
+        }
+    }
 }
 
 /// STT (Speech-to-Text) provider configurations
@@ -516,6 +807,9 @@ impl Default for Config {
             channels: ChannelsConfig::default(),
             voice: VoiceConfig::default(),
             agent: AgentConfig::default(),
+            mcp: McpConfig::default(),
+            sandbox: SandboxConfig::default(),
+            memory: MemoryConfig::default(),
         }
     }
 }
@@ -633,6 +927,9 @@ impl Config {
             channels: overlay.channels,
             voice: overlay.voice,
             agent: overlay.agent,
+            mcp: overlay.mcp,
+            sandbox: overlay.sandbox,
+            memory: overlay.memory,
         }
     }
 
@@ -1160,7 +1457,7 @@ api_key = "test-openai-key"
     #[test]
     fn test_agent_config_default() {
         let agent = AgentConfig::default();
-        assert_eq!(agent.approval_policy, "ask");
+        assert_eq!(agent.approval_policy, ApprovalPolicy::Ask);
         assert_eq!(agent.max_concurrent, 4);
     }
 
@@ -1173,10 +1470,77 @@ max_concurrent = 8
         "#;
 
         let config: Config = toml::from_str(toml_content).unwrap();
-        assert_eq!(config.agent.approval_policy, "auto-always");
+        assert_eq!(config.agent.approval_policy, ApprovalPolicy::AutoAlways);
         assert_eq!(config.agent.max_concurrent, 8);
     }
 
+    #[test]
+    fn test_approval_policy_accepts_legacy_and_unknown_values() {
+        let config: Config = toml::from_str("[agent]\napproval_policy = \"approve-only\"").unwrap();
+        assert_eq!(config.agent.approval_policy, ApprovalPolicy::Ask);
+        // Unknown values fall back to prompting rather than breaking config load
+        let config: Config = toml::from_str("[agent]\napproval_policy = \"yolo\"").unwrap();
+        assert_eq!(config.agent.approval_policy, ApprovalPolicy::Ask);
+    }
+
+    #[test]
+    fn test_sandbox_config_per_tool() {
+        let config: Config = toml::from_str(
+            r#"
+[sandbox.bash]
+enabled = true
+network = false
+writable_paths = ["~/.cargo"]
+"#,
+        )
+        .unwrap();
+        assert!(config.sandbox.bash.enabled);
+        assert!(!config.sandbox.bash.network);
+        assert_eq!(config.sandbox.bash.writable_paths, vec!["~/.cargo"]);
+        assert!(!config.sandbox.execute_code.enabled);
+        assert!(config.sandbox.execute_code.network);
+    }
+
+    #[test]
+    fn test_memory_embedding_config() {
+        let config: Config = toml::from_str(
+            r#"
+[memory.embedding]
+backend = "openai"
+base_url = "http://localhost:11434/v1"
+model = "nomic-embed-text"
+"#,
+        )
+        .unwrap();
+        let embedding = &config.memory.embedding;
+        assert_eq!(embedding.backend, EmbeddingBackendKind::Openai);
+        assert_eq!(embedding.model.as_deref(), Some("nomic-embed-text"));
+        assert_eq!(embedding.dimensions, None);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.memory.embedding.backend, EmbeddingBackendKind::Local);
+    }
+
+    #[test]
+    fn test_memory_recall_config() {
+        let config: Config = toml::from_str(
+            r#"
+[memory.recall]
+enabled = true
+token_budget = 400
+"#,
+        )
+        .unwrap();
+        let recall = &config.memory.recall;
+        assert!(recall.enabled);
+        assert_eq!(recall.token_budget, 400);
+        assert_eq!(recall.max_results, 5);
+        assert!(recall.sessions);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert!(!config.memory.recall.enabled);
+    }
+
     #[test]
     fn test_agent_config_defaults_when_absent() {
         // Config without [agent] section should use defaults
@@ -1186,7 +1550,7 @@ level = "info"
         "#;
 
         let config: Config = toml::from_str(toml_content).unwrap();
-        assert_eq!(config.agent.approval_policy, "ask");
+        assert_eq!(config.agent.approval_policy, ApprovalPolicy::Ask);
         assert_eq!(config.agent.max_concurrent, 4);
     }
 
@@ -1220,7 +1584,7 @@ level = "info"
         // Verify it round-trips
         let content = fs::read_to_string(&config_path).unwrap();
         let loaded: Config = toml::from_str(&content).unwrap();
-        assert_eq!(loaded.agent.approval_policy, "auto-session");
+        assert_eq!(loaded.agent.approval_policy, ApprovalPolicy::AutoSession);
         assert_eq!(loaded.logging.level, "info");
     }
 
@@ -1228,14 +1592,14 @@ level = "info"
     fn test_config_save_with_agent_section() {
         let temp_file = NamedTempFile::new().unwrap();
         let mut config = Config::default();
-        config.agent.approval_policy = "auto-always".to_string();
+        config.agent.approval_policy = ApprovalPolicy::AutoAlways;
         config.agent.max_concurrent = 2;
 
         config.save(temp_file.path()).unwrap();
 
         let contents = fs::read_to_string(temp_file.path()).unwrap();
         let loaded: Config = toml::from_str(&contents).unwrap();
-        assert_eq!(loaded.agent.approval_policy, "auto-always");
+        assert_eq!(loaded.agent.approval_policy, ApprovalPolicy::AutoAlways);
         assert_eq!(loaded.agent.max_concurrent, 2);
     }
 }
@@ -1273,6 +1637,18 @@ pub fn resolve_provider_from_config(config: &Config) -> (&str, &str) {
             .unwrap_or("default");
         return ("Google Gemini", model);
     }
+    if config.providers.bedrock.as_ref().is_some_and(|p| p.enabled) {
+        let model = config.providers.bedrock.as_ref()
+            .and_then(|p| p.default_model.as_deref())
+            .unwrap_or("default");
+        return ("AWS Bedrock", model);
+    }
+    if config.providers.vertex.as_ref().is_some_and(|p| p.enabled) {
+        let model = config.providers.vertex.as_ref()
+            .and_then(|p| p.default_model.as_deref())
+            .unwrap_or("default");
+        return ("Google Vertex AI", model);
+    }
     // Default - nothing configured
     ("Not configured", "N/A")
 }
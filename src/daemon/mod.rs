@@ -0,0 +1,11 @@
+//! Daemon Module
+//!
+//! Support for running OpenCrabs headless (`opencrabs daemon`): the PID/lock
+//! file that keeps a single instance running and lets `daemon status|stop`
+//! find it, and the systemd/launchd unit that starts it at login.
+
+mod pid;
+mod service;
+
+pub use pid::{PidFile, default_pid_path, is_process_alive, read_pid, running_daemon, terminate};
+pub use service::install_service;
@@ -0,0 +1,187 @@
+//! PID/lock file for the headless daemon
+
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Where the daemon records its PID (`~/.opencrabs/daemon.pid`)
+pub fn default_pid_path() -> PathBuf {
+    crate::config::opencrabs_home().join("daemon.pid")
+}
+
+/// Read the PID stored in a PID file, if any
+pub fn read_pid(path: &Path) -> Option<u32> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// PID of the daemon that owns `path`, if it is still running
+pub fn running_daemon(path: &Path) -> Option<u32> {
+    read_pid(path).filter(|&pid| is_process_alive(pid))
+}
+
+/// Whether a process with this PID is running
+pub fn is_process_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        // Signal 0 only checks that the process exists and we may signal it
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    #[cfg(windows)]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// Ask a process to shut down gracefully (SIGTERM on Unix)
+pub fn terminate(pid: u32) -> Result<()> {
+    #[cfg(unix)]
+    let status = std::process::Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status();
+
+    #[cfg(not(unix))]
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status();
+
+    let status = status.context("Failed to signal daemon")?;
+    anyhow::ensure!(status.success(), "Failed to signal process {}", pid);
+    Ok(())
+}
+
+/// Exclusive PID file held for the lifetime of the daemon.
+///
+/// Acquiring fails while another live process owns the file; a file left
+/// behind by a crashed daemon is replaced. The file is removed on drop.
+#[derive(Debug)]
+pub struct PidFile {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl PidFile {
+    /// Claim `path` for the current process
+    pub fn acquire(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let pid = std::process::id();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        // Second attempt only happens after removing a stale file
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", pid)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    tracing::debug!("Acquired PID file {} ({})", path.display(), pid);
+                    return Ok(Self { path, pid });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => match read_pid(&path) {
+                    Some(owner) if owner != pid && is_process_alive(owner) => {
+                        anyhow::bail!("OpenCrabs daemon is already running (pid {})", owner);
+                    }
+                    _ => {
+                        tracing::warn!("Removing stale PID file {}", path.display());
+                        std::fs::remove_file(&path).with_context(|| {
+                            format!("Failed to remove stale PID file {}", path.display())
+                        })?;
+                    }
+                },
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()));
+                }
+            }
+        }
+
+        anyhow::bail!("Could not acquire PID file {}", path.display())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Don't delete a file another daemon has since claimed
+        if read_pid(&self.path) == Some(self.pid)
+            && let Err(e) = std::fs::remove_file(&self.path)
+        {
+            tracing::warn!("Failed to remove PID file {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_is_exclusive_and_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.pid");
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pid_file_refuses_live_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.pid");
+
+        // A live process that isn't us
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        std::fs::write(&path, format!("{}\n", child.id())).unwrap();
+
+        let err = PidFile::acquire(&path).unwrap_err();
+        assert!(err.to_string().contains("already running"));
+
+        assert_eq!(running_daemon(&path), Some(child.id()));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(running_daemon(&path), None);
+
+        // Once the owner is gone the file is stale and gets replaced
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(read_pid(pid_file.path()), Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_stale_garbage_is_replaced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.pid");
+        std::fs::write(&path, "not a pid").unwrap();
+
+        let _pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+    }
+}
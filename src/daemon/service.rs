@@ -0,0 +1,138 @@
+//! Service unit installation (systemd user unit / macOS LaunchAgent)
+//!
+//! Both run `opencrabs daemon`, so channels keep answering without a TUI.
+
+use std::path::PathBuf;
+
+/// Install the appropriate daemon service for the current platform.
+/// Returns the path of the unit file that was written.
+pub fn install_service() -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe().map_err(|e| format!("Failed to get exe path: {}", e))?;
+
+    #[cfg(target_os = "linux")]
+    {
+        install_systemd_service(&exe_path)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        install_launchagent(&exe_path)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = exe_path;
+        Err("Daemon installation not supported on this platform".to_string())
+    }
+}
+
+/// Contents of the systemd user unit that runs `<exe> daemon`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn systemd_unit(exe_path: &std::path::Path) -> String {
+    format!(
+        r#"[Unit]
+Description=OpenCrabs AI Orchestration Agent (headless channels)
+After=network-online.target
+Wants=network-online.target
+
+[Service]
+Type=simple
+ExecStart="{}" daemon
+Restart=on-failure
+RestartSec=5
+KillSignal=SIGTERM
+TimeoutStopSec=30
+
+[Install]
+WantedBy=default.target
+"#,
+        exe_path.display()
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn install_systemd_service(exe_path: &std::path::Path) -> Result<PathBuf, String> {
+    // $XDG_CONFIG_HOME/systemd/user, falling back to ~/.config/systemd/user
+    let service_dir = dirs::config_dir()
+        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
+        .ok_or("Cannot determine config dir")?
+        .join("systemd")
+        .join("user");
+
+    std::fs::create_dir_all(&service_dir)
+        .map_err(|e| format!("Failed to create systemd dir: {}", e))?;
+
+    let service_path = service_dir.join("opencrabs.service");
+    std::fs::write(&service_path, systemd_unit(exe_path))
+        .map_err(|e| format!("Failed to write service file: {}", e))?;
+
+    // Pick up the new/changed unit, then enable it
+    let steps: [&[&str]; 2] = [
+        &["--user", "daemon-reload"],
+        &["--user", "enable", "opencrabs"],
+    ];
+    for args in steps {
+        std::process::Command::new("systemctl")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run systemctl {}: {}", args.join(" "), e))?;
+    }
+
+    Ok(service_path)
+}
+
+#[cfg(target_os = "macos")]
+fn install_launchagent(exe_path: &std::path::Path) -> Result<PathBuf, String> {
+    let agents_dir = dirs::home_dir()
+        .ok_or("Cannot determine home dir")?
+        .join("Library")
+        .join("LaunchAgents");
+
+    std::fs::create_dir_all(&agents_dir)
+        .map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
+
+    let plist_content = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>com.opencrabs.agent</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>daemon</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        exe_path.display()
+    );
+
+    let plist_path = agents_dir.join("com.opencrabs.agent.plist");
+    std::fs::write(&plist_path, plist_content)
+        .map_err(|e| format!("Failed to write plist: {}", e))?;
+
+    std::process::Command::new("launchctl")
+        .args(["load", &plist_path.to_string_lossy()])
+        .output()
+        .map_err(|e| format!("Failed to load launch agent: {}", e))?;
+
+    Ok(plist_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit_runs_daemon_subcommand() {
+        let unit = systemd_unit(std::path::Path::new("/usr/local/bin/opencrabs"));
+        assert!(unit.contains("ExecStart=\"/usr/local/bin/opencrabs\" daemon\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+}
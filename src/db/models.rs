@@ -71,6 +71,37 @@ pub struct ToolExecution {
     pub created_at: DateTime<Utc>,
 }
 
+/// Channel session model — links a messaging-channel user to their session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSession {
+    pub channel: String,
+    pub external_id: String,
+    pub session_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Checkpoint of the files an agent turn modified
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    pub restored_at: Option<DateTime<Utc>>,
+    /// Number of files saved (computed, not stored)
+    pub file_count: i64,
+}
+
+/// A file's content before the checkpoint's turn modified it
+#[derive(Debug, Clone)]
+pub struct CheckpointFile {
+    pub checkpoint_id: Uuid,
+    pub path: std::path::PathBuf,
+    /// `None` when the file did not exist yet
+    pub content: Option<Vec<u8>>,
+}
+
 /// Plan model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plan {
@@ -158,6 +189,20 @@ impl File {
     }
 }
 
+impl ChannelSession {
+    /// Create a new channel session mapping
+    pub fn new(channel: String, external_id: String, session_id: Uuid) -> Self {
+        let now = Utc::now();
+        Self {
+            channel,
+            external_id,
+            session_id,
+            created_at: now,
+            last_seen: now,
+        }
+    }
+}
+
 // Manual FromRow implementations to handle type conversions
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Session {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
@@ -220,6 +265,56 @@ impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for File {
     }
 }
 
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for ChannelSession {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        Ok(ChannelSession {
+            channel: row.try_get("channel")?,
+            external_id: row.try_get("external_id")?,
+            session_id: Uuid::parse_str(row.try_get("session_id")?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            created_at: DateTime::from_timestamp(row.try_get("created_at")?, 0)
+                .ok_or_else(|| sqlx::Error::Decode("Invalid timestamp for created_at".into()))?,
+            last_seen: DateTime::from_timestamp(row.try_get("last_seen")?, 0)
+                .ok_or_else(|| sqlx::Error::Decode("Invalid timestamp for last_seen".into()))?,
+        })
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Checkpoint {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        Ok(Checkpoint {
+            id: Uuid::parse_str(row.try_get("id")?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            session_id: Uuid::parse_str(row.try_get("session_id")?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            label: row.try_get("label")?,
+            created_at: DateTime::from_timestamp(row.try_get("created_at")?, 0)
+                .ok_or_else(|| sqlx::Error::Decode("Invalid timestamp for created_at".into()))?,
+            restored_at: row
+                .try_get::<Option<i64>, _>("restored_at")?
+                .and_then(|ts| DateTime::from_timestamp(ts, 0)),
+            file_count: row.try_get("file_count")?,
+        })
+    }
+}
+
+impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for CheckpointFile {
+    fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
+        use sqlx::Row;
+
+        Ok(CheckpointFile {
+            checkpoint_id: Uuid::parse_str(row.try_get("checkpoint_id")?)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            path: std::path::PathBuf::from(row.try_get::<String, _>("path")?),
+            content: row.try_get("content")?,
+        })
+    }
+}
+
 impl<'r> sqlx::FromRow<'r, sqlx::sqlite::SqliteRow> for Plan {
     fn from_row(row: &'r sqlx::sqlite::SqliteRow) -> Result<Self, sqlx::Error> {
         use sqlx::Row;
@@ -0,0 +1,224 @@
+//! Channel Session Repository
+//!
+//! Database operations for channel user → session mappings.
+
+use crate::db::models::ChannelSession;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+/// Repository for channel session operations
+#[derive(Clone)]
+pub struct ChannelSessionRepository {
+    pool: SqlitePool,
+}
+
+impl ChannelSessionRepository {
+    /// Create a new channel session repository
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Find the mapping for a user on a channel
+    pub async fn find(&self, channel: &str, external_id: &str) -> Result<Option<ChannelSession>> {
+        let mapping = sqlx::query_as::<_, ChannelSession>(
+            "SELECT * FROM channel_sessions WHERE channel = ? AND external_id = ?",
+        )
+        .bind(channel)
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to find channel session")?;
+
+        Ok(mapping)
+    }
+
+    /// Create or replace the mapping for a user on a channel
+    pub async fn upsert(&self, mapping: &ChannelSession) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO channel_sessions (channel, external_id, session_id, created_at, last_seen)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (channel, external_id) DO UPDATE
+            SET session_id = excluded.session_id, last_seen = excluded.last_seen
+            "#,
+        )
+        .bind(&mapping.channel)
+        .bind(&mapping.external_id)
+        .bind(mapping.session_id.to_string())
+        .bind(mapping.created_at.timestamp())
+        .bind(mapping.last_seen.timestamp())
+        .execute(&self.pool)
+        .await
+        .context("Failed to save channel session")?;
+
+        tracing::debug!(
+            "Linked {}:{} to session {}",
+            mapping.channel,
+            mapping.external_id,
+            mapping.session_id
+        );
+        Ok(())
+    }
+
+    /// Record activity for a user on a channel
+    pub async fn touch(&self, channel: &str, external_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE channel_sessions SET last_seen = ? WHERE channel = ? AND external_id = ?",
+        )
+        .bind(Utc::now().timestamp())
+        .bind(channel)
+        .bind(external_id)
+        .execute(&self.pool)
+        .await
+        .context("Failed to update channel session")?;
+
+        Ok(())
+    }
+
+    /// Delete the mapping for a user on a channel. Returns whether one existed.
+    pub async fn delete(&self, channel: &str, external_id: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM channel_sessions WHERE channel = ? AND external_id = ?")
+                .bind(channel)
+                .bind(external_id)
+                .execute(&self.pool)
+                .await
+                .context("Failed to delete channel session")?;
+
+        tracing::debug!("Unlinked {}:{}", channel, external_id);
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List mappings, optionally for a single channel (most recently active first)
+    pub async fn list(&self, channel: Option<&str>) -> Result<Vec<ChannelSession>> {
+        let mappings = if let Some(channel) = channel {
+            sqlx::query_as::<_, ChannelSession>(
+                "SELECT * FROM channel_sessions WHERE channel = ? ORDER BY last_seen DESC",
+            )
+            .bind(channel)
+            .fetch_all(&self.pool)
+            .await
+        } else {
+            sqlx::query_as::<_, ChannelSession>(
+                "SELECT * FROM channel_sessions ORDER BY last_seen DESC",
+            )
+            .fetch_all(&self.pool)
+            .await
+        }
+        .context("Failed to list channel sessions")?;
+
+        Ok(mappings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::db::models::Session;
+    use crate::db::repository::SessionRepository;
+
+    async fn setup() -> (ChannelSessionRepository, SessionRepository) {
+        let db = Database::connect_in_memory()
+            .await
+            .expect("Failed to create database");
+        db.run_migrations().await.expect("Failed to run migrations");
+        (
+            ChannelSessionRepository::new(db.pool().clone()),
+            SessionRepository::new(db.pool().clone()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_channel_session_crud() {
+        let (repo, session_repo) = setup().await;
+
+        let first = Session::new(Some("Telegram: Ana".to_string()), None);
+        let second = Session::new(Some("Telegram: Ana".to_string()), None);
+        session_repo
+            .create(&first)
+            .await
+            .expect("Failed to create session");
+        session_repo
+            .create(&second)
+            .await
+            .expect("Failed to create session");
+
+        // Create
+        let mapping = ChannelSession::new("telegram".to_string(), "42".to_string(), first.id);
+        repo.upsert(&mapping).await.expect("Failed to upsert");
+        let found = repo.find("telegram", "42").await.expect("Failed to find");
+        assert_eq!(found.map(|m| m.session_id), Some(first.id));
+
+        // Same user on another channel is a different mapping
+        assert!(
+            repo.find("discord", "42")
+                .await
+                .expect("Failed to find")
+                .is_none()
+        );
+
+        // Relink
+        let relinked = ChannelSession::new("telegram".to_string(), "42".to_string(), second.id);
+        repo.upsert(&relinked).await.expect("Failed to upsert");
+        let found = repo.find("telegram", "42").await.expect("Failed to find");
+        assert_eq!(found.map(|m| m.session_id), Some(second.id));
+
+        // Delete
+        assert!(
+            repo.delete("telegram", "42")
+                .await
+                .expect("Failed to delete")
+        );
+        assert!(
+            !repo
+                .delete("telegram", "42")
+                .await
+                .expect("Failed to delete")
+        );
+        assert!(
+            repo.find("telegram", "42")
+                .await
+                .expect("Failed to find")
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_channel_session_list_and_cascade() {
+        let (repo, session_repo) = setup().await;
+
+        let session = Session::new(None, None);
+        session_repo
+            .create(&session)
+            .await
+            .expect("Failed to create session");
+
+        for (channel, id) in [("telegram", "1"), ("slack", "U1"), ("slack", "U2")] {
+            repo.upsert(&ChannelSession::new(
+                channel.to_string(),
+                id.to_string(),
+                session.id,
+            ))
+            .await
+            .expect("Failed to upsert");
+        }
+
+        assert_eq!(repo.list(None).await.expect("Failed to list").len(), 3);
+        assert_eq!(
+            repo.list(Some("slack"))
+                .await
+                .expect("Failed to list")
+                .len(),
+            2
+        );
+
+        // Deleting the session drops its mappings
+        session_repo
+            .delete(session.id)
+            .await
+            .expect("Failed to delete session");
+        assert!(repo.list(None).await.expect("Failed to list").is_empty());
+    }
+}
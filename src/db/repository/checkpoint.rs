@@ -0,0 +1,174 @@
+//! Checkpoint Repository
+//!
+//! Database operations for per-turn file checkpoints.
+
+use crate::db::models::{Checkpoint, CheckpointFile};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Checkpoint columns plus the number of saved files
+const SELECT_CHECKPOINT: &str = r#"
+    SELECT c.*,
+        (SELECT COUNT(*) FROM checkpoint_files f WHERE f.checkpoint_id = c.id) AS file_count
+    FROM checkpoints c
+"#;
+
+/// Repository for checkpoint operations
+#[derive(Clone)]
+pub struct CheckpointRepository {
+    pool: SqlitePool,
+}
+
+impl CheckpointRepository {
+    /// Create a new checkpoint repository
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create the checkpoint row unless it already exists
+    pub async fn ensure(&self, checkpoint: &Checkpoint) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO checkpoints (id, session_id, label, created_at, restored_at)
+            VALUES (?, ?, ?, ?, NULL)
+            "#,
+        )
+        .bind(checkpoint.id.to_string())
+        .bind(checkpoint.session_id.to_string())
+        .bind(&checkpoint.label)
+        .bind(checkpoint.created_at.timestamp())
+        .execute(&self.pool)
+        .await
+        .context("Failed to create checkpoint")?;
+
+        Ok(())
+    }
+
+    /// Whether the checkpoint already holds `path`
+    pub async fn has_file(&self, checkpoint_id: Uuid, path: &Path) -> Result<bool> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM checkpoint_files WHERE checkpoint_id = ? AND path = ?",
+        )
+        .bind(checkpoint_id.to_string())
+        .bind(path.to_string_lossy().to_string())
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to look up checkpoint file")?;
+
+        Ok(count > 0)
+    }
+
+    /// Save a file's previous content. The first save for a path wins, so the
+    /// checkpoint keeps the state from before the turn.
+    pub async fn add_file(&self, file: &CheckpointFile) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO checkpoint_files (checkpoint_id, path, content) VALUES (?, ?, ?)",
+        )
+        .bind(file.checkpoint_id.to_string())
+        .bind(file.path.to_string_lossy().to_string())
+        .bind(&file.content)
+        .execute(&self.pool)
+        .await
+        .context("Failed to save checkpoint file")?;
+
+        Ok(())
+    }
+
+    /// Checkpoints of a session that haven't been rolled back, newest first
+    pub async fn list_active(&self, session_id: Uuid) -> Result<Vec<Checkpoint>> {
+        let checkpoints = sqlx::query_as::<_, Checkpoint>(&format!(
+            "{} WHERE c.session_id = ? AND c.restored_at IS NULL \
+             ORDER BY c.created_at DESC, c.rowid DESC",
+            SELECT_CHECKPOINT
+        ))
+        .bind(session_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list checkpoints")?;
+
+        Ok(checkpoints)
+    }
+
+    /// Files saved in a checkpoint
+    pub async fn files(&self, checkpoint_id: Uuid) -> Result<Vec<CheckpointFile>> {
+        let files = sqlx::query_as::<_, CheckpointFile>(
+            "SELECT * FROM checkpoint_files WHERE checkpoint_id = ? ORDER BY path",
+        )
+        .bind(checkpoint_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list checkpoint files")?;
+
+        Ok(files)
+    }
+
+    /// Mark a checkpoint as rolled back
+    pub async fn mark_restored(&self, checkpoint_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE checkpoints SET restored_at = ? WHERE id = ?")
+            .bind(Utc::now().timestamp())
+            .bind(checkpoint_id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to mark checkpoint restored")?;
+
+        tracing::debug!("Checkpoint {} restored", checkpoint_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::db::models::Session;
+    use crate::db::repository::SessionRepository;
+
+    #[tokio::test]
+    async fn test_first_snapshot_wins_and_restored_are_hidden() {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let session = Session::new(Some("Test".to_string()), None);
+        SessionRepository::new(db.pool().clone())
+            .create(&session)
+            .await
+            .unwrap();
+        let repo = CheckpointRepository::new(db.pool().clone());
+
+        let checkpoint = Checkpoint {
+            id: Uuid::new_v4(),
+            session_id: session.id,
+            label: "edit things".to_string(),
+            created_at: Utc::now(),
+            restored_at: None,
+            file_count: 0,
+        };
+        repo.ensure(&checkpoint).await.unwrap();
+        repo.ensure(&checkpoint).await.unwrap();
+
+        let path = Path::new("/tmp/a.txt");
+        for content in [Some(b"before".to_vec()), Some(b"during".to_vec())] {
+            repo.add_file(&CheckpointFile {
+                checkpoint_id: checkpoint.id,
+                path: path.to_path_buf(),
+                content,
+            })
+            .await
+            .unwrap();
+        }
+        assert!(repo.has_file(checkpoint.id, path).await.unwrap());
+
+        let files = repo.files(checkpoint.id).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].content.as_deref(), Some(&b"before"[..]));
+
+        let active = repo.list_active(session.id).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].file_count, 1);
+
+        repo.mark_restored(checkpoint.id).await.unwrap();
+        assert!(repo.list_active(session.id).await.unwrap().is_empty());
+    }
+}
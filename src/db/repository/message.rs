@@ -43,6 +43,24 @@ impl MessageRepository {
         Ok(messages)
     }
 
+    /// Find the messages of a session after `sequence`, oldest first
+    pub async fn find_by_session_after(
+        &self,
+        session_id: Uuid,
+        sequence: i32,
+    ) -> Result<Vec<Message>> {
+        let messages = sqlx::query_as::<_, Message>(
+            "SELECT * FROM messages WHERE session_id = ? AND sequence > ? ORDER BY sequence ASC",
+        )
+        .bind(session_id.to_string())
+        .bind(sequence)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to find new messages by session")?;
+
+        Ok(messages)
+    }
+
     /// Create a new message
     pub async fn create(&self, message: &Message) -> Result<()> {
         sqlx::query(
@@ -251,5 +269,12 @@ mod tests {
             .await
             .expect("Failed to count");
         assert_eq!(count, 3);
+
+        let newer = message_repo
+            .find_by_session_after(session.id, 1)
+            .await
+            .expect("Failed to list newer");
+        let contents: Vec<&str> = newer.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["Message 1", "Message 2"]);
     }
 }
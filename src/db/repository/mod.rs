@@ -2,11 +2,15 @@
 //!
 //! Repository pattern implementations for database access.
 
+pub mod channel_session;
+pub mod checkpoint;
 pub mod file;
 pub mod message;
 pub mod plan;
 pub mod session;
 
+pub use channel_session::ChannelSessionRepository;
+pub use checkpoint::CheckpointRepository;
 pub use file::FileRepository;
 pub use message::MessageRepository;
 pub use plan::PlanRepository;
@@ -0,0 +1,22 @@
+//! HTTP / WebSocket Gateway
+//!
+//! Exposes the agent over a local HTTP API so other programs (editors, scripts,
+//! web dashboards) can drive OpenCrabs without the TUI.
+//!
+//! - `GET  /health` — liveness probe (never authenticated)
+//! - `POST /v1/sessions` — create a session
+//! - `POST /v1/messages` — send a message through the full tool loop
+//! - `GET  /v1/events` — WebSocket stream of [`ProgressEvent`]s
+//!
+//! When `gateway.auth_mode = "token"`, every `/v1` route requires
+//! `Authorization: Bearer <token>`, or `?token=<token>` for browser WebSocket
+//! clients that cannot set headers on the upgrade request.
+//!
+//! [`ProgressEvent`]: crate::brain::agent::ProgressEvent
+
+mod server;
+
+pub use server::{GatewayEvent, GatewayServer};
+
+/// Env var consulted when `gateway.token` is not set in config
+pub const GATEWAY_TOKEN_ENV: &str = "OPENCRABS_GATEWAY_TOKEN";
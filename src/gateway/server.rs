@@ -0,0 +1,639 @@
+//! Gateway Server
+//!
+//! Axum router, token auth middleware and request handlers. Each HTTP message
+//! gets its own channel-style `AgentService` whose progress callback feeds a
+//! shared broadcast that WebSocket clients subscribe to.
+
+use super::GATEWAY_TOKEN_ENV;
+use crate::brain::agent::{AgentResponse, ProgressCallback, ProgressEvent};
+use crate::channels::ChannelFactory;
+use crate::config::GatewayConfig;
+use crate::services::SessionService;
+use anyhow::{Context, Result};
+use axum::extract::ws::{Message as WsMessage, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Broadcast capacity — WebSocket clients further behind than this skip events
+const EVENT_BUFFER: usize = 256;
+
+/// A progress event tagged with the session that produced it
+#[derive(Debug, Clone, Serialize)]
+pub struct GatewayEvent {
+    pub session_id: Uuid,
+    #[serde(flatten)]
+    pub event: ProgressEvent,
+}
+
+#[derive(Clone)]
+struct GatewayState {
+    factory: Arc<ChannelFactory>,
+    session_service: SessionService,
+    events: broadcast::Sender<GatewayEvent>,
+    token: Option<Arc<str>>,
+    /// `gateway.auto_approve_tools` — otherwise "ask" tools are refused
+    auto_approve: bool,
+}
+
+/// HTTP/WebSocket server fronting the agent
+pub struct GatewayServer {
+    addr: SocketAddr,
+    state: GatewayState,
+}
+
+impl GatewayServer {
+    /// Build a server from config. Fails if `auth_mode = "token"` and no token
+    /// is available, so the gateway never silently starts unauthenticated.
+    pub fn new(config: &GatewayConfig, factory: Arc<ChannelFactory>) -> Result<Self> {
+        let ip: IpAddr = config
+            .bind
+            .parse()
+            .with_context(|| format!("Invalid gateway bind address: {}", config.bind))?;
+        let addr = SocketAddr::new(ip, config.port);
+
+        let token = match config.auth_mode.as_str() {
+            "token" => {
+                let token = config
+                    .token
+                    .clone()
+                    .filter(|t| !t.is_empty())
+                    .or_else(|| std::env::var(GATEWAY_TOKEN_ENV).ok().filter(|t| !t.is_empty()))
+                    .with_context(|| {
+                        format!(
+                            "gateway.auth_mode is \"token\" but no token is set (gateway.token or {})",
+                            GATEWAY_TOKEN_ENV
+                        )
+                    })?;
+                Some(Arc::from(token))
+            }
+            "none" => {
+                if !ip.is_loopback() {
+                    tracing::warn!(
+                        "Gateway auth disabled on non-loopback address {} — anyone who can reach it can drive the agent",
+                        addr
+                    );
+                }
+                None
+            }
+            other => anyhow::bail!(
+                "Unknown gateway.auth_mode '{}' (expected \"token\" or \"none\")",
+                other
+            ),
+        };
+
+        if config.auto_approve_tools {
+            tracing::warn!(
+                "gateway.auto_approve_tools is on — any authenticated client can run bash and file writes without approval"
+            );
+        }
+
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+
+        Ok(Self {
+            addr,
+            state: GatewayState {
+                session_service: SessionService::new(factory.service_context()),
+                factory,
+                events,
+                token,
+                auto_approve: config.auto_approve_tools,
+            },
+        })
+    }
+
+    /// Subscribe to the same progress stream WebSocket clients receive
+    pub fn subscribe(&self) -> broadcast::Receiver<GatewayEvent> {
+        self.state.events.subscribe()
+    }
+
+    fn router(&self) -> Router {
+        let api = Router::new()
+            .route("/sessions", post(create_session))
+            .route("/messages", post(send_message))
+            .route("/events", get(events_ws))
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                require_token,
+            ));
+
+        Router::new()
+            .route("/health", get(health))
+            .nest("/v1", api)
+            .with_state(self.state.clone())
+    }
+
+    /// Bind and serve in the background. Binding happens before spawning so a
+    /// port clash is returned to the caller instead of only being logged.
+    /// Returns the bound address (useful with port 0) and the server task.
+    pub async fn start(self) -> Result<(SocketAddr, tokio::task::JoinHandle<()>)> {
+        let listener = tokio::net::TcpListener::bind(self.addr)
+            .await
+            .with_context(|| format!("Failed to bind gateway on {}", self.addr))?;
+        let local_addr = listener.local_addr()?;
+        let app = self.router();
+
+        tracing::info!("🌐 Gateway listening on http://{}", local_addr);
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("Gateway server error: {}", e);
+            }
+        });
+
+        Ok((local_addr, handle))
+    }
+}
+
+/// JSON error body: `{"error": "..."}`
+struct GatewayError {
+    status: StatusCode,
+    message: String,
+}
+
+impl GatewayError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn internal(err: impl std::fmt::Display) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    }
+}
+
+impl IntoResponse for GatewayError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TokenQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Accepts `Authorization: Bearer <token>`. `?token=<token>` is honoured only
+/// on the `/v1/events` WebSocket upgrade, since browsers can't set headers
+/// there; anywhere else it would leak the token into logs and referrers.
+async fn require_token(
+    State(state): State<GatewayState>,
+    Query(query): Query<TokenQuery>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = state.token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or_else(|| query.token.filter(|_| is_events_upgrade(&request)));
+
+    match provided {
+        Some(token) if tokens_match(expected, &token) => next.run(request).await,
+        _ => GatewayError::new(StatusCode::UNAUTHORIZED, "Missing or invalid gateway token")
+            .into_response(),
+    }
+}
+
+/// WebSocket upgrade request for the event stream
+fn is_events_upgrade(request: &Request) -> bool {
+    request.uri().path().ends_with("/events")
+        && request
+            .headers()
+            .get(header::UPGRADE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+}
+
+/// Constant-time comparison so the token can't be recovered via response timing
+fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok", "version": crate::VERSION }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CreateSessionRequest {
+    #[serde(default)]
+    title: Option<String>,
+}
+
+async fn create_session(
+    State(state): State<GatewayState>,
+    Json(req): Json<CreateSessionRequest>,
+) -> Result<Json<serde_json::Value>, GatewayError> {
+    let session = state
+        .session_service
+        .create_session(Some(req.title.unwrap_or_else(|| "Gateway".to_string())))
+        .await
+        .map_err(GatewayError::internal)?;
+
+    Ok(Json(json!({ "session_id": session.id, "title": session.title })))
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageRequest {
+    /// Existing session to continue; a new one is created when omitted
+    #[serde(default)]
+    session_id: Option<Uuid>,
+    message: String,
+    #[serde(default)]
+    model: Option<String>,
+    /// Plan-mode style read-only run (no write/exec tools)
+    #[serde(default)]
+    read_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct MessageResponse {
+    session_id: Uuid,
+    #[serde(flatten)]
+    response: AgentResponse,
+}
+
+async fn send_message(
+    State(state): State<GatewayState>,
+    Json(req): Json<MessageRequest>,
+) -> Result<Json<MessageResponse>, GatewayError> {
+    if req.message.trim().is_empty() {
+        return Err(GatewayError::new(StatusCode::BAD_REQUEST, "message must not be empty"));
+    }
+
+    let session_id = match req.session_id {
+        Some(id) => {
+            state
+                .session_service
+                .get_session(id)
+                .await
+                .map_err(GatewayError::internal)?
+                .ok_or_else(|| {
+                    GatewayError::new(StatusCode::NOT_FOUND, format!("Session {} not found", id))
+                })?;
+            id
+        }
+        None => {
+            state
+                .session_service
+                .create_session(Some("Gateway".to_string()))
+                .await
+                .map_err(GatewayError::internal)?
+                .id
+        }
+    };
+
+    let events = state.events.clone();
+    let progress: ProgressCallback = Arc::new(move |event| {
+        // No subscribers is fine — the HTTP response still carries the result
+        let _ = events.send(GatewayEvent { session_id, event });
+    });
+
+    let agent = state
+        .factory
+        .build_agent_service()
+        .with_auto_approve_tools(state.auto_approve)
+        .with_progress_callback(Some(progress));
+
+    tracing::info!("Gateway: message for session {}", session_id);
+    let response = agent
+        .send_message_with_tools_and_mode(session_id, req.message, req.model, req.read_only, None)
+        .await
+        .map_err(GatewayError::internal)?;
+
+    Ok(Json(MessageResponse {
+        session_id,
+        response,
+    }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EventsQuery {
+    /// Only forward events for this session
+    #[serde(default)]
+    session_id: Option<Uuid>,
+}
+
+async fn events_ws(
+    State(state): State<GatewayState>,
+    Query(query): Query<EventsQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let rx = state.events.subscribe();
+    ws.on_upgrade(move |socket| stream_events(socket, rx, query.session_id))
+}
+
+async fn stream_events(
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<GatewayEvent>,
+    filter: Option<Uuid>,
+) {
+    loop {
+        tokio::select! {
+            event = rx.recv() => match event {
+                Ok(event) => {
+                    if filter.is_some_and(|id| id != event.session_id) {
+                        continue;
+                    }
+                    let Ok(payload) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if socket.send(WsMessage::Text(payload.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Gateway WebSocket client lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(WsMessage::Close(_))) | Some(Err(_)) | None => break,
+                // Client → server frames carry nothing for now; pings are answered by axum
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brain::provider::{
+        ContentBlock, ContentDelta, LLMRequest, LLMResponse, MessageDelta, Provider,
+        ProviderStream, Role, StopReason, StreamEvent, StreamMessage, TokenUsage,
+    };
+    use crate::db::Database;
+    use crate::services::ServiceContext;
+    use async_trait::async_trait;
+
+    struct MockProvider;
+
+    #[async_trait]
+    impl Provider for MockProvider {
+        async fn complete(
+            &self,
+            _request: LLMRequest,
+        ) -> crate::brain::provider::Result<LLMResponse> {
+            Ok(LLMResponse {
+                id: "gw-1".to_string(),
+                model: "mock-model".to_string(),
+                content: vec![ContentBlock::Text {
+                    text: "Hello from the gateway".to_string(),
+                }],
+                stop_reason: Some(StopReason::EndTurn),
+                usage: TokenUsage {
+                    input_tokens: 5,
+                    output_tokens: 7,
+                    reasoning_tokens: 0,
+                },
+            })
+        }
+
+        async fn stream(
+            &self,
+            request: LLMRequest,
+        ) -> crate::brain::provider::Result<ProviderStream> {
+            let response = self.complete(request).await?;
+            let text = "Hello from the gateway".to_string();
+            let events = vec![
+                Ok(StreamEvent::MessageStart {
+                    message: StreamMessage {
+                        id: response.id,
+                        model: response.model,
+                        role: Role::Assistant,
+                        usage: response.usage,
+                    },
+                }),
+                Ok(StreamEvent::ContentBlockStart {
+                    index: 0,
+                    content_block: ContentBlock::Text {
+                        text: String::new(),
+                    },
+                }),
+                Ok(StreamEvent::ContentBlockDelta {
+                    index: 0,
+                    delta: ContentDelta::TextDelta { text },
+                }),
+                Ok(StreamEvent::ContentBlockStop { index: 0 }),
+                Ok(StreamEvent::MessageDelta {
+                    delta: MessageDelta {
+                        stop_reason: Some(StopReason::EndTurn),
+                        stop_sequence: None,
+                    },
+                    usage: response.usage,
+                }),
+                Ok(StreamEvent::MessageStop),
+            ];
+            Ok(Box::pin(futures::stream::iter(events)))
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+
+        fn supported_models(&self) -> Vec<String> {
+            vec!["mock-model".to_string()]
+        }
+
+        fn context_window(&self, _model: &str) -> Option<u32> {
+            Some(4096)
+        }
+
+        fn calculate_cost(&self, _model: &str, _input: u32, _output: u32) -> f64 {
+            0.0
+        }
+    }
+
+    async fn test_factory() -> Arc<ChannelFactory> {
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+        Arc::new(ChannelFactory::new(
+            Arc::new(MockProvider),
+            context,
+            "You are a test agent.".to_string(),
+            std::env::temp_dir(),
+            std::env::temp_dir(),
+            Arc::new(tokio::sync::Mutex::new(None)),
+            Default::default(),
+        ))
+    }
+
+    fn test_config(token: Option<&str>) -> GatewayConfig {
+        GatewayConfig {
+            port: 0,
+            bind: "127.0.0.1".to_string(),
+            auth_mode: "token".to_string(),
+            enabled: true,
+            token: token.map(str::to_string),
+            auto_approve_tools: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tools_need_approval_unless_configured() {
+        let mut config = test_config(Some("t"));
+        let server = GatewayServer::new(&config, test_factory().await).unwrap();
+        assert!(!server.state.auto_approve);
+
+        config.auto_approve_tools = true;
+        let server = GatewayServer::new(&config, test_factory().await).unwrap();
+        assert!(server.state.auto_approve);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("secret", ""));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_auth_mode() {
+        let mut config = test_config(Some("t"));
+        config.auth_mode = "magic".to_string();
+        assert!(GatewayServer::new(&config, test_factory().await).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_health_is_unauthenticated() {
+        let server = GatewayServer::new(&test_config(Some("t0k")), test_factory().await).unwrap();
+        let (addr, handle) = server.start().await.unwrap();
+
+        let resp = reqwest::get(format!("http://{}/health", addr)).await.unwrap();
+        assert_eq!(resp.status(), 200);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_api_requires_token() {
+        let server = GatewayServer::new(&test_config(Some("t0k")), test_factory().await).unwrap();
+        let (addr, handle) = server.start().await.unwrap();
+        let client = reqwest::Client::new();
+        let url = format!("http://{}/v1/sessions", addr);
+
+        let resp = client.post(&url).json(&json!({})).send().await.unwrap();
+        assert_eq!(resp.status(), 401);
+
+        let resp = client
+            .post(&url)
+            .bearer_auth("wrong")
+            .json(&json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 401);
+
+        let resp = client
+            .post(&url)
+            .bearer_auth("t0k")
+            .json(&json!({ "title": "From test" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["title"], "From test");
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_query_token_only_accepted_for_event_stream() {
+        let server = GatewayServer::new(&test_config(Some("t0k")), test_factory().await).unwrap();
+        let (addr, handle) = server.start().await.unwrap();
+        let client = reqwest::Client::new();
+
+        let resp = client
+            .post(format!("http://{}/v1/sessions?token=t0k", addr))
+            .json(&json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 401);
+
+        // Passes auth; the handshake itself fails without the other WebSocket headers
+        let resp = client
+            .get(format!("http://{}/v1/events?token=t0k", addr))
+            .header("Upgrade", "websocket")
+            .send()
+            .await
+            .unwrap();
+        assert_ne!(resp.status(), 401);
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_send_message_runs_agent_and_broadcasts_progress() {
+        let server = GatewayServer::new(&test_config(Some("t0k")), test_factory().await).unwrap();
+        let mut events = server.subscribe();
+        let (addr, handle) = server.start().await.unwrap();
+
+        let resp = reqwest::Client::new()
+            .post(format!("http://{}/v1/messages", addr))
+            .bearer_auth("t0k")
+            .json(&json!({ "message": "hi" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["content"], "Hello from the gateway");
+        let session_id: Uuid = serde_json::from_value(body["session_id"].clone()).unwrap();
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.session_id, session_id);
+        let event_json = serde_json::to_value(&event).unwrap();
+        assert!(event_json["type"].is_string());
+
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session_is_404() {
+        let server = GatewayServer::new(&test_config(Some("t0k")), test_factory().await).unwrap();
+        let (addr, handle) = server.start().await.unwrap();
+
+        let resp = reqwest::Client::new()
+            .post(format!("http://{}/v1/messages", addr))
+            .bearer_auth("t0k")
+            .json(&json!({ "session_id": Uuid::new_v4(), "message": "hi" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+
+        handle.abort();
+    }
+}
@@ -29,9 +29,12 @@ pub mod app;
 pub mod brain;
 pub mod cli;
 pub mod config;
+pub mod daemon;
 pub mod db;
 pub mod error;
+pub mod gateway;
 pub mod logging;
+pub mod mcp;
 pub mod memory;
 pub mod services;
 pub mod tui;
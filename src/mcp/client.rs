@@ -0,0 +1,328 @@
+//! MCP client
+//!
+//! Performs the `initialize` handshake and exposes `tools/list` and
+//! `tools/call` over whichever transport the server is configured with.
+
+use super::error::{McpError, Result};
+use super::protocol::{
+    CallToolResult, Implementation, JsonRpcRequest, ListToolsResult, PROTOCOL_VERSION, ToolInfo,
+};
+use super::transport::{HttpTransport, StdioTransport, Transport};
+use crate::config::McpServerConfig;
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bound on `tools/list` pages, in case a server keeps handing out cursors
+const MAX_LIST_PAGES: usize = 50;
+
+/// A live connection to one MCP server
+pub struct McpClient {
+    server: String,
+    transport: Box<dyn Transport>,
+    next_id: AtomicU64,
+    timeout: Duration,
+    server_info: Option<Implementation>,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("server", &self.server)
+            .field("server_info", &self.server_info)
+            .finish()
+    }
+}
+
+impl McpClient {
+    /// Start (or reach) the configured server and complete the handshake
+    pub async fn connect(server: &str, config: &McpServerConfig) -> Result<Self> {
+        let transport: Box<dyn Transport> = match (&config.command, &config.url) {
+            (Some(command), _) => {
+                Box::new(StdioTransport::spawn(command, &config.args, &config.env)?)
+            }
+            (None, Some(url)) => Box::new(HttpTransport::new(url.clone(), config.headers.clone())),
+            (None, None) => {
+                return Err(McpError::Config(format!(
+                    "server '{}' needs either `command` or `url`",
+                    server
+                )));
+            }
+        };
+
+        Self::initialize(server, transport, Duration::from_secs(config.timeout_secs)).await
+    }
+
+    async fn initialize(
+        server: &str,
+        transport: Box<dyn Transport>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut client = Self {
+            server: server.to_string(),
+            transport,
+            next_id: AtomicU64::new(1),
+            timeout,
+            server_info: None,
+        };
+
+        let result = client
+            .request(
+                "initialize",
+                Some(json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": "opencrabs",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                })),
+            )
+            .await?;
+
+        client.server_info = result
+            .get("serverInfo")
+            .and_then(|info| serde_json::from_value(info.clone()).ok());
+
+        client
+            .transport
+            .notify(JsonRpcRequest::notification(
+                "notifications/initialized",
+                None,
+            ))
+            .await?;
+
+        tracing::debug!(
+            "MCP server '{}' initialized (protocol {})",
+            server,
+            result
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown")
+        );
+        Ok(client)
+    }
+
+    /// Configured name of this server
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Name and version the server reported during the handshake
+    pub fn server_info(&self) -> Option<&Implementation> {
+        self.server_info.as_ref()
+    }
+
+    /// List every tool the server offers, following pagination cursors
+    pub async fn list_tools(&self) -> Result<Vec<ToolInfo>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_LIST_PAGES {
+            let params = cursor.as_ref().map(|c| json!({ "cursor": c }));
+            let page: ListToolsResult =
+                serde_json::from_value(self.request("tools/list", params).await?)?;
+            tools.extend(page.tools);
+
+            match page.next_cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => return Ok(tools),
+            }
+        }
+
+        tracing::warn!(
+            "MCP server '{}' returned more than {} tool pages, truncating",
+            self.server,
+            MAX_LIST_PAGES
+        );
+        Ok(tools)
+    }
+
+    /// Invoke a tool by its server-side name
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<CallToolResult> {
+        let result = self
+            .request(
+                "tools/call",
+                Some(json!({ "name": name, "arguments": arguments })),
+            )
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    async fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = JsonRpcRequest::new(id, method, params);
+
+        let response = tokio::time::timeout(self.timeout, self.transport.request(request))
+            .await
+            .map_err(|_| McpError::Timeout(self.timeout.as_secs()))??;
+
+        if let Some(error) = response.error {
+            return Err(McpError::Server {
+                code: error.code,
+                message: error.message,
+            });
+        }
+        response
+            .result
+            .ok_or_else(|| McpError::Protocol(format!("{} response has no result", method)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn http_config(url: String) -> McpServerConfig {
+        McpServerConfig {
+            enabled: true,
+            command: None,
+            args: Vec::new(),
+            env: HashMap::new(),
+            url: Some(url),
+            headers: HashMap::from([("Authorization".to_string(), "Bearer t0k".to_string())]),
+            timeout_secs: 5,
+            trust_annotations: false,
+        }
+    }
+
+    async fn mock_handshake(server: &mut mockito::ServerGuard) -> (mockito::Mock, mockito::Mock) {
+        let init = server
+            .mock("POST", "/mcp")
+            .match_header("authorization", "Bearer t0k")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"id": 1, "method": "initialize"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("mcp-session-id", "sess-42")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-03-26","capabilities":{"tools":{}},"serverInfo":{"name":"weather","version":"1.0.0"}}}"#,
+            )
+            .create_async()
+            .await;
+        let initialized = server
+            .mock("POST", "/mcp")
+            .match_header("mcp-session-id", "sess-42")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"method": "notifications/initialized"}),
+            ))
+            .with_status(202)
+            .create_async()
+            .await;
+        (init, initialized)
+    }
+
+    #[tokio::test]
+    async fn test_http_handshake_and_tool_listing() {
+        let mut server = mockito::Server::new_async().await;
+        let (init, initialized) = mock_handshake(&mut server).await;
+        let first_page = server
+            .mock("POST", "/mcp")
+            .match_header("mcp-session-id", "sess-42")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"id": 2, "method": "tools/list"}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(
+                "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"tools\":[{\"name\":\"forecast\",\"inputSchema\":{\"type\":\"object\"}}],\"nextCursor\":\"p2\"}}\n\n",
+            )
+            .create_async()
+            .await;
+        let second_page = server
+            .mock("POST", "/mcp")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"id": 3, "method": "tools/list", "params": {"cursor": "p2"}}),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":3,"result":{"tools":[{"name":"alerts","inputSchema":{"type":"object"}}]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = McpClient::connect("weather", &http_config(format!("{}/mcp", server.url())))
+            .await
+            .unwrap();
+        assert_eq!(client.server_info().unwrap().name, "weather");
+
+        let tools = client.list_tools().await.unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["forecast", "alerts"]);
+
+        init.assert_async().await;
+        initialized.assert_async().await;
+        first_page.assert_async().await;
+        second_page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_error_is_surfaced() {
+        let mut server = mockito::Server::new_async().await;
+        let _handshake = mock_handshake(&mut server).await;
+        let _call = server
+            .mock("POST", "/mcp")
+            .match_body(mockito::Matcher::PartialJson(json!({"method": "tools/call"})))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32602,"message":"Unknown tool: nope"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = McpClient::connect("weather", &http_config(format!("{}/mcp", server.url())))
+            .await
+            .unwrap();
+        let err = client.call_tool("nope", json!({})).await.unwrap_err();
+        assert!(matches!(err, McpError::Server { code: -32602, .. }));
+    }
+
+    #[tokio::test]
+    async fn test_missing_command_and_url_is_rejected() {
+        let mut config = http_config(String::new());
+        config.url = None;
+        let err = McpClient::connect("broken", &config).await.unwrap_err();
+        assert!(matches!(err, McpError::Config(_)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_round_trip_answers_server_ping() {
+        // A scripted server: answers initialize, pings us before answering
+        // tools/call, then exits.
+        let script = r#"
+            read -r line
+            echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-03-26","capabilities":{},"serverInfo":{"name":"echo","version":"0.1"}}}'
+            read -r line
+            read -r line
+            echo '{"jsonrpc":"2.0","id":"srv-1","method":"ping"}'
+            read -r pong
+            case "$pong" in
+              *'"id":"srv-1"'*'"result"'*) echo '{"jsonrpc":"2.0","id":2,"result":{"content":[{"type":"text","text":"pong ok"}]}}' ;;
+              *) echo '{"jsonrpc":"2.0","id":2,"result":{"content":[{"type":"text","text":"no pong"}],"isError":true}}' ;;
+            esac
+        "#;
+        let config = McpServerConfig {
+            enabled: true,
+            command: Some("sh".to_string()),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
+            timeout_secs: 5,
+            trust_annotations: false,
+        };
+
+        let client = McpClient::connect("echo", &config).await.unwrap();
+        let result = client.call_tool("echo", json!({})).await.unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.to_text(), "pong ok");
+
+        // Script has exited; further requests fail instead of hanging
+        assert!(client.list_tools().await.is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! MCP error types
+
+use crate::brain::tools::ToolError;
+use thiserror::Error;
+
+/// Errors raised while talking to an MCP server
+#[derive(Debug, Error)]
+pub enum McpError {
+    /// Server entry is missing both `command` and `url`, or is otherwise unusable
+    #[error("Invalid MCP server config: {0}")]
+    Config(String),
+
+    /// Transport-level failure (bad HTTP status, broken pipe, ...)
+    #[error("MCP transport error: {0}")]
+    Transport(String),
+
+    /// The server answered with a JSON-RPC error object
+    #[error("MCP server error {code}: {message}")]
+    Server { code: i64, message: String },
+
+    /// The server sent something that is not valid MCP
+    #[error("MCP protocol error: {0}")]
+    Protocol(String),
+
+    /// No response within the configured timeout
+    #[error("MCP request timed out after {0}s")]
+    Timeout(u64),
+
+    /// The server process exited or the connection was dropped
+    #[error("MCP connection closed")]
+    Closed,
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSON error
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// HTTP error
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Result type for MCP operations
+pub type Result<T> = std::result::Result<T, McpError>;
+
+impl From<McpError> for ToolError {
+    fn from(err: McpError) -> Self {
+        match err {
+            McpError::Timeout(secs) => ToolError::Timeout(secs),
+            other => ToolError::Execution(other.to_string()),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! MCP (Model Context Protocol) Module
+//!
+//! Connects to external MCP servers listed under `[mcp.servers]` in
+//! config.toml, discovers their tools via `tools/list`, and registers each
+//! one in the `ToolRegistry` as `mcp__<server>__<tool>`. Calls are proxied
+//! through `tools/call`. Servers are reached over stdio (spawned process)
+//! or streamable HTTP.
+//!
+//! The reverse direction lives in `server`: `opencrabs mcp serve` publishes
+//! our own registry to other MCP hosts over stdio.
+
+mod client;
+mod error;
+pub mod protocol;
+mod server;
+mod tool;
+mod transport;
+
+pub use client::McpClient;
+pub use error::{McpError, Result};
+pub use server::{ALLOW_ALL, McpServer};
+pub use tool::{McpTool, qualified_name};
+
+use crate::brain::tools::ToolRegistry;
+use crate::config::McpConfig;
+use std::sync::Arc;
+
+/// Connect to every enabled server and register its tools.
+///
+/// Servers are contacted concurrently. A server that fails to start or
+/// answer is logged and skipped so it can't block startup. Returns the
+/// number of tools registered.
+pub async fn register_servers(config: &McpConfig, registry: &mut ToolRegistry) -> usize {
+    let connections = config
+        .servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .map(|(name, server)| async move {
+            let result = async {
+                let client = Arc::new(McpClient::connect(name, server).await?);
+                let tools = client.list_tools().await?;
+                Ok::<_, McpError>((client, tools))
+            }
+            .await;
+            (name, result)
+        });
+
+    let mut registered = 0;
+    for (name, result) in futures::future::join_all(connections).await {
+        match result {
+            Ok((client, tools)) => {
+                tracing::info!("🔌 MCP server '{}' connected ({} tools)", name, tools.len());
+                for info in tools {
+                    registry.register(Arc::new(McpTool::new(
+                        client.clone(),
+                        info,
+                        config.servers[name].trust_annotations,
+                    )));
+                    registered += 1;
+                }
+            }
+            Err(e) => tracing::warn!("⚠️ MCP server '{}' unavailable: {}", name, e),
+        }
+    }
+    registered
+}
@@ -0,0 +1,328 @@
+//! MCP wire types
+//!
+//! JSON-RPC 2.0 envelopes plus the subset of the MCP schema used for tool
+//! discovery and invocation.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// MCP protocol revision we speak
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// JSON-RPC version string
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// JSON-RPC error code for an unknown method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for malformed params
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC error code for a failure inside the server
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// A JSON-RPC request, or a notification when `id` is absent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// Build a request that expects a response
+    pub fn new(id: u64, method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Some(Value::from(id)),
+            method: method.into(),
+            params,
+        }
+    }
+
+    /// Build a fire-and-forget notification
+    pub fn notification(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: None,
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// A JSON-RPC response carrying either `result` or `error`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.into(),
+                data: None,
+            }),
+        }
+    }
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// Any message that can arrive on an MCP connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonRpcMessage {
+    Request(JsonRpcRequest),
+    Response(JsonRpcResponse),
+}
+
+/// Name and version of an MCP client or server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Implementation {
+    pub name: String,
+    pub version: String,
+}
+
+/// A tool as advertised by `tools/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Behavioural hints a server may attach to a tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+}
+
+/// Result of `tools/list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListToolsResult {
+    pub tools: Vec<ToolInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Result of `tools/call`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<Content>,
+    #[serde(default)]
+    pub is_error: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+}
+
+impl CallToolResult {
+    /// A successful result holding a single text block
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![Content::Text { text: text.into() }],
+            is_error: false,
+            structured_content: None,
+        }
+    }
+
+    /// A failed result holding a single text block
+    pub fn error(text: impl Into<String>) -> Self {
+        Self {
+            is_error: true,
+            ..Self::text(text)
+        }
+    }
+
+    /// Flatten the content blocks into plain text for the LLM
+    pub fn to_text(&self) -> String {
+        let parts: Vec<String> = self.content.iter().filter_map(Content::to_text).collect();
+        if parts.is_empty()
+            && let Some(structured) = &self.structured_content
+        {
+            return structured.to_string();
+        }
+        parts.join("\n")
+    }
+}
+
+/// A content block inside a tool result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Text {
+        text: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Image {
+        data: String,
+        mime_type: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Audio {
+        data: String,
+        mime_type: String,
+    },
+    Resource {
+        resource: EmbeddedResource,
+    },
+    ResourceLink {
+        uri: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Block types added in later protocol revisions
+    #[serde(other)]
+    Unknown,
+}
+
+impl Content {
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Content::Text { text } => Some(text.clone()),
+            Content::Image { data, mime_type } => Some(format!(
+                "[image: {}, {} bytes base64]",
+                mime_type,
+                data.len()
+            )),
+            Content::Audio { data, mime_type } => Some(format!(
+                "[audio: {}, {} bytes base64]",
+                mime_type,
+                data.len()
+            )),
+            Content::Resource { resource } => Some(
+                resource
+                    .text
+                    .clone()
+                    .unwrap_or_else(|| format!("[resource: {}]", resource.uri)),
+            ),
+            Content::ResourceLink { uri, name } => Some(match name {
+                Some(name) => format!("[resource link: {} ({})]", name, uri),
+                None => format!("[resource link: {}]", uri),
+            }),
+            Content::Unknown => None,
+        }
+    }
+}
+
+/// Resource contents embedded in a tool result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedResource {
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_distinguishes_requests_from_responses() {
+        let request: JsonRpcMessage =
+            serde_json::from_value(json!({"jsonrpc": "2.0", "id": 7, "method": "ping"})).unwrap();
+        assert!(matches!(request, JsonRpcMessage::Request(r) if r.method == "ping"));
+
+        let response: JsonRpcMessage =
+            serde_json::from_value(json!({"jsonrpc": "2.0", "id": 7, "result": {}})).unwrap();
+        assert!(matches!(response, JsonRpcMessage::Response(r) if r.id == json!(7)));
+    }
+
+    #[test]
+    fn test_notification_omits_id() {
+        let value = serde_json::to_value(JsonRpcRequest::notification(
+            "notifications/initialized",
+            None,
+        ))
+        .unwrap();
+        assert!(value.get("id").is_none());
+        assert!(value.get("params").is_none());
+    }
+
+    #[test]
+    fn test_call_result_flattens_content() {
+        let result: CallToolResult = serde_json::from_value(json!({
+            "content": [
+                {"type": "text", "text": "hello"},
+                {"type": "image", "data": "aGk=", "mimeType": "image/png"},
+                {"type": "resource", "resource": {"uri": "file:///a.txt", "text": "file body"}},
+                {"type": "something_new", "payload": 1}
+            ],
+            "isError": false
+        }))
+        .unwrap();
+
+        assert_eq!(
+            result.to_text(),
+            "hello\n[image: image/png, 4 bytes base64]\nfile body"
+        );
+    }
+
+    #[test]
+    fn test_call_result_falls_back_to_structured_content() {
+        let result: CallToolResult = serde_json::from_value(json!({
+            "content": [],
+            "structuredContent": {"temperature": 21}
+        }))
+        .unwrap();
+        assert_eq!(result.to_text(), r#"{"temperature":21}"#);
+    }
+
+    #[test]
+    fn test_tool_info_reads_annotations() {
+        let info: ToolInfo = serde_json::from_value(json!({
+            "name": "get_weather",
+            "inputSchema": {"type": "object"},
+            "annotations": {"readOnlyHint": true}
+        }))
+        .unwrap();
+        assert_eq!(info.annotations.unwrap().read_only_hint, Some(true));
+        assert!(info.description.is_none());
+    }
+}
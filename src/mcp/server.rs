@@ -0,0 +1,490 @@
+//! MCP server
+//!
+//! Publishes the tools in a `ToolRegistry` to an MCP host over stdio
+//! (`opencrabs mcp serve`). Calls are checked against the tool permission
+//! policy: calls it would ask about in the TUI are hidden and refused unless
+//! the tool is explicitly allowlisted, since there is no one to ask on this
+//! side of the pipe, and denied calls never run.
+
+use super::error::Result;
+use super::protocol::{
+    CallToolResult, INTERNAL_ERROR, INVALID_PARAMS, JsonRpcMessage, JsonRpcRequest,
+    JsonRpcResponse, METHOD_NOT_FOUND, PROTOCOL_VERSION, ToolAnnotations, ToolInfo,
+};
+use crate::brain::tools::{PolicyAction, Tool, ToolExecutionContext, ToolRegistry};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+/// JSON-RPC error code for unparseable input
+const PARSE_ERROR: i64 = -32700;
+
+/// Allowlist entry that permits every tool
+pub const ALLOW_ALL: &str = "*";
+
+#[derive(Deserialize)]
+struct CallToolParams {
+    name: String,
+    #[serde(default)]
+    arguments: Option<Value>,
+}
+
+/// Serves a tool registry over MCP
+pub struct McpServer {
+    registry: Arc<ToolRegistry>,
+    allowed_tools: HashSet<String>,
+    context: ToolExecutionContext,
+}
+
+impl McpServer {
+    pub fn new(registry: Arc<ToolRegistry>, context: ToolExecutionContext) -> Self {
+        Self {
+            registry,
+            allowed_tools: HashSet::new(),
+            context,
+        }
+    }
+
+    /// Permit tools the policy would ask about; `"*"` permits all of them.
+    /// Policy denials still apply.
+    pub fn with_allowed_tools(mut self, tools: impl IntoIterator<Item = String>) -> Self {
+        self.allowed_tools.extend(tools);
+        self
+    }
+
+    fn is_allowlisted(&self, name: &str) -> bool {
+        self.allowed_tools.contains(ALLOW_ALL) || self.allowed_tools.contains(name)
+    }
+
+    /// Policy outcome for a call from the host, with `ask` resolved by the allowlist
+    fn permission(&self, name: &str, input: &Value) -> PolicyAction {
+        match self.registry.check_policy(name, input, &self.context) {
+            Some(decision) => match decision.action {
+                PolicyAction::Ask if self.is_allowlisted(name) => PolicyAction::Allow,
+                action => action,
+            },
+            None => PolicyAction::Deny,
+        }
+    }
+
+    /// Listed tools are judged without arguments, so a tool that the policy
+    /// only allows for particular arguments is listed once it's allowlisted
+    fn is_exposed(&self, tool: &dyn Tool) -> bool {
+        self.permission(tool.name(), &json!({})) == PolicyAction::Allow
+    }
+
+    fn exposed_tools(&self) -> Vec<Arc<dyn Tool>> {
+        let mut names = self.registry.list_tools();
+        names.sort();
+        names
+            .iter()
+            .filter_map(|name| self.registry.get(name))
+            .filter(|tool| self.is_exposed(tool.as_ref()))
+            .collect()
+    }
+
+    /// Handle one incoming message; returns the reply for requests
+    pub async fn handle_message(&self, message: JsonRpcMessage) -> Option<JsonRpcResponse> {
+        let JsonRpcMessage::Request(request) = message else {
+            // We never send requests, so there is nothing to match responses against
+            return None;
+        };
+        let Some(id) = request.id.clone() else {
+            tracing::debug!("MCP notification: {}", request.method);
+            return None;
+        };
+        Some(self.handle_request(id, request).await)
+    }
+
+    async fn handle_request(&self, id: Value, request: JsonRpcRequest) -> JsonRpcResponse {
+        match request.method.as_str() {
+            "initialize" => JsonRpcResponse::success(
+                id,
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": { "tools": { "listChanged": false } },
+                    "serverInfo": {
+                        "name": "opencrabs",
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            ),
+            "ping" => JsonRpcResponse::success(id, json!({})),
+            "tools/list" => {
+                let tools: Vec<ToolInfo> = self
+                    .exposed_tools()
+                    .iter()
+                    .map(|tool| ToolInfo {
+                        name: tool.name().to_string(),
+                        description: Some(tool.description().to_string()),
+                        input_schema: tool.input_schema(),
+                        annotations: Some(ToolAnnotations {
+                            title: None,
+                            read_only_hint: Some(tool.is_read_only()),
+                            destructive_hint: Some(tool.requires_approval()),
+                        }),
+                    })
+                    .collect();
+                JsonRpcResponse::success(id, json!({ "tools": tools }))
+            }
+            "tools/call" => {
+                let params: CallToolParams =
+                    match serde_json::from_value(request.params.unwrap_or(Value::Null)) {
+                        Ok(params) => params,
+                        Err(e) => {
+                            return JsonRpcResponse::error(
+                                id,
+                                INVALID_PARAMS,
+                                format!("Invalid tools/call params: {}", e),
+                            );
+                        }
+                    };
+                match self.call_tool(params).await {
+                    Ok(result) => match serde_json::to_value(result) {
+                        Ok(value) => JsonRpcResponse::success(id, value),
+                        Err(e) => JsonRpcResponse::error(id, INTERNAL_ERROR, e.to_string()),
+                    },
+                    Err(message) => JsonRpcResponse::error(id, INVALID_PARAMS, message),
+                }
+            }
+            other => {
+                JsonRpcResponse::error(id, METHOD_NOT_FOUND, format!("Method not found: {}", other))
+            }
+        }
+    }
+
+    /// Run a tool. `Err` is a protocol error (unknown tool); tool failures
+    /// and refusals come back as `isError` results the model can read.
+    async fn call_tool(
+        &self,
+        params: CallToolParams,
+    ) -> std::result::Result<CallToolResult, String> {
+        let tool = self
+            .registry
+            .get(&params.name)
+            .ok_or_else(|| format!("Unknown tool: {}", params.name))?;
+
+        let arguments = params.arguments.unwrap_or_else(|| json!({}));
+        match self.permission(tool.name(), &arguments) {
+            PolicyAction::Allow => {}
+            PolicyAction::Ask => {
+                tracing::warn!("🚫 MCP host tried to call gated tool '{}'", params.name);
+                return Ok(CallToolResult::error(format!(
+                    "Tool '{}' requires approval and is not allowlisted. \
+                     Restart `opencrabs mcp serve` with --allow-tool {} to enable it.",
+                    params.name, params.name
+                )));
+            }
+            PolicyAction::Deny => {
+                tracing::warn!("🚫 MCP host tried to call denied tool '{}'", params.name);
+                return Ok(CallToolResult::error(format!(
+                    "Tool '{}' is denied by the permission policy for these arguments.",
+                    params.name
+                )));
+            }
+        }
+
+        // Reaching here means the policy allows the call or the operator allowlisted it
+        let context = self.context.clone().with_auto_approve(true);
+
+        Ok(
+            match self
+                .registry
+                .execute(&params.name, arguments, &context)
+                .await
+            {
+                Ok(result) if result.success => CallToolResult::text(result.output),
+                Ok(result) => CallToolResult::error(
+                    result
+                        .error
+                        .unwrap_or_else(|| "Tool failed without an error message".to_string()),
+                ),
+                Err(e) => CallToolResult::error(e.to_string()),
+            },
+        )
+    }
+
+    /// Serve newline-delimited JSON-RPC until `reader` reaches EOF.
+    ///
+    /// Requests are handled concurrently so a slow tool call doesn't block
+    /// pings or other calls; replies may therefore arrive out of order.
+    pub async fn serve<R, W>(self: Arc<Self>, reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let writer = Arc::new(Mutex::new(writer));
+        let mut lines = BufReader::new(reader).lines();
+        let mut in_flight = JoinSet::new();
+
+        while let Some(line) = lines.next_line().await? {
+            // Reap finished calls so a long session doesn't accumulate handles
+            while in_flight.try_join_next().is_some() {}
+
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message = match serde_json::from_str::<JsonRpcMessage>(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    let reply = JsonRpcResponse::error(Value::Null, PARSE_ERROR, e.to_string());
+                    write_line(&writer, &reply).await?;
+                    continue;
+                }
+            };
+
+            let server = self.clone();
+            let writer = writer.clone();
+            in_flight.spawn(async move {
+                if let Some(reply) = server.handle_message(message).await
+                    && let Err(e) = write_line(&writer, &reply).await
+                {
+                    tracing::warn!("Failed to write MCP reply: {}", e);
+                }
+            });
+        }
+
+        // Let outstanding calls finish before the host sees EOF
+        while in_flight.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Serve over this process's stdin/stdout
+    pub async fn serve_stdio(self: Arc<Self>) -> Result<()> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+}
+
+async fn write_line<W, T>(writer: &Mutex<W>, message: &T) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: serde::Serialize,
+{
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut writer = writer.lock().await;
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brain::tools::{ToolCapability, ToolPolicy, ToolResult};
+    use async_trait::async_trait;
+    use uuid::Uuid;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+        fn description(&self) -> &str {
+            "Echo the input text"
+        }
+        fn input_schema(&self) -> Value {
+            json!({"type": "object", "properties": {"text": {"type": "string"}}})
+        }
+        fn capabilities(&self) -> Vec<ToolCapability> {
+            vec![ToolCapability::ReadFiles]
+        }
+        async fn execute(
+            &self,
+            input: Value,
+            _context: &ToolExecutionContext,
+        ) -> crate::brain::tools::Result<ToolResult> {
+            Ok(ToolResult::success(
+                input["text"].as_str().unwrap_or_default().to_string(),
+            ))
+        }
+    }
+
+    struct WipeTool;
+
+    #[async_trait]
+    impl Tool for WipeTool {
+        fn name(&self) -> &str {
+            "wipe"
+        }
+        fn description(&self) -> &str {
+            "Delete everything"
+        }
+        fn input_schema(&self) -> Value {
+            json!({"type": "object"})
+        }
+        fn capabilities(&self) -> Vec<ToolCapability> {
+            vec![ToolCapability::WriteFiles]
+        }
+        async fn execute(
+            &self,
+            _input: Value,
+            _context: &ToolExecutionContext,
+        ) -> crate::brain::tools::Result<ToolResult> {
+            Ok(ToolResult::success("wiped".to_string()))
+        }
+    }
+
+    fn server(allowed: &[&str]) -> McpServer {
+        server_with_policy(allowed, ToolPolicy::default())
+    }
+
+    fn server_with_policy(allowed: &[&str], policy: ToolPolicy) -> McpServer {
+        let mut registry = ToolRegistry::new();
+        registry.register(Arc::new(EchoTool));
+        registry.register(Arc::new(WipeTool));
+        registry.set_policy(Arc::new(policy));
+        McpServer::new(
+            Arc::new(registry),
+            ToolExecutionContext::new(Uuid::new_v4()),
+        )
+        .with_allowed_tools(allowed.iter().map(|s| s.to_string()))
+    }
+
+    async fn call(server: &McpServer, method: &str, params: Value) -> JsonRpcResponse {
+        server
+            .handle_message(JsonRpcMessage::Request(JsonRpcRequest::new(
+                1,
+                method,
+                Some(params),
+            )))
+            .await
+            .unwrap()
+    }
+
+    fn listed_names(response: &JsonRpcResponse) -> Vec<String> {
+        response.result.as_ref().unwrap()["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_dangerous_tools_are_hidden_by_default() {
+        let server = server(&[]);
+        let response = call(&server, "tools/list", json!({})).await;
+        assert_eq!(listed_names(&response), vec!["echo"]);
+
+        let tool = &response.result.unwrap()["tools"][0];
+        assert_eq!(tool["inputSchema"]["properties"]["text"]["type"], "string");
+        assert_eq!(tool["annotations"]["readOnlyHint"], true);
+    }
+
+    #[tokio::test]
+    async fn test_gated_tool_call_is_refused() {
+        let server = server(&[]);
+        let response = call(&server, "tools/call", json!({"name": "wipe"})).await;
+        let result: CallToolResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(result.is_error);
+        assert!(result.to_text().contains("--allow-tool wipe"));
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_tool_is_listed_and_runs() {
+        let server = server(&["wipe"]);
+        let response = call(&server, "tools/list", json!({})).await;
+        assert_eq!(listed_names(&response), vec!["echo", "wipe"]);
+
+        let response = call(
+            &server,
+            "tools/call",
+            json!({"name": "wipe", "arguments": {}}),
+        )
+        .await;
+        let result: CallToolResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.to_text(), "wiped");
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_allows_everything() {
+        let server = server(&[ALLOW_ALL]);
+        let response = call(&server, "tools/list", json!({})).await;
+        assert_eq!(listed_names(&response), vec!["echo", "wipe"]);
+    }
+
+    #[tokio::test]
+    async fn test_policy_decides_exposure() {
+        let policy = ToolPolicy::from_toml(
+            r#"
+[[rules]]
+action = "allow"
+tool = "wipe"
+
+[[rules]]
+action = "deny"
+tool = "echo"
+"#,
+        )
+        .unwrap();
+        // Allowed by policy without an allowlist; denied despite the wildcard
+        let server = server_with_policy(&[ALLOW_ALL], policy);
+        let response = call(&server, "tools/list", json!({})).await;
+        assert_eq!(listed_names(&response), vec!["wipe"]);
+
+        let response = call(
+            &server,
+            "tools/call",
+            json!({"name": "echo", "arguments": {"text": "hi"}}),
+        )
+        .await;
+        let result: CallToolResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(result.is_error);
+        assert!(result.to_text().contains("denied"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_and_method_are_protocol_errors() {
+        let server = server(&[]);
+        let response = call(&server, "tools/call", json!({"name": "nope"})).await;
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+
+        let response = call(&server, "resources/list", json!({})).await;
+        assert_eq!(response.error.unwrap().code, METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_stream() {
+        let server = Arc::new(server(&[]));
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            "not json\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"text":"hi"}}}"#,
+            "\n",
+        );
+        let (writer, mut output) = tokio::io::duplex(64 * 1024);
+
+        server.serve(input.as_bytes(), writer).await.unwrap();
+
+        let mut raw = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut output, &mut raw)
+            .await
+            .unwrap();
+        let replies: Vec<JsonRpcResponse> = raw
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(replies.len(), 3);
+        let init = replies.iter().find(|r| r.id == json!(1)).unwrap();
+        assert_eq!(
+            init.result.as_ref().unwrap()["serverInfo"]["name"],
+            "opencrabs"
+        );
+        let parse_error = replies.iter().find(|r| r.id.is_null()).unwrap();
+        assert_eq!(parse_error.error.as_ref().unwrap().code, PARSE_ERROR);
+        let echo = replies.iter().find(|r| r.id == json!(2)).unwrap();
+        assert_eq!(echo.result.as_ref().unwrap()["content"][0]["text"], "hi");
+    }
+}
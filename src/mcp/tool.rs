@@ -0,0 +1,167 @@
+//! Dynamic `Tool` wrapper around a remote MCP tool
+
+use super::client::McpClient;
+use super::protocol::ToolInfo;
+use crate::brain::tools::{Result, Tool, ToolCapability, ToolExecutionContext, ToolResult};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Provider APIs cap tool names at 64 characters
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Name under which a remote tool is registered: `mcp__<server>__<tool>`.
+///
+/// Characters outside `[A-Za-z0-9_-]` are replaced so the result is accepted
+/// by every provider's tool-name pattern.
+pub fn qualified_name(server: &str, tool: &str) -> String {
+    let sanitize = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let mut name = format!("mcp__{}__{}", sanitize(server), sanitize(tool));
+    name.truncate(MAX_TOOL_NAME_LEN);
+    name
+}
+
+/// A tool discovered on an MCP server, proxied through `tools/call`
+pub struct McpTool {
+    client: Arc<McpClient>,
+    info: ToolInfo,
+    name: String,
+    description: String,
+    read_only: bool,
+}
+
+impl McpTool {
+    /// `trust_annotations` comes from the server's config; without it the
+    /// server's read-only hint is ignored.
+    pub fn new(client: Arc<McpClient>, info: ToolInfo, trust_annotations: bool) -> Self {
+        let name = qualified_name(client.server(), &info.name);
+        let description = match &info.description {
+            Some(desc) if !desc.trim().is_empty() => {
+                format!("[MCP: {}] {}", client.server(), desc)
+            }
+            _ => format!("Tool '{}' from MCP server '{}'", info.name, client.server()),
+        };
+        let read_only = read_only(&info, trust_annotations);
+        Self {
+            client,
+            info,
+            name,
+            description,
+            read_only,
+        }
+    }
+}
+
+/// Whether to treat the tool as read-only: the server says so and the user
+/// trusts it to say so.
+fn read_only(info: &ToolInfo, trust_annotations: bool) -> bool {
+    trust_annotations
+        && info
+            .annotations
+            .as_ref()
+            .and_then(|a| a.read_only_hint)
+            .unwrap_or(false)
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn input_schema(&self) -> Value {
+        if self.info.input_schema.is_object() {
+            self.info.input_schema.clone()
+        } else {
+            json!({ "type": "object", "properties": {} })
+        }
+    }
+
+    fn capabilities(&self) -> Vec<ToolCapability> {
+        // We can't know what a remote tool does; only a read-only hint from a
+        // trusted server skips approval.
+        if self.is_read_only() {
+            vec![ToolCapability::Network]
+        } else {
+            vec![ToolCapability::Network, ToolCapability::SystemModification]
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    async fn execute(&self, input: Value, _context: &ToolExecutionContext) -> Result<ToolResult> {
+        let arguments = if input.is_null() { json!({}) } else { input };
+        let result = self.client.call_tool(&self.info.name, arguments).await?;
+
+        let text = result.to_text();
+        let tool_result = if result.is_error {
+            ToolResult::error(if text.is_empty() {
+                format!("MCP tool '{}' reported an error", self.info.name)
+            } else {
+                text
+            })
+        } else {
+            ToolResult::success(text)
+        };
+        Ok(tool_result.with_metadata("mcp_server".to_string(), self.client.server().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualified_name_is_sanitized() {
+        assert_eq!(
+            qualified_name("github", "create_issue"),
+            "mcp__github__create_issue"
+        );
+        assert_eq!(
+            qualified_name("my server", "fs.read"),
+            "mcp__my_server__fs_read"
+        );
+    }
+
+    #[test]
+    fn test_read_only_hint_needs_trust() {
+        let info: ToolInfo = serde_json::from_value(json!({
+            "name": "delete_repo",
+            "inputSchema": { "type": "object" },
+            "annotations": { "readOnlyHint": true }
+        }))
+        .unwrap();
+        assert!(!read_only(&info, false));
+        assert!(read_only(&info, true));
+
+        let unannotated: ToolInfo = serde_json::from_value(json!({
+            "name": "list_repos",
+            "inputSchema": { "type": "object" }
+        }))
+        .unwrap();
+        assert!(!read_only(&unannotated, true));
+    }
+
+    #[test]
+    fn test_qualified_name_is_truncated() {
+        let name = qualified_name("srv", &"x".repeat(100));
+        assert_eq!(name.len(), MAX_TOOL_NAME_LEN);
+        assert!(name.starts_with("mcp__srv__"));
+    }
+}
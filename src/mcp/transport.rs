@@ -0,0 +1,341 @@
+//! MCP transports
+//!
+//! - **stdio:** spawns the server and exchanges newline-delimited JSON-RPC
+//!   over its stdin/stdout. A reader task routes responses back to waiting
+//!   callers and answers server-initiated `ping`s.
+//! - **Streamable HTTP:** POSTs each message to a single endpoint; responses
+//!   come back as plain JSON or as an SSE stream.
+
+use super::error::{McpError, Result};
+use super::protocol::{JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, METHOD_NOT_FOUND};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{Mutex, RwLock, oneshot};
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// A bidirectional JSON-RPC channel to one MCP server
+#[async_trait]
+pub(crate) trait Transport: Send + Sync {
+    /// Send a request and wait for its response
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse>;
+
+    /// Send a notification; no response is expected
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()>;
+}
+
+type PendingMap = Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<JsonRpcResponse>>>>;
+
+/// Removes a request's waiter when the request finishes or is dropped, e.g.
+/// by the client's timeout, so abandoned ids don't pile up in the map.
+struct PendingGuard<'a> {
+    pending: &'a PendingMap,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+/// Transport over a child process's stdin/stdout
+pub(crate) struct StdioTransport {
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: PendingMap,
+    closed: Arc<AtomicBool>,
+    /// Held so the process lives (and is killed) with the transport
+    _child: Child,
+}
+
+impl StdioTransport {
+    pub(crate) fn spawn(
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| McpError::Transport(format!("Failed to spawn '{}': {}", command, e)))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| McpError::Transport("Child stdin unavailable".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| McpError::Transport("Child stdout unavailable".to_string()))?;
+
+        // Server logs go to our log file, never to the terminal
+        if let Some(stderr) = child.stderr.take() {
+            let command = command.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!("[mcp:{}] {}", command, line);
+                }
+            });
+        }
+
+        let stdin = Arc::new(Mutex::new(stdin));
+        let pending: PendingMap = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        tokio::spawn(read_loop(
+            BufReader::new(stdout),
+            stdin.clone(),
+            pending.clone(),
+            closed.clone(),
+        ));
+
+        Ok(Self {
+            stdin,
+            pending,
+            closed,
+            _child: child,
+        })
+    }
+}
+
+async fn write_message<T: serde::Serialize>(stdin: &Mutex<ChildStdin>, message: &T) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(line.as_bytes()).await?;
+    stdin.flush().await?;
+    Ok(())
+}
+
+async fn read_loop(
+    stdout: BufReader<tokio::process::ChildStdout>,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: PendingMap,
+    closed: Arc<AtomicBool>,
+) {
+    let mut lines = stdout.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<JsonRpcMessage>(&line) {
+            Ok(JsonRpcMessage::Response(response)) => {
+                let waiter = response.id.as_u64().and_then(|id| {
+                    pending
+                        .lock()
+                        .expect("MCP pending map lock poisoned")
+                        .remove(&id)
+                });
+                match waiter {
+                    Some(tx) => {
+                        let _ = tx.send(response);
+                    }
+                    None => tracing::debug!("Dropping MCP response for unknown id {}", response.id),
+                }
+            }
+            Ok(JsonRpcMessage::Request(request)) => {
+                // Server-initiated requests: we only support ping
+                let Some(id) = request.id else {
+                    tracing::debug!("MCP notification: {}", request.method);
+                    continue;
+                };
+                let reply = if request.method == "ping" {
+                    JsonRpcResponse::success(id, Value::Object(Default::default()))
+                } else {
+                    JsonRpcResponse::error(
+                        id,
+                        METHOD_NOT_FOUND,
+                        format!("Method not supported by client: {}", request.method),
+                    )
+                };
+                if let Err(e) = write_message(&stdin, &reply).await {
+                    tracing::warn!("Failed to answer MCP server request: {}", e);
+                }
+            }
+            Err(e) => tracing::debug!("Ignoring malformed MCP message ({}): {}", e, line),
+        }
+    }
+
+    // Process exited: dropping the senders wakes every waiter with Closed
+    closed.store(true, Ordering::SeqCst);
+    pending
+        .lock()
+        .expect("MCP pending map lock poisoned")
+        .clear();
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let id = request
+            .id
+            .as_ref()
+            .and_then(Value::as_u64)
+            .ok_or_else(|| McpError::Protocol("Request id must be numeric".to_string()))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .expect("MCP pending map lock poisoned")
+            .insert(id, tx);
+        let _guard = PendingGuard {
+            pending: &self.pending,
+            id,
+        };
+
+        // The reader may have exited before our waiter was registered
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(McpError::Closed);
+        }
+
+        write_message(&self.stdin, &request).await?;
+        rx.await.map_err(|_| McpError::Closed)
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()> {
+        write_message(&self.stdin, &notification).await
+    }
+}
+
+/// Transport over MCP streamable HTTP
+pub(crate) struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+    session_id: RwLock<Option<String>>,
+}
+
+impl HttpTransport {
+    pub(crate) fn new(url: String, headers: HashMap<String, String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            headers,
+            session_id: RwLock::new(None),
+        }
+    }
+
+    async fn post(&self, message: &JsonRpcRequest) -> Result<reqwest::Response> {
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .json(message);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(session_id) = self.session_id.read().await.as_ref() {
+            builder = builder.header(SESSION_HEADER, session_id);
+        }
+
+        let response = builder.send().await?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.write().await = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(McpError::Transport(format!(
+                "{} returned {}: {}",
+                self.url, status, body
+            )));
+        }
+        Ok(response)
+    }
+}
+
+/// Pick the response with the given id out of an SSE body
+fn response_from_sse(body: &str, id: &Value) -> Option<JsonRpcResponse> {
+    body.lines()
+        .filter_map(|line| line.trim().strip_prefix("data:"))
+        .filter_map(|data| serde_json::from_str::<JsonRpcMessage>(data.trim()).ok())
+        .find_map(|message| match message {
+            JsonRpcMessage::Response(response) if &response.id == id => Some(response),
+            _ => None,
+        })
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        let id = request.id.clone().unwrap_or(Value::Null);
+        let response = self.post(&request).await?;
+
+        let is_sse = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        let body = response.text().await?;
+
+        if is_sse {
+            response_from_sse(&body, &id).ok_or_else(|| {
+                McpError::Protocol(format!("No response for request {} in SSE stream", id))
+            })
+        } else {
+            Ok(serde_json::from_str(&body)?)
+        }
+    }
+
+    async fn notify(&self, notification: JsonRpcRequest) -> Result<()> {
+        self.post(&notification).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_response_from_sse_matches_id() {
+        let body = "event: message\n\
+                    data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\"}\n\n\
+                    data: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"old\":true}}\n\n\
+                    data: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"ok\":true}}\n\n";
+        let response = response_from_sse(body, &json!(2)).unwrap();
+        assert_eq!(response.result.unwrap()["ok"], true);
+        assert!(response_from_sse(body, &json!(3)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_request_is_forgotten() {
+        // Reads requests but never answers
+        let transport = StdioTransport::spawn(
+            "sh",
+            &["-c".to_string(), "cat > /dev/null".to_string()],
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        let request = JsonRpcRequest::new(7, "tools/list", None);
+        let timed_out = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            transport.request(request),
+        )
+        .await;
+        assert!(timed_out.is_err());
+        assert!(transport.pending.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,464 @@
+//! Backend — where vector embeddings come from.
+//!
+//! The bundled GGUF model runs in-process via llama.cpp; a pre-downloaded
+//! GGUF file does the same without touching the network; an OpenAI-compatible
+//! `/embeddings` endpoint moves the work to a server (OpenAI, Ollama,
+//! llama.cpp) and works on CPUs without AVX. Embeddings from different
+//! backends live in different vector spaces, so each backend gets its own
+//! store file (see `store_file_name`).
+
+use once_cell::sync::Lazy;
+use qmd::{EmbeddingEngine, Store, pull_model};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::{EmbeddingBackendKind, EmbeddingConfig};
+
+/// Vector size of the bundled embeddinggemma-300M model.
+pub const DEFAULT_DIMENSIONS: usize = 768;
+
+/// Inputs per request to an `/embeddings` endpoint.
+const REMOTE_BATCH_SIZE: usize = 32;
+
+/// Env var read when `memory.embedding.api_key` is unset.
+const API_KEY_ENV: &str = "OPENCRABS_EMBEDDING_API_KEY";
+
+/// Embedding settings, read once so the store and the engine agree for the
+/// lifetime of the process. Changes take effect on restart.
+static CONFIG: Lazy<EmbeddingConfig> = Lazy::new(|| {
+    crate::config::Config::load()
+        .map(|c| c.memory.embedding)
+        .unwrap_or_default()
+});
+
+pub(super) fn embedding_config() -> &'static EmbeddingConfig {
+    &CONFIG
+}
+
+/// A source of embeddings for documents and queries.
+///
+/// Calls block; callers run them on `spawn_blocking` threads.
+pub trait EmbeddingBackend: Send {
+    /// Model name stored alongside each embedding.
+    fn model(&self) -> &str;
+
+    /// Embed a search query.
+    fn embed_query(&mut self, query: &str) -> Result<Vec<f32>, String>;
+
+    /// Embed a document, with its title where the model can use one.
+    fn embed_document(&mut self, body: &str, title: &str) -> Result<Vec<f32>, String>;
+
+    /// Embed many `(body, title)` documents. Results are in input order.
+    fn embed_batch(&mut self, docs: &[(String, String)]) -> Vec<Result<Vec<f32>, String>> {
+        docs.iter()
+            .map(|(body, title)| self.embed_document(body, title))
+            .collect()
+    }
+}
+
+/// Build the configured backend. Loads (and for `local`, downloads) the model.
+pub(super) fn create_backend(
+    config: &EmbeddingConfig,
+) -> Result<Box<dyn EmbeddingBackend>, String> {
+    match config.backend {
+        EmbeddingBackendKind::Local => {
+            let uri = config
+                .model
+                .as_deref()
+                .unwrap_or(qmd::llm::DEFAULT_EMBED_MODEL_URI);
+            Ok(Box::new(GgufBackend::pull(uri)?))
+        }
+        EmbeddingBackendKind::Path => {
+            let path = config.path.as_deref().ok_or_else(|| {
+                "memory.embedding.backend = \"path\" needs memory.embedding.path".to_string()
+            })?;
+            Ok(Box::new(GgufBackend::open(Path::new(path))?))
+        }
+        EmbeddingBackendKind::Openai => Ok(Box::new(OpenAiBackend::new(config)?)),
+        EmbeddingBackendKind::None => {
+            Err("embeddings disabled (memory.embedding.backend = \"none\")".to_string())
+        }
+    }
+}
+
+/// Vector size the store is created with.
+pub(super) fn dimensions(config: &EmbeddingConfig) -> usize {
+    config.dimensions.unwrap_or(DEFAULT_DIMENSIONS)
+}
+
+/// Store file for the configured backend.
+///
+/// The bundled model (and `none`, which never writes vectors) keep the
+/// original `memory.db`. Any other backend gets `memory-<id>.db`, so switching
+/// starts from an empty index that startup reindexing and backfill fill with
+/// embeddings from the new backend — and switching back reuses the old one.
+pub(super) fn store_file_name(config: &EmbeddingConfig) -> String {
+    let identity = match config.backend {
+        EmbeddingBackendKind::Local => match config.model.as_deref() {
+            None => return "memory.db".to_string(),
+            Some(uri) if uri == qmd::llm::DEFAULT_EMBED_MODEL_URI => {
+                return "memory.db".to_string();
+            }
+            Some(uri) => format!("local:{uri}"),
+        },
+        EmbeddingBackendKind::None => return "memory.db".to_string(),
+        EmbeddingBackendKind::Path => {
+            // The file name identifies the model; moving the file keeps the index
+            let name = config
+                .path
+                .as_deref()
+                .and_then(|p| Path::new(p).file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            format!("path:{name}")
+        }
+        EmbeddingBackendKind::Openai => format!(
+            "openai:{}:{}",
+            config.base_url.as_deref().unwrap_or_default(),
+            config.model.as_deref().unwrap_or_default()
+        ),
+    };
+    let identity = format!("{identity}:{}", dimensions(config));
+    let hash = Store::hash_content(&identity);
+    format!("memory-{}.db", hash.get(..12).unwrap_or(&hash))
+}
+
+/// One-line description of the configured backend, for `/memory`.
+pub fn describe_backend() -> String {
+    let config = embedding_config();
+    match config.backend {
+        EmbeddingBackendKind::Local => match &config.model {
+            Some(uri) => format!("local GGUF ({uri})"),
+            None => "local GGUF (embeddinggemma-300M)".to_string(),
+        },
+        EmbeddingBackendKind::Path => {
+            format!("GGUF file {}", config.path.as_deref().unwrap_or("?"))
+        }
+        EmbeddingBackendKind::Openai => format!(
+            "{} at {}",
+            config.model.as_deref().unwrap_or("?"),
+            config.base_url.as_deref().unwrap_or("?")
+        ),
+        EmbeddingBackendKind::None => "none (keyword search only)".to_string(),
+    }
+}
+
+/// Disable llama.cpp's C-level logging globally.
+///
+/// Must be called once before creating any EmbeddingEngine.
+/// Routes all llama.cpp log output through the tracing framework
+/// with logging disabled — zero stderr pollution.
+fn silence_llama_logs() {
+    use llama_cpp_2::{LogOptions, send_logs_to_tracing};
+    send_logs_to_tracing(LogOptions::default().with_logs_enabled(false));
+}
+
+/// Verify the CPU supports the instruction sets required by llama.cpp.
+/// Returns Err on x86 without AVX; passes through on ARM/other architectures.
+fn check_cpu_features() -> Result<(), String> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if !std::arch::is_x86_feature_detected!("avx") {
+            return Err(
+                "CPU lacks AVX — llama.cpp GGUF inference requires AVX (Sandy Bridge 2011+). \
+                 Memory search will use FTS-only; set memory.embedding.backend = \"openai\" \
+                 to use an embeddings server instead."
+                    .to_string(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// GGUF embedding model run in-process via llama.cpp.
+pub struct GgufBackend {
+    engine: EmbeddingEngine,
+    model: String,
+}
+
+impl GgufBackend {
+    /// Download the model behind `uri` (cached after the first call) and load it.
+    pub fn pull(uri: &str) -> Result<Self, String> {
+        check_cpu_features()?;
+        let pull =
+            pull_model(uri, false).map_err(|e| format!("Failed to pull embedding model: {e}"))?;
+        tracing::info!(
+            "Embedding model ready: {} ({:.1} MB)",
+            pull.model,
+            pull.size_bytes as f64 / 1_048_576.0
+        );
+        Self::load(&pull.path, pull.model)
+    }
+
+    /// Load a GGUF file that is already on disk. Never touches the network.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        check_cpu_features()?;
+        if !path.is_file() {
+            return Err(format!("Embedding model not found: {}", path.display()));
+        }
+        let model = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        Self::load(path, model)
+    }
+
+    fn load(path: &Path, model: String) -> Result<Self, String> {
+        silence_llama_logs();
+        let engine = EmbeddingEngine::new(path)
+            .map_err(|e| format!("Failed to init embedding engine: {e}"))?;
+        Ok(Self { engine, model })
+    }
+}
+
+impl EmbeddingBackend for GgufBackend {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed_query(&mut self, query: &str) -> Result<Vec<f32>, String> {
+        self.engine
+            .embed_query(query)
+            .map(|r| r.embedding)
+            .map_err(|e| e.to_string())
+    }
+
+    fn embed_document(&mut self, body: &str, title: &str) -> Result<Vec<f32>, String> {
+        self.engine
+            .embed_document(body, Some(title))
+            .map(|r| r.embedding)
+            .map_err(|e| e.to_string())
+    }
+
+    fn embed_batch(&mut self, docs: &[(String, String)]) -> Vec<Result<Vec<f32>, String>> {
+        let items: Vec<(String, Option<String>)> = docs
+            .iter()
+            .map(|(body, title)| (body.clone(), Some(title.clone())))
+            .collect();
+        self.engine
+            .embed_batch_with_progress(&items, |done, total| {
+                if done % 10 == 0 || done == total {
+                    tracing::debug!("Embedding progress: {done}/{total}");
+                }
+            })
+            .into_iter()
+            .map(|r| r.map(|e| e.embedding).map_err(|e| e.to_string()))
+            .collect()
+    }
+}
+
+/// Any OpenAI-compatible `/embeddings` endpoint.
+pub struct OpenAiBackend {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+    model: String,
+    /// Sent as `dimensions` when configured explicitly
+    requested_dimensions: Option<usize>,
+    /// Vector size the store was created with
+    dimensions: usize,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    #[serde(default)]
+    index: Option<usize>,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiBackend {
+    pub fn new(config: &EmbeddingConfig) -> Result<Self, String> {
+        let base_url = config.base_url.as_deref().ok_or_else(|| {
+            "memory.embedding.backend = \"openai\" needs memory.embedding.base_url".to_string()
+        })?;
+        let model = config.model.clone().ok_or_else(|| {
+            "memory.embedding.backend = \"openai\" needs memory.embedding.model".to_string()
+        })?;
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var(API_KEY_ENV).ok())
+            .filter(|k| !k.is_empty());
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(120))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+
+        Ok(Self {
+            client,
+            url: embeddings_url(base_url),
+            api_key,
+            model,
+            requested_dimensions: config.dimensions,
+            dimensions: dimensions(config),
+        })
+    }
+
+    fn request(&self, inputs: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|_| "embedding requests need a Tokio runtime".to_string())?;
+
+        let mut body = serde_json::json!({ "model": self.model, "input": inputs });
+        if let Some(dims) = self.requested_dimensions {
+            body["dimensions"] = serde_json::json!(dims);
+        }
+        let mut request = self.client.post(&self.url).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let mut data = runtime.block_on(async {
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Embedding request failed: {e}"))?;
+            let status = response.status();
+            if !status.is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(format!("Embedding endpoint returned {status}: {text}"));
+            }
+            response
+                .json::<EmbeddingsResponse>()
+                .await
+                .map(|r| r.data)
+                .map_err(|e| format!("Invalid embeddings response: {e}"))
+        })?;
+
+        if data.len() != inputs.len() {
+            return Err(format!(
+                "Embedding endpoint returned {} vectors for {} inputs",
+                data.len(),
+                inputs.len()
+            ));
+        }
+        data.sort_by_key(|d| d.index.unwrap_or(usize::MAX));
+        data.into_iter()
+            .map(|d| {
+                if d.embedding.len() == self.dimensions {
+                    Ok(d.embedding)
+                } else {
+                    Err(format!(
+                        "{} returned {}-dimensional vectors; set memory.embedding.dimensions = {}",
+                        self.model,
+                        d.embedding.len(),
+                        d.embedding.len()
+                    ))
+                }
+            })
+            .collect()
+    }
+}
+
+impl EmbeddingBackend for OpenAiBackend {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed_query(&mut self, query: &str) -> Result<Vec<f32>, String> {
+        self.request(&[query])?
+            .pop()
+            .ok_or_else(|| "Empty embeddings response".to_string())
+    }
+
+    fn embed_document(&mut self, body: &str, _title: &str) -> Result<Vec<f32>, String> {
+        self.embed_query(body)
+    }
+
+    fn embed_batch(&mut self, docs: &[(String, String)]) -> Vec<Result<Vec<f32>, String>> {
+        let mut out = Vec::with_capacity(docs.len());
+        for batch in docs.chunks(REMOTE_BATCH_SIZE) {
+            let inputs: Vec<&str> = batch.iter().map(|(body, _)| body.as_str()).collect();
+            match self.request(&inputs) {
+                Ok(vectors) => out.extend(vectors.into_iter().map(Ok)),
+                Err(e) => out.extend(batch.iter().map(|_| Err(e.clone()))),
+            }
+            tracing::debug!("Embedding progress: {}/{}", out.len(), docs.len());
+        }
+        out
+    }
+}
+
+/// `{base_url}/embeddings`, accepting a base URL with or without the suffix.
+fn embeddings_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    if base.ends_with("/embeddings") {
+        base.to_string()
+    } else {
+        format!("{base}/embeddings")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(backend: EmbeddingBackendKind) -> EmbeddingConfig {
+        EmbeddingConfig {
+            backend,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_embeddings_url() {
+        assert_eq!(
+            embeddings_url("http://localhost:11434/v1"),
+            "http://localhost:11434/v1/embeddings"
+        );
+        assert_eq!(
+            embeddings_url("https://api.openai.com/v1/embeddings/"),
+            "https://api.openai.com/v1/embeddings"
+        );
+    }
+
+    #[test]
+    fn test_default_backend_keeps_original_store() {
+        assert_eq!(
+            store_file_name(&config(EmbeddingBackendKind::Local)),
+            "memory.db"
+        );
+        assert_eq!(
+            store_file_name(&config(EmbeddingBackendKind::None)),
+            "memory.db"
+        );
+    }
+
+    #[test]
+    fn test_store_file_per_vector_space() {
+        let mut ollama = config(EmbeddingBackendKind::Openai);
+        ollama.base_url = Some("http://localhost:11434/v1".to_string());
+        ollama.model = Some("nomic-embed-text".to_string());
+        let name = store_file_name(&ollama);
+        assert!(name.starts_with("memory-") && name.ends_with(".db"));
+        assert_ne!(name, "memory.db");
+
+        // API key doesn't change the vector space
+        let mut with_key = ollama.clone();
+        with_key.api_key = Some("secret".to_string());
+        assert_eq!(store_file_name(&with_key), name);
+
+        // Model and dimensions do
+        let mut other_model = ollama.clone();
+        other_model.model = Some("mxbai-embed-large".to_string());
+        assert_ne!(store_file_name(&other_model), name);
+        let mut other_dims = ollama.clone();
+        other_dims.dimensions = Some(1024);
+        assert_ne!(store_file_name(&other_dims), name);
+    }
+
+    #[test]
+    fn test_openai_backend_requires_endpoint() {
+        let mut cfg = config(EmbeddingBackendKind::Openai);
+        assert!(OpenAiBackend::new(&cfg).is_err());
+        cfg.base_url = Some("http://localhost:8080/v1".to_string());
+        assert!(OpenAiBackend::new(&cfg).is_err());
+        cfg.model = Some("bge-m3".to_string());
+        assert!(OpenAiBackend::new(&cfg).is_ok());
+    }
+}
@@ -0,0 +1,973 @@
+//! Code — index a source tree into the `"code"` collection and search it.
+//!
+//! Files are split into chunks at definition boundaries (functions, impls,
+//! classes, ...) found via syntect scopes, so each document holds one or a
+//! few whole definitions. Each chunk is stored under the path
+//! `{abs_file}#{file_hash}:{start}-{end}`, which lets a sync pass skip
+//! unchanged files by prefix without reading their chunks back.
+//!
+//! A root is walked once per process ([`ensure_code_index`]); after that a
+//! file watcher re-chunks only the files that change.
+
+use once_cell::sync::Lazy;
+use qmd::{SearchResult, Store, hybrid_search_rrf};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use syntect::parsing::{ParseState, Scope, ScopeStackOp, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use super::COLLECTION_CODE;
+use super::embedding::{backfill_embeddings, engine_if_ready};
+use super::search::{extract_snippet, sanitize_fts_query};
+use super::watch::CodeWatcher;
+
+/// Files larger than this are generated or vendored more often than not.
+const MAX_FILE_BYTES: u64 = 256 * 1024;
+/// Upper bound on files indexed per root, so pointing at `/` can't stall the agent.
+const MAX_FILES: usize = 20_000;
+/// A chunk is closed at the next definition boundary once it reaches this many lines.
+const TARGET_CHUNK_LINES: usize = 40;
+/// A chunk is cut here even without a boundary.
+const MAX_CHUNK_LINES: usize = 120;
+/// Keeps chunks well inside the embedding model's context window.
+const MAX_CHUNK_BYTES: usize = 6_000;
+/// Lines of each chunk shown in search results.
+const PREVIEW_LINES: usize = 12;
+
+/// Extensions treated as source code.
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "pyi", "js", "jsx", "mjs", "cjs", "ts", "tsx", "go", "java", "kt", "kts", "scala",
+    "c", "h", "cc", "cpp", "cxx", "hpp", "hh", "cs", "m", "mm", "swift", "rb", "php", "lua", "pl",
+    "sh", "bash", "zsh", "sql", "ex", "exs", "erl", "hs", "ml", "mli", "clj", "dart", "vue",
+    "svelte", "zig", "nim", "r", "jl", "proto", "toml", "yaml", "yml", "md",
+];
+
+/// Directories never descended into by the fallback walker.
+const SKIP_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "vendor",
+    "__pycache__",
+];
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+/// Scopes that mark the name of a definition.
+static DEFINITION_SCOPES: Lazy<Vec<Scope>> = Lazy::new(|| {
+    [
+        "entity.name.function",
+        "entity.name.class",
+        "entity.name.struct",
+        "entity.name.enum",
+        "entity.name.trait",
+        "entity.name.impl",
+        "entity.name.interface",
+        "entity.name.type",
+        "entity.name.namespace",
+        "entity.name.module",
+    ]
+    .iter()
+    .filter_map(|s| Scope::new(s).ok())
+    .collect()
+});
+
+/// Set while a background embedding backfill for code chunks is running.
+static BACKFILLING: AtomicBool = AtomicBool::new(false);
+
+/// Roots kept current by a file watcher, with their stats. The async lock is
+/// held through a root's first pass so concurrent searches don't walk it twice.
+static WATCHED_ROOTS: Lazy<tokio::sync::Mutex<HashMap<PathBuf, CodeIndexStats>>> =
+    Lazy::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+/// A contiguous range of lines from one source file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeChunk {
+    /// First line, 1-based
+    pub start_line: usize,
+    /// Last line, 1-based and inclusive
+    pub end_line: usize,
+    /// Name of the first definition in the chunk, if any
+    pub symbol: Option<String>,
+    pub text: String,
+}
+
+/// A single search result from the code index.
+#[derive(Debug, Clone)]
+pub struct CodeResult {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub symbol: Option<String>,
+    pub snippet: String,
+    pub rank: f64,
+}
+
+/// Outcome of a sync pass over a source tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeIndexStats {
+    /// Source files found under the root
+    pub files: usize,
+    /// Files that were new or changed and got re-chunked
+    pub indexed: usize,
+    /// Chunks written for those files
+    pub chunks: usize,
+    /// Files whose chunks were dropped because they no longer exist
+    pub removed: usize,
+    /// Whether the file list was cut at `MAX_FILES`
+    pub truncated: bool,
+}
+
+/// Changes applied by a watcher pass over a batch of paths.
+#[derive(Debug, Default)]
+struct CodeSync {
+    /// Files that were new or changed and got re-chunked
+    indexed: usize,
+    /// Chunks written for those files
+    chunks: usize,
+    /// Files indexed for the first time
+    added: usize,
+    /// Files whose chunks were dropped
+    removed: usize,
+}
+
+/// Whether `root` should be indexed without being asked: a git checkout that
+/// isn't the home directory itself.
+pub fn should_auto_index(root: &Path) -> bool {
+    root.join(".git").exists() && dirs::home_dir().is_none_or(|home| home != root)
+}
+
+/// Bring the code index for `root` up to date.
+///
+/// Only files whose content changed since the last pass are re-chunked, and
+/// chunks of deleted files are removed. New chunks are embedded in the
+/// background when the engine is ready; until then search is FTS-only for them.
+pub async fn index_code(
+    store: &'static Mutex<Store>,
+    root: &Path,
+) -> Result<CodeIndexStats, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {e}", root.display()))?;
+
+    let stats = tokio::task::spawn_blocking(move || index_code_sync(store, &root))
+        .await
+        .map_err(|e| format!("spawn_blocking failed: {e}"))??;
+
+    if stats.chunks > 0 && engine_if_ready().is_some() {
+        spawn_backfill(store);
+    }
+    Ok(stats)
+}
+
+/// Index `root` on first use and keep it current with a file watcher, so
+/// searches don't walk the tree. Later calls return the stats kept up to date
+/// by the watcher. If no watcher can be started (e.g. the OS watch limit is
+/// reached), nothing is cached and every call syncs the tree instead.
+pub async fn ensure_code_index(
+    store: &'static Mutex<Store>,
+    root: &Path,
+) -> Result<CodeIndexStats, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {e}", root.display()))?;
+
+    let mut watched = WATCHED_ROOTS.lock().await;
+    if let Some(stats) = watched.get(&root) {
+        return Ok(stats.clone());
+    }
+
+    // Start watching before the full pass so edits made during it aren't lost
+    let watcher = CodeWatcher::start(&root)
+        .inspect_err(|e| tracing::warn!("Code watcher unavailable for {}: {e}", root.display()))
+        .ok();
+    let stats = index_code(store, &root).await?;
+    if let Some(watcher) = watcher {
+        watched.insert(root, stats.clone());
+        tokio::spawn(watcher.run(store));
+    }
+    Ok(stats)
+}
+
+/// Re-chunk or drop the files behind a batch of changed paths under `root`.
+pub(super) async fn sync_code_paths(
+    store: &'static Mutex<Store>,
+    root: &Path,
+    paths: Vec<PathBuf>,
+) -> Result<(), String> {
+    let root_owned = root.to_path_buf();
+    let sync =
+        tokio::task::spawn_blocking(move || sync_code_paths_sync(store, &root_owned, &paths))
+            .await
+            .map_err(|e| format!("spawn_blocking failed: {e}"))??;
+    if sync.indexed == 0 && sync.removed == 0 {
+        return Ok(());
+    }
+
+    if let Some(stats) = WATCHED_ROOTS.lock().await.get_mut(root) {
+        stats.files = (stats.files + sync.added).saturating_sub(sync.removed);
+        stats.indexed += sync.indexed;
+        stats.chunks += sync.chunks;
+        stats.removed += sync.removed;
+    }
+    tracing::debug!(
+        "Code watcher {}: {} re-chunked ({} chunks), {} removed",
+        root.display(),
+        sync.indexed,
+        sync.chunks,
+        sync.removed
+    );
+    if sync.chunks > 0 && engine_if_ready().is_some() {
+        spawn_backfill(store);
+    }
+    Ok(())
+}
+
+/// Embed chunks that don't have an embedding yet, one backfill at a time.
+fn spawn_backfill(store: &'static Mutex<Store>) {
+    if BACKFILLING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::task::spawn_blocking(move || {
+        backfill_embeddings(store);
+        BACKFILLING.store(false, Ordering::SeqCst);
+    });
+}
+
+fn index_code_sync(store: &Mutex<Store>, root: &Path) -> Result<CodeIndexStats, String> {
+    let (files, truncated) = list_source_files(root);
+    let mut stats = CodeIndexStats {
+        files: files.len(),
+        truncated,
+        ..Default::default()
+    };
+
+    let existing = existing_chunks(store, root)?;
+    let mut seen: HashSet<String> = HashSet::new();
+    for path in &files {
+        let Some(source) = read_source(path) else {
+            continue;
+        };
+        let file = path.to_string_lossy().to_string();
+        seen.insert(file.clone());
+        if let Some(chunks) = index_source(store, root, path, &source, existing.get(&file))? {
+            stats.indexed += 1;
+            stats.chunks += chunks;
+        }
+    }
+
+    // A truncated listing can't tell deleted files from unlisted ones
+    if !truncated {
+        for (file, (_, docs)) in &existing {
+            if !seen.contains(file) {
+                drop_chunks(store, docs)?;
+                stats.removed += 1;
+            }
+        }
+    }
+
+    tracing::debug!(
+        "Code index {}: {} files, {} re-chunked ({} chunks), {} removed",
+        root.display(),
+        stats.files,
+        stats.indexed,
+        stats.chunks,
+        stats.removed
+    );
+    Ok(stats)
+}
+
+fn sync_code_paths_sync(
+    store: &Mutex<Store>,
+    root: &Path,
+    paths: &[PathBuf],
+) -> Result<CodeSync, String> {
+    let existing = existing_chunks(store, root)?;
+    let mut sync = CodeSync::default();
+    let mut done: HashSet<PathBuf> = HashSet::new();
+
+    for path in paths {
+        // A directory moved in brings its files without an event per file
+        let mut files = Vec::new();
+        if path.is_dir() {
+            if is_indexable(root, path) {
+                walk(path, &mut files);
+            }
+        } else {
+            files.push(path.clone());
+        }
+
+        for file in files {
+            if !done.insert(file.clone()) {
+                continue;
+            }
+            let key = file.to_string_lossy().to_string();
+            let source = (is_code_file(&file) && is_indexable(root, &file))
+                .then(|| read_source(&file))
+                .flatten();
+            match source {
+                Some(source) => {
+                    let previous = existing.get(&key);
+                    if let Some(chunks) = index_source(store, root, &file, &source, previous)? {
+                        sync.indexed += 1;
+                        sync.chunks += chunks;
+                        sync.added += previous.is_none() as usize;
+                    }
+                }
+                None => {
+                    if let Some((_, docs)) = existing.get(&key) {
+                        drop_chunks(store, docs)?;
+                        sync.removed += 1;
+                    }
+                }
+            }
+        }
+
+        // Deleting a directory only reports the directory itself
+        if !path.exists() {
+            for (file, (_, docs)) in &existing {
+                let file = Path::new(file);
+                if file != path && file.starts_with(path) && done.insert(file.to_path_buf()) {
+                    drop_chunks(store, docs)?;
+                    sync.removed += 1;
+                }
+            }
+        }
+    }
+    Ok(sync)
+}
+
+/// File → (hash, chunk doc paths) for everything already indexed under `root`.
+fn existing_chunks(
+    store: &Mutex<Store>,
+    root: &Path,
+) -> Result<HashMap<String, (String, Vec<String>)>, String> {
+    let s = store
+        .lock()
+        .map_err(|e| format!("Store lock poisoned: {e}"))?;
+    let paths = s
+        .get_active_document_paths(COLLECTION_CODE)
+        .map_err(|e| format!("Failed to list code documents: {e}"))?;
+    let mut existing: HashMap<String, (String, Vec<String>)> = HashMap::new();
+    for doc_path in paths {
+        if let Some((file, hash, ..)) = parse_doc_path(&doc_path)
+            && Path::new(file).starts_with(root)
+        {
+            let entry = existing
+                .entry(file.to_string())
+                .or_insert_with(|| (hash.to_string(), Vec::new()));
+            entry.1.push(doc_path.clone());
+        }
+    }
+    Ok(existing)
+}
+
+/// Re-chunk one file unless its hash matches what's indexed. Returns the
+/// number of chunks written, `None` when the file was unchanged.
+fn index_source(
+    store: &Mutex<Store>,
+    root: &Path,
+    path: &Path,
+    source: &str,
+    existing: Option<&(String, Vec<String>)>,
+) -> Result<Option<usize>, String> {
+    let file = path.to_string_lossy();
+    let full_hash = Store::hash_content(source);
+    let hash = full_hash.get(..12).unwrap_or(&full_hash);
+
+    let stale = match existing {
+        Some((old_hash, _)) if old_hash == hash => return Ok(None),
+        Some((_, docs)) => docs.as_slice(),
+        None => &[],
+    };
+
+    let rel = path.strip_prefix(root).unwrap_or(path).to_string_lossy();
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let chunks = chunk_source(source, ext);
+    let now = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    drop_chunks(store, stale)?;
+    let s = store
+        .lock()
+        .map_err(|e| format!("Store lock poisoned: {e}"))?;
+    for chunk in &chunks {
+        let doc_path = format!("{file}#{hash}:{}-{}", chunk.start_line, chunk.end_line);
+        let title = match &chunk.symbol {
+            Some(sym) => format!("{rel}:{}-{} {sym}", chunk.start_line, chunk.end_line),
+            None => format!("{rel}:{}-{}", chunk.start_line, chunk.end_line),
+        };
+        let content_hash = Store::hash_content(&chunk.text);
+        s.insert_content(&content_hash, &chunk.text, &now)
+            .map_err(|e| format!("Failed to insert content: {e}"))?;
+        s.insert_document(
+            COLLECTION_CODE,
+            &doc_path,
+            &title,
+            &content_hash,
+            &now,
+            &now,
+        )
+        .map_err(|e| format!("Failed to insert document: {e}"))?;
+    }
+    Ok(Some(chunks.len()))
+}
+
+fn drop_chunks(store: &Mutex<Store>, docs: &[String]) -> Result<(), String> {
+    if docs.is_empty() {
+        return Ok(());
+    }
+    let s = store
+        .lock()
+        .map_err(|e| format!("Store lock poisoned: {e}"))?;
+    for doc_path in docs {
+        if let Err(e) = s.deactivate_document(COLLECTION_CODE, doc_path) {
+            tracing::debug!("Failed to drop chunk {doc_path}: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Hybrid search over code chunks under `root`: FTS5 (BM25) + vector via RRF.
+///
+/// `path_filter` keeps only files whose path relative to `root` contains it.
+pub async fn search_code(
+    store: &'static Mutex<Store>,
+    root: &Path,
+    query: &str,
+    path_filter: Option<&str>,
+    n: usize,
+) -> Result<Vec<CodeResult>, String> {
+    let fts_query = sanitize_fts_query(query);
+    if fts_query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let query_owned = query.to_string();
+    let path_filter = path_filter.map(str::to_string);
+
+    tokio::task::spawn_blocking(move || {
+        let query_embedding: Option<Vec<f32>> = engine_if_ready().and_then(|em| {
+            em.lock()
+                .ok()
+                .and_then(|mut e| e.embed_query(&query_owned).ok())
+        });
+
+        // Other roots share the collection — over-fetch, then filter
+        let fetch = n * 4;
+        let keep = |r: &SearchResult| {
+            parse_doc_path(&r.doc.path).is_some_and(|(file, ..)| {
+                let file = Path::new(file);
+                file.starts_with(&root)
+                    && path_filter.as_deref().is_none_or(|f| {
+                        file.strip_prefix(&root)
+                            .is_ok_and(|rel| rel.to_string_lossy().contains(f))
+                    })
+            })
+        };
+
+        let store = store
+            .lock()
+            .map_err(|e| format!("Store lock poisoned: {e}"))?;
+        let fts_results: Vec<SearchResult> = store
+            .search_fts(&fts_query, fetch, Some(COLLECTION_CODE))
+            .map_err(|e| format!("FTS search failed: {e}"))?
+            .into_iter()
+            .filter(|r| keep(r))
+            .collect();
+
+        let vec_results: Vec<SearchResult> = match &query_embedding {
+            Some(emb) => store
+                .search_vec(emb, fetch, Some(COLLECTION_CODE))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|r| keep(r))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let to_tuples = |results: &[SearchResult]| -> Vec<(String, String, String, String)> {
+            results
+                .iter()
+                .map(|r| {
+                    let body = store
+                        .get_document(COLLECTION_CODE, &r.doc.path)
+                        .ok()
+                        .flatten()
+                        .and_then(|d| d.body)
+                        .unwrap_or_default();
+                    (
+                        r.doc.path.clone(),
+                        r.doc.display_path.clone(),
+                        r.doc.title.clone(),
+                        body,
+                    )
+                })
+                .collect()
+        };
+
+        let ranked: Vec<(String, String, String, f64)> = if vec_results.is_empty() {
+            to_tuples(&fts_results)
+                .into_iter()
+                .zip(&fts_results)
+                .map(|((path, _, title, body), r)| (path, title, body, r.score))
+                .collect()
+        } else {
+            hybrid_search_rrf(to_tuples(&fts_results), to_tuples(&vec_results), 60)
+                .into_iter()
+                .map(|r| (r.file, r.title, r.body, r.score))
+                .collect()
+        };
+
+        Ok(ranked
+            .into_iter()
+            .filter_map(|(doc_path, title, body, rank)| {
+                let (file, _, start_line, end_line) = parse_doc_path(&doc_path)?;
+                Some(CodeResult {
+                    path: PathBuf::from(file),
+                    start_line,
+                    end_line,
+                    symbol: symbol_from_title(&title),
+                    snippet: preview(&body, &fts_query),
+                    rank,
+                })
+            })
+            .take(n)
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking failed: {e}"))?
+}
+
+/// Split a chunk document path into `(file, file_hash, start_line, end_line)`.
+fn parse_doc_path(doc_path: &str) -> Option<(&str, &str, usize, usize)> {
+    let (file, rest) = doc_path.rsplit_once('#')?;
+    let (hash, range) = rest.split_once(':')?;
+    let (start, end) = range.split_once('-')?;
+    Some((file, hash, start.parse().ok()?, end.parse().ok()?))
+}
+
+/// Titles are `{rel}:{start}-{end}` with the symbol appended after a space.
+fn symbol_from_title(title: &str) -> Option<String> {
+    title
+        .rsplit_once(' ')
+        .map(|(_, sym)| sym.to_string())
+        .filter(|s| !s.is_empty() && !s.contains(':'))
+}
+
+/// The head of a chunk, or the region around the first match when the
+/// match sits past the head.
+fn preview(body: &str, fts_query: &str) -> String {
+    let head: Vec<&str> = body.lines().take(PREVIEW_LINES).collect();
+    let head = head.join("\n");
+    let terms: Vec<String> = fts_query
+        .split_whitespace()
+        .map(|w| w.trim_matches('"').to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let head_lower = head.to_lowercase();
+    if terms.is_empty() || terms.iter().any(|t| head_lower.contains(t.as_str())) {
+        return head;
+    }
+    extract_snippet(body, fts_query, 400)
+}
+
+/// Read a file as source text, skipping oversized, binary and non-UTF-8 files.
+fn read_source(path: &Path) -> Option<String> {
+    let meta = std::fs::metadata(path).ok()?;
+    if !meta.is_file() || meta.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether a path under `root` belongs in the index by the same rules as
+/// [`list_source_files`]: not ignored by git in a checkout, otherwise not
+/// inside a hidden or build directory.
+fn is_indexable(root: &Path, path: &Path) -> bool {
+    let Ok(rel) = path.strip_prefix(root) else {
+        return false;
+    };
+    if root.join(".git").exists() {
+        return !rel.starts_with(".git") && !git_ignores(root, path);
+    }
+    !rel.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name.starts_with('.') || SKIP_DIRS.contains(&name.as_ref())
+    })
+}
+
+/// Directories the code watcher watches under `root`: `root` itself and
+/// every directory holding a file [`list_source_files`] would consider, so
+/// ignored and build directories never take a watch.
+pub(super) fn watch_dirs(root: &Path) -> Vec<PathBuf> {
+    let files = git_ls_files(root).unwrap_or_else(|| {
+        let mut out = Vec::new();
+        walk(root, &mut out);
+        out
+    });
+    let mut dirs = BTreeSet::from([root.to_path_buf()]);
+    for file in &files {
+        for dir in file.ancestors().skip(1) {
+            if !dir.starts_with(root) || !dirs.insert(dir.to_path_buf()) {
+                break;
+            }
+        }
+    }
+    dirs.into_iter().collect()
+}
+
+/// A directory created under a watched `root` plus its subdirectories,
+/// minus those the listing rules exclude.
+pub(super) fn new_watch_dirs(root: &Path, dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        if !is_indexable(root, &dir) {
+            continue;
+        }
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            stack.extend(
+                entries
+                    .flatten()
+                    .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                    .map(|e| e.path()),
+            );
+        }
+        dirs.push(dir);
+    }
+    dirs
+}
+
+fn git_ignores(root: &Path, path: &Path) -> bool {
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["check-ignore", "-q", "--"])
+        .arg(path)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+fn is_code_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| CODE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Source files under `root`, honouring `.gitignore` when `root` is in a git
+/// checkout. The flag is set when the list was cut at `MAX_FILES`.
+fn list_source_files(root: &Path) -> (Vec<PathBuf>, bool) {
+    let mut files = git_ls_files(root).unwrap_or_else(|| {
+        let mut out = Vec::new();
+        walk(root, &mut out);
+        out
+    });
+    files.retain(|p| is_code_file(p));
+    files.sort();
+    let truncated = files.len() > MAX_FILES;
+    files.truncate(MAX_FILES);
+    (files, truncated)
+}
+
+fn git_ls_files(root: &Path) -> Option<Vec<PathBuf>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(
+        output
+            .stdout
+            .split(|b| *b == 0)
+            .filter(|p| !p.is_empty())
+            .map(|p| root.join(String::from_utf8_lossy(p).as_ref()))
+            .filter(|p| p.is_file())
+            .collect(),
+    )
+}
+
+/// Recursive walk used outside git checkouts. Skips hidden and build directories.
+fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if out.len() > MAX_FILES {
+            return;
+        }
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !name.starts_with('.') && !SKIP_DIRS.contains(&name.as_ref()) {
+                walk(&path, out);
+            }
+        } else if file_type.is_file() {
+            out.push(path);
+        }
+    }
+}
+
+/// Split source text into chunks of whole definitions.
+///
+/// Boundaries are lines where syntect sees a definition name (plus the doc
+/// comments and attributes directly above it). Files without a known syntax,
+/// or without any definitions, split at top-level lines after a blank line.
+pub fn chunk_source(source: &str, extension: &str) -> Vec<CodeChunk> {
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let mut symbols = SYNTAX_SET
+        .find_syntax_by_extension(extension)
+        .map(|syntax| definition_lines(source, syntax))
+        .unwrap_or_default();
+    symbols.resize(lines.len(), None);
+
+    let mut boundaries = vec![false; lines.len()];
+    if symbols.iter().any(Option::is_some) {
+        for (i, sym) in symbols.iter().enumerate() {
+            if sym.is_some() {
+                boundaries[attach_leading_comments(&lines, i)] = true;
+            }
+        }
+    } else {
+        for i in 1..lines.len() {
+            let line = lines[i];
+            boundaries[i] = lines[i - 1].trim().is_empty()
+                && !line.trim().is_empty()
+                && !line.starts_with(char::is_whitespace);
+        }
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, line) in lines.iter().enumerate() {
+        let len = i - start;
+        let cut = len > 0
+            && ((boundaries[i] && len >= TARGET_CHUNK_LINES)
+                || len >= MAX_CHUNK_LINES
+                || bytes + line.len() > MAX_CHUNK_BYTES);
+        if cut {
+            push_chunk(&mut chunks, &lines, &symbols, start, i);
+            start = i;
+            bytes = 0;
+        }
+        bytes += line.len() + 1;
+    }
+    push_chunk(&mut chunks, &lines, &symbols, start, lines.len());
+    chunks
+}
+
+fn push_chunk(
+    chunks: &mut Vec<CodeChunk>,
+    lines: &[&str],
+    symbols: &[Option<String>],
+    start: usize,
+    end: usize,
+) {
+    let text = lines[start..end].join("\n");
+    if text.trim().is_empty() {
+        return;
+    }
+    chunks.push(CodeChunk {
+        start_line: start + 1,
+        end_line: end,
+        symbol: symbols[start..end].iter().flatten().next().cloned(),
+        text,
+    });
+}
+
+/// Move a definition boundary up over the comments and attributes attached to it.
+fn attach_leading_comments(lines: &[&str], mut i: usize) -> usize {
+    const PREFIXES: &[&str] = &["//", "#", "@", "/*", "*", "--", ";;"];
+    while i > 0 {
+        let prev = lines[i - 1].trim_start();
+        if prev.is_empty() || !PREFIXES.iter().any(|p| prev.starts_with(p)) {
+            break;
+        }
+        i -= 1;
+    }
+    i
+}
+
+/// For each line, the name of the definition that starts on it, if any.
+fn definition_lines(source: &str, syntax: &SyntaxReference) -> Vec<Option<String>> {
+    let mut state = ParseState::new(syntax);
+    let mut out = Vec::new();
+    for line in LinesWithEndings::from(source) {
+        let ops = state.parse_line(line, &SYNTAX_SET).unwrap_or_default();
+        let symbol = ops.iter().find_map(|(pos, op)| match op {
+            ScopeStackOp::Push(scope)
+                if DEFINITION_SCOPES.iter().any(|d| d.is_prefix_of(*scope)) =>
+            {
+                let name: String = line
+                    .get(*pos..)?
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                (!name.is_empty()).then_some(name)
+            }
+            _ => None,
+        });
+        out.push(symbol);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_rust_by_definitions() {
+        let mut source = String::new();
+        for i in 0..3 {
+            source.push_str(&format!("/// Doc for f{i}\n#[inline]\nfn f{i}() {{\n"));
+            for j in 0..45 {
+                source.push_str(&format!("    let x{j} = {j};\n"));
+            }
+            source.push_str("}\n\n");
+        }
+
+        let chunks = chunk_source(&source, "rs");
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].symbol.as_deref(), Some("f0"));
+        assert_eq!(chunks[1].symbol.as_deref(), Some("f1"));
+        // Doc comment and attribute stay with the function they describe
+        assert!(chunks[1].text.starts_with("/// Doc for f1"));
+        assert_eq!(chunks[0].start_line, 1);
+        assert_eq!(chunks[1].start_line, chunks[0].end_line + 1);
+        assert_eq!(chunks[2].end_line, source.lines().count());
+    }
+
+    #[test]
+    fn test_small_definitions_share_a_chunk() {
+        let source = "fn a() {}\n\nfn b() {}\n\nstruct C;\n";
+        let chunks = chunk_source(source, "rs");
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].symbol.as_deref(), Some("a"));
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 5));
+    }
+
+    #[test]
+    fn test_long_definition_is_cut() {
+        let mut source = String::from("fn big() {\n");
+        for j in 0..300 {
+            source.push_str(&format!("    call({j});\n"));
+        }
+        source.push_str("}\n");
+
+        let chunks = chunk_source(&source, "rs");
+        assert!(chunks.len() >= 3);
+        assert!(
+            chunks
+                .iter()
+                .all(|c| c.end_line - c.start_line < MAX_CHUNK_LINES)
+        );
+    }
+
+    #[test]
+    fn test_unknown_syntax_splits_on_blank_lines() {
+        let mut source = String::new();
+        for block in 0..3 {
+            source.push_str(&format!("block{block}:\n"));
+            for j in 0..50 {
+                source.push_str(&format!("  item{j}\n"));
+            }
+            source.push('\n');
+        }
+        let chunks = chunk_source(&source, "zzz");
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[1].text.starts_with("block1:"));
+        assert_eq!(chunks[1].symbol, None);
+    }
+
+    #[test]
+    fn test_chunk_empty_source() {
+        assert!(chunk_source("", "rs").is_empty());
+        assert!(chunk_source("\n\n  \n", "rs").is_empty());
+    }
+
+    #[test]
+    fn test_parse_doc_path() {
+        assert_eq!(
+            parse_doc_path("/repo/src/a#b.rs#0123abcd4567:10-42"),
+            Some(("/repo/src/a#b.rs", "0123abcd4567", 10, 42))
+        );
+        assert_eq!(parse_doc_path("/repo/src/main.rs"), None);
+        assert_eq!(parse_doc_path("/repo/x.rs#abc:1-x"), None);
+    }
+
+    #[test]
+    fn test_symbol_from_title() {
+        assert_eq!(
+            symbol_from_title("src/lib.rs:1-40 parse"),
+            Some("parse".to_string())
+        );
+        assert_eq!(symbol_from_title("src/lib.rs:1-40"), None);
+        assert_eq!(symbol_from_title("my dir/lib.rs:1-40"), None);
+    }
+
+    #[test]
+    fn test_watched_paths_follow_listing_rules() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        assert!(is_indexable(root, &root.join("src/main.rs")));
+        assert!(!is_indexable(root, &root.join("target/debug/build/out.rs")));
+        assert!(!is_indexable(root, &root.join(".cache/x.py")));
+        assert!(!is_indexable(root, Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_watch_dirs_skip_ignored_directories() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        for file in [
+            "src/lib.rs",
+            "src/bin/cli.rs",
+            "target/debug/out.rs",
+            ".cache/x.py",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+            std::fs::write(&path, "fn main() {}\n").expect("write");
+        }
+
+        assert_eq!(
+            watch_dirs(root),
+            vec![root.to_path_buf(), root.join("src"), root.join("src/bin")]
+        );
+
+        std::fs::create_dir_all(root.join("docs/guide")).expect("mkdir");
+        let mut added = new_watch_dirs(root, &root.join("docs"));
+        added.sort();
+        assert_eq!(added, vec![root.join("docs"), root.join("docs/guide")]);
+        assert!(new_watch_dirs(root, &root.join("target/debug")).is_empty());
+    }
+}
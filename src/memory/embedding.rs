@@ -1,64 +1,29 @@
-//! Embedding — singleton engine, generate and store vector embeddings.
+//! Embedding — singleton backend, generate and store vector embeddings.
 
 use once_cell::sync::OnceCell;
-use qmd::{EmbeddingEngine, Store, pull_model};
+use qmd::Store;
 use std::sync::Mutex;
 
-static ENGINE: OnceCell<Mutex<EmbeddingEngine>> = OnceCell::new();
+use super::backend::{EmbeddingBackend, create_backend, embedding_config};
 
-/// Disable llama.cpp's C-level logging globally.
-///
-/// Must be called once before creating any EmbeddingEngine.
-/// Routes all llama.cpp log output through the tracing framework
-/// with logging disabled — zero stderr pollution.
-fn silence_llama_logs() {
-    use llama_cpp_2::{LogOptions, send_logs_to_tracing};
-    send_logs_to_tracing(LogOptions::default().with_logs_enabled(false));
-}
+static ENGINE: OnceCell<Mutex<Box<dyn EmbeddingBackend>>> = OnceCell::new();
 
-/// Get (or create) the shared embedding engine.
+/// Get (or create) the shared embedding backend configured under `[memory.embedding]`.
 ///
-/// Downloads the embeddinggemma-300M model (~300MB) on first call.
-/// Returns Err if the download fails (e.g. no internet) or if the CPU lacks
-/// AVX (required by llama.cpp GGUF inference) — callers fall back to FTS-only.
-pub fn get_engine() -> Result<&'static Mutex<EmbeddingEngine>, String> {
+/// For the default local backend this downloads the embeddinggemma-300M model
+/// (~300MB) on first call. Returns Err if the model can't be loaded (no internet,
+/// CPU without AVX, endpoint misconfigured, backend `none`) — callers fall back
+/// to FTS-only.
+pub fn get_engine() -> Result<&'static Mutex<Box<dyn EmbeddingBackend>>, String> {
     ENGINE.get_or_try_init(|| {
-        check_cpu_features()?;
-        silence_llama_logs();
-
-        let pull = pull_model(qmd::llm::DEFAULT_EMBED_MODEL_URI, false)
-            .map_err(|e| format!("Failed to pull embedding model: {e}"))?;
-
-        let engine = EmbeddingEngine::new(&pull.path)
-            .map_err(|e| format!("Failed to init embedding engine: {e}"))?;
-
-        tracing::info!(
-            "Embedding engine ready: {} ({:.1} MB)",
-            pull.model,
-            pull.size_bytes as f64 / 1_048_576.0
-        );
-        Ok(Mutex::new(engine))
+        let backend = create_backend(embedding_config())?;
+        tracing::info!("Embedding backend ready: {}", backend.model());
+        Ok(Mutex::new(backend))
     })
 }
 
-/// Verify the CPU supports the instruction sets required by llama.cpp.
-/// Returns Err on x86 without AVX; passes through on ARM/other architectures.
-fn check_cpu_features() -> Result<(), String> {
-    #[cfg(target_arch = "x86_64")]
-    {
-        if !std::arch::is_x86_feature_detected!("avx") {
-            return Err(
-                "CPU lacks AVX — llama.cpp GGUF inference requires AVX (Sandy Bridge 2011+). \
-                 Memory search will use FTS-only."
-                    .to_string(),
-            );
-        }
-    }
-    Ok(())
-}
-
 /// Returns the engine if already initialized, without triggering a download.
-pub fn engine_if_ready() -> Option<&'static Mutex<EmbeddingEngine>> {
+pub fn engine_if_ready() -> Option<&'static Mutex<Box<dyn EmbeddingBackend>>> {
     ENGINE.get()
 }
 
@@ -74,9 +39,9 @@ pub fn embed_content(store: &Mutex<Store>, body: &str) {
     let title = Store::extract_title(body);
     let hash = Store::hash_content(body);
 
-    let emb = match engine_mutex.lock() {
-        Ok(mut engine) => match engine.embed_document(body, Some(&title)) {
-            Ok(emb) => emb,
+    let (embedding, model) = match engine_mutex.lock() {
+        Ok(mut engine) => match engine.embed_document(body, &title) {
+            Ok(emb) => (emb, engine.model().to_string()),
             Err(e) => {
                 tracing::debug!("Embedding failed: {e}");
                 return;
@@ -90,16 +55,21 @@ pub fn embed_content(store: &Mutex<Store>, body: &str) {
         .format("%Y-%m-%dT%H:%M:%S")
         .to_string();
     if let Ok(s) = store.lock()
-        && let Err(e) = s.insert_embedding(&hash, 0, 0, &emb.embedding, &emb.model, &now)
+        && let Err(e) = s.insert_embedding(&hash, 0, 0, &embedding, &model, &now)
     {
         tracing::debug!("Failed to store embedding: {e}");
     }
 }
 
+/// Documents embedded per engine lock during a backfill. Searches embed
+/// their query with the same engine, so it is released between batches.
+const BACKFILL_BATCH: usize = 32;
+
 /// Backfill embeddings for all documents that don't have one yet.
 ///
-/// Initializes the engine (downloading the model if needed) and batch-embeds
-/// any documents missing embeddings. Lock ordering: store → release → engine → release → store.
+/// Initializes the engine (downloading the model if needed) and embeds any
+/// documents missing embeddings, `BACKFILL_BATCH` at a time. Lock ordering
+/// per batch: engine → release → store → release.
 pub(super) fn backfill_embeddings(store: &Mutex<Store>) {
     let engine_mutex = match get_engine() {
         Ok(e) => e,
@@ -122,42 +92,39 @@ pub(super) fn backfill_embeddings(store: &Mutex<Store>) {
     let count = needing.len();
     tracing::info!("Backfilling embeddings for {count} documents");
 
-    let items: Vec<(String, Option<String>)> = needing
-        .iter()
-        .map(|(_hash, _path, body)| {
-            let title = Store::extract_title(body);
-            (body.clone(), Some(title))
-        })
-        .collect();
-
-    // Engine lock: batch embed → release
-    let results: Vec<_> = {
-        let mut engine = match engine_mutex.lock() {
-            Ok(e) => e,
-            Err(_) => return,
-        };
-        engine
-            .embed_batch_with_progress(&items, |done, total| {
-                if done % 10 == 0 || done == total {
-                    tracing::debug!("Embedding progress: {done}/{total}");
-                }
+    let mut stored = 0usize;
+    for batch in needing.chunks(BACKFILL_BATCH) {
+        let items: Vec<(String, String)> = batch
+            .iter()
+            .map(|(_hash, _path, body)| {
+                let title = Store::extract_title(body);
+                (body.clone(), title)
             })
-            .into_iter()
-            .map(|r| r.ok())
-            .collect()
-    };
+            .collect();
+
+        // Engine lock: embed this batch → release
+        let (results, model): (Vec<_>, String) = {
+            let mut engine = match engine_mutex.lock() {
+                Ok(e) => e,
+                Err(_) => return,
+            };
+            let results = engine
+                .embed_batch(&items)
+                .into_iter()
+                .map(|r| {
+                    r.inspect_err(|e| tracing::debug!("Embedding failed: {e}"))
+                        .ok()
+                })
+                .collect();
+            (results, engine.model().to_string())
+        };
 
-    // Store lock: insert all embeddings → release
-    let now = chrono::Local::now()
-        .format("%Y-%m-%dT%H:%M:%S")
-        .to_string();
-    let mut stored = 0usize;
-    if let Ok(s) = store.lock() {
-        for (i, emb) in results.iter().enumerate() {
-            if let Some(emb) = emb {
-                let hash = &needing[i].0;
-                if s.insert_embedding(hash, 0, 0, &emb.embedding, &emb.model, &now)
-                    .is_ok()
+        // Store lock: insert this batch → release
+        let now = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        if let Ok(s) = store.lock() {
+            for ((hash, _path, _body), emb) in batch.iter().zip(&results) {
+                if let Some(emb) = emb
+                    && s.insert_embedding(hash, 0, 0, emb, &model, &now).is_ok()
                 {
                     stored += 1;
                 }
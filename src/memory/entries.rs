@@ -0,0 +1,641 @@
+//! Entries — curated long-term memory written by the agent via `memory_write`.
+//!
+//! Entries live in `~/.opencrabs/memory/entries.json` (the source of truth) and
+//! are mirrored into the `"entries"` collection so `memory::search` finds them
+//! next to daily logs and brain files. Expired entries are dropped from both.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use qmd::Store;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::COLLECTION_ENTRIES;
+use super::embedding::{embed_content, engine_if_ready};
+
+/// Cosine similarity above which two entries count as the same memory.
+const DUPLICATE_SIMILARITY: f32 = 0.92;
+/// Word-overlap (Jaccard) fallback used when no embedding engine is loaded.
+const DUPLICATE_OVERLAP: f32 = 0.8;
+/// Nearest entries re-checked by cosine similarity when adding.
+const DUPLICATE_CANDIDATES: usize = 5;
+
+/// Serializes read-modify-write cycles on `entries.json`.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// What kind of memory an entry holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryKind {
+    Fact,
+    Preference,
+    Decision,
+    ProjectNote,
+}
+
+impl EntryKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::Fact => "fact",
+            EntryKind::Preference => "preference",
+            EntryKind::Decision => "decision",
+            EntryKind::ProjectNote => "project-note",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "fact" => Some(EntryKind::Fact),
+            "preference" => Some(EntryKind::Preference),
+            "decision" => Some(EntryKind::Decision),
+            "project-note" | "note" => Some(EntryKind::ProjectNote),
+            _ => None,
+        }
+    }
+}
+
+/// A single curated memory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub id: String,
+    pub kind: EntryKind,
+    pub content: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Session the entry was written (or last updated) in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_session: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl MemoryEntry {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+    }
+
+    /// Document body indexed for search.
+    fn body(&self) -> String {
+        let mut body = format!("{}: {}", self.kind.as_str(), self.content);
+        if !self.tags.is_empty() {
+            body.push_str(&format!("\nTags: {}", self.tags.join(", ")));
+        }
+        body
+    }
+
+    fn title(&self) -> String {
+        let first = self.content.lines().next().unwrap_or("");
+        let short: String = first.chars().take(80).collect();
+        format!("{}: {}", self.kind.as_str(), short)
+    }
+}
+
+/// Fields of a new entry.
+#[derive(Debug, Clone)]
+pub struct NewEntry {
+    pub kind: EntryKind,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub source_session: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Changes to an existing entry; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default)]
+pub struct EntryUpdate {
+    pub kind: Option<EntryKind>,
+    pub content: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub source_session: Option<String>,
+    /// `Some(None)` removes the expiry
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+}
+
+/// Result of `add_entry`.
+#[derive(Debug, Clone)]
+pub enum AddOutcome {
+    Added(MemoryEntry),
+    /// Not written: an existing entry already says (nearly) the same thing
+    Duplicate {
+        existing: MemoryEntry,
+        similarity: f32,
+    },
+}
+
+/// Path of the entries file: `~/.opencrabs/memory/entries.json`
+pub fn entries_path() -> PathBuf {
+    crate::config::opencrabs_home()
+        .join("memory")
+        .join("entries.json")
+}
+
+/// Add an entry unless a near-duplicate exists (`force` skips the check).
+pub async fn add_entry(
+    store: &'static Mutex<Store>,
+    new: NewEntry,
+    force: bool,
+) -> Result<AddOutcome, String> {
+    blocking(move || {
+        let _guard = FILE_LOCK
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let mut entries = load()?;
+
+        if !force && let Some((i, similarity)) = find_duplicate(store, &entries, &new.content) {
+            return Ok(AddOutcome::Duplicate {
+                existing: entries[i].clone(),
+                similarity,
+            });
+        }
+
+        let now = Utc::now();
+        let entry = MemoryEntry {
+            id: new_id(&entries),
+            kind: new.kind,
+            content: new.content.trim().to_string(),
+            tags: normalize_tags(new.tags),
+            source_session: new.source_session,
+            created_at: now,
+            updated_at: now,
+            expires_at: new.expires_at,
+        };
+        entries.push(entry.clone());
+        save(&entries)?;
+        index_entry(store, &entry)?;
+        Ok(AddOutcome::Added(entry))
+    })
+    .await
+}
+
+/// Change an existing entry and re-index it.
+pub async fn update_entry(
+    store: &'static Mutex<Store>,
+    id: &str,
+    update: EntryUpdate,
+) -> Result<MemoryEntry, String> {
+    let id = id.to_string();
+    blocking(move || {
+        let _guard = FILE_LOCK
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let mut entries = load()?;
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| format!("No memory entry with id {id}"))?;
+
+        if let Some(kind) = update.kind {
+            entry.kind = kind;
+        }
+        if let Some(content) = update.content {
+            entry.content = content.trim().to_string();
+        }
+        if let Some(tags) = update.tags {
+            entry.tags = normalize_tags(tags);
+        }
+        if let Some(session) = update.source_session {
+            entry.source_session = Some(session);
+        }
+        if let Some(expires_at) = update.expires_at {
+            entry.expires_at = expires_at;
+        }
+        entry.updated_at = Utc::now();
+
+        let entry = entry.clone();
+        save(&entries)?;
+        index_entry(store, &entry)?;
+        Ok(entry)
+    })
+    .await
+}
+
+/// Delete an entry and drop it from the index.
+pub async fn forget_entry(store: &'static Mutex<Store>, id: &str) -> Result<MemoryEntry, String> {
+    let id = id.to_string();
+    blocking(move || {
+        let _guard = FILE_LOCK
+            .lock()
+            .map_err(|e| format!("Lock poisoned: {e}"))?;
+        let mut entries = load()?;
+        let pos = entries
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| format!("No memory entry with id {id}"))?;
+        let entry = entries.remove(pos);
+        save(&entries)?;
+        deindex(store, std::slice::from_ref(&entry.id))?;
+        Ok(entry)
+    })
+    .await
+}
+
+/// Live entries, optionally filtered by kind and tag, newest first.
+pub async fn list_entries(
+    kind: Option<EntryKind>,
+    tag: Option<String>,
+) -> Result<Vec<MemoryEntry>, String> {
+    blocking(move || {
+        let now = Utc::now();
+        let mut entries: Vec<MemoryEntry> = load()?
+            .into_iter()
+            .filter(|e| !e.is_expired(now))
+            .filter(|e| kind.is_none_or(|k| e.kind == k))
+            .filter(|e| {
+                tag.as_deref()
+                    .is_none_or(|t| e.tags.iter().any(|x| x.eq_ignore_ascii_case(t)))
+            })
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.updated_at));
+        Ok(entries)
+    })
+    .await
+}
+
+/// Bring the `entries` collection in line with `entries.json`: drop expired
+/// entries, index new or changed ones, deactivate removed ones.
+/// Returns the number of live entries.
+pub(super) fn sync_entries(store: &Mutex<Store>) -> Result<usize, String> {
+    let _guard = FILE_LOCK
+        .lock()
+        .map_err(|e| format!("Lock poisoned: {e}"))?;
+    let entries = drop_expired(store, load()?)?;
+    for entry in &entries {
+        index_entry(store, entry)?;
+    }
+
+    let live: HashSet<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    let stale: Vec<String> = {
+        let s = store
+            .lock()
+            .map_err(|e| format!("Store lock poisoned: {e}"))?;
+        s.get_active_document_paths(COLLECTION_ENTRIES)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| !live.contains(p.as_str()))
+            .collect()
+    };
+    deindex(store, &stale)?;
+    Ok(entries.len())
+}
+
+/// Remove entries whose expiry has passed. Cheap enough to run before every search.
+pub(super) fn expire_due(store: &Mutex<Store>) -> Result<(), String> {
+    let _guard = FILE_LOCK
+        .lock()
+        .map_err(|e| format!("Lock poisoned: {e}"))?;
+    drop_expired(store, load()?).map(|_| ())
+}
+
+fn drop_expired(
+    store: &Mutex<Store>,
+    entries: Vec<MemoryEntry>,
+) -> Result<Vec<MemoryEntry>, String> {
+    let now = Utc::now();
+    let (expired, live): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| e.is_expired(now));
+    if !expired.is_empty() {
+        save(&live)?;
+        let ids: Vec<String> = expired.into_iter().map(|e| e.id).collect();
+        tracing::debug!("Expired {} memory entries", ids.len());
+        deindex(store, &ids)?;
+    }
+    Ok(live)
+}
+
+/// Run blocking work (file IO, store and engine locks) off the async runtime.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("spawn_blocking failed: {e}"))?
+}
+
+fn load() -> Result<Vec<MemoryEntry>, String> {
+    let path = entries_path();
+    match std::fs::read_to_string(&path) {
+        Ok(content) if content.trim().is_empty() => Ok(Vec::new()),
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Write via a temp file and rename, so a crash never leaves half a file.
+fn save(entries: &[MemoryEntry]) -> Result<(), String> {
+    let path = entries_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create memory dir: {e}"))?;
+    }
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize memory entries: {e}"))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Insert or refresh an entry's document. Skips unchanged entries by hash.
+fn index_entry(store: &Mutex<Store>, entry: &MemoryEntry) -> Result<(), String> {
+    let body = entry.body();
+    let hash = Store::hash_content(&body);
+    {
+        let s = store
+            .lock()
+            .map_err(|e| format!("Store lock poisoned: {e}"))?;
+        if matches!(s.find_active_document(COLLECTION_ENTRIES, &entry.id), Ok(Some((_, ref h, _))) if h == &hash)
+        {
+            return Ok(());
+        }
+        let now = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        s.insert_content(&hash, &body, &now)
+            .map_err(|e| format!("Failed to insert content: {e}"))?;
+        s.insert_document(
+            COLLECTION_ENTRIES,
+            &entry.id,
+            &entry.title(),
+            &hash,
+            &now,
+            &now,
+        )
+        .map_err(|e| format!("Failed to insert document: {e}"))?;
+    }
+    embed_content(store, &body);
+    Ok(())
+}
+
+fn deindex(store: &Mutex<Store>, ids: &[String]) -> Result<(), String> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let s = store
+        .lock()
+        .map_err(|e| format!("Store lock poisoned: {e}"))?;
+    for id in ids {
+        if let Err(e) = s.deactivate_document(COLLECTION_ENTRIES, id) {
+            tracing::debug!("Failed to drop memory entry {id}: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Index and similarity of the closest existing entry, if it's close enough
+/// to count as a duplicate.
+///
+/// Uses embeddings when the engine is loaded — the nearest entries by vector
+/// search are re-embedded and compared by cosine similarity — and word
+/// overlap otherwise, so exact and near-verbatim repeats are always caught.
+fn find_duplicate(
+    store: &Mutex<Store>,
+    entries: &[MemoryEntry],
+    content: &str,
+) -> Option<(usize, f32)> {
+    let now = Utc::now();
+    let live = || {
+        entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !e.is_expired(now))
+    };
+
+    let lexical = live()
+        .map(|(i, e)| (i, word_overlap(content, &e.content)))
+        .filter(|(_, sim)| *sim >= DUPLICATE_OVERLAP)
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    if lexical.is_some() {
+        return lexical;
+    }
+
+    let engine = engine_if_ready()?;
+    let embedding = engine.lock().ok()?.embed_document(content, "").ok()?;
+    let candidates: Vec<String> = store
+        .lock()
+        .ok()?
+        .search_vec(&embedding, DUPLICATE_CANDIDATES, Some(COLLECTION_ENTRIES))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| r.doc.path)
+        .collect();
+
+    let mut engine = engine.lock().ok()?;
+    live()
+        .filter(|(_, e)| candidates.contains(&e.id))
+        .filter_map(|(i, e)| {
+            let other = engine.embed_document(&e.content, "").ok()?;
+            Some((i, cosine_similarity(&embedding, &other)))
+        })
+        .filter(|(_, sim)| *sim >= DUPLICATE_SIMILARITY)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Jaccard similarity of the lowercase word sets of two texts.
+fn word_overlap(a: &str, b: &str) -> f32 {
+    let words = |s: &str| -> HashSet<String> {
+        s.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let shared = a.intersection(&b).count();
+    shared as f32 / (a.len() + b.len() - shared) as f32
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !out.contains(&tag) {
+            out.push(tag);
+        }
+    }
+    out
+}
+
+fn new_id(entries: &[MemoryEntry]) -> String {
+    loop {
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        if !entries.iter().any(|e| e.id == id) {
+            return id;
+        }
+    }
+}
+
+/// Parse an expiry: a duration from `now` (`"12h"`, `"30d"`, `"2w"`), a date
+/// (`"2026-12-31"`, end of that day UTC), an RFC 3339 timestamp, or `"never"`
+/// (returns `None`). Dates and timestamps must lie after `now`.
+pub fn parse_expiry(s: &str, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+    let s = s.trim();
+    if s.is_empty() || s.eq_ignore_ascii_case("never") {
+        return Ok(None);
+    }
+    let absolute = DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(23, 59, 59))
+                .map(|t| t.and_utc())
+        });
+    if let Some(at) = absolute {
+        if at <= now {
+            return Err(format!("Invalid expiry '{s}': must be in the future"));
+        }
+        return Ok(Some(at));
+    }
+    let invalid = || format!("Invalid expiry '{s}': use e.g. 12h, 30d, 2w, 2026-12-31 or never");
+    let unit_at = s.len() - s.chars().last().map(char::len_utf8).unwrap_or(0);
+    let (num, unit) = s.split_at(unit_at);
+    let n: i64 = num.trim().parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "h" => Duration::try_hours(n),
+        "d" => Duration::try_days(n),
+        "w" => Duration::try_weeks(n),
+        _ => return Err(invalid()),
+    };
+    if n <= 0 {
+        return Err(format!("Invalid expiry '{s}': must be in the future"));
+    }
+    // Counts too large for a timestamp are rejected rather than panicking
+    duration
+        .and_then(|d| now.checked_add_signed(d))
+        .map(Some)
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, content: &str) -> MemoryEntry {
+        let now = Utc::now();
+        MemoryEntry {
+            id: id.to_string(),
+            kind: EntryKind::Fact,
+            content: content.to_string(),
+            tags: vec![],
+            source_session: None,
+            created_at: now,
+            updated_at: now,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_entry_kind_parse() {
+        assert_eq!(
+            EntryKind::parse("project_note"),
+            Some(EntryKind::ProjectNote)
+        );
+        assert_eq!(EntryKind::parse("Preference"), Some(EntryKind::Preference));
+        assert_eq!(EntryKind::parse("opinion"), None);
+        assert_eq!(
+            serde_json::to_string(&EntryKind::ProjectNote).ok(),
+            Some("\"project-note\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_expiry() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+        assert_eq!(parse_expiry("never", now), Ok(None));
+        assert_eq!(parse_expiry("30d", now), Ok(Some(now + Duration::days(30))));
+        assert_eq!(
+            parse_expiry("12h", now),
+            Ok(Some(now + Duration::hours(12)))
+        );
+        assert_eq!(parse_expiry("2w", now), Ok(Some(now + Duration::weeks(2))));
+        assert_eq!(
+            parse_expiry("2026-12-31", now).map(|t| t.map(|t| t.to_rfc3339())),
+            Ok(Some("2026-12-31T23:59:59+00:00".to_string()))
+        );
+        assert!(parse_expiry("soon", now).is_err());
+        assert!(parse_expiry("0d", now).is_err());
+        assert!(parse_expiry("5y", now).is_err());
+        assert!(parse_expiry("100000000d", now).is_err());
+        assert!(parse_expiry("9223372036854775807h", now).is_err());
+
+        // Absolute expiries in the past would be dropped on the next load
+        assert!(parse_expiry("2026-02-28", now).is_err());
+        assert!(parse_expiry("2026-03-01T11:59:59Z", now).is_err());
+        assert!(parse_expiry("2026-03-01", now).is_ok());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let now = Utc::now();
+        let mut e = entry("a", "temporary");
+        assert!(!e.is_expired(now));
+        e.expires_at = Some(now - Duration::minutes(1));
+        assert!(e.is_expired(now));
+    }
+
+    #[test]
+    fn test_word_overlap() {
+        assert_eq!(word_overlap("User prefers tabs.", "user prefers TABS"), 1.0);
+        assert!(word_overlap("User prefers tabs", "User prefers spaces") < DUPLICATE_OVERLAP);
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags(vec![
+                "#Rust".into(),
+                "rust".into(),
+                " ".into(),
+                "CLI".into()
+            ]),
+            vec!["rust", "cli"]
+        );
+    }
+
+    #[test]
+    fn test_body_and_title() {
+        let mut e = entry("a", "Deploys go through staging first\nsecond line");
+        e.kind = EntryKind::Decision;
+        e.tags = vec!["deploy".into()];
+        assert_eq!(
+            e.body(),
+            "decision: Deploys go through staging first\nsecond line\nTags: deploy"
+        );
+        assert_eq!(e.title(), "decision: Deploys go through staging first");
+    }
+
+    #[test]
+    fn test_entries_roundtrip_json() {
+        let e = entry("abcd1234", "Project uses sqlite");
+        let json = serde_json::to_string(&vec![e.clone()]).expect("serializes");
+        assert!(!json.contains("expires_at"));
+        let back: Vec<MemoryEntry> = serde_json::from_str(&json).expect("deserializes");
+        assert_eq!(back, vec![e]);
+    }
+}
@@ -4,8 +4,13 @@ use qmd::Store;
 use std::path::Path;
 use std::sync::Mutex;
 
-use super::embedding::{backfill_embeddings, embed_content};
-use super::{COLLECTION_BRAIN, COLLECTION_MEMORY};
+use super::backend::describe_backend;
+use super::embedding::{backfill_embeddings, embed_content, engine_if_ready};
+use super::watch::{WatchStatus, watch_status};
+use super::entries::sync_entries;
+use super::{
+    COLLECTION_BRAIN, COLLECTION_CODE, COLLECTION_ENTRIES, COLLECTION_MEMORY, COLLECTION_SESSIONS,
+};
 
 /// Brain files loaded from the workspace root (`~/.opencrabs/`).
 const BRAIN_FILES: &[&str] = &[
@@ -26,27 +31,82 @@ const BRAIN_FILES: &[&str] = &[
 /// Skips re-indexing if the file's SHA-256 hash hasn't changed.
 /// Generates an embedding when the engine is already initialized.
 pub async fn index_file(store: &'static Mutex<Store>, path: &Path) -> Result<(), String> {
+    index_path(store, COLLECTION_MEMORY, path).await.map(|_| ())
+}
+
+/// Index a single file into `collection`. Returns `true` if new content was
+/// indexed, `false` if hash-skipped.
+///
+/// Empty brain files are dropped from the index, matching `reindex`.
+pub(super) async fn index_path(
+    store: &'static Mutex<Store>,
+    collection: &'static str,
+    path: &Path,
+) -> Result<bool, String> {
     let body = tokio::fs::read_to_string(path)
         .await
         .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
 
+    if collection == COLLECTION_BRAIN && body.trim().is_empty() {
+        remove_path(store, collection, path).await?;
+        return Ok(false);
+    }
+
     let path = path.to_path_buf();
     tokio::task::spawn_blocking(move || {
         let indexed = {
             let s = store.lock().map_err(|e| format!("Store lock poisoned: {e}"))?;
-            index_file_sync(&s, COLLECTION_MEMORY, &path, &body)?
+            index_file_sync(&s, collection, &path, &body)?
         };
 
         if indexed {
             embed_content(store, &body);
         }
 
-        Ok(())
+        Ok(indexed)
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking failed: {e}"))?
+}
+
+/// Deactivate the document for a deleted file. Returns `true` if it was indexed.
+pub(super) async fn remove_path(
+    store: &'static Mutex<Store>,
+    collection: &'static str,
+    path: &Path,
+) -> Result<bool, String> {
+    let rel_path = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+
+    tokio::task::spawn_blocking(move || {
+        let s = store.lock().map_err(|e| format!("Store lock poisoned: {e}"))?;
+        let active = matches!(s.find_active_document(collection, &rel_path), Ok(Some(_)));
+        if active {
+            s.deactivate_document(collection, &rel_path)
+                .map_err(|e| format!("Failed to remove {rel_path}: {e}"))?;
+        }
+        Ok(active)
     })
     .await
     .map_err(|e| format!("spawn_blocking failed: {e}"))?
 }
 
+/// The collection a workspace file belongs to: daily logs in `memory/`, or one
+/// of the known brain files at the workspace root. `None` for anything else.
+pub(super) fn classify(home: &Path, path: &Path) -> Option<&'static str> {
+    if path.extension().and_then(|e| e.to_str()) != Some("md") {
+        return None;
+    }
+    let parent = path.parent()?;
+    if parent == home.join("memory") {
+        return Some(COLLECTION_MEMORY);
+    }
+    let name = path.file_name()?.to_str()?;
+    (parent == home && BRAIN_FILES.contains(&name)).then_some(COLLECTION_BRAIN)
+}
+
 /// Synchronous inner implementation for indexing a single file into a given collection.
 /// Returns `true` if new content was indexed, `false` if hash-skipped.
 fn index_file_sync(
@@ -84,7 +144,8 @@ fn index_file_sync(
     Ok(true)
 }
 
-/// Walk `~/.opencrabs/memory/*.md` and `~/.opencrabs/*.md` brain files, indexing all.
+/// Walk `~/.opencrabs/memory/*.md` and `~/.opencrabs/*.md` brain files, indexing all,
+/// and sync curated entries from `memory/entries.json`.
 ///
 /// Also deactivates entries for files that no longer exist on disk.
 /// After indexing, backfills embeddings for any documents missing them.
@@ -147,6 +208,13 @@ pub async fn reindex(store: &'static Mutex<Store>) -> Result<usize, String> {
         }
     }
 
+    // --- Sync curated entries (drops expired ones) ---
+    match tokio::task::spawn_blocking(move || sync_entries(store)).await {
+        Ok(Ok(n)) => tracing::debug!("Synced {n} memory entries"),
+        Ok(Err(e)) => tracing::warn!("Memory entries sync failed: {e}"),
+        Err(e) => tracing::warn!("Memory entries sync failed: {e}"),
+    }
+
     // --- Prune deleted files from both collections ---
     let prune_result: Result<(), String> = tokio::task::spawn_blocking({
         move || {
@@ -188,3 +256,74 @@ pub async fn reindex(store: &'static Mutex<Store>) -> Result<usize, String> {
     tracing::info!("Memory reindex complete: {} files", indexed);
     Ok(indexed)
 }
+
+/// Document counts and embedding backlog of the memory store, for `/memory`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexStatus {
+    /// Active documents per collection, with a display label
+    pub collections: Vec<(&'static str, usize)>,
+    /// Documents still waiting for an embedding (FTS-only until then)
+    pub pending_embeddings: usize,
+    /// Configured embedding backend
+    pub backend: String,
+    /// Whether the embedding engine is loaded and vector search is available
+    pub engine_ready: bool,
+    pub watcher: WatchStatus,
+}
+
+/// Collect the current index status.
+pub async fn index_status(store: &'static Mutex<Store>) -> Result<IndexStatus, String> {
+    const COLLECTIONS: &[(&str, &str)] = &[
+        ("Daily logs", COLLECTION_MEMORY),
+        ("Brain files", COLLECTION_BRAIN),
+        ("Memory entries", COLLECTION_ENTRIES),
+        ("Session messages", COLLECTION_SESSIONS),
+        ("Code chunks", COLLECTION_CODE),
+    ];
+
+    tokio::task::spawn_blocking(move || {
+        let s = store.lock().map_err(|e| format!("Store lock poisoned: {e}"))?;
+        let collections = COLLECTIONS
+            .iter()
+            .map(|&(label, collection)| {
+                let count = s
+                    .get_active_document_paths(collection)
+                    .map(|p| p.len())
+                    .unwrap_or(0);
+                (label, count)
+            })
+            .collect();
+        let pending_embeddings = s
+            .get_hashes_needing_embedding()
+            .map(|h| h.len())
+            .unwrap_or(0);
+
+        Ok(IndexStatus {
+            collections,
+            pending_embeddings,
+            backend: describe_backend(),
+            engine_ready: engine_if_ready().is_some(),
+            watcher: watch_status(),
+        })
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking failed: {e}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let home = Path::new("/home/u/.opencrabs");
+        assert_eq!(
+            classify(home, &home.join("memory/2026-02-15.md")),
+            Some(COLLECTION_MEMORY)
+        );
+        assert_eq!(classify(home, &home.join("MEMORY.md")), Some(COLLECTION_BRAIN));
+        assert_eq!(classify(home, &home.join("notes.md")), None);
+        assert_eq!(classify(home, &home.join("memory/memory.db")), None);
+        assert_eq!(classify(home, &home.join("memory/old/2025-01-01.md")), None);
+    }
+}
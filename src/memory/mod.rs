@@ -1,23 +1,87 @@
 //! Memory Module
 //!
 //! Provides long-term memory search via the `qmd` crate's FTS5 engine and
-//! vector semantic search (embeddinggemma-300M). Hybrid RRF when the model
-//! is available, FTS-only fallback otherwise.
+//! vector semantic search (embeddinggemma-300M by default, or any backend
+//! configured under `[memory.embedding]`). Hybrid RRF when the model is
+//! available, FTS-only fallback otherwise. The working directory's source
+//! tree is indexed into a separate `code` collection searched by `code_search`.
 
+mod backend;
+mod code;
 mod embedding;
+mod entries;
 mod index;
 mod search;
 mod store;
+mod watch;
 
+pub use backend::EmbeddingBackend;
+pub use code::{CodeIndexStats, CodeResult, ensure_code_index, search_code, should_auto_index};
 pub use embedding::{embed_content, engine_if_ready, get_engine};
-pub use index::{index_file, reindex};
-pub use search::search;
+pub use entries::{
+    AddOutcome, EntryKind, EntryUpdate, MemoryEntry, NewEntry, add_entry, entries_path,
+    forget_entry, list_entries, parse_expiry, update_entry,
+};
+pub use index::{IndexStatus, index_file, index_status, reindex};
+pub use search::{search, search_related};
 pub use store::get_store;
+pub use watch::WatchStatus;
+
+/// Index existing memory files and warm up the embedding engine in the
+/// background, then keep memory and brain files indexed as they change on
+/// disk until the task is aborted. Called once at startup by the TUI and the daemon.
+pub fn spawn_startup_index() -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {
+        // Start watching before the full pass so edits made during it aren't lost
+        let watcher = watch::MemoryWatcher::start()
+            .inspect_err(|e| tracing::warn!("Memory file watcher unavailable: {e}"))
+            .ok();
+
+        match get_store() {
+            Ok(store) => match reindex(store).await {
+                Ok(n) => tracing::info!("Startup memory reindex: {n} files"),
+                Err(e) => tracing::warn!("Startup memory reindex failed: {e}"),
+            },
+            Err(e) => tracing::warn!("Memory store init failed at startup: {e}"),
+        }
+        // Index the project the agent was started in so code_search is warm
+        if let Ok(cwd) = std::env::current_dir()
+            && should_auto_index(&cwd)
+            && let Ok(store) = get_store()
+        {
+            match ensure_code_index(store, &cwd).await {
+                Ok(stats) => tracing::info!(
+                    "Startup code index: {} files, {} re-chunked",
+                    stats.files,
+                    stats.indexed
+                ),
+                Err(e) => tracing::warn!("Startup code index failed: {e}"),
+            }
+        }
+        // Warm up embedding engine so first search doesn't pay model download cost.
+        // reindex() already calls get_engine() during backfill, but if all docs were
+        // already embedded, this ensures the engine is ready for search.
+        match tokio::task::spawn_blocking(get_engine).await {
+            Ok(Ok(_)) => tracing::info!("Embedding engine warmed up"),
+            Ok(Err(e)) => tracing::warn!("Embedding engine init skipped: {e}"),
+            Err(e) => tracing::warn!("Embedding engine warmup failed: {e}"),
+        }
+
+        if let Some(watcher) = watcher
+            && let Ok(store) = get_store()
+        {
+            watcher.run(store).await;
+        }
+    })
+}
 
 /// A single search result from the memory index.
 #[derive(Debug, Clone)]
 pub struct MemoryResult {
     pub path: String,
+    /// Collection the document belongs to ("memory", "brain", "entries", "sessions")
+    pub collection: String,
+    pub title: String,
     pub snippet: String,
     pub rank: f64,
 }
@@ -26,3 +90,9 @@ pub struct MemoryResult {
 const COLLECTION_MEMORY: &str = "memory";
 /// Collection name for workspace brain files (SOUL.md, MEMORY.md, etc.).
 const COLLECTION_BRAIN: &str = "brain";
+/// Collection name for source chunks of indexed working directories.
+const COLLECTION_CODE: &str = "code";
+/// Collection name for session message history (indexed by `session_search`).
+pub const COLLECTION_SESSIONS: &str = "sessions";
+/// Collection name for curated entries written by `memory_write`.
+pub const COLLECTION_ENTRIES: &str = "entries";
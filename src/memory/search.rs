@@ -1,16 +1,20 @@
 //! Search — hybrid FTS5 + vector search via Reciprocal Rank Fusion.
 
 use qmd::{SearchResult, Store, hybrid_search_rrf};
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Mutex;
 
 use super::embedding::engine_if_ready;
-use super::{COLLECTION_BRAIN, MemoryResult};
+use super::entries::{entries_path, expire_due};
+use super::{COLLECTION_BRAIN, COLLECTION_CODE, COLLECTION_ENTRIES, MemoryResult};
 
-/// Hybrid search across memory logs: FTS5 (BM25) + vector (cosine) via RRF.
+/// Hybrid search across memory logs, brain files and curated entries:
+/// FTS5 (BM25) + vector (cosine) via RRF.
 ///
 /// Falls back to FTS-only when the embedding engine is unavailable.
-/// Returns up to `n` results sorted by relevance.
+/// Returns up to `n` results sorted by relevance. Source chunks from the
+/// `code` collection are left to `code_search`.
 pub async fn search(
     store: &'static Mutex<Store>,
     query: &str,
@@ -20,14 +24,66 @@ pub async fn search(
     if fts_query.is_empty() {
         return Ok(vec![]);
     }
+    let query = SearchQuery {
+        terms: fts_query.clone(),
+        fts: fts_query,
+        text: query.to_string(),
+        snippet_len: 200,
+        skip: &[COLLECTION_CODE],
+    };
+    run_search(store, query, n).await
+}
+
+/// Find memories related to a piece of free text, such as an incoming user
+/// message. Unlike `search`, the keyword side matches any salient word rather
+/// than all of them, and brain files are skipped — they're already part of
+/// the system brain.
+pub async fn search_related(
+    store: &'static Mutex<Store>,
+    text: &str,
+    n: usize,
+) -> Result<Vec<MemoryResult>, String> {
+    let keywords = salient_words(text);
+    if keywords.is_empty() && engine_if_ready().is_none() {
+        return Ok(vec![]);
+    }
+    let quoted: Vec<String> = keywords.iter().map(|w| format!("\"{w}\"")).collect();
+    let query = SearchQuery {
+        fts: quoted.join(" OR "),
+        terms: quoted.join(" "),
+        text: text.to_string(),
+        snippet_len: 400,
+        skip: &[COLLECTION_CODE, COLLECTION_BRAIN],
+    };
+    run_search(store, query, n).await
+}
 
-    let query_owned = query.to_string();
+struct SearchQuery {
+    /// FTS5 MATCH expression; empty skips the keyword side
+    fts: String,
+    /// Quoted words that snippets are centred on
+    terms: String,
+    /// Raw text embedded for the vector side
+    text: String,
+    snippet_len: usize,
+    /// Collections left out of the results
+    skip: &'static [&'static str],
+}
 
+async fn run_search(
+    store: &'static Mutex<Store>,
+    query: SearchQuery,
+    n: usize,
+) -> Result<Vec<MemoryResult>, String> {
     tokio::task::spawn_blocking(move || {
+        if let Err(e) = expire_due(store) {
+            tracing::debug!("Memory entry expiry failed: {e}");
+        }
+
         // Engine lock → embed query → release (before store lock)
         let query_embedding: Option<Vec<f32>> = engine_if_ready().and_then(|em| {
             em.lock().ok().and_then(|mut e| {
-                e.embed_query(&query_owned).ok().map(|r| r.embedding)
+                e.embed_query(&query.text).ok()
             })
         });
 
@@ -35,15 +91,41 @@ pub async fn search(
         let store = store.lock().map_err(|e| format!("Store lock poisoned: {e}"))?;
         let home = crate::config::opencrabs_home();
 
-        let fts_results = store
-            .search_fts(&fts_query, n, None)
-            .map_err(|e| format!("FTS search failed: {e}"))?;
+        // Code chunks share the store — over-fetch, then drop them
+        let fts_results: Vec<SearchResult> = if query.fts.is_empty() {
+            Vec::new()
+        } else {
+            store
+                .search_fts(&query.fts, n * 3, None)
+                .map_err(|e| format!("FTS search failed: {e}"))?
+                .into_iter()
+                .filter(|r| !query.skip.contains(&r.doc.collection_name.as_str()))
+                .take(n)
+                .collect()
+        };
 
         // Hybrid path: combine FTS + vector results via Reciprocal Rank Fusion
         if let Some(ref query_emb) = query_embedding {
-            let vec_results = store.search_vec(query_emb, n, None).unwrap_or_default();
+            let vec_results: Vec<SearchResult> = store
+                .search_vec(query_emb, n * 3, None)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|r| !query.skip.contains(&r.doc.collection_name.as_str()))
+                .take(n)
+                .collect();
 
             if !vec_results.is_empty() {
+                // RRF output only carries the resolved path — keep each one's collection
+                let collections: HashMap<String, String> = fts_results
+                    .iter()
+                    .chain(&vec_results)
+                    .map(|r| {
+                        (
+                            resolve_path(&home, &r.doc.collection_name, &r.doc.path),
+                            r.doc.collection_name.clone(),
+                        )
+                    })
+                    .collect();
                 let fts_tuples = results_to_tuples(&store, &home, &fts_results);
                 let vec_tuples = results_to_tuples(&store, &home, &vec_results);
                 let rrf = hybrid_search_rrf(fts_tuples, vec_tuples, 60);
@@ -52,8 +134,10 @@ pub async fn search(
                     .into_iter()
                     .take(n)
                     .map(|r| MemoryResult {
+                        collection: collections.get(&r.file).cloned().unwrap_or_default(),
+                        snippet: extract_snippet(&r.body, &query.terms, query.snippet_len),
                         path: r.file,
-                        snippet: extract_snippet(&r.body, &fts_query, 200),
+                        title: r.title,
                         rank: r.score,
                     })
                     .collect());
@@ -67,12 +151,14 @@ pub async fn search(
                 let snippet = match store.get_document(&r.doc.collection_name, &r.doc.path) {
                     Ok(Some(doc)) => {
                         let body = doc.body.as_deref().unwrap_or("");
-                        extract_snippet(body, &fts_query, 200)
+                        extract_snippet(body, &query.terms, query.snippet_len)
                     }
                     _ => r.doc.title.clone(),
                 };
                 MemoryResult {
                     path: resolve_path(&home, &r.doc.collection_name, &r.doc.path),
+                    collection: r.doc.collection_name.clone(),
+                    title: r.doc.title.clone(),
                     snippet,
                     rank: r.score,
                 }
@@ -83,6 +169,32 @@ pub async fn search(
     .map_err(|e| format!("spawn_blocking failed: {e}"))?
 }
 
+/// Distinct content words of `text`, lowercased, in order of appearance.
+fn salient_words(text: &str) -> Vec<String> {
+    const STOPWORDS: &[&str] = &[
+        "about", "after", "again", "also", "been", "before", "being", "could", "does", "doing",
+        "from", "have", "here", "into", "just", "like", "make", "more", "need", "only", "please",
+        "should", "some", "than", "that", "their", "them", "then", "there", "these", "they",
+        "this", "want", "were", "what", "when", "where", "which", "while", "will", "with",
+        "would", "your",
+    ];
+    const MAX_WORDS: usize = 12;
+
+    let mut words: Vec<String> = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric() && c != '_' && c != '-') {
+        let word = word.trim_matches('-').to_lowercase();
+        if word.chars().count() < 4 || STOPWORDS.contains(&word.as_str()) || words.contains(&word)
+        {
+            continue;
+        }
+        words.push(word);
+        if words.len() == MAX_WORDS {
+            break;
+        }
+    }
+    words
+}
+
 /// Convert SearchResults to RRF tuple format: (file_path, display_path, title, body).
 fn results_to_tuples(
     store: &Store,
@@ -110,9 +222,12 @@ fn results_to_tuples(
 }
 
 /// Resolve filesystem path for a search result based on its collection.
+/// Curated entries resolve to `entries.json#<id>`.
 fn resolve_path(home: &Path, collection: &str, doc_path: &str) -> String {
     let p = if collection == COLLECTION_BRAIN {
         home.join(doc_path)
+    } else if collection == COLLECTION_ENTRIES {
+        return format!("{}#{doc_path}", entries_path().display());
     } else {
         home.join("memory").join(doc_path)
     };
@@ -121,7 +236,7 @@ fn resolve_path(home: &Path, collection: &str, doc_path: &str) -> String {
 
 /// Sanitize a search query for FTS5: wrap each word in double quotes
 /// to avoid syntax errors from special characters, then join with spaces (implicit AND).
-fn sanitize_fts_query(query: &str) -> String {
+pub(super) fn sanitize_fts_query(query: &str) -> String {
     query
         .split_whitespace()
         .map(|w| {
@@ -133,7 +248,7 @@ fn sanitize_fts_query(query: &str) -> String {
 }
 
 /// Extract a snippet from body text around the first query term match.
-fn extract_snippet(body: &str, query: &str, max_len: usize) -> String {
+pub(super) fn extract_snippet(body: &str, query: &str, max_len: usize) -> String {
     let query_lower = query.to_lowercase();
     let body_lower = body.to_lowercase();
 
@@ -191,4 +306,13 @@ mod tests {
         let snippet = extract_snippet(body, "\"nonexistent\"", 60);
         assert!(snippet.contains("Some content"));
     }
+
+    #[test]
+    fn test_salient_words() {
+        assert_eq!(
+            salient_words("How should we handle the auth-token refresh? What about auth retries"),
+            vec!["handle", "auth-token", "refresh", "auth", "retries"]
+        );
+        assert!(salient_words("is it ok?").is_empty());
+    }
 }
@@ -5,15 +5,19 @@ use qmd::Store;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+use super::backend::{dimensions, embedding_config, store_file_name};
+
 static STORE: OnceCell<Mutex<Store>> = OnceCell::new();
 
 /// Get (or create) the shared memory qmd Store.
 ///
-/// The database lives at `~/.opencrabs/memory/memory.db`.
+/// The database lives at `~/.opencrabs/memory/memory.db` (or `memory-<id>.db`
+/// for embedding backends other than the bundled model).
 /// First call initializes the schema via `Store::open` and creates the vector table.
 pub fn get_store() -> Result<&'static Mutex<Store>, String> {
     STORE.get_or_try_init(|| {
-        let db_path = memory_dir().join("memory.db");
+        let config = embedding_config();
+        let db_path = memory_dir().join(store_file_name(config));
 
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
@@ -24,7 +28,7 @@ pub fn get_store() -> Result<&'static Mutex<Store>, String> {
             .map_err(|e| format!("Failed to open memory store: {e}"))?;
 
         store
-            .ensure_vector_table(768)
+            .ensure_vector_table(dimensions(config))
             .map_err(|e| format!("Failed to create vector table: {e}"))?;
 
         tracing::info!("Memory qmd store ready at {}", db_path.display());
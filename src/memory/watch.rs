@@ -0,0 +1,277 @@
+//! Watch — keep memory logs, brain files and curated entries indexed as they
+//! change on disk.
+//!
+//! Edits made outside OpenCrabs (an editor on `MEMORY.md`, a synced daily log)
+//! would otherwise only become searchable on the next startup reindex. The
+//! same debounce keeps `code_search`'s index of the working directory current.
+
+use chrono::{DateTime, Local};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use qmd::Store;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::code::{new_watch_dirs, sync_code_paths, watch_dirs};
+use super::entries::{entries_path, sync_entries};
+use super::index::{classify, index_path, remove_path};
+
+/// Quiet period after the last event before a batch of changes is applied.
+/// Editors often write a file several times (temp file, rename, chmod) per save.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+static STATUS: Lazy<Mutex<WatchStatus>> = Lazy::new(|| Mutex::new(WatchStatus::default()));
+
+/// What the watcher has done since startup.
+#[derive(Debug, Clone, Default)]
+pub struct WatchStatus {
+    /// Directories being watched; empty when the watcher isn't running
+    pub watching: Vec<PathBuf>,
+    /// Files re-indexed after a change
+    pub reindexed: usize,
+    /// Files removed from the index after being deleted
+    pub removed: usize,
+    pub last_change: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+}
+
+/// Snapshot of the watcher's status.
+pub fn watch_status() -> WatchStatus {
+    STATUS.lock().map(|s| s.clone()).unwrap_or_default()
+}
+
+fn update_status(f: impl FnOnce(&mut WatchStatus)) {
+    if let Ok(mut s) = STATUS.lock() {
+        f(&mut s);
+    }
+}
+
+/// File-system watcher over `~/.opencrabs/` (brain files) and
+/// `~/.opencrabs/memory/` (daily logs). Events are buffered from the moment
+/// it's created, so nothing is missed while the startup reindex runs.
+pub struct MemoryWatcher {
+    // Dropping the watcher stops event delivery
+    _watcher: RecommendedWatcher,
+    rx: mpsc::UnboundedReceiver<PathBuf>,
+    home: PathBuf,
+}
+
+impl MemoryWatcher {
+    /// Start watching the workspace and memory directories.
+    pub fn start() -> Result<Self, String> {
+        let home = crate::config::opencrabs_home();
+        let memory_dir = home.join("memory");
+        std::fs::create_dir_all(&memory_dir)
+            .map_err(|e| format!("Failed to create memory dir: {e}"))?;
+
+        let entries_file = entries_path();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                    ) {
+                        for path in event.paths {
+                            // memory.db and its WAL live here too — only markdown
+                            // and the curated entries file matter
+                            if path.extension().and_then(|e| e.to_str()) == Some("md")
+                                || path == entries_file
+                            {
+                                let _ = tx.send(path);
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::debug!("Memory watcher error: {e}"),
+            }
+        })
+        .map_err(|e| format!("Failed to create file watcher: {e}"))?;
+
+        for dir in [&home, &memory_dir] {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {e}", dir.display()))?;
+        }
+
+        update_status(|s| s.watching = vec![home.clone(), memory_dir]);
+        Ok(Self {
+            _watcher: watcher,
+            rx,
+            home,
+        })
+    }
+
+    /// Apply changes as they arrive, debounced, until the task is aborted.
+    pub async fn run(mut self, store: &'static Mutex<Store>) {
+        while let Some(pending) = next_batch(&mut self.rx).await {
+            for path in pending {
+                apply_change(store, &self.home, &path).await;
+            }
+        }
+        update_status(|s| s.watching.clear());
+    }
+}
+
+/// Watcher over a code root indexed for `code_search`. Each directory the
+/// indexer would list gets its own non-recursive watch, so `target/`,
+/// `node_modules/` and other ignored trees don't use up the OS watch limit;
+/// directories created later are picked up as they appear. Like
+/// [`MemoryWatcher`], events are buffered from creation so the initial
+/// index pass can't miss edits.
+pub(super) struct CodeWatcher {
+    watcher: RecommendedWatcher,
+    watched: HashSet<PathBuf>,
+    rx: mpsc::UnboundedReceiver<PathBuf>,
+    root: PathBuf,
+}
+
+impl CodeWatcher {
+    pub(super) fn start(root: &Path) -> Result<Self, String> {
+        let git_dir = root.join(".git");
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            match res {
+                Ok(event) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                    ) {
+                        // Every git command touches .git; nothing in it is indexed
+                        for path in event.paths.into_iter().filter(|p| !p.starts_with(&git_dir)) {
+                            let _ = tx.send(path);
+                        }
+                    }
+                }
+                Err(e) => tracing::debug!("Code watcher error: {e}"),
+            }
+        })
+        .map_err(|e| format!("Failed to create file watcher: {e}"))?;
+
+        let mut watched = HashSet::new();
+        for dir in watch_dirs(root) {
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(|e| format!("Failed to watch {}: {e}", dir.display()))?;
+            watched.insert(dir);
+        }
+        Ok(Self {
+            watcher,
+            watched,
+            rx,
+            root: root.to_path_buf(),
+        })
+    }
+
+    /// Re-chunk changed files, debounced, until the watcher stops.
+    pub(super) async fn run(mut self, store: &'static Mutex<Store>) {
+        while let Some(pending) = next_batch(&mut self.rx).await {
+            self.follow_dirs(&pending);
+            if let Err(e) = sync_code_paths(store, &self.root, pending.into_iter().collect()).await
+            {
+                tracing::warn!("Code watcher failed on {}: {e}", self.root.display());
+            }
+        }
+    }
+
+    /// Watch directories that appeared in a batch and drop the watches of
+    /// ones that went away, so a recreated directory is watched again.
+    fn follow_dirs(&mut self, paths: &HashSet<PathBuf>) {
+        for path in paths {
+            if path.is_dir() {
+                if self.watched.contains(path) {
+                    continue;
+                }
+                for dir in new_watch_dirs(&self.root, path) {
+                    if self.watched.contains(&dir) {
+                        continue;
+                    }
+                    match self.watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                        Ok(()) => {
+                            self.watched.insert(dir);
+                        }
+                        Err(e) => {
+                            tracing::warn!("Code watcher can't follow {}: {e}", dir.display())
+                        }
+                    }
+                }
+            } else if !path.exists() {
+                let gone: Vec<PathBuf> = self
+                    .watched
+                    .iter()
+                    .filter(|dir| dir.starts_with(path))
+                    .cloned()
+                    .collect();
+                for dir in gone {
+                    let _ = self.watcher.unwatch(&dir);
+                    self.watched.remove(&dir);
+                }
+            }
+        }
+    }
+}
+
+/// Wait for the next change, then collect everything that arrives until
+/// `DEBOUNCE` passes without an event. `None` once the watcher is gone.
+async fn next_batch(rx: &mut mpsc::UnboundedReceiver<PathBuf>) -> Option<HashSet<PathBuf>> {
+    let first = rx.recv().await?;
+    let mut pending = HashSet::from([first]);
+    while let Ok(Some(path)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+        pending.insert(path);
+    }
+    Some(pending)
+}
+
+/// Re-index a changed file, or drop it from the index if it's gone.
+async fn apply_change(store: &'static Mutex<Store>, home: &Path, path: &Path) {
+    if path == entries_path() {
+        // Hash-skip makes this a no-op for memory_write's own saves
+        let result = tokio::task::spawn_blocking(move || sync_entries(store))
+            .await
+            .map_err(|e| format!("spawn_blocking failed: {e}"))
+            .and_then(|r| r);
+        if let Err(e) = result {
+            tracing::warn!("Memory watcher failed on {}: {e}", path.display());
+            update_status(|s| s.last_error = Some(e));
+        }
+        return;
+    }
+    let Some(collection) = classify(home, path) else {
+        return;
+    };
+
+    let result = if path.is_file() {
+        index_path(store, collection, path)
+            .await
+            .map(|changed| (changed, false))
+    } else {
+        remove_path(store, collection, path)
+            .await
+            .map(|removed| (false, removed))
+    };
+
+    match result {
+        Ok((reindexed, removed)) => {
+            if reindexed || removed {
+                tracing::debug!(
+                    "Memory watcher: {} {}",
+                    if removed { "removed" } else { "re-indexed" },
+                    path.display()
+                );
+                update_status(|s| {
+                    s.reindexed += reindexed as usize;
+                    s.removed += removed as usize;
+                    s.last_change = Some(Local::now());
+                });
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Memory watcher failed on {}: {e}", path.display());
+            update_status(|s| s.last_error = Some(e));
+        }
+    }
+}
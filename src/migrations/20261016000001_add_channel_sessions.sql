@@ -0,0 +1,21 @@
+-- Migration to persist channel user sessions
+-- Maps an external user on a messaging channel (Telegram, Discord, Slack,
+-- WhatsApp) to the session that holds their conversation, so contacts keep
+-- their history across restarts.
+
+-- ==================================================
+-- Channel Sessions Table
+-- ==================================================
+
+CREATE TABLE IF NOT EXISTS channel_sessions (
+    channel TEXT NOT NULL,  -- telegram, discord, slack, whatsapp
+    external_id TEXT NOT NULL,  -- Channel-specific user/chat ID
+    session_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    last_seen INTEGER NOT NULL,  -- Timestamp of the most recent message
+
+    PRIMARY KEY (channel, external_id),
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_channel_sessions_session_id ON channel_sessions(session_id);
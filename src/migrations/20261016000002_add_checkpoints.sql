@@ -0,0 +1,33 @@
+-- Migration to add per-turn file checkpoints
+-- Before a tool first modifies a file during an agent turn, the file's
+-- previous content is saved here so the whole turn can be rolled back
+-- (/undo, /restore).
+
+-- ==================================================
+-- Checkpoints Table
+-- ==================================================
+
+CREATE TABLE IF NOT EXISTS checkpoints (
+    id TEXT PRIMARY KEY NOT NULL,
+    session_id TEXT NOT NULL,
+    label TEXT NOT NULL,  -- Start of the user message that began the turn
+    created_at INTEGER NOT NULL,
+    restored_at INTEGER,  -- Set once the turn has been rolled back
+
+    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkpoints_session_id ON checkpoints(session_id, created_at);
+
+-- ==================================================
+-- Checkpoint Files Table
+-- ==================================================
+
+CREATE TABLE IF NOT EXISTS checkpoint_files (
+    checkpoint_id TEXT NOT NULL,
+    path TEXT NOT NULL,
+    content BLOB,  -- NULL when the file did not exist before the turn
+
+    PRIMARY KEY (checkpoint_id, path),
+    FOREIGN KEY (checkpoint_id) REFERENCES checkpoints(id) ON DELETE CASCADE
+);
@@ -0,0 +1,169 @@
+//! Channel Session Service
+//!
+//! Resolves which session a messaging-channel user talks to. Mappings live in
+//! the `channel_sessions` table so non-owner contacts keep their conversation
+//! across restarts.
+
+use crate::db::{
+    models::ChannelSession,
+    repository::{ChannelSessionRepository, SessionRepository},
+};
+use crate::services::{ServiceContext, SessionService};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// Service for channel user → session mappings
+#[derive(Clone)]
+pub struct ChannelSessionService {
+    context: ServiceContext,
+    /// Serializes resolution so concurrent first messages from one user
+    /// don't each create a session
+    resolve_lock: Arc<Mutex<()>>,
+}
+
+impl ChannelSessionService {
+    /// Create a new channel session service
+    pub fn new(context: ServiceContext) -> Self {
+        Self {
+            context,
+            resolve_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Get the session for a user on a channel, creating one titled `title`
+    /// if they have none yet (or their session was deleted).
+    pub async fn resolve_session(
+        &self,
+        channel: &str,
+        external_id: &str,
+        title: String,
+    ) -> Result<Uuid> {
+        let _guard = self.resolve_lock.lock().await;
+        let repo = ChannelSessionRepository::new(self.context.pool());
+
+        if let Some(mapping) = repo.find(channel, external_id).await? {
+            let sessions = SessionRepository::new(self.context.pool());
+            if sessions.find_by_id(mapping.session_id).await?.is_some() {
+                repo.touch(channel, external_id).await?;
+                return Ok(mapping.session_id);
+            }
+            tracing::warn!(
+                "Session {} for {}:{} no longer exists, starting a new one",
+                mapping.session_id,
+                channel,
+                external_id
+            );
+        }
+
+        let session = SessionService::new(self.context.clone())
+            .create_session(Some(title))
+            .await?;
+        repo.upsert(&ChannelSession::new(
+            channel.to_string(),
+            external_id.to_string(),
+            session.id,
+        ))
+        .await
+        .context("Failed to link channel session")?;
+
+        tracing::info!(
+            "Linked {}:{} to new session {}",
+            channel,
+            external_id,
+            session.id
+        );
+        Ok(session.id)
+    }
+
+    /// List mappings, optionally for a single channel
+    pub async fn list_mappings(&self, channel: Option<&str>) -> Result<Vec<ChannelSession>> {
+        let repo = ChannelSessionRepository::new(self.context.pool());
+        repo.list(channel).await
+    }
+
+    /// Remove a user's mapping so their next message starts a fresh session.
+    /// The old session itself is kept. Returns whether a mapping existed.
+    pub async fn unlink(&self, channel: &str, external_id: &str) -> Result<bool> {
+        let repo = ChannelSessionRepository::new(self.context.pool());
+        let removed = repo.delete(channel, external_id).await?;
+        if removed {
+            tracing::info!("Unlinked {}:{}", channel, external_id);
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_test_service() -> ChannelSessionService {
+        use crate::db::Database;
+
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        ChannelSessionService::new(ServiceContext::new(db.pool().clone()))
+    }
+
+    #[tokio::test]
+    async fn test_resolve_reuses_mapping() {
+        let service = create_test_service().await;
+
+        let first = service
+            .resolve_session("telegram", "42", "Telegram: Ana".to_string())
+            .await
+            .unwrap();
+        let again = service
+            .resolve_session("telegram", "42", "Telegram: Ana".to_string())
+            .await
+            .unwrap();
+        assert_eq!(first, again);
+
+        let other = service
+            .resolve_session("discord", "42", "Discord: ana".to_string())
+            .await
+            .unwrap();
+        assert_ne!(first, other);
+        assert_eq!(service.list_mappings(None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unlink_starts_fresh_session() {
+        let service = create_test_service().await;
+
+        let first = service
+            .resolve_session("slack", "U1", "Slack: U1".to_string())
+            .await
+            .unwrap();
+        assert!(service.unlink("slack", "U1").await.unwrap());
+        assert!(!service.unlink("slack", "U1").await.unwrap());
+
+        let second = service
+            .resolve_session("slack", "U1", "Slack: U1".to_string())
+            .await
+            .unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_replaces_deleted_session() {
+        let service = create_test_service().await;
+
+        let first = service
+            .resolve_session("whatsapp", "15551234", "WhatsApp: 15551234".to_string())
+            .await
+            .unwrap();
+        SessionService::new(service.context.clone())
+            .delete_session(first)
+            .await
+            .unwrap();
+
+        let second = service
+            .resolve_session("whatsapp", "15551234", "WhatsApp: 15551234".to_string())
+            .await
+            .unwrap();
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,265 @@
+//! Checkpoint Service
+//!
+//! Before a file-modifying tool writes, the file's previous content is saved
+//! into the current turn's checkpoint. Restoring a checkpoint rolls back that
+//! turn and every later one, including multi-file edits and files the turn
+//! created.
+
+use crate::db::{
+    models::{Checkpoint, CheckpointFile},
+    repository::CheckpointRepository,
+};
+use crate::services::ServiceContext;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Outcome of an undo / restore
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    /// Checkpoints rolled back, newest first
+    pub checkpoints: usize,
+    /// Files written back or removed
+    pub files: usize,
+}
+
+/// Service for file checkpoints
+#[derive(Clone)]
+pub struct CheckpointService {
+    context: ServiceContext,
+}
+
+impl CheckpointService {
+    /// Create a new checkpoint service
+    pub fn new(context: ServiceContext) -> Self {
+        Self { context }
+    }
+
+    /// Checkpoints of a session that can still be restored, newest first
+    pub async fn list(&self, session_id: Uuid) -> Result<Vec<Checkpoint>> {
+        CheckpointRepository::new(self.context.pool())
+            .list_active(session_id)
+            .await
+    }
+
+    /// Roll back the most recent checkpoint, `None` if there is nothing to undo
+    pub async fn undo(&self, session_id: Uuid) -> Result<Option<RestoreSummary>> {
+        let checkpoints = self.list(session_id).await?;
+        match checkpoints.first() {
+            Some(latest) => Ok(Some(self.rollback(&checkpoints[..1], latest.id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Restore the files to how they were before checkpoint `id` (a full id
+    /// or unique prefix), rolling back every later checkpoint too
+    pub async fn restore(&self, session_id: Uuid, id: &str) -> Result<RestoreSummary> {
+        let checkpoints = self.list(session_id).await?;
+        let matches: Vec<usize> = checkpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.id.to_string().starts_with(id))
+            .map(|(i, _)| i)
+            .collect();
+
+        match matches.as_slice() {
+            [index] => {
+                let target = checkpoints[*index].id;
+                self.rollback(&checkpoints[..=*index], target).await
+            }
+            [] => anyhow::bail!("No checkpoint matching '{}' in this session", id),
+            _ => anyhow::bail!("Checkpoint id '{}' is ambiguous", id),
+        }
+    }
+
+    /// Roll back `checkpoints` (newest first) so older content wins
+    async fn rollback(&self, checkpoints: &[Checkpoint], target: Uuid) -> Result<RestoreSummary> {
+        let repo = CheckpointRepository::new(self.context.pool());
+        let mut summary = RestoreSummary::default();
+
+        for checkpoint in checkpoints {
+            for file in repo.files(checkpoint.id).await? {
+                restore_file(&file).await?;
+                summary.files += 1;
+            }
+            repo.mark_restored(checkpoint.id).await?;
+            summary.checkpoints += 1;
+        }
+
+        tracing::info!(
+            "Restored checkpoint {} ({} checkpoints, {} files)",
+            target,
+            summary.checkpoints,
+            summary.files
+        );
+        Ok(summary)
+    }
+}
+
+/// Put one saved file back, removing it if it didn't exist before
+async fn restore_file(file: &CheckpointFile) -> Result<()> {
+    match &file.content {
+        Some(content) => {
+            if let Some(parent) = file.path.parent() {
+                tokio::fs::create_dir_all(parent).await.ok();
+            }
+            tokio::fs::write(&file.path, content)
+                .await
+                .with_context(|| format!("Failed to restore {}", file.path.display()))
+        }
+        None => match tokio::fs::remove_file(&file.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", file.path.display()))
+            }
+            _ => Ok(()),
+        },
+    }
+}
+
+/// The checkpoint for one agent turn, handed to tools through
+/// `ToolExecutionContext`. The row is only created once a file is saved,
+/// so turns that don't write anything leave no empty checkpoints behind.
+#[derive(Clone)]
+pub struct TurnCheckpoint {
+    context: ServiceContext,
+    checkpoint: Checkpoint,
+}
+
+impl TurnCheckpoint {
+    /// Start a checkpoint for a turn in `session_id`
+    pub fn new(context: ServiceContext, session_id: Uuid, label: String) -> Self {
+        Self {
+            context,
+            checkpoint: Checkpoint {
+                id: Uuid::new_v4(),
+                session_id,
+                label,
+                created_at: Utc::now(),
+                restored_at: None,
+                file_count: 0,
+            },
+        }
+    }
+
+    /// Checkpoint id
+    pub fn id(&self) -> Uuid {
+        self.checkpoint.id
+    }
+
+    /// Save `path`'s current content before it is modified. Only the first
+    /// snapshot of a path per turn is kept.
+    pub async fn snapshot(&self, path: &Path) -> Result<()> {
+        let repo = CheckpointRepository::new(self.context.pool());
+        if repo.has_file(self.checkpoint.id, path).await? {
+            return Ok(());
+        }
+
+        let content = match tokio::fs::read(path).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to snapshot {}", path.display()));
+            }
+        };
+
+        repo.ensure(&self.checkpoint).await?;
+        repo.add_file(&CheckpointFile {
+            checkpoint_id: self.checkpoint.id,
+            path: path.to_path_buf(),
+            content,
+        })
+        .await
+    }
+}
+
+impl std::fmt::Debug for TurnCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TurnCheckpoint")
+            .field("id", &self.checkpoint.id)
+            .field("session_id", &self.checkpoint.session_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::SessionService;
+    use tempfile::TempDir;
+
+    async fn setup() -> (ServiceContext, Uuid) {
+        use crate::db::Database;
+
+        let db = Database::connect_in_memory().await.unwrap();
+        db.run_migrations().await.unwrap();
+        let context = ServiceContext::new(db.pool().clone());
+        let session = SessionService::new(context.clone())
+            .create_session(Some("Test".to_string()))
+            .await
+            .unwrap();
+        (context, session.id)
+    }
+
+    #[tokio::test]
+    async fn test_undo_rolls_back_multi_file_turn() {
+        let (context, session_id) = setup().await;
+        let dir = TempDir::new().unwrap();
+        let edited = dir.path().join("a.txt");
+        let created = dir.path().join("new/b.txt");
+        std::fs::write(&edited, "original").unwrap();
+
+        let turn = TurnCheckpoint::new(context.clone(), session_id, "edit".to_string());
+        turn.snapshot(&edited).await.unwrap();
+        std::fs::write(&edited, "first edit").unwrap();
+        turn.snapshot(&edited).await.unwrap();
+        std::fs::write(&edited, "second edit").unwrap();
+        turn.snapshot(&created).await.unwrap();
+        std::fs::create_dir_all(created.parent().unwrap()).unwrap();
+        std::fs::write(&created, "new file").unwrap();
+
+        let service = CheckpointService::new(context);
+        let listed = service.list(session_id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].file_count, 2);
+
+        let summary = service.undo(session_id).await.unwrap().unwrap();
+        assert_eq!(
+            summary,
+            RestoreSummary {
+                checkpoints: 1,
+                files: 2
+            }
+        );
+        assert_eq!(std::fs::read_to_string(&edited).unwrap(), "original");
+        assert!(!created.exists());
+        assert!(service.undo(session_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_restore_rolls_back_later_turns() {
+        let (context, session_id) = setup().await;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "v1").unwrap();
+
+        let mut ids = Vec::new();
+        for next in ["v2", "v3"] {
+            let turn = TurnCheckpoint::new(context.clone(), session_id, next.to_string());
+            turn.snapshot(&path).await.unwrap();
+            std::fs::write(&path, next).unwrap();
+            ids.push(turn.id().to_string());
+        }
+        // A turn without writes leaves no checkpoint
+        TurnCheckpoint::new(context.clone(), session_id, "chat".to_string());
+
+        let service = CheckpointService::new(context);
+        assert_eq!(service.list(session_id).await.unwrap().len(), 2);
+        assert!(service.restore(session_id, "zzz").await.is_err());
+
+        let summary = service.restore(session_id, &ids[0][..8]).await.unwrap();
+        assert_eq!(summary.checkpoints, 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "v1");
+        assert!(service.list(session_id).await.unwrap().is_empty());
+    }
+}
@@ -3,12 +3,16 @@
 //! This module contains the business logic services that orchestrate
 //! operations between the database layer and the application layer.
 
+pub mod channel_session;
+pub mod checkpoint;
 mod context;
 pub mod file;
 pub mod message;
 pub mod plan;
 pub mod session;
 
+pub use channel_session::ChannelSessionService;
+pub use checkpoint::CheckpointService;
 pub use context::{ServiceContext, ServiceManager};
 pub use file::FileService;
 pub use message::MessageService;
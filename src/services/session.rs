@@ -96,15 +96,12 @@ impl SessionService {
         Ok(())
     }
 
-    /// Update session usage statistics
+    /// Add to session usage statistics. The increment happens in a single
+    /// UPDATE, so concurrent callers (e.g. parallel sub-agents rolling usage
+    /// into their parent) don't overwrite each other's totals.
     pub async fn update_session_usage(&self, id: Uuid, token_count: i32, cost: f64) -> Result<()> {
-        let mut session = self.get_session_required(id).await?;
-        session.token_count += token_count;
-        session.total_cost += cost;
-        session.updated_at = Utc::now();
-
         let repo = SessionRepository::new(self.context.pool());
-        repo.update(&session)
+        repo.update_stats(id, token_count, cost)
             .await
             .context("Failed to update session usage")?;
 
@@ -273,6 +270,24 @@ mod tests {
         assert!((updated.total_cost - 0.075).abs() < 0.0001);
     }
 
+    #[tokio::test]
+    async fn test_concurrent_session_usage_updates_all_count() {
+        let service = create_test_service().await;
+        let session = service
+            .create_session(Some("Test".to_string()))
+            .await
+            .unwrap();
+
+        let updates = (0..10).map(|_| service.update_session_usage(session.id, 10, 0.01));
+        for result in futures::future::join_all(updates).await {
+            result.unwrap();
+        }
+
+        let updated = service.get_session_required(session.id).await.unwrap();
+        assert_eq!(updated.token_count, 100);
+        assert!((updated.total_cost - 0.1).abs() < 0.0001);
+    }
+
     #[tokio::test]
     async fn test_archive_unarchive_session() {
         let service = create_test_service().await;
@@ -10,11 +10,13 @@ use crate::brain::{BrainLoader, CommandLoader, SelfUpdater, UserCommand};
 use crate::db::models::{Message, Session};
 use crate::brain::agent::AgentService;
 use crate::brain::provider::{ContentBlock, LLMRequest};
-use crate::services::{MessageService, PlanService, ServiceContext, SessionService};
+use crate::services::{
+    CheckpointService, MessageService, PlanService, ServiceContext, SessionService,
+};
 use anyhow::Result;
 use ratatui::text::Line;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -70,6 +72,22 @@ pub const SLASH_COMMANDS: &[SlashCommand] = &[
         name: "/cd",
         description: "Change working directory",
     },
+    SlashCommand {
+        name: "/undo",
+        description: "Undo the last turn's file changes",
+    },
+    SlashCommand {
+        name: "/checkpoints",
+        description: "List file checkpoints",
+    },
+    SlashCommand {
+        name: "/restore",
+        description: "Restore files to a checkpoint",
+    },
+    SlashCommand {
+        name: "/memory",
+        description: "Memory index status",
+    },
 ];
 
 /// Approval option selected by the user
@@ -99,8 +117,85 @@ pub struct ApprovalData {
     pub response_tx: mpsc::UnboundedSender<ToolApprovalResponse>,
     pub requested_at: std::time::Instant,
     pub state: ApprovalState,
-    pub selected_option: usize,  // 0-2, arrow key navigation
+    pub selected_option: usize,  // index into choices(), arrow key navigation
     pub show_details: bool,      // V key toggle
+    /// What the call would change, for file-modifying tools
+    pub diff: Option<Vec<super::diff::FileDiff>>,
+    /// Files the call writes (for "Yes for this file")
+    pub files: Vec<PathBuf>,
+    /// Input field the user can edit before approving
+    pub edit_field: Option<&'static str>,
+}
+
+/// An option in the inline approval prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalChoice {
+    Yes,
+    YesForFile,
+    Edit,
+    Always,
+    No,
+}
+
+impl ApprovalChoice {
+    pub fn label(self) -> &'static str {
+        match self {
+            ApprovalChoice::Yes => "Yes",
+            ApprovalChoice::YesForFile => "Yes, and don't ask again for this file",
+            ApprovalChoice::Edit => "Edit before applying",
+            ApprovalChoice::Always => "Always",
+            ApprovalChoice::No => "No",
+        }
+    }
+}
+
+impl ApprovalData {
+    /// Options offered for this request, in display order
+    pub fn choices(&self) -> Vec<ApprovalChoice> {
+        if self.files.is_empty() {
+            return vec![ApprovalChoice::Yes, ApprovalChoice::Always, ApprovalChoice::No];
+        }
+        let mut choices = vec![ApprovalChoice::Yes, ApprovalChoice::YesForFile];
+        if self.edit_field.is_some() {
+            choices.push(ApprovalChoice::Edit);
+        }
+        choices.extend([ApprovalChoice::Always, ApprovalChoice::No]);
+        choices
+    }
+
+    /// Currently highlighted option
+    pub fn selected_choice(&self) -> ApprovalChoice {
+        let choices = self.choices();
+        choices
+            .get(self.selected_option)
+            .copied()
+            .unwrap_or(ApprovalChoice::No)
+    }
+}
+
+/// Input field of a file tool the user may rewrite before approving
+fn approval_edit_field(tool_name: &str, tool_input: &Value) -> Option<&'static str> {
+    match tool_name {
+        "write_file" | "write" => Some("content"),
+        "notebook_edit" => tool_input.get("source").map(|_| "source"),
+        "edit_file" | "edit" => match tool_input.get("operation")?.as_str()? {
+            "replace" | "replace_lines" => Some("new_text"),
+            "insert_line" => Some("text"),
+            "regex_replace" => Some("replacement"),
+            "apply_patch" => Some("patch"),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// An approval whose input is being edited in the input box
+#[derive(Debug, Clone)]
+pub struct ApprovalEdit {
+    pub request_id: Uuid,
+    pub field: &'static str,
+    /// What was in the input box before editing started
+    pub draft: String,
 }
 
 /// State for the /approve policy selector menu
@@ -155,6 +250,8 @@ pub struct ToolCallEntry {
     pub description: String,
     pub success: bool,
     pub details: Option<String>,
+    /// Nesting level — calls made by a delegated sub-agent sit at depth 1
+    pub depth: usize,
 }
 
 /// A group of tool calls displayed as a collapsible bullet
@@ -252,6 +349,10 @@ pub struct App {
     // Approval policy state
     pub approval_auto_session: bool,
     pub approval_auto_always: bool,
+    /// Files approved with "Yes for this file" this session
+    pub approved_files: HashSet<PathBuf>,
+    /// Approval whose input is being edited, if any
+    pub approval_edit: Option<ApprovalEdit>,
 
     // Plan mode state
     pub current_plan: Option<PlanDocument>,
@@ -342,6 +443,7 @@ pub struct App {
     session_service: SessionService,
     message_service: MessageService,
     plan_service: PlanService,
+    checkpoint_service: CheckpointService,
 
     // Events
     event_handler: EventHandler,
@@ -359,10 +461,10 @@ impl App {
 
         // Load persisted approval policy from config.toml
         let (approval_auto_session, approval_auto_always) = match crate::config::Config::load() {
-            Ok(cfg) => match cfg.agent.approval_policy.as_str() {
-                "auto-session" => (true, false),
-                "auto-always" => (false, true),
-                _ => (false, false),
+            Ok(cfg) => match cfg.agent.approval_policy {
+                crate::config::ApprovalPolicy::AutoSession => (true, false),
+                crate::config::ApprovalPolicy::AutoAlways => (false, true),
+                crate::config::ApprovalPolicy::Ask => (false, false),
             },
             Err(_) => (false, false),
         };
@@ -390,6 +492,8 @@ impl App {
             help_scroll_offset: 0,
             approval_auto_session,
             approval_auto_always,
+            approved_files: HashSet::new(),
+            approval_edit: None,
             current_plan: None,
             plan_scroll_offset: 0,
             selected_task_index: None,
@@ -437,7 +541,8 @@ impl App {
             sudo_input: String::new(),
             session_service: SessionService::new(context.clone()),
             message_service: MessageService::new(context.clone()),
-            plan_service: PlanService::new(context),
+            plan_service: PlanService::new(context.clone()),
+            checkpoint_service: CheckpointService::new(context),
             agent_service,
             event_handler: EventHandler::new(),
             prompt_analyzer: PromptAnalyzer::new(),
@@ -601,7 +706,7 @@ impl App {
                     )
                 })?;
 
-                Ok(response.approved)
+                Ok(response.decision())
             })
         });
         
@@ -728,18 +833,11 @@ impl App {
                     tool_name, self.active_tool_group.is_some(), self.messages.len());
                 // Show tool call in progress
                 let desc = Self::format_tool_description(&tool_name, &tool_input);
-                let entry = ToolCallEntry { description: desc, success: true, details: None };
-                if let Some(ref mut group) = self.active_tool_group {
-                    group.calls.push(entry);
-                } else {
-                    self.active_tool_group = Some(ToolCallGroup {
-                        calls: vec![entry],
-                        expanded: false,
-                    });
-                }
-                if self.auto_scroll {
-                    self.scroll_offset = 0;
-                }
+                self.start_tool_entry(desc, 0);
+            }
+            TuiEvent::SubagentToolCallStarted { agent, tool_name, tool_input } => {
+                let desc = format!("{}: {}", agent, Self::format_tool_description(&tool_name, &tool_input));
+                self.start_tool_entry(desc, 1);
             }
             TuiEvent::IntermediateText(text) => {
                 tracing::info!("[TUI] IntermediateText: len={} active_group={} streaming={}",
@@ -792,39 +890,11 @@ impl App {
                 // Reset timer so "thinking..." counter restarts after each tool call
                 self.processing_started_at = Some(std::time::Instant::now());
                 let desc = Self::format_tool_description(&tool_name, &tool_input);
-                let details = if summary.is_empty() { None } else { Some(summary) };
-
-                // Update the existing Started entry instead of pushing a duplicate.
-                // Match by description — the Started entry has the same desc but no details.
-                let updated = if let Some(ref mut group) = self.active_tool_group {
-                    if let Some(existing) = group.calls.iter_mut().rev()
-                        .find(|c| c.description == desc && c.details.is_none())
-                    {
-                        existing.success = success;
-                        existing.details = details.clone();
-                        true
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
-
-                // Fallback: push as new entry if no matching Started entry found
-                if !updated {
-                    let entry = ToolCallEntry { description: desc, success, details };
-                    if let Some(ref mut group) = self.active_tool_group {
-                        group.calls.push(entry);
-                    } else {
-                        self.active_tool_group = Some(ToolCallGroup {
-                            calls: vec![entry],
-                            expanded: false,
-                        });
-                    }
-                }
-                if self.auto_scroll {
-                    self.scroll_offset = 0;
-                }
+                self.complete_tool_entry(desc, 0, success, summary);
+            }
+            TuiEvent::SubagentToolCallCompleted { agent, tool_name, tool_input, success, summary } => {
+                let desc = format!("{}: {}", agent, Self::format_tool_description(&tool_name, &tool_input));
+                self.complete_tool_entry(desc, 1, success, summary);
             }
             TuiEvent::CompactionSummary(summary) => {
                 // Agent has summarized history — clear the TUI view for a fresh start.
@@ -1188,6 +1258,82 @@ impl App {
         })
     }
 
+    /// Put a pending approval's editable field into the input box
+    fn start_approval_edit(&mut self, request_id: Uuid, field: &'static str) {
+        let Some(text) = self
+            .messages
+            .iter()
+            .rev()
+            .find_map(|m| m.approval.as_ref())
+            .filter(|a| a.request_id == request_id)
+            .and_then(|a| a.tool_input.get(field))
+            .map(|value| match value {
+                // Notebook sources are arrays of lines
+                Value::Array(lines) => lines.iter().filter_map(|l| l.as_str()).collect(),
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+        else {
+            return;
+        };
+
+        let draft = std::mem::replace(&mut self.input_buffer, text);
+        self.cursor_position = self.input_buffer.len();
+        self.slash_suggestions_active = false;
+        self.approval_edit = Some(ApprovalEdit { request_id, field, draft });
+    }
+
+    /// Approve the pending request with the edited field
+    fn submit_approval_edit(&mut self) {
+        let Some(edit) = self.approval_edit.as_ref() else {
+            return;
+        };
+        let text = self.input_buffer.clone();
+        let approval = self
+            .messages
+            .iter()
+            .rev()
+            .find_map(|m| m.approval.as_ref())
+            .filter(|a| a.request_id == edit.request_id && a.state == ApprovalState::Pending);
+        let Some(approval) = approval else {
+            self.end_approval_edit();
+            self.push_system_message("That approval is no longer pending; the edit was discarded.".to_string());
+            return;
+        };
+
+        let mut input = approval.tool_input.clone();
+        let value = if input.get(edit.field).is_some_and(|v| v.is_array()) {
+            Value::Array(text.split_inclusive('\n').map(|l| Value::String(l.to_string())).collect())
+        } else {
+            Value::String(text)
+        };
+        input[edit.field] = value;
+
+        let request_id = approval.request_id;
+        let response = ToolApprovalResponse {
+            request_id,
+            approved: true,
+            reason: None,
+            edited_input: Some(input),
+        };
+        if let Err(e) = approval.response_tx.send(response.clone()) {
+            tracing::error!("Failed to send approval response back to agent: {:?}", e);
+        }
+        let _ = self.event_sender().send(TuiEvent::ToolApprovalResponse(response));
+        self.messages.retain(|m| {
+            m.approval.as_ref().is_none_or(|a| a.request_id != request_id)
+        });
+        self.end_approval_edit();
+    }
+
+    /// Leave approval editing and give the input box back its draft
+    fn end_approval_edit(&mut self) {
+        if let Some(edit) = self.approval_edit.take() {
+            self.input_buffer = edit.draft;
+            self.cursor_position = self.input_buffer.len();
+        }
+    }
+
     pub fn has_pending_plan_approval(&self) -> bool {
         self.messages.iter().rev().any(|msg| {
             msg.plan_approval
@@ -1269,12 +1415,12 @@ impl App {
                 }
 
                 // Persist to config.toml
-                let policy_str = match selected {
-                    0 => "ask",
-                    1 => "auto-session",
-                    _ => "auto-always",
+                let policy = match selected {
+                    0 => crate::config::ApprovalPolicy::Ask,
+                    1 => crate::config::ApprovalPolicy::AutoSession,
+                    _ => crate::config::ApprovalPolicy::AutoAlways,
                 };
-                if let Err(e) = crate::config::Config::write_key("agent", "approval_policy", policy_str) {
+                if let Err(e) = crate::config::Config::write_key("agent", "approval_policy", policy.as_str()) {
                     tracing::warn!("Failed to persist approval policy: {}", e);
                 }
 
@@ -1294,9 +1440,21 @@ impl App {
             return Ok(());
         }
 
+        // While editing a tool's input, the input box works as usual except
+        // that Enter approves with the edited text and Esc goes back
+        if self.approval_edit.is_some() {
+            if keys::is_submit(&event) {
+                self.submit_approval_edit();
+                return Ok(());
+            } else if keys::is_cancel(&event) {
+                self.end_approval_edit();
+                return Ok(());
+            }
+        }
+
         // Intercept keys when an inline approval is pending
-        // Options: Yes(0), Always(1), No(2)
-        if self.has_pending_approval() {
+        // Options come from ApprovalData::choices()
+        if self.has_pending_approval() && self.approval_edit.is_none() {
             if keys::is_left(&event) || keys::is_up(&event) {
                 // Navigate options left
                 if let Some(approval) = self
@@ -1318,46 +1476,61 @@ impl App {
                     .find_map(|m| m.approval.as_mut())
                     .filter(|a| a.state == ApprovalState::Pending)
                 {
-                    approval.selected_option = (approval.selected_option + 1).min(2);
+                    let last = approval.choices().len() - 1;
+                    approval.selected_option = (approval.selected_option + 1).min(last);
                 }
                 return Ok(());
             } else if keys::is_enter(&event) || keys::is_submit(&event) {
-                // Confirm: Yes(0)=approve once, Always(1)=approve always, No(2)=deny
-                let approval_data: Option<(Uuid, usize, mpsc::UnboundedSender<ToolApprovalResponse>)> = self
+                // Confirm the highlighted option
+                let approval_data: Option<(Uuid, ApprovalChoice, mpsc::UnboundedSender<ToolApprovalResponse>, Vec<PathBuf>, Option<&'static str>)> = self
                     .messages
                     .iter()
                     .rev()
                     .find_map(|m| m.approval.as_ref())
                     .filter(|a| a.state == ApprovalState::Pending)
-                    .map(|a| (a.request_id, a.selected_option, a.response_tx.clone()));
+                    .map(|a| (a.request_id, a.selected_choice(), a.response_tx.clone(), a.files.clone(), a.edit_field));
 
-                if let Some((request_id, selected, response_tx)) = approval_data {
-                    if selected == 2 {
+                if let Some((request_id, selected, response_tx, files, edit_field)) = approval_data {
+                    if selected == ApprovalChoice::Edit {
+                        // Stays pending until the edit is applied or abandoned
+                        if let Some(field) = edit_field {
+                            self.start_approval_edit(request_id, field);
+                        }
+                        return Ok(());
+                    }
+                    if selected == ApprovalChoice::No {
                         // "No" — deny
                         let response = ToolApprovalResponse {
                             request_id,
                             approved: false,
                             reason: Some("User denied permission".to_string()),
+                            edited_input: None,
                         };
                         if let Err(e) = response_tx.send(response.clone()) {
                             tracing::error!("Failed to send denial response back to agent: {:?}", e);
                         }
                         let _ = self.event_sender().send(TuiEvent::ToolApprovalResponse(response));
                     } else {
-                        // "Yes" (0) or "Always" (1)
-                        let option = if selected == 1 {
-                            ApprovalOption::AllowAlways
-                        } else {
-                            ApprovalOption::AllowOnce
-                        };
-                        if matches!(option, ApprovalOption::AllowAlways) {
+                        // "Yes", "Yes for this file" or "Always"
+                        if selected == ApprovalChoice::Always {
                             self.approval_auto_session = true;
                             self.push_system_message("Auto-approve enabled for this session. Use /approve to reset.".to_string());
+                        } else if selected == ApprovalChoice::YesForFile {
+                            let names: Vec<String> = files
+                                .iter()
+                                .map(|f| f.strip_prefix(&self.working_directory).unwrap_or(f).display().to_string())
+                                .collect();
+                            self.approved_files.extend(files);
+                            self.push_system_message(format!(
+                                "Changes to {} will be approved for the rest of this session.",
+                                names.join(", ")
+                            ));
                         }
                         let response = ToolApprovalResponse {
                             request_id,
                             approved: true,
                             reason: None,
+                            edited_input: None,
                         };
                         if let Err(e) = response_tx.send(response.clone()) {
                             tracing::error!("Failed to send approval response back to agent: {:?}", e);
@@ -1385,6 +1558,7 @@ impl App {
                         request_id,
                         approved: false,
                         reason: Some("User denied permission".to_string()),
+                        edited_input: None,
                     };
                     if let Err(e) = response_tx.send(response.clone()) {
                         tracing::error!("Failed to send denial response back to agent: {:?}", e);
@@ -1630,6 +1804,7 @@ impl App {
                                     request_id: approval.request_id,
                                     approved: false,
                                     reason: Some("Operation cancelled".to_string()),
+                                    edited_input: None,
                                 });
                                 approval.state = ApprovalState::Denied("Operation cancelled".to_string());
                             }
@@ -1723,7 +1898,7 @@ impl App {
         } else if event.code == KeyCode::Backspace && event.modifiers.contains(KeyModifiers::ALT) {
             // Alt+Backspace — delete last word
             self.delete_last_word();
-        } else if keys::is_up(&event) && !self.slash_suggestions_active && !self.input_history.is_empty() {
+        } else if keys::is_up(&event) && !self.slash_suggestions_active && !self.input_history.is_empty() && self.approval_edit.is_none() {
             // Arrow Up — browse input history (older)
             match self.input_history_index {
                 None => {
@@ -2039,6 +2214,8 @@ impl App {
         self.mode = AppMode::Chat;
         self.approval_auto_session = false;
         self.approval_auto_always = false;
+        self.approved_files.clear();
+        self.approval_edit = None;
 
         // Sync shared session ID for channels (Telegram, WhatsApp)
         *self.shared_session_id.lock().await = Some(session.id);
@@ -2079,6 +2256,8 @@ impl App {
         self.scroll_offset = 0;
         self.approval_auto_session = false;
         self.approval_auto_always = false;
+        self.approved_files.clear();
+        self.approval_edit = None;
 
         // Sync shared session ID for channels (Telegram, WhatsApp)
         *self.shared_session_id.lock().await = Some(session.id);
@@ -2357,6 +2536,15 @@ impl App {
                 let _ = self.open_directory_picker().await;
                 true
             }
+            "/undo" | "/checkpoints" | "/restore" => {
+                let arg = input.split_whitespace().nth(1);
+                self.handle_checkpoint_command(cmd, arg).await;
+                true
+            }
+            "/memory" => {
+                self.handle_memory_command().await;
+                true
+            }
             _ if input.starts_with('/') => {
                 // Check user-defined commands
                 if let Some(user_cmd) = self.user_commands.iter().find(|c| c.name == cmd) {
@@ -2384,6 +2572,165 @@ impl App {
         }
     }
 
+    /// `/undo`, `/checkpoints` and `/restore <id>`
+    async fn handle_checkpoint_command(&mut self, cmd: &str, arg: Option<&str>) {
+        let Some(session_id) = self.current_session.as_ref().map(|s| s.id) else {
+            self.push_system_message("No active session.".to_string());
+            return;
+        };
+        if cmd != "/checkpoints" && self.is_processing {
+            self.push_system_message(
+                "Wait for the agent to finish before restoring files.".to_string(),
+            );
+            return;
+        }
+
+        let message = match (cmd, arg) {
+            ("/checkpoints", _) => match self.checkpoint_service.list(session_id).await {
+                Ok(checkpoints) if checkpoints.is_empty() => {
+                    "No checkpoints in this session.".to_string()
+                }
+                Ok(checkpoints) => {
+                    let mut lines = vec!["Checkpoints (newest first):".to_string()];
+                    for c in &checkpoints {
+                        lines.push(format!(
+                            "  {}  {}  {} file{}  {}",
+                            &c.id.to_string()[..8],
+                            c.created_at
+                                .with_timezone(&chrono::Local)
+                                .format("%H:%M:%S"),
+                            c.file_count,
+                            if c.file_count == 1 { "" } else { "s" },
+                            c.label
+                        ));
+                    }
+                    lines.push("Use /restore <id> to roll back to before a turn.".to_string());
+                    lines.join("\n")
+                }
+                Err(e) => format!("Failed to list checkpoints: {}", e),
+            },
+            ("/undo", _) => match self.checkpoint_service.undo(session_id).await {
+                Ok(Some(summary)) => format!("Undid last turn: restored {} file(s).", summary.files),
+                Ok(None) => "Nothing to undo.".to_string(),
+                Err(e) => format!("Undo failed: {}", e),
+            },
+            (_, Some(id)) => match self.checkpoint_service.restore(session_id, id).await {
+                Ok(summary) => format!(
+                    "Restored {} file(s), rolling back {} turn(s).",
+                    summary.files, summary.checkpoints
+                ),
+                Err(e) => format!("Restore failed: {}", e),
+            },
+            (_, None) => "Usage: /restore <checkpoint id> (see /checkpoints)".to_string(),
+        };
+        self.push_system_message(message);
+    }
+
+    /// `/memory` — document counts, embedding backlog and file watcher activity
+    async fn handle_memory_command(&mut self) {
+        let status = match crate::memory::get_store() {
+            Ok(store) => crate::memory::index_status(store).await,
+            Err(e) => Err(e),
+        };
+        let message = match status {
+            Ok(status) => {
+                let mut lines = vec!["Memory index:".to_string()];
+                for (label, count) in &status.collections {
+                    lines.push(format!("  {:<18}{}", label, count));
+                }
+                lines.push(if status.engine_ready {
+                    format!(
+                        "  Embeddings: {}, {} document(s) waiting",
+                        status.backend, status.pending_embeddings
+                    )
+                } else {
+                    format!(
+                        "  Embeddings: {} not loaded, keyword search only ({} waiting)",
+                        status.backend, status.pending_embeddings
+                    )
+                });
+                let watcher = &status.watcher;
+                if watcher.watching.is_empty() {
+                    lines.push("  Watcher: not running".to_string());
+                } else {
+                    let dirs: Vec<String> = watcher
+                        .watching
+                        .iter()
+                        .map(|d| d.display().to_string())
+                        .collect();
+                    lines.push(format!("  Watcher: {}", dirs.join(", ")));
+                    let last = watcher
+                        .last_change
+                        .map(|t| format!(", last at {}", t.format("%H:%M:%S")))
+                        .unwrap_or_default();
+                    lines.push(format!(
+                        "    {} re-indexed, {} removed{}",
+                        watcher.reindexed, watcher.removed, last
+                    ));
+                }
+                if let Some(ref e) = watcher.last_error {
+                    lines.push(format!("    Last error: {}", e));
+                }
+                lines.join("\n")
+            }
+            Err(e) => format!("Memory index unavailable: {}", e),
+        };
+        self.push_system_message(message);
+    }
+
+    /// Add an in-progress entry to the active tool group
+    fn start_tool_entry(&mut self, description: String, depth: usize) {
+        let entry = ToolCallEntry { description, success: true, details: None, depth };
+        if let Some(ref mut group) = self.active_tool_group {
+            group.calls.push(entry);
+        } else {
+            self.active_tool_group = Some(ToolCallGroup {
+                calls: vec![entry],
+                expanded: false,
+            });
+        }
+        if self.auto_scroll {
+            self.scroll_offset = 0;
+        }
+    }
+
+    /// Mark the matching in-progress entry as finished
+    fn complete_tool_entry(&mut self, description: String, depth: usize, success: bool, summary: String) {
+        let details = if summary.is_empty() { None } else { Some(summary) };
+
+        // Update the existing Started entry instead of pushing a duplicate.
+        // Match by description — the Started entry has the same desc but no details.
+        let updated = if let Some(ref mut group) = self.active_tool_group {
+            if let Some(existing) = group.calls.iter_mut().rev()
+                .find(|c| c.description == description && c.depth == depth && c.details.is_none())
+            {
+                existing.success = success;
+                existing.details = details.clone();
+                true
+            } else {
+                false
+            }
+        } else {
+            false
+        };
+
+        // Fallback: push as new entry if no matching Started entry found
+        if !updated {
+            let entry = ToolCallEntry { description, success, details, depth };
+            if let Some(ref mut group) = self.active_tool_group {
+                group.calls.push(entry);
+            } else {
+                self.active_tool_group = Some(ToolCallGroup {
+                    calls: vec![entry],
+                    expanded: false,
+                });
+            }
+        }
+        if self.auto_scroll {
+            self.scroll_offset = 0;
+        }
+    }
+
     /// Format a human-readable description of a tool call from its name and input
     pub fn format_tool_description(tool_name: &str, tool_input: &Value) -> String {
         match tool_name {
@@ -2406,16 +2753,78 @@ impl App {
             }
             "edit_file" | "edit" => {
                 let path = tool_input.get("path").and_then(|v| v.as_str()).unwrap_or("?");
-                format!("Edit {}", path)
+                let patch = tool_input.get("patch").and_then(|v| v.as_str());
+                match patch.map(crate::brain::tools::patch::parse) {
+                    Some(Ok(files)) => {
+                        let names: Vec<&str> = files
+                            .iter()
+                            .map(|f| f.display_path())
+                            .filter(|p| *p != "?")
+                            .collect();
+                        match names.as_slice() {
+                            [] => format!("Patch {}", path),
+                            [one] => format!("Patch {}", one),
+                            [first, rest @ ..] => {
+                                format!("Patch {} (+{} more files)", first, rest.len())
+                            }
+                        }
+                    }
+                    Some(Err(_)) => format!("Patch {} (malformed)", path),
+                    None => format!("Edit {}", path),
+                }
             }
             "ls" => {
                 let path = tool_input.get("path").and_then(|v| v.as_str()).unwrap_or(".");
                 format!("ls {}", path)
             }
+            "git" => {
+                let op = tool_input.get("operation").and_then(|v| v.as_str()).unwrap_or("?");
+                let detail = match op {
+                    "commit" => tool_input.get("message").and_then(|v| v.as_str())
+                        .and_then(|m| m.lines().next()).map(|m| m.to_string()),
+                    "stage" | "unstage" | "diff" | "log" => tool_input.get("paths").and_then(|v| v.as_array())
+                        .map(|p| p.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(" "))
+                        .filter(|p| !p.is_empty()),
+                    "blame" => tool_input.get("path").and_then(|v| v.as_str()).map(|p| p.to_string()),
+                    "branch" | "stash" => tool_input.get("action").and_then(|v| v.as_str()).map(|a| {
+                        let name = tool_input.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                        format!("{} {}", a, name).trim_end().to_string()
+                    }),
+                    _ => None,
+                };
+                match detail {
+                    Some(detail) => format!("git {}: {}", op, detail),
+                    None => format!("git {}", op),
+                }
+            }
             "glob" => {
                 let pattern = tool_input.get("pattern").and_then(|v| v.as_str()).unwrap_or("?");
                 format!("Glob {}", pattern)
             }
+            "memory_write" => {
+                let op = tool_input.get("operation").and_then(|v| v.as_str()).unwrap_or("?");
+                let detail = match op {
+                    "add" => tool_input.get("content").and_then(|v| v.as_str()),
+                    "update" | "forget" => tool_input.get("id").and_then(|v| v.as_str()),
+                    _ => None,
+                };
+                match detail {
+                    Some(detail) => {
+                        let short: String = detail.chars().take(60).collect();
+                        format!("Memory {}: {}", op, short)
+                    }
+                    None => format!("Memory {}", op),
+                }
+            }
+            "code_search" => {
+                let query = tool_input.get("query").and_then(|v| v.as_str()).unwrap_or("?");
+                let path = tool_input.get("path").and_then(|v| v.as_str()).unwrap_or("");
+                if path.is_empty() {
+                    format!("Code search: {}", query)
+                } else {
+                    format!("Code search: {} in {}", query, path)
+                }
+            }
             "grep" => {
                 let pattern = tool_input.get("pattern").and_then(|v| v.as_str()).unwrap_or("?");
                 let path = tool_input.get("path").and_then(|v| v.as_str()).unwrap_or("");
@@ -2463,6 +2872,15 @@ impl App {
                 format!("Plan: {}", op)
             }
             "session_context" => "Session context".to_string(),
+            "delegate" => {
+                let task = tool_input.get("task").and_then(|v| v.as_str()).unwrap_or("?");
+                let short: String = task.chars().take(60).collect();
+                if task.chars().count() > 60 {
+                    format!("Delegate: {}...", short)
+                } else {
+                    format!("Delegate: {}", short)
+                }
+            }
             other => other.to_string(),
         }
     }
@@ -2536,7 +2954,7 @@ impl App {
                             let success = entry["s"].as_bool().unwrap_or(true);
                             let output = entry["o"].as_str().map(|s| s.to_string())
                                 .filter(|s| !s.is_empty());
-                            ToolCallEntry { description: desc, success, details: output }
+                            ToolCallEntry { description: desc, success, details: output, depth: 0 }
                         })
                         .collect()
                 } else {
@@ -2547,6 +2965,7 @@ impl App {
                             description: desc.to_string(),
                             success: true,
                             details: None,
+                            depth: 0,
                         })
                         .collect()
                 };
@@ -2741,6 +3160,7 @@ impl App {
                     request_id: approval.request_id,
                     approved: false,
                     reason: Some("Superseded".to_string()),
+                    edited_input: None,
                 });
                 approval.state = ApprovalState::Denied("Superseded".to_string());
             }
@@ -2889,10 +3309,12 @@ impl App {
                     request_id: approval.request_id,
                     approved: false,
                     reason: Some("Agent completed without resolution".to_string()),
+                    edited_input: None,
                 });
                 approval.state = ApprovalState::Denied("Agent completed without resolution".to_string());
             }
         }
+        self.end_approval_edit();
 
         // Finalize active tool group into a display message
         if let Some(group) = self.active_tool_group.take() {
@@ -3513,10 +3935,12 @@ impl App {
                     request_id: approval.request_id,
                     approved: false,
                     reason: Some("Error occurred".to_string()),
+                    edited_input: None,
                 });
                 approval.state = ApprovalState::Denied("Error occurred".to_string());
             }
         }
+        self.end_approval_edit();
         // Finalize any active tool group
         if let Some(group) = self.active_tool_group.take() {
             let count = group.calls.len();
@@ -3586,17 +4010,32 @@ impl App {
                     request_id: approval.request_id,
                     approved: false,
                     reason: Some("Superseded by new request".to_string()),
+                    edited_input: None,
                 });
                 approval.state = ApprovalState::Denied("Superseded by new request".to_string());
             }
         }
+        self.end_approval_edit();
+
+        // Files this call writes, and what it would change in them
+        let diff = super::diff::preview(&request.tool_name, &request.tool_input, &self.working_directory);
+        let mut files: Vec<PathBuf> = diff
+            .iter()
+            .flatten()
+            .map(|d| d.path.clone())
+            .collect();
+        files.dedup();
+        let files_approved = !files.is_empty() && files.iter().all(|f| self.approved_files.contains(f));
+        let edit_field = approval_edit_field(&request.tool_name, &request.tool_input)
+            .filter(|_| !files.is_empty());
 
         // Auto-approve silently if policy allows
-        if self.approval_auto_always || self.approval_auto_session {
+        if self.approval_auto_always || self.approval_auto_session || files_approved {
             let response = ToolApprovalResponse {
                 request_id: request.request_id,
                 approved: true,
                 reason: None,
+                edited_input: None,
             };
             let _ = request.response_tx.send(response.clone());
             let _ = self.event_sender().send(TuiEvent::ToolApprovalResponse(response));
@@ -3641,6 +4080,9 @@ impl App {
                 state: ApprovalState::Pending,
                 selected_option: 0,
                 show_details: false,
+                edit_field,
+                diff,
+                files,
             }),
             approve_menu: None,
             details: None,
@@ -0,0 +1,364 @@
+//! Change Previews
+//!
+//! Computes line diffs of what a file-modifying tool call (`edit_file`,
+//! `write_file`, `notebook_edit`) would change, so the approval prompt can
+//! show the actual change instead of raw JSON.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Above this many old × new lines, skip the LCS and show a plain replace
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// One line of a rendered diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    /// Unchanged line (1-based number in the new file)
+    Context { line: usize, text: String },
+    /// Line only in the old file (1-based number in the old file)
+    Removed { line: usize, text: String },
+    /// Line only in the new file (1-based number in the new file)
+    Added { line: usize, text: String },
+    /// Unchanged lines left out between changes
+    Skipped(usize),
+}
+
+/// Diff of one file (or notebook cell)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    /// Path shown in the header, relative to the working directory
+    pub label: String,
+    /// File the change applies to
+    pub path: PathBuf,
+    /// Syntax used for highlighting (extension or language name)
+    pub language: String,
+    pub lines: Vec<DiffLine>,
+}
+
+impl FileDiff {
+    /// Number of added and removed lines
+    pub fn stats(&self) -> (usize, usize) {
+        self.lines.iter().fold((0, 0), |(add, del), l| match l {
+            DiffLine::Added { .. } => (add + 1, del),
+            DiffLine::Removed { .. } => (add, del + 1),
+            _ => (add, del),
+        })
+    }
+}
+
+/// Diff what a tool call would change. `None` for tools that don't modify
+/// files or when the change can't be computed (the tool reports why when it
+/// runs).
+pub fn preview(tool_name: &str, input: &Value, working_dir: &Path) -> Option<Vec<FileDiff>> {
+    let diffs = match tool_name {
+        "edit_file" | "edit" => crate::brain::tools::edit::preview(input, working_dir)
+            .ok()?
+            .into_iter()
+            .map(|change| {
+                file_diff(
+                    working_dir,
+                    change.path,
+                    change.before.as_deref().unwrap_or_default(),
+                    change.after.as_deref().unwrap_or_default(),
+                )
+            })
+            .collect(),
+        "write_file" | "write" => {
+            let path = resolve(input.get("path")?.as_str()?, working_dir);
+            let new = input.get("content")?.as_str()?;
+            let old = std::fs::read_to_string(&path).unwrap_or_default();
+            vec![file_diff(working_dir, path, &old, new)]
+        }
+        "notebook_edit" => vec![notebook_diff(input, working_dir)?],
+        _ => return None,
+    };
+    Some(diffs)
+}
+
+fn resolve(path: &str, working_dir: &Path) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        working_dir.join(path)
+    }
+}
+
+fn file_diff(working_dir: &Path, path: PathBuf, old: &str, new: &str) -> FileDiff {
+    FileDiff {
+        label: path
+            .strip_prefix(working_dir)
+            .unwrap_or(&path)
+            .display()
+            .to_string(),
+        language: path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("txt")
+            .to_string(),
+        lines: line_diff(old, new),
+        path,
+    }
+}
+
+/// Diff of the cell a `notebook_edit` call touches
+fn notebook_diff(input: &Value, working_dir: &Path) -> Option<FileDiff> {
+    let path = resolve(input.get("path")?.as_str()?, working_dir);
+    let notebook: Value = serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+    let cells = notebook.get("cells")?.as_array()?;
+    let new_source = input.get("source").map(cell_source).unwrap_or_default();
+
+    let (index, cell_type, old, new) = match input.get("operation")?.as_str()? {
+        "add_cell" => {
+            let index = input
+                .get("position")
+                .and_then(|p| p.as_u64())
+                .map_or(cells.len(), |p| p as usize);
+            let cell_type = input.get("cell_type").and_then(|t| t.as_str())?;
+            (index, cell_type, String::new(), new_source)
+        }
+        "edit_cell" | "delete_cell" => {
+            let index = input.get("index")?.as_u64()? as usize;
+            let cell = cells.get(index)?;
+            let old = cell.get("source").map(cell_source).unwrap_or_default();
+            let new = if input.get("operation")?.as_str()? == "edit_cell" {
+                new_source
+            } else {
+                String::new()
+            };
+            (index, cell.get("cell_type")?.as_str()?, old, new)
+        }
+        _ => return None,
+    };
+
+    let mut diff = file_diff(working_dir, path, &old, &new);
+    diff.label = format!("{} [cell {}]", diff.label, index);
+    diff.language = if cell_type == "code" {
+        notebook
+            .pointer("/metadata/kernelspec/language")
+            .and_then(|l| l.as_str())
+            .unwrap_or("python")
+            .to_string()
+    } else {
+        "markdown".to_string()
+    };
+    Some(diff)
+}
+
+/// Notebook cell source is a string or a list of lines
+fn cell_source(source: &Value) -> String {
+    match source {
+        Value::String(s) => s.clone(),
+        Value::Array(lines) => lines.iter().filter_map(|l| l.as_str()).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Line diff of `old` → `new` with `CONTEXT_LINES` of context around each
+/// change. Empty when nothing changed.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Only the middle between the common prefix and suffix needs an LCS
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<Op> = vec![Op::Equal; prefix];
+    if mid_a.len().saturating_mul(mid_b.len()) <= MAX_LCS_CELLS {
+        ops.extend(lcs_ops(mid_a, mid_b));
+    } else {
+        ops.extend(std::iter::repeat_n(Op::Remove, mid_a.len()));
+        ops.extend(std::iter::repeat_n(Op::Add, mid_b.len()));
+    }
+    ops.extend(std::iter::repeat_n(Op::Equal, suffix));
+
+    // Keep unchanged lines only near a change
+    let mut keep = vec![false; ops.len()];
+    for (i, op) in ops.iter().enumerate() {
+        if *op != Op::Equal {
+            let start = i.saturating_sub(CONTEXT_LINES);
+            let end = (i + CONTEXT_LINES + 1).min(ops.len());
+            keep[start..end].iter_mut().for_each(|k| *k = true);
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut old_no, mut new_no, mut skipped) = (0, 0, 0);
+    for (op, keep) in ops.iter().zip(keep) {
+        if !keep {
+            skipped += 1;
+            old_no += 1;
+            new_no += 1;
+            continue;
+        }
+        if skipped > 0 {
+            lines.push(DiffLine::Skipped(skipped));
+            skipped = 0;
+        }
+        match op {
+            Op::Equal => {
+                lines.push(DiffLine::Context {
+                    line: new_no + 1,
+                    text: b[new_no].to_string(),
+                });
+                old_no += 1;
+                new_no += 1;
+            }
+            Op::Remove => {
+                lines.push(DiffLine::Removed {
+                    line: old_no + 1,
+                    text: a[old_no].to_string(),
+                });
+                old_no += 1;
+            }
+            Op::Add => {
+                lines.push(DiffLine::Added {
+                    line: new_no + 1,
+                    text: b[new_no].to_string(),
+                });
+                new_no += 1;
+            }
+        }
+    }
+    // Trailing unchanged lines aren't worth a marker unless there was a change
+    if skipped > 0 && !lines.is_empty() {
+        lines.push(DiffLine::Skipped(skipped));
+    }
+    lines
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Remove,
+    Add,
+}
+
+/// Edit script from a longest-common-subsequence table
+fn lcs_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let width = b.len() + 1;
+    // table[i * width + j] = LCS length of a[i..] and b[j..]
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            table[i * width + j] = if a[i] == b[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            ops.push(Op::Equal);
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push(Op::Remove);
+            i += 1;
+        } else {
+            ops.push(Op::Add);
+            j += 1;
+        }
+    }
+    ops.extend(std::iter::repeat_n(Op::Remove, a.len() - i));
+    ops.extend(std::iter::repeat_n(Op::Add, b.len() - j));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_line_diff_keeps_context_and_skips_the_rest() {
+        let old: String = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        let new = old.replace("line 10\n", "line ten\n");
+
+        let diff = line_diff(&old, &new);
+        assert_eq!(diff.first(), Some(&DiffLine::Skipped(6)));
+        assert!(diff.contains(&DiffLine::Removed {
+            line: 10,
+            text: "line 10".to_string()
+        }));
+        assert!(diff.contains(&DiffLine::Added {
+            line: 10,
+            text: "line ten".to_string()
+        }));
+        assert_eq!(diff.last(), Some(&DiffLine::Skipped(7)));
+        assert!(line_diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_preview_write_and_edit() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let write = preview(
+            "write_file",
+            &json!({"path": "new.py", "content": "print(1)\n"}),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(write[0].label, "new.py");
+        assert_eq!(write[0].language, "py");
+        assert_eq!(write[0].stats(), (1, 0));
+
+        let edit = preview(
+            "edit_file",
+            &json!({
+                "path": "main.rs",
+                "operation": "replace",
+                "old_text": "fn main() {}",
+                "new_text": "fn main() {\n    run();\n}"
+            }),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(edit[0].stats(), (3, 1));
+
+        assert!(preview("bash", &json!({"command": "ls"}), dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_preview_notebook_cell() {
+        let dir = TempDir::new().unwrap();
+        let notebook = json!({
+            "cells": [{"cell_type": "code", "source": ["x = 1\n", "print(x)"], "metadata": {}}],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        std::fs::write(dir.path().join("a.ipynb"), notebook.to_string()).unwrap();
+
+        let diff = preview(
+            "notebook_edit",
+            &json!({
+                "path": "a.ipynb",
+                "operation": "edit_cell",
+                "index": 0,
+                "source": ["x = 2\n", "print(x)"]
+            }),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(diff[0].label, "a.ipynb [cell 0]");
+        assert_eq!(diff[0].language, "python");
+        assert_eq!(diff[0].stats(), (1, 1));
+    }
+}
@@ -71,6 +71,18 @@ pub enum TuiEvent {
     /// A tool call has completed
     ToolCallCompleted { tool_name: String, tool_input: Value, success: bool, summary: String },
 
+    /// A delegated sub-agent started a tool call (shown nested under `delegate`)
+    SubagentToolCallStarted { agent: String, tool_name: String, tool_input: Value },
+
+    /// A delegated sub-agent finished a tool call
+    SubagentToolCallCompleted {
+        agent: String,
+        tool_name: String,
+        tool_input: Value,
+        success: bool,
+        summary: String,
+    },
+
     /// Intermediate text the agent sent between tool call batches
     IntermediateText(String),
 
@@ -165,6 +177,22 @@ pub struct ToolApprovalResponse {
 
     /// Optional reason for denial
     pub reason: Option<String>,
+
+    /// Tool input the user edited before approving
+    pub edited_input: Option<Value>,
+}
+
+impl ToolApprovalResponse {
+    /// The decision to hand back to the agent
+    pub fn decision(self) -> crate::brain::agent::ApprovalDecision {
+        use crate::brain::agent::ApprovalDecision;
+
+        match (self.approved, self.edited_input) {
+            (false, _) => ApprovalDecision::Denied,
+            (true, Some(input)) => ApprovalDecision::Edited(input),
+            (true, None) => ApprovalDecision::Approved,
+        }
+    }
 }
 
 /// Application mode
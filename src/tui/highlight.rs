@@ -132,6 +132,40 @@ pub fn highlight_code(code: &str, language: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Highlights a file one line at a time, keeping parser state between
+/// lines (used for diff previews, where lines arrive out of context)
+pub struct LineHighlighter {
+    highlighter: HighlightLines<'static>,
+}
+
+impl LineHighlighter {
+    /// Highlighter for a language name or file extension, `None` if unknown
+    pub fn new(language: &str) -> Option<Self> {
+        let syntax = find_syntax(language)?;
+        Some(Self {
+            highlighter: HighlightLines::new(syntax, get_theme()),
+        })
+    }
+
+    /// Styled spans for one line (without its line ending)
+    pub fn highlight(&mut self, line: &str) -> Vec<Span<'static>> {
+        let line = format!("{}\n", line);
+        match self.highlighter.highlight_line(&line, &SYNTAX_SET) {
+            Ok(ranges) => ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    Span::styled(
+                        text.trim_end_matches('\n').to_string(),
+                        syntect_style_to_ratatui(style),
+                    )
+                })
+                .filter(|span| !span.content.is_empty())
+                .collect(),
+            Err(_) => vec![Span::raw(line.trim_end().to_string())],
+        }
+    }
+}
+
 /// Get a list of all supported languages
 pub fn supported_languages() -> Vec<String> {
     SYNTAX_SET
@@ -197,6 +231,16 @@ mod tests {
         assert!(!is_language_supported("not_a_real_language"));
     }
 
+    #[test]
+    fn test_line_highlighter() {
+        assert!(LineHighlighter::new("not_a_real_language").is_none());
+        let mut highlighter = LineHighlighter::new("rs").unwrap();
+        let spans = highlighter.highlight("let x = 1;");
+        let text: String = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "let x = 1;");
+        assert!(spans.len() > 1);
+    }
+
     #[test]
     fn test_empty_code() {
         let code = "";
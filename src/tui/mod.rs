@@ -3,6 +3,7 @@
 //! Provides an interactive terminal interface for the AI orchestration agent using Ratatui.
 
 pub mod app;
+pub mod diff;
 pub mod error;
 pub mod events;
 pub mod onboarding;
@@ -1827,6 +1827,8 @@ Respond with EXACTLY six sections using these delimiters. No extra text before t
                 "none".to_string()
             },
             enabled: false,
+            token: config.gateway.token.clone(),
+            auto_approve_tools: config.gateway.auto_approve_tools,
         };
 
         // Channels config — tokens from wizard setup sub-steps
@@ -2108,7 +2110,7 @@ Respond with EXACTLY six sections using these delimiters. No extra text before t
 
         // Install daemon if requested
         if self.install_daemon
-            && let Err(e) = install_daemon_service() {
+            && let Err(e) = crate::daemon::install_service() {
                 tracing::warn!("Failed to install daemon: {}", e);
                 // Non-fatal — don't block onboarding completion
             }
@@ -2159,127 +2161,6 @@ pub fn is_first_time() -> bool {
     !has_env_key
 }
 
-/// Install the appropriate daemon service for the current platform
-fn install_daemon_service() -> Result<(), String> {
-    #[cfg(target_os = "linux")]
-    {
-        install_systemd_service()
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        install_launchagent()
-    }
-
-    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-    {
-        Err("Daemon installation not supported on this platform".to_string())
-    }
-}
-
-#[cfg(target_os = "linux")]
-fn install_systemd_service() -> Result<(), String> {
-    let service_dir = dirs::config_dir()
-        .ok_or("Cannot determine config dir")?
-        .parent()
-        .ok_or("Cannot determine parent of config dir")?
-        .join(".config")
-        .join("systemd")
-        .join("user");
-
-    // Try the standard XDG path first
-    let service_dir = if service_dir.exists() {
-        service_dir
-    } else {
-        dirs::home_dir()
-            .ok_or("Cannot determine home dir")?
-            .join(".config")
-            .join("systemd")
-            .join("user")
-    };
-
-    std::fs::create_dir_all(&service_dir)
-        .map_err(|e| format!("Failed to create systemd dir: {}", e))?;
-
-    let exe_path = std::env::current_exe()
-        .map_err(|e| format!("Failed to get exe path: {}", e))?;
-
-    let service_content = format!(
-        r#"[Unit]
-Description=OpenCrabs AI Orchestration Agent
-After=network.target
-
-[Service]
-Type=simple
-ExecStart={}
-Restart=on-failure
-RestartSec=5
-
-[Install]
-WantedBy=default.target
-"#,
-        exe_path.display()
-    );
-
-    let service_path = service_dir.join("opencrabs.service");
-    std::fs::write(&service_path, service_content)
-        .map_err(|e| format!("Failed to write service file: {}", e))?;
-
-    // Enable the service
-    std::process::Command::new("systemctl")
-        .args(["--user", "enable", "opencrabs"])
-        .output()
-        .map_err(|e| format!("Failed to enable service: {}", e))?;
-
-    Ok(())
-}
-
-#[cfg(target_os = "macos")]
-fn install_launchagent() -> Result<(), String> {
-    let agents_dir = dirs::home_dir()
-        .ok_or("Cannot determine home dir")?
-        .join("Library")
-        .join("LaunchAgents");
-
-    std::fs::create_dir_all(&agents_dir)
-        .map_err(|e| format!("Failed to create LaunchAgents dir: {}", e))?;
-
-    let exe_path = std::env::current_exe()
-        .map_err(|e| format!("Failed to get exe path: {}", e))?;
-
-    let plist_content = format!(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.opencrabs.agent</string>
-    <key>ProgramArguments</key>
-    <array>
-        <string>{}</string>
-    </array>
-    <key>RunAtLoad</key>
-    <true/>
-    <key>KeepAlive</key>
-    <true/>
-</dict>
-</plist>
-"#,
-        exe_path.display()
-    );
-
-    let plist_path = agents_dir.join("com.opencrabs.agent.plist");
-    std::fs::write(&plist_path, plist_content)
-        .map_err(|e| format!("Failed to write plist: {}", e))?;
-
-    std::process::Command::new("launchctl")
-        .args(["load", &plist_path.to_string_lossy()])
-        .output()
-        .map_err(|e| format!("Failed to load launch agent: {}", e))?;
-
-    Ok(())
-}
-
 /// Fetch models from provider API. No API key needed for most providers.
 /// If api_key is provided, includes it (some endpoints filter by access level).
 /// Returns empty vec on failure (callers fall back to static list).
@@ -270,7 +270,12 @@ fn render_chat(f: &mut Frame, app: &mut App, area: Rect) {
     for msg_idx in 0..app.messages.len() {
         // Render inline approval messages
         if let Some(ref approval) = app.messages[msg_idx].approval {
-            render_inline_approval(&mut lines, approval, content_width);
+            let editing = app
+                .approval_edit
+                .as_ref()
+                .filter(|e| e.request_id == approval.request_id)
+                .map(|e| e.field);
+            render_inline_approval(&mut lines, approval, editing, content_width);
             lines.push(Line::from(""));
             continue;
         }
@@ -775,9 +780,11 @@ fn render_tool_group<'a>(
                     .fg(Color::Red)
                     .add_modifier(Modifier::ITALIC)
             };
+            // Sub-agent calls are indented under their delegate call
+            let indent = "   ".repeat(call.depth);
             lines.push(Line::from(vec![
                 Span::styled(
-                    format!("    {} ", connector),
+                    format!("    {}{} ", indent, connector),
                     Style::default().fg(Color::DarkGray),
                 ),
                 Span::styled(call.description.clone(), style),
@@ -785,7 +792,11 @@ fn render_tool_group<'a>(
 
             // Show tool output details below the description
             if let Some(ref details) = call.details {
-                let continuation = if is_last_call(i) { "   " } else { "│  " };
+                let continuation = if is_last_call(i) {
+                    format!("{}   ", indent)
+                } else {
+                    format!("{}│  ", indent)
+                };
                 let default_detail_style = Style::default().fg(Color::Rgb(90, 90, 90));
                 for detail_line in details.lines().take(30) {
                     // Diff-aware coloring: red for deletions, green for additions
@@ -849,9 +860,10 @@ fn render_tool_group<'a>(
 fn render_inline_approval<'a>(
     lines: &mut Vec<Line<'a>>,
     approval: &super::app::ApprovalData,
+    editing: Option<&str>,
     _content_width: usize,
 ) {
-    use super::app::ApprovalState;
+    use super::app::{ApprovalChoice, ApprovalState};
 
     match &approval.state {
         ApprovalState::Pending => {
@@ -870,6 +882,13 @@ fn render_inline_approval<'a>(
                 ),
             ]));
 
+            // File changes are reviewed by their diff, not their JSON
+            if let Some(ref diffs) = approval.diff {
+                render_diff_preview(lines, diffs, approval.show_details);
+            } else if let Some(patch) = approval.tool_input.get("patch").and_then(|v| v.as_str()) {
+                render_patch_preview(lines, patch);
+            }
+
             // Show params if expanded (V toggle)
             if approval.show_details
                 && let Some(obj) = approval.tool_input.as_object() {
@@ -903,15 +922,34 @@ fn render_inline_approval<'a>(
                     }
                 }
 
+            if let Some(field) = editing {
+                lines.push(Line::from(Span::styled(
+                    format!("  Editing {} in the input box — Enter applies, Esc returns", field),
+                    Style::default().fg(Color::Cyan),
+                )));
+                return;
+            }
+
             // "Do you approve?" + vertical option list with ❯ selector
-            // Order: Yes(0), Always(1), No(2)
             lines.push(Line::from(vec![
                 Span::styled(
                     "  Do you approve?",
                     Style::default().fg(Color::DarkGray),
                 ),
             ]));
-            let options = [("Yes", Color::Green), ("Always", Color::Yellow), ("No", Color::Red)];
+            let options: Vec<(&str, Color)> = approval
+                .choices()
+                .into_iter()
+                .map(|choice| {
+                    let color = match choice {
+                        ApprovalChoice::Yes | ApprovalChoice::YesForFile => Color::Green,
+                        ApprovalChoice::Edit => Color::Cyan,
+                        ApprovalChoice::Always => Color::Yellow,
+                        ApprovalChoice::No => Color::Red,
+                    };
+                    (choice.label(), color)
+                })
+                .collect();
             for (i, (label, color)) in options.iter().enumerate() {
                 if i == approval.selected_option {
                     lines.push(Line::from(vec![
@@ -960,6 +998,117 @@ fn render_inline_approval<'a>(
     }
 }
 
+/// Syntax-highlighted diff of what a file tool would change, shown in the
+/// approval prompt. Long diffs are cut short unless details are expanded.
+fn render_diff_preview(lines: &mut Vec<Line<'_>>, diffs: &[super::diff::FileDiff], expanded: bool) {
+    use super::diff::DiffLine;
+    use super::highlight::LineHighlighter;
+
+    const MAX_PREVIEW_LINES: usize = 60;
+
+    let total: usize = diffs.iter().map(|d| d.lines.len() + 1).sum();
+    let mut shown = 0;
+    for diff in diffs {
+        if !expanded && shown >= MAX_PREVIEW_LINES {
+            break;
+        }
+        let (added, removed) = diff.stats();
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  ── {} ", diff.label),
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("+{}", added), Style::default().fg(Color::Green)),
+            Span::styled(" ", Style::default()),
+            Span::styled(format!("−{}", removed), Style::default().fg(Color::Red)),
+        ]));
+        shown += 1;
+        if diff.lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "    (no changes)",
+                Style::default().fg(Color::DarkGray),
+            )));
+            continue;
+        }
+
+        let mut highlighter = LineHighlighter::new(&diff.language);
+        for line in &diff.lines {
+            if !expanded && shown >= MAX_PREVIEW_LINES {
+                break;
+            }
+            shown += 1;
+            let (number, sign, text, sign_color, background) = match line {
+                DiffLine::Skipped(count) => {
+                    lines.push(Line::from(Span::styled(
+                        format!("    ⋯ {} unchanged lines", count),
+                        Style::default().fg(Color::DarkGray),
+                    )));
+                    continue;
+                }
+                DiffLine::Context { line, text } => (line, ' ', text, Color::DarkGray, None),
+                DiffLine::Removed { line, text } => {
+                    (line, '-', text, Color::Red, Some(Color::Rgb(60, 20, 20)))
+                }
+                DiffLine::Added { line, text } => {
+                    (line, '+', text, Color::Green, Some(Color::Rgb(20, 50, 20)))
+                }
+            };
+
+            let mut spans = vec![
+                Span::styled(format!("  {:>4} ", number), Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!("{} ", sign),
+                    Style::default().fg(sign_color).add_modifier(Modifier::BOLD),
+                ),
+            ];
+            let text = text.replace('\t', "    ");
+            let code = match highlighter.as_mut() {
+                Some(h) => h.highlight(&text),
+                None => vec![Span::styled(text, Style::default().fg(Color::Gray))],
+            };
+            spans.extend(code.into_iter().map(|span| match background {
+                Some(bg) => span.patch_style(Style::default().bg(bg)),
+                None => span,
+            }));
+            lines.push(Line::from(spans));
+        }
+    }
+
+    if !expanded && total > shown {
+        lines.push(Line::from(Span::styled(
+            format!("    ... {} more lines (V to expand)", total - shown),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+}
+
+/// Colored preview of a unified diff in the approval prompt
+fn render_patch_preview(lines: &mut Vec<Line<'_>>, patch: &str) {
+    const MAX_PREVIEW_LINES: usize = 40;
+
+    let total = patch.lines().count();
+    for line in patch.lines().take(MAX_PREVIEW_LINES) {
+        let style = if line.starts_with("+++") || line.starts_with("---") {
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+        } else if line.starts_with("@@") {
+            Style::default().fg(Color::Cyan)
+        } else if line.starts_with('+') {
+            Style::default().fg(Color::Green)
+        } else if line.starts_with('-') {
+            Style::default().fg(Color::Red)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        lines.push(Line::from(Span::styled(format!("    {}", line), style)));
+    }
+    if total > MAX_PREVIEW_LINES {
+        lines.push(Line::from(Span::styled(
+            format!("    ... {} more lines", total - MAX_PREVIEW_LINES),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+}
+
 /// Render an inline plan approval selector (Approve / Reject / Request Changes / View Plan)
 fn render_inline_plan_approval<'a>(
     lines: &mut Vec<Line<'a>>,
@@ -1460,6 +1609,10 @@ fn render_help(f: &mut Frame, app: &App, area: Rect) {
         kv("/compact", "Compact context now", blue),
         kv("/rebuild", "Build & restart from source", blue),
         kv("/cd", "Change working directory", blue),
+        kv("/undo", "Undo last turn's file changes", blue),
+        kv("/checkpoints", "List file checkpoints", blue),
+        kv("/restore <id>", "Restore files to a checkpoint", blue),
+        kv("/memory", "Memory index status", blue),
         kv("/whisper", "Speak anywhere, paste to clipboard", blue),
         Line::from(""),
         Line::from(""),
@@ -1503,7 +1656,9 @@ fn render_help(f: &mut Frame, app: &App, area: Rect) {
         kv("↑ / ↓", "Navigate options", blue),
         kv("Enter", "Confirm selection", blue),
         kv("D / Esc", "Deny", Color::Red),
-        kv("V", "Toggle details", blue),
+        kv("V", "Toggle details / full diff", blue),
+        kv("Enter (edit)", "Run with edited input", blue),
+        kv("Esc (edit)", "Back to options", blue),
         Line::from(""),
         section_header("FEATURES"),
         Line::from(vec![